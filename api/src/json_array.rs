@@ -0,0 +1,126 @@
+use futures_lite::{AsyncRead, Stream};
+use serde::Serialize;
+use std::{
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// serialized items are buffered until at least this many bytes are
+/// available or the stream is not immediately ready, whichever comes
+/// first
+const FLUSH_THRESHOLD: usize = 8 * 1024;
+
+/// An [`AsyncRead`] that serializes each item of a [`Stream`] as
+/// an element of a single json array
+pub(crate) struct JsonArrayReader<S> {
+    stream: Pin<Box<S>>,
+    buffer: Vec<u8>,
+    position: usize,
+    started: bool,
+    done: bool,
+    error: Option<io::Error>,
+}
+
+impl<S> Debug for JsonArrayReader<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonArrayReader")
+            .field("buffered", &(self.buffer.len() - self.position))
+            .field("started", &self.started)
+            .field("done", &self.done)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<S> JsonArrayReader<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream: Box::pin(stream),
+            buffer: Vec::new(),
+            position: 0,
+            started: false,
+            done: false,
+            error: None,
+        }
+    }
+
+    /// pulls items from the stream into the buffer. returns pending
+    /// only if there is nothing buffered
+    fn fill(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        while self.buffer.len() < FLUSH_THRESHOLD {
+            match self.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    let len_before_item = self.buffer.len();
+                    self.buffer.push(if self.started { b',' } else { b'[' });
+                    if let Err(e) = serde_json::to_writer(&mut self.buffer, &item) {
+                        // anything already buffered is still sent, but the
+                        // array is left unterminated so that the client
+                        // cannot mistake the response for a complete one
+                        self.buffer.truncate(len_before_item);
+                        self.error = Some(e.into());
+                        self.done = true;
+                        break;
+                    }
+                    self.started = true;
+                }
+
+                Poll::Ready(None) => {
+                    if !self.started {
+                        self.buffer.push(b'[');
+                    }
+                    self.buffer.push(b']');
+                    self.done = true;
+                    break;
+                }
+
+                Poll::Pending if self.buffer.is_empty() => return Poll::Pending,
+                Poll::Pending => break,
+            }
+        }
+
+        Poll::Ready(())
+    }
+}
+
+impl<S> AsyncRead for JsonArrayReader<S>
+where
+    S: Stream,
+    S::Item: Serialize,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        loop {
+            let buffered = &this.buffer[this.position..];
+            if !buffered.is_empty() {
+                let len = buffered.len().min(buf.len());
+                buf[..len].copy_from_slice(&buffered[..len]);
+                this.position += len;
+                if this.position == this.buffer.len() {
+                    this.buffer.clear();
+                    this.position = 0;
+                }
+                return Poll::Ready(Ok(len));
+            }
+
+            if let Some(error) = this.error.take() {
+                return Poll::Ready(Err(error));
+            }
+
+            if this.done {
+                return Poll::Ready(Ok(0));
+            }
+
+            futures_lite::ready!(this.fill(cx));
+        }
+    }
+}
@@ -32,10 +32,14 @@ custom handling for deserialization errors, use
     unused_qualifications
 )]
 
+mod json_array;
+use json_array::JsonArrayReader;
+
+use futures_lite::Stream;
 use serde::{de::DeserializeOwned, Serialize};
 pub use serde_json::{json, Value};
 use std::{fmt::Debug, future::Future, marker::PhantomData};
-use trillium::{async_trait, conn_try, Conn, Handler, KnownHeaderName::ContentType};
+use trillium::{async_trait, conn_try, Body, Conn, Handler, KnownHeaderName::ContentType};
 
 /**
 Trillium API handler
@@ -193,6 +197,45 @@ pub trait ApiConnExt {
     */
     fn with_json(self, response: &impl Serialize) -> Self;
 
+    /**
+    Sends a json array response body, serializing each item of the
+    provided [`Stream`] as it becomes available instead of collecting
+    the full array in memory. This sets a status code of 200, sets the
+    content-type to application/json, and
+    [halts](trillium::Conn::halt) the conn. The body is sent with
+    chunked encoding, and serialized items are flushed whenever the
+    stream is not immediately ready or several kilobytes are buffered.
+
+    Because the status and headers will already have been sent, a
+    serialization error partway through the stream cannot be reported
+    with a status code. Instead, items serialized before the error are
+    sent and the response body ends without closing the array, so the
+    client will not receive a valid json document.
+
+    ## Examples
+
+    ```
+    use trillium_api::{json, ApiConnExt};
+    use futures_lite::{stream, StreamExt};
+
+    async fn handler(conn: trillium::Conn) -> trillium::Conn {
+        let items = stream::iter(1..=3).map(|n| json!({ "n": n }));
+        conn.stream_json_array(items)
+    }
+
+    # use trillium_testing::prelude::*;
+    assert_ok!(
+        get("/").on(&handler),
+        r#"[{"n":1},{"n":2},{"n":3}]"#,
+        "content-type" => "application/json"
+    );
+    ```
+    */
+    fn stream_json_array<S>(self, stream: S) -> Self
+    where
+        S: Stream + Send + Sync + 'static,
+        S::Item: Serialize;
+
     /**
     Attempts to deserialize a type from the request body, based on the
     request content type.
@@ -286,6 +329,15 @@ impl ApiConnExt for Conn {
         self.ok(body).with_header(ContentType, "application/json")
     }
 
+    fn stream_json_array<S>(self, stream: S) -> Self
+    where
+        S: Stream + Send + Sync + 'static,
+        S::Item: Serialize,
+    {
+        let body = Body::new_streaming(JsonArrayReader::new(stream), None);
+        self.ok(body).with_header(ContentType, "application/json")
+    }
+
     async fn deserialize<T>(&mut self) -> Result<T, Value>
     where
         T: DeserializeOwned,
@@ -296,7 +348,7 @@ impl ApiConnExt for Conn {
             .map_err(|e| json!({ "errorType": "io error", "message": e.to_string() }))?;
 
         let content_type = self
-            .headers()
+            .request_headers()
             .get_str(ContentType)
             .and_then(|c| c.parse().ok())
             .unwrap_or(mime::APPLICATION_JSON);
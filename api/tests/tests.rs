@@ -43,3 +43,44 @@ fn form_urlencoded_json_response() {
         r#"{"s":{"string":"string","numbers":null}}"#
     );
 }
+
+#[test]
+fn json_array_stream() {
+    use futures_lite::stream;
+
+    let empty =
+        |conn: trillium::Conn| async move { conn.stream_json_array(stream::empty::<Struct>()) };
+    assert_ok!(get("/").on(&empty), "[]", "content-type" => "application/json");
+
+    let many = |conn: trillium::Conn| async move { conn.stream_json_array(stream::iter(0..5000)) };
+    let expected = format!(
+        "[{}]",
+        (0..5000)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    assert_ok!(get("/").on(&many), expected);
+}
+
+#[test]
+fn json_array_stream_serialization_error() {
+    use futures_lite::{future::block_on, stream, AsyncReadExt};
+
+    struct Unserializable;
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("nope"))
+        }
+    }
+
+    let handler = |conn: trillium::Conn| async move {
+        conn.stream_json_array(stream::iter([Ok(1), Ok(2), Err(Unserializable)]))
+    };
+
+    let mut conn = get("/").on(&handler);
+    let mut reader = conn.take_response_body().unwrap().into_reader();
+    let mut body = vec![];
+    assert!(block_on(reader.read_to_end(&mut body)).is_err());
+    assert_eq!(body, br#"[{"Ok":1},{"Ok":2}"#);
+}
@@ -39,27 +39,281 @@ assert_ok!(
 */
 
 pub use askama;
-pub use askama::Template;
-use trillium::KnownHeaderName::ContentType;
+pub use askama::{DynTemplate, Template};
+use futures_lite::{io::Cursor, AsyncReadExt};
+use std::convert::TryInto;
+use trillium::{Body, HeaderValues, KnownHeaderName::ContentType, Status};
 
 /// extends trillium conns with the ability to render askama templates
-pub trait AskamaConnExt {
+pub trait AskamaConnExt: Sized {
     /// renders an askama template, halting the conn and setting a 200
     /// status code. also sets the mime type based on the template
-    /// extension
+    /// extension, unless a content-type has already been set. if the
+    /// template fails to render, this logs the error and sets a 500
+    /// status with an empty body instead.
     fn render(self, template: impl Template) -> Self;
+
+    /**
+    renders an askama template, halting the conn and setting a 200
+    status code. also sets the mime type based on the template
+    extension.
+
+    # Errors
+
+    returns the askama error if the template fails to render
+
+    ```
+    use std::fmt::{self, Display, Formatter};
+    use trillium_askama::{AskamaConnExt, Template};
+    use trillium_testing::prelude::*;
+
+    struct Unrenderable;
+    impl Display for Unrenderable {
+        fn fmt(&self, _: &mut Formatter<'_>) -> fmt::Result {
+            Err(fmt::Error)
+        }
+    }
+
+    #[derive(Template)]
+    #[template(source = "hello {{ name }}", ext = "txt")]
+    struct HelloTemplate<T: Display> {
+        name: T,
+    }
+
+    let conn: Conn = get("/").into();
+    assert!(conn.try_render(HelloTemplate { name: Unrenderable }).is_err());
+
+    let handler = |conn: Conn| async move {
+        conn.try_render(HelloTemplate { name: "trillium" }).unwrap()
+    };
+    assert_ok!(get("/").on(&handler), "hello trillium", "content-type" => "text/plain");
+
+    // render logs the error and responds with a 500 instead
+    let handler = |conn: Conn| async move {
+        conn.render(HelloTemplate { name: Unrenderable })
+    };
+    assert_response!(get("/").on(&handler), 500, "");
+    ```
+    */
+    fn try_render(self, template: impl Template) -> askama::Result<Self>;
+
+    /**
+    renders an askama template, halting the conn and setting the
+    provided status code. also sets the mime type based on the
+    template extension. like [`AskamaConnExt::render`], if the
+    template fails to render, this logs the error and sets a 500
+    status with an empty body instead.
+
+    ```
+    use trillium_askama::{AskamaConnExt, Template};
+    use trillium_testing::prelude::*;
+
+    #[derive(Template)]
+    #[template(source = "{{ path }} was not found", ext = "html")]
+    struct NotFoundTemplate<'a> {
+        path: &'a str,
+    }
+
+    let handler = |conn: Conn| async move {
+        let path = conn.path().to_string();
+        conn.render_with_status(NotFoundTemplate { path: &path }, 404)
+    };
+
+    let conn = get("/missing").on(&handler);
+    assert!(conn.is_halted());
+    assert_response!(conn, 404, "/missing was not found", "content-type" => "text/html");
+    ```
+    */
+    fn render_with_status(self, template: impl Template, status: impl TryInto<Status>) -> Self;
+
+    /**
+    renders an askama template and appends it to the response body,
+    or sets it as the body if there is none, for responses that are
+    composed of several fragments. unlike [`AskamaConnExt::render`],
+    this does not set a status or halt the conn. the mime type is set
+    from the template extension unless a content-type has already
+    been set. if the template fails to render, this logs the error
+    and sets a 500 status with an empty body, as
+    [`AskamaConnExt::render`] does.
+
+    ```
+    use trillium_askama::{AskamaConnExt, Template};
+    use trillium_testing::prelude::*;
+
+    #[derive(Template)]
+    #[template(source = "<li>{{ item }}</li>", ext = "html")]
+    struct ItemTemplate<'a> {
+        item: &'a str,
+    }
+
+    let handler = |conn: Conn| async move {
+        ["one", "two"]
+            .into_iter()
+            .fold(conn, |conn, item| conn.render_fragment(ItemTemplate { item }))
+            .with_status(200)
+    };
+
+    let conn = get("/").on(&handler);
+    assert!(!conn.is_halted());
+    assert_ok!(conn, "<li>one</li><li>two</li>", "content-type" => "text/html");
+    ```
+    */
+    fn render_fragment(self, template: impl Template) -> Self;
+
+    /**
+    renders a type-erased askama template, halting the conn and
+    setting a 200 status code, exactly as [`AskamaConnExt::render`]
+    does. every [`Template`] is a [`DynTemplate`], so this allows
+    choosing between templates of different types at runtime, such
+    as by content negotiation.
+
+    ```
+    use trillium_askama::{AskamaConnExt, DynTemplate, Template};
+    use trillium_testing::prelude::*;
+
+    #[derive(Template)]
+    #[template(source = "<p>{{ name }}</p>", ext = "html")]
+    struct HtmlTemplate<'a> {
+        name: &'a str,
+    }
+
+    #[derive(Template)]
+    #[template(source = "{{ name }}", ext = "txt")]
+    struct TextTemplate<'a> {
+        name: &'a str,
+    }
+
+    let handler = |conn: Conn| async move {
+        let name = "trillium";
+        let template: Box<dyn DynTemplate + Send> =
+            match conn.negotiate_content_type(&["text/html", "text/plain"]) {
+                Some("text/plain") => Box::new(TextTemplate { name }),
+                _ => Box::new(HtmlTemplate { name }),
+            };
+        conn.render_dyn(&*template)
+    };
+
+    assert_ok!(
+        get("/").with_request_header("accept", "text/plain").on(&handler),
+        "trillium",
+        "content-type" => "text/plain"
+    );
+    assert_ok!(get("/").on(&handler), "<p>trillium</p>", "content-type" => "text/html");
+    ```
+    */
+    fn render_dyn(self, template: &dyn DynTemplate) -> Self;
+
+    /**
+    renders an askama template, halting the conn and setting a 200
+    status code, with the provided content-type. unlike
+    [`AskamaConnExt::render`], this does not look up a mime type from
+    the template extension, and replaces any content-type that has
+    already been set. this is useful for templates with extensions
+    that are not known mime types. if the template fails to render,
+    this logs the error and sets a 500 status with an empty body.
+
+    ```
+    use trillium_askama::{AskamaConnExt, Template};
+    use trillium_testing::prelude::*;
+
+    #[derive(Template)]
+    #[template(source = "<name>{{ name }}</name>", ext = "j2")]
+    struct NameTemplate<'a> {
+        name: &'a str,
+    }
+
+    let handler = |conn: Conn| async move {
+        conn.render_with_mime(NameTemplate { name: "trillium" }, "application/xml")
+    };
+
+    assert_ok!(
+        get("/").on(&handler),
+        "<name>trillium</name>",
+        "content-type" => "application/xml"
+    );
+    ```
+    */
+    fn render_with_mime(self, template: impl Template, mime: impl Into<HeaderValues>) -> Self;
 }
 
 impl AskamaConnExt for trillium::Conn {
-    fn render(mut self, template: impl Template) -> Self {
-        use askama::DynTemplate;
-        let text = template.render().unwrap();
-        if let Some(extension) = template.extension() {
-            if let Some(mime) = mime_db::lookup(extension) {
-                self.headers_mut().insert(ContentType, mime);
-            }
+    fn render(self, template: impl Template) -> Self {
+        self.render_with_status(template, Status::Ok)
+    }
+
+    fn render_with_status(self, template: impl Template, status: impl TryInto<Status>) -> Self {
+        match template.render() {
+            Ok(text) => send_rendered(self, &template, text, status),
+            Err(e) => render_failed(self, &e),
+        }
+    }
+
+    fn render_fragment(mut self, template: impl Template) -> Self {
+        let text = match template.render() {
+            Ok(text) => text,
+            Err(e) => return render_failed(self, &e),
+        };
+
+        set_content_type(&mut self, &template);
+
+        let body = match self.take_response_body() {
+            None => Body::from(text),
+            Some(body) => match body.static_bytes() {
+                Some(bytes) => Body::from([bytes, text.as_bytes()].concat()),
+                None => {
+                    let len = body.len().map(|len| len + text.len() as u64);
+                    Body::new_streaming(body.into_reader().chain(Cursor::new(text)), len)
+                }
+            },
+        };
+
+        self.with_body(body)
+    }
+
+    fn try_render(self, template: impl Template) -> askama::Result<Self> {
+        let text = template.render()?;
+        Ok(send_rendered(self, &template, text, Status::Ok))
+    }
+
+    fn render_dyn(self, template: &dyn DynTemplate) -> Self {
+        match template.dyn_render() {
+            Ok(text) => send_rendered(self, template, text, Status::Ok),
+            Err(e) => render_failed(self, &e),
         }
+    }
 
-        self.ok(text)
+    fn render_with_mime(self, template: impl Template, mime: impl Into<HeaderValues>) -> Self {
+        match template.render() {
+            Ok(text) => self
+                .with_header(ContentType, mime)
+                .with_body(text)
+                .with_status(Status::Ok)
+                .halt(),
+            Err(e) => render_failed(self, &e),
+        }
     }
 }
+
+fn render_failed(mut conn: trillium::Conn, error: &askama::Error) -> trillium::Conn {
+    log::error!("unable to render askama template: {}", error);
+    conn.take_response_body();
+    conn.with_status(500).halt()
+}
+
+fn set_content_type(conn: &mut trillium::Conn, template: &dyn DynTemplate) {
+    if let Some(extension) = template.extension() {
+        if let Some(mime) = mime_db::lookup(extension) {
+            conn.response_headers_mut().try_insert(ContentType, mime);
+        }
+    }
+}
+
+fn send_rendered(
+    mut conn: trillium::Conn,
+    template: &dyn DynTemplate,
+    text: String,
+    status: impl TryInto<Status>,
+) -> trillium::Conn {
+    set_content_type(&mut conn, template);
+    conn.with_body(text).with_status(status).halt()
+}
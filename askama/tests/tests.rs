@@ -0,0 +1,114 @@
+use trillium::{Conn, KnownHeaderName::ContentType};
+use trillium_askama::{AskamaConnExt, DynTemplate, Template};
+use trillium_testing::prelude::*;
+
+#[derive(Template)]
+#[template(source = "<p>{{ name }}</p>", ext = "html")]
+struct HelloTemplate<'a> {
+    name: &'a str,
+}
+
+#[test]
+fn mime_from_extension() {
+    let handler = |conn: Conn| async move { conn.render(HelloTemplate { name: "trillium" }) };
+    assert_ok!(
+        get("/").on(&handler),
+        "<p>trillium</p>",
+        "content-type" => "text/html"
+    );
+}
+
+#[test]
+fn existing_content_type_is_preserved() {
+    let handler = |conn: Conn| async move {
+        conn.with_header(ContentType, "text/plain")
+            .render(HelloTemplate { name: "trillium" })
+    };
+    assert_ok!(
+        get("/").on(&handler),
+        "<p>trillium</p>",
+        "content-type" => "text/plain"
+    );
+
+    let handler = |conn: Conn| async move {
+        conn.with_header(ContentType, "text/plain")
+            .render_with_status(HelloTemplate { name: "trillium" }, 404)
+    };
+    assert_response!(
+        get("/").on(&handler),
+        404,
+        "<p>trillium</p>",
+        "content-type" => "text/plain"
+    );
+}
+
+#[test]
+fn fragments_are_appended() {
+    let handler = |conn: Conn| async move {
+        conn.with_status(201)
+            .with_body("<h1>names</h1>")
+            .render_fragment(HelloTemplate { name: "one" })
+            .render_fragment(HelloTemplate { name: "two" })
+    };
+    let conn = get("/").on(&handler);
+    assert!(!conn.is_halted());
+    assert_response!(
+        conn,
+        201,
+        "<h1>names</h1><p>one</p><p>two</p>",
+        "content-type" => "text/html"
+    );
+
+    let handler = |conn: Conn| async move {
+        conn.with_body_stream(futures_lite::io::Cursor::new("<ul>"), None)
+            .render_fragment(HelloTemplate { name: "streamed" })
+            .with_status(200)
+    };
+    let mut conn = get("/").on(&handler);
+    assert_eq!(conn.response_len(), None);
+    assert_body!(&mut conn, "<ul><p>streamed</p>");
+}
+
+#[derive(Template)]
+#[template(source = "hello {{ name }}", ext = "txt")]
+struct HelloText<'a> {
+    name: &'a str,
+}
+
+#[test]
+fn dyn_templates() {
+    let handler = |conn: Conn| async move {
+        let template: Box<dyn DynTemplate + Send> = if conn.path() == "/text" {
+            Box::new(HelloText { name: "trillium" })
+        } else {
+            Box::new(HelloTemplate { name: "trillium" })
+        };
+        conn.render_dyn(&*template)
+    };
+
+    assert_ok!(
+        get("/text").on(&handler),
+        "hello trillium",
+        "content-type" => "text/plain"
+    );
+    assert_ok!(
+        get("/").on(&handler),
+        "<p>trillium</p>",
+        "content-type" => "text/html"
+    );
+}
+
+#[test]
+fn explicit_mime() {
+    let handler = |conn: Conn| async move {
+        conn.with_header(ContentType, "text/plain")
+            .render_with_mime(HelloTemplate { name: "trillium" }, "application/xhtml+xml")
+    };
+    let conn = get("/").on(&handler);
+    assert!(conn.is_halted());
+    assert_ok!(
+        conn,
+        "<p>trillium</p>",
+        "content-type" => "application/xhtml+xml"
+    );
+}
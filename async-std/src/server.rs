@@ -5,7 +5,7 @@ use async_std::{
     prelude::*,
     task::{block_on, spawn},
 };
-use std::{convert::TryInto, env, io::Result, net::IpAddr, pin::Pin};
+use std::{convert::TryInto, env, io::Result, net::SocketAddr, pin::Pin, time::Duration};
 use trillium::{log_error, Info};
 use trillium_server_common::Server;
 #[cfg(unix)]
@@ -60,12 +60,9 @@ impl Server for AsyncStdServer {
         })
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
         match transport {
-            Tcp(transport) => transport
-                .peer_addr()
-                .ok()
-                .map(|socket_addr| socket_addr.ip()),
+            Tcp(transport) => transport.peer_addr().ok(),
 
             Unix(_) => None,
         }
@@ -94,6 +91,10 @@ impl Server for AsyncStdServer {
         block_on(fut)
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         if let Tcp(transport) = transport {
             log_error!(transport.set_nodelay(nodelay));
@@ -132,11 +133,8 @@ impl Server for AsyncStdServer {
         Box::pin(async move { listener.accept().await.map(|(t, _)| t) })
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
-        transport
-            .peer_addr()
-            .ok()
-            .map(|socket_addr| socket_addr.ip())
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
+        transport.peer_addr().ok()
     }
 
     fn listener_from_tcp(tcp: std::net::TcpListener) -> Self::Listener {
@@ -155,6 +153,10 @@ impl Server for AsyncStdServer {
         block_on(fut);
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         log_error!(transport.set_nodelay(nodelay));
     }
@@ -1,6 +1,5 @@
 use std::collections::HashMap;
 use trillium::Conn;
-use trillium_http::Status;
 
 #[derive(serde::Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -14,7 +13,7 @@ pub(crate) struct AlbMultiHeadersResponse {
 
 impl AlbMultiHeadersResponse {
     pub async fn from_conn(mut conn: Conn) -> Self {
-        let status = conn.status().unwrap_or(Status::NotFound);
+        let status = conn.effective_status();
         let (body, is_base64_encoded) = response_body(&mut conn).await;
 
         let multi_value_headers = conn
@@ -59,7 +58,7 @@ async fn response_body(conn: &mut Conn) -> (Option<String>, bool) {
 
 impl AlbResponse {
     pub async fn from_conn(mut conn: Conn) -> Self {
-        let status = conn.status().unwrap_or(Status::NotFound);
+        let status = conn.effective_status();
         let (body, is_base64_encoded) = response_body(&mut conn).await;
         let headers =
             conn.inner()
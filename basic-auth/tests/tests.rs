@@ -93,3 +93,48 @@ fn edge_cases() {
         401
     );
 }
+
+#[test]
+fn conn_ext() {
+    let handler = |conn: Conn| async move {
+        match conn.basic_auth() {
+            Some((username, password)) => conn.ok(format!("{username}/{password}")),
+            None => conn.with_basic_auth_challenge("a \"realm\""),
+        }
+    };
+
+    assert_ok!(
+        get("/")
+            .with_request_header(
+                "Authorization",
+                format!("BASIC  {}", base64::encode("jacob:7r1ll1um:x"))
+            )
+            .on(&handler),
+        "jacob/7r1ll1um:x"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("Authorization", format!("Basic {}", base64::encode(":")))
+            .on(&handler),
+        "/"
+    );
+
+    for authorization in [
+        String::from("Basic not base64!"),
+        format!("Basic {}", base64::encode("no colon")),
+        format!("Bearer {}", base64::encode("jacob:7r1ll1um")),
+        String::from("Basic"),
+    ] {
+        assert_response!(
+            get("/")
+                .with_request_header("Authorization", authorization)
+                .on(&handler),
+            401,
+            "",
+            "www-authenticate" => r#"Basic realm="a \"realm\"""#
+        );
+    }
+
+    assert_status!(get("/").on(&handler), 401);
+}
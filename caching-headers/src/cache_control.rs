@@ -68,6 +68,28 @@ pub enum CacheControlDirective {
 A representation of the
 [`Cache-Control`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Cache-Control)
 header.
+
+When building a response header, note that `no-cache` and `no-store`
+are frequently confused:
+
+* [`CacheControlHeader::no_cache`] allows caches to store the
+  response, but requires them to revalidate it with the origin (with
+  `If-None-Match` or `If-Modified-Since`) before every reuse. pair
+  this with an etag or last-modified to get cheap `304 Not Modified`
+  responses for content that may change at any time.
+* [`CacheControlHeader::no_store`] forbids storing any part of the
+  response anywhere, and should be used for sensitive content.
+  nothing is revalidated because nothing is kept.
+
+```
+use trillium_caching_headers::CacheControlHeader;
+assert_eq!(CacheControlHeader::no_cache().to_string(), "no-cache");
+assert_eq!(CacheControlHeader::no_store().to_string(), "no-store");
+assert_eq!(
+    CacheControlHeader::no_cache().with_must_revalidate().to_string(),
+    "no-cache,must-revalidate"
+);
+```
 */
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CacheControlHeader(Vec<CacheControlDirective>);
@@ -107,6 +129,40 @@ impl From<CacheControlHeader> for HeaderValues {
 }
 
 impl CacheControlHeader {
+    /// constructs a `no-cache` header, which allows the response to be
+    /// stored but requires that it be revalidated with the origin
+    /// before each use. this is not the same as
+    /// [`CacheControlHeader::no_store`]
+    pub fn no_cache() -> Self {
+        Self(vec![NoCache])
+    }
+
+    /// constructs a `no-store` header, which forbids any cache from
+    /// storing the response at all. this is not the same as
+    /// [`CacheControlHeader::no_cache`]
+    pub fn no_store() -> Self {
+        Self(vec![NoStore])
+    }
+
+    /// chainable method to add a `must-revalidate` directive, which
+    /// forbids caches from serving the response once it is stale
+    /// without first revalidating it, even when disconnected from the
+    /// origin
+    #[must_use]
+    pub fn with_must_revalidate(self) -> Self {
+        self.with_directive(MustRevalidate)
+    }
+
+    /// chainable method to add an arbitrary directive, if it is not
+    /// already present
+    #[must_use]
+    pub fn with_directive(mut self, directive: CacheControlDirective) -> Self {
+        if !self.0.contains(&directive) {
+            self.0.push(directive);
+        }
+        self
+    }
+
     /// returns true if one of the directives is `immutable`
     pub fn is_immutable(&self) -> bool {
         self.contains(&Immutable)
@@ -312,6 +368,23 @@ impl FromStr for CacheControlHeader {
 #[cfg(test)]
 mod test {
     use super::*;
+    #[test]
+    fn builders() {
+        assert_eq!(CacheControlHeader::no_cache(), "no-cache".parse().unwrap());
+        assert_eq!(CacheControlHeader::no_store(), "no-store".parse().unwrap());
+        assert!(CacheControlHeader::no_cache().is_no_cache());
+        assert!(!CacheControlHeader::no_cache().is_no_store());
+        assert!(!CacheControlHeader::no_store().is_no_cache());
+
+        let revalidate = CacheControlHeader::no_cache()
+            .with_must_revalidate()
+            .with_directive(MaxAge(Duration::ZERO))
+            .with_must_revalidate();
+        assert!(revalidate.must_revalidate());
+        assert_eq!(revalidate.max_age(), Some(Duration::ZERO));
+        assert_eq!(revalidate.to_string(), "no-cache,must-revalidate,max-age=0");
+    }
+
     #[test]
     fn parse() {
         assert_eq!(
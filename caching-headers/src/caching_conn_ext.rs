@@ -38,7 +38,8 @@ pub trait CachingHeadersExt: Sized {
         I: IntoIterator<Item = N>,
         N: Into<HeaderName<'static>>;
 
-    /// chainable method to set cache control and return self. primarily useful on Conn
+    /// chainable method to set cache control and return self. primarily useful on Conn.
+    /// see [`CacheControlHeader`] for the difference between `no-cache` and `no-store`
     fn with_cache_control(mut self, cache_control: impl Into<CacheControlHeader>) -> Self {
         self.set_cache_control(cache_control);
         self
@@ -132,7 +133,7 @@ impl CachingHeadersExt for trillium::Headers {
     fn set_last_modified(&mut self, system_time: SystemTime) {
         self.insert(
             KnownHeaderName::LastModified,
-            httpdate::fmt_http_date(system_time),
+            trillium::format_http_date(system_time),
         );
     }
 
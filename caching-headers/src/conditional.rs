@@ -0,0 +1,93 @@
+use crate::{
+    etag::{if_none_match_matches, not_modified},
+    CachingHeadersExt,
+};
+use etag::EntityTag;
+use std::time::SystemTime;
+use trillium::{Conn, KnownHeaderName, Method};
+
+/**
+# Conditional GET support for [`trillium::Conn`]
+
+Unlike the [`Etag`](crate::Etag) and [`Modified`](crate::Modified)
+handlers, which compare validators after the response has been
+built, these are called from within a handler, so that a response can
+be short-circuited with a `304 Not Modified` before any work is done
+to build it. Only `GET` and `HEAD` requests are answered with a 304.
+
+```
+use trillium::Conn;
+use trillium_caching_headers::{ConditionalConnExt, EntityTag};
+use trillium_testing::prelude::*;
+
+let handler = |conn: Conn| async move {
+    let conn = conn.with_conditional_etag(&EntityTag::strong("v1"));
+    if conn.is_halted() {
+        return conn;
+    }
+    conn.ok("expensive content")
+};
+
+assert_ok!(get("/").on(&handler), "expensive content", "etag" => r#""v1""#);
+
+let conn = get("/").with_request_header("if-none-match", r#""v0", "v1""#).on(&handler);
+assert_status!(&conn, 304);
+```
+*/
+pub trait ConditionalConnExt: Sized {
+    /// sets the `ETag` response header, and if the request's
+    /// `If-None-Match` header matches it by weak comparison, removes
+    /// any response body, sets a 304 Not Modified status and halts
+    fn with_conditional_etag(self, entity_tag: &EntityTag) -> Self;
+
+    /// sets the `Last-Modified` response header, and if the request
+    /// has an `If-Modified-Since` header that is not earlier than it
+    /// and no `If-None-Match` header, removes any response body, sets
+    /// a 304 Not Modified status and halts
+    fn with_conditional_last_modified(self, last_modified: SystemTime) -> Self;
+}
+
+fn is_get_or_head(conn: &Conn) -> bool {
+    matches!(conn.method(), Method::Get | Method::Head)
+}
+
+impl ConditionalConnExt for Conn {
+    fn with_conditional_etag(self, entity_tag: &EntityTag) -> Self {
+        let conn = self.with_etag(entity_tag);
+        let matches = conn
+            .request_headers()
+            .get_str(KnownHeaderName::IfNoneMatch)
+            .is_some_and(|if_none_match| if_none_match_matches(if_none_match, entity_tag, false));
+
+        if matches && is_get_or_head(&conn) {
+            not_modified(conn).halt()
+        } else {
+            conn
+        }
+    }
+
+    fn with_conditional_last_modified(self, last_modified: SystemTime) -> Self {
+        let conn = self.with_last_modified(last_modified);
+
+        // an If-None-Match header takes precedence over If-Modified-Since
+        if conn
+            .request_headers()
+            .has_header(KnownHeaderName::IfNoneMatch)
+            || !is_get_or_head(&conn)
+        {
+            return conn;
+        }
+
+        // http dates have a resolution of one second, so this compares
+        // the header as it was sent rather than the precise time
+        match (conn.if_modified_since(), conn.last_modified()) {
+            (Some(if_modified_since), Some(last_modified))
+                if last_modified <= if_modified_since =>
+            {
+                not_modified(conn).halt()
+            }
+
+            _ => conn,
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use etag::EntityTag;
-use trillium::{async_trait, Conn, Handler, Status};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Status};
 
 use crate::CachingHeadersExt;
 
@@ -56,7 +56,10 @@ impl Handler for Etag {
     }
 
     async fn before_send(&self, mut conn: Conn) -> Conn {
-        let if_none_match = conn.if_none_match();
+        let if_none_match = conn
+            .request_headers()
+            .get_str(KnownHeaderName::IfNoneMatch)
+            .map(String::from);
 
         let etag = conn.etag().or_else(|| {
             let etag = conn
@@ -73,17 +76,51 @@ impl Handler for Etag {
         });
 
         if let (Some(ref etag), Some(ref if_none_match)) = (etag, if_none_match) {
-            let eq = if self.strong {
-                etag.strong_eq(if_none_match)
-            } else {
-                etag.weak_eq(if_none_match)
-            };
-
-            if eq {
-                return conn.with_status(Status::NotModified);
+            if if_none_match_matches(if_none_match, etag, self.strong) {
+                return not_modified(conn);
             }
         }
 
         conn
     }
 }
+
+/// whether an `If-None-Match` header, which is either `*` or a list
+/// of entity tags, matches this entity tag
+pub(crate) fn if_none_match_matches(if_none_match: &str, etag: &EntityTag, strong: bool) -> bool {
+    if if_none_match.trim() == "*" {
+        return true;
+    }
+
+    entity_tags(if_none_match).any(|candidate| {
+        if strong {
+            etag.strong_eq(&candidate)
+        } else {
+            etag.weak_eq(&candidate)
+        }
+    })
+}
+
+/// the entity tags in a comma-separated list. entity tags may
+/// themselves contain commas, so this reads each quoted tag rather
+/// than splitting on them
+fn entity_tags(mut list: &str) -> impl Iterator<Item = EntityTag> + '_ {
+    std::iter::from_fn(move || loop {
+        list = list.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+        let weak_len = if list.starts_with("W/") { 2 } else { 0 };
+        let end = list[weak_len..]
+            .strip_prefix('"')
+            .and_then(|tag| tag.find('"'))?;
+        let (tag, rest) = list.split_at(weak_len + end + 2);
+        list = rest;
+        if let Ok(tag) = tag.parse() {
+            return Some(tag);
+        }
+    })
+}
+
+/// responds with a 304 Not Modified status, removing any response body
+pub(crate) fn not_modified(mut conn: Conn) -> Conn {
+    conn.inner_mut().take_response_body();
+    conn.with_status(Status::NotModified)
+}
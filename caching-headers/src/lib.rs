@@ -3,7 +3,9 @@
 
 This crate provides three handlers: [`Etag`], [`Modified`], and
 [`CachingHeaders`], as well as a [`CachingHeadersExt`] that extends
-[`trillium::Headers`] with some accessors.
+[`trillium::Headers`] with some accessors, and a
+[`ConditionalConnExt`] for answering conditional requests from within
+a handler.
 
 Unless you are sure that you _don't_ want either etag or last-modified
 behavior, please use the combined [`CachingHeaders`] handler.
@@ -29,6 +31,9 @@ pub use modified::Modified;
 mod caching_conn_ext;
 pub use caching_conn_ext::CachingHeadersExt;
 
+mod conditional;
+pub use conditional::ConditionalConnExt;
+
 mod cache_control;
 pub use cache_control::{CacheControlDirective, CacheControlHeader};
 
@@ -1,5 +1,5 @@
-use crate::CachingHeadersExt;
-use trillium::{async_trait, Conn, Handler, Status};
+use crate::{etag::not_modified, CachingHeadersExt};
+use trillium::{async_trait, Conn, Handler};
 
 /**
 # A handler for the `Last-Modified` and `If-Modified-Since` header interaction.
@@ -26,7 +26,7 @@ impl Handler for Modified {
             (Some(if_modified_since), Some(last_modified))
                 if last_modified <= if_modified_since =>
             {
-                conn.with_status(Status::NotModified).halt()
+                not_modified(conn).halt()
             }
 
             _ => conn,
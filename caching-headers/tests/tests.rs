@@ -0,0 +1,111 @@
+use std::time::{Duration, SystemTime};
+use trillium::{Conn, KnownHeaderName};
+use trillium_caching_headers::{caching_headers, CachingHeadersExt, ConditionalConnExt, EntityTag};
+use trillium_testing::prelude::*;
+
+fn http_date(time: SystemTime) -> String {
+    httpdate::fmt_http_date(time)
+}
+
+#[test]
+fn conditional_etag() {
+    let handler = |conn: Conn| async move {
+        conn.with_body("already set")
+            .with_conditional_etag(&EntityTag::strong("abc"))
+    };
+
+    let mut conn = get("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, r#"W/"abc""#)
+        .on(&handler);
+    assert_status!(&conn, 304);
+    assert!(conn.is_halted());
+    assert_headers!(&mut conn, "etag" => r#""abc""#);
+    assert_eq!(conn.take_response_body_string(), None);
+
+    let conn = get("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, r#""x,y", "abc""#)
+        .on(&handler);
+    assert_status!(&conn, 304);
+
+    let conn = get("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, "*")
+        .on(&handler);
+    assert_status!(&conn, 304);
+
+    let mut conn = get("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, r#""abcd""#)
+        .on(&handler);
+    assert!(!conn.is_halted());
+    assert_body!(&mut conn, "already set");
+
+    let conn = post("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, r#""abc""#)
+        .on(&handler);
+    assert!(!conn.is_halted());
+}
+
+#[test]
+fn conditional_last_modified() {
+    let last_modified = SystemTime::now() - Duration::from_secs(60);
+    let handler = move |conn: Conn| async move {
+        conn.with_body("already set")
+            .with_conditional_last_modified(last_modified)
+    };
+
+    // the sub-second part of last_modified is not sent, and so is ignored
+    let mut conn = get("/")
+        .with_request_header(KnownHeaderName::IfModifiedSince, http_date(last_modified))
+        .on(&handler);
+    assert_status!(&conn, 304);
+    assert!(conn.is_halted());
+    assert_eq!(conn.take_response_body_string(), None);
+
+    let mut conn = get("/")
+        .with_request_header(
+            KnownHeaderName::IfModifiedSince,
+            http_date(last_modified - Duration::from_secs(10)),
+        )
+        .on(&handler);
+    assert!(!conn.is_halted());
+    assert_body!(&mut conn, "already set");
+
+    let conn = get("/")
+        .with_request_header(KnownHeaderName::IfModifiedSince, http_date(last_modified))
+        .with_request_header(KnownHeaderName::IfNoneMatch, r#""other""#)
+        .on(&handler);
+    assert!(!conn.is_halted());
+}
+
+#[test]
+fn handler_strips_body_for_not_modified() {
+    let handler = (caching_headers(), "static content");
+    let mut conn = get("/").on(&handler);
+    let etag = conn
+        .inner()
+        .response_headers()
+        .get_str(KnownHeaderName::Etag)
+        .unwrap()
+        .to_string();
+    assert_body!(&mut conn, "static content");
+
+    let mut conn = get("/")
+        .with_request_header(KnownHeaderName::IfNoneMatch, format!(r#""other", {etag}"#))
+        .on(&handler);
+    assert_status!(&conn, 304);
+    assert_eq!(conn.take_response_body_string(), None);
+}
+
+#[test]
+fn last_modified_is_clamped_and_truncated() {
+    use std::time::UNIX_EPOCH;
+
+    let before_epoch = UNIX_EPOCH - Duration::from_secs(60);
+    let conn = get("/")
+        .on(&move |conn: Conn| async move { conn.with_last_modified(before_epoch).ok("ok") });
+    assert_headers!(conn, "last-modified" => "Thu, 01 Jan 1970 00:00:00 GMT");
+
+    let fractional = UNIX_EPOCH + Duration::from_millis(784_111_777_999);
+    let conn =
+        get("/").on(&move |conn: Conn| async move { conn.with_last_modified(fractional).ok("ok") });
+    assert_headers!(conn, "last-modified" => "Sun, 06 Nov 1994 08:49:37 GMT");
+}
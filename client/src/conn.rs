@@ -281,7 +281,7 @@ impl<C: Connector> Conn<'_, C> {
     type Conn = trillium_client::Conn<'static, TcpConnector>;
 
     let handler = |conn: trillium::Conn| async move {
-        let header = conn.headers().get_str("some-request-header").unwrap_or_default();
+        let header = conn.request_headers().get_str("some-request-header").unwrap_or_default();
         let response = format!("some-request-header was {}", header);
         conn.ok(response)
     };
@@ -313,7 +313,7 @@ impl<C: Connector> Conn<'_, C> {
     type Conn = trillium_client::Conn<'static, TcpConnector>;
 
     let handler = |conn: trillium::Conn| async move {
-        let header = conn.headers().get_str("some-request-header").unwrap_or_default();
+        let header = conn.request_headers().get_str("some-request-header").unwrap_or_default();
         let response = format!("some-request-header was {}", header);
         conn.ok(response)
     };
@@ -0,0 +1,154 @@
+/// A policy for which response content types are worth compressing.
+///
+/// Already-compressed formats such as jpeg, mp4, and zip gain nothing
+/// from a second round of compression, so by default only the content
+/// types listed in [`CompressibleTypes::DEFAULT_ALLOWED`] are
+/// compressed, excluding [`CompressibleTypes::DEFAULT_DENIED`].
+/// Responses without a `Content-Type` header are always eligible for
+/// compression.
+///
+/// Patterns are matched case-insensitively against the content type
+/// with any parameters (such as `; charset=utf-8`) removed, and may take
+/// any of the following forms:
+///
+/// * an exact type, such as `application/json`
+/// * a wildcard subtype, such as `text/*`
+/// * a structured syntax suffix, such as `application/*+json`
+/// * `*/*`, which matches any content type
+///
+/// A content type is compressible if it matches at least one allowed
+/// pattern and no denied patterns.
+///
+/// ```
+/// use trillium_compression::CompressibleTypes;
+///
+/// let types = CompressibleTypes::default();
+/// assert!(types.is_compressible("text/html; charset=utf-8"));
+/// assert!(types.is_compressible("application/ld+json"));
+/// assert!(!types.is_compressible("image/jpeg"));
+/// assert!(!types.is_compressible("text/event-stream"));
+///
+/// let types = CompressibleTypes::default()
+///     .with_allowed("application/x-ndjson")
+///     .with_denied("text/csv");
+/// assert!(types.is_compressible("application/x-ndjson"));
+/// assert!(!types.is_compressible("text/csv"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressibleTypes {
+    allowed: Vec<String>,
+    denied: Vec<String>,
+}
+
+impl Default for CompressibleTypes {
+    fn default() -> Self {
+        Self {
+            allowed: Self::DEFAULT_ALLOWED
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            denied: Self::DEFAULT_DENIED.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl CompressibleTypes {
+    /// the content type patterns that are compressed by default
+    pub const DEFAULT_ALLOWED: &'static [&'static str] = &[
+        "text/*",
+        "application/javascript",
+        "application/json",
+        "application/wasm",
+        "application/x-javascript",
+        "application/xml",
+        "application/*+json",
+        "application/*+xml",
+        "application/vnd.ms-fontobject",
+        "font/otf",
+        "font/ttf",
+        "image/bmp",
+        "image/svg+xml",
+        "image/x-icon",
+    ];
+
+    /// the content type patterns that are excluded from compression
+    /// by default. server-sent events are excluded because
+    /// compression buffers the event stream.
+    pub const DEFAULT_DENIED: &'static [&'static str] = &["text/event-stream"];
+
+    /// constructs a new policy with the default allowed and denied
+    /// content types
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    constructs a policy with no allowed or denied content types,
+    which will not compress any response that has a content type
+
+    ```
+    use trillium_compression::CompressibleTypes;
+
+    let types = CompressibleTypes::none().with_allowed("text/html");
+    assert!(types.is_compressible("text/html"));
+    assert!(!types.is_compressible("text/plain"));
+    ```
+    */
+    pub fn none() -> Self {
+        Self {
+            allowed: vec![],
+            denied: vec![],
+        }
+    }
+
+    /// chainable setter to add a pattern to the allowed list
+    #[must_use]
+    pub fn with_allowed(mut self, pattern: impl Into<String>) -> Self {
+        self.allowed.push(pattern.into().to_ascii_lowercase());
+        self
+    }
+
+    /// chainable setter to add a pattern to the denied list. denied
+    /// patterns take precedence over allowed patterns.
+    #[must_use]
+    pub fn with_denied(mut self, pattern: impl Into<String>) -> Self {
+        self.denied.push(pattern.into().to_ascii_lowercase());
+        self
+    }
+
+    /// determines whether a response with the provided `Content-Type`
+    /// header value should be compressed
+    pub fn is_compressible(&self, content_type: &str) -> bool {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        let matches = |pattern: &String| pattern_matches(pattern, &essence);
+        self.allowed.iter().any(matches) && !self.denied.iter().any(matches)
+    }
+}
+
+fn pattern_matches(pattern: &str, essence: &str) -> bool {
+    let (pattern_type, pattern_subtype) = match pattern.split_once('/') {
+        Some(split) => split,
+        None => return false,
+    };
+
+    let (essence_type, essence_subtype) = match essence.split_once('/') {
+        Some(split) => split,
+        None => return false,
+    };
+
+    if pattern_type != "*" && pattern_type != essence_type {
+        return false;
+    }
+
+    match pattern_subtype.strip_prefix('*') {
+        Some("") => true,
+        Some(suffix) if suffix.starts_with('+') => essence_subtype.ends_with(suffix),
+        _ => pattern_subtype == essence_subtype,
+    }
+}
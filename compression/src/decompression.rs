@@ -0,0 +1,152 @@
+use crate::CompressionAlgorithm;
+use async_compression::futures::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder};
+use futures_lite::{io::BufReader, AsyncRead, AsyncReadExt};
+use std::{io, pin::Pin};
+use trillium::{async_trait, Conn, KnownHeaderName::ContentEncoding, Status};
+
+/// the most content codings that may be applied to a single request
+/// body. each coding multiplies the work of decoding the body, and
+/// there is no reason to apply more than one compression
+const MAX_CONTENT_CODINGS: usize = 2;
+
+/// the errors that can occur while decompressing a request body with
+/// [`DecompressionConnExt`]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum DecompressionError {
+    /// the request body was encoded with a content coding that this
+    /// crate does not support
+    #[error("unsupported content coding {0}")]
+    UnsupportedEncoding(String),
+
+    /// the request body was encoded with more content codings than
+    /// are accepted
+    #[error("request body has more than {0} content codings")]
+    TooManyEncodings(usize),
+
+    /// the decompressed body was longer than the maximum length
+    #[error("decompressed request body exceeded the maximum length of {0} bytes")]
+    TooLong(u64),
+
+    /// the body could not be read, or was not validly encoded
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl DecompressionError {
+    /// the response status that best describes this error: `415
+    /// Unsupported Media Type` for an unsupported coding, `413
+    /// Payload Too Large` for a body that is too long, and `400 Bad
+    /// Request` otherwise
+    pub fn status(&self) -> Status {
+        match self {
+            Self::UnsupportedEncoding(_) => Status::UnsupportedMediaType,
+            Self::TooLong(_) => Status::PayloadTooLarge,
+            Self::TooManyEncodings(_) | Self::Io(_) => Status::BadRequest,
+        }
+    }
+}
+
+/// parses the request `content-encoding` into the codings that were
+/// applied, in the order they were applied. `identity` is ignored
+fn content_codings(header: &str) -> Result<Vec<CompressionAlgorithm>, DecompressionError> {
+    let codings = header
+        .split(',')
+        .map(str::trim)
+        .filter(|coding| !coding.is_empty() && !coding.eq_ignore_ascii_case("identity"))
+        .map(|coding| {
+            coding
+                .parse()
+                .map_err(|_| DecompressionError::UnsupportedEncoding(coding.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if codings.len() > MAX_CONTENT_CODINGS {
+        return Err(DecompressionError::TooManyEncodings(MAX_CONTENT_CODINGS));
+    }
+
+    Ok(codings)
+}
+
+type Decoding<'a> = Pin<Box<dyn AsyncRead + Send + 'a>>;
+
+/**
+Extension trait to read a request body that was compressed with a
+`Content-Encoding`, from a [`trillium::Conn`]
+*/
+#[async_trait]
+pub trait DecompressionConnExt {
+    /**
+    reads the request body, removing each of the content codings
+    listed in its `Content-Encoding` header in the reverse of the
+    order they were applied, such that `gzip, br` is decoded as
+    brotli and then as gzip. a body without a `Content-Encoding` is
+    read as-is. `max_len` applies to the fully decoded body, and
+    decoding stops as soon as it is exceeded, so a small body that
+    would decompress to something very large is never held in memory.
+
+    ```
+    use trillium_compression::DecompressionConnExt;
+    use trillium_testing::prelude::*;
+
+    let mut conn = post("/")
+        .with_request_header("content-encoding", "br, identity")
+        .with_request_body(vec![0x0b, 0x02, 0x80, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x03])
+        .on(&());
+
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.request_body_decompressed(1024).await.unwrap(), b"hello");
+    # });
+    ```
+
+    # Errors
+
+    returns [`DecompressionError::UnsupportedEncoding`] for a coding
+    other than `br`, `gzip`, `deflate`, or `identity`, and
+    [`DecompressionError::TooManyEncodings`] for more than two
+    codings other than `identity`, before reading the body. returns [`DecompressionError::TooLong`] if the
+    decoded body is longer than `max_len`, and
+    [`DecompressionError::Io`] if the body cannot be read or any of
+    its codings is invalid. [`DecompressionError::status`] maps each
+    of these to a response status.
+    */
+    async fn request_body_decompressed(
+        &mut self,
+        max_len: u64,
+    ) -> Result<Vec<u8>, DecompressionError>;
+}
+
+#[async_trait]
+impl DecompressionConnExt for Conn {
+    async fn request_body_decompressed(
+        &mut self,
+        max_len: u64,
+    ) -> Result<Vec<u8>, DecompressionError> {
+        let codings = content_codings(
+            self.request_headers()
+                .get_str(ContentEncoding)
+                .unwrap_or_default(),
+        )?;
+
+        let mut body: Decoding<'_> = Box::pin(self.request_body().await);
+        for coding in codings.into_iter().rev() {
+            let reader = BufReader::new(body);
+            body = match coding {
+                CompressionAlgorithm::Brotli => Box::pin(BrotliDecoder::new(reader)),
+                CompressionAlgorithm::Gzip => Box::pin(GzipDecoder::new(reader)),
+                CompressionAlgorithm::Deflate => Box::pin(ZlibDecoder::new(reader)),
+            };
+        }
+
+        let mut decoded = vec![];
+        body.take(max_len.saturating_add(1))
+            .read_to_end(&mut decoded)
+            .await?;
+
+        if decoded.len() as u64 > max_len {
+            return Err(DecompressionError::TooLong(max_len));
+        }
+
+        Ok(decoded)
+    }
+}
@@ -2,12 +2,29 @@
 Body compression for trillium.rs
 
 Currently, this crate only supports compressing outbound bodies with
-the brotli and gzip algorithms, although more algorithms may be added
-in the future. The correct algorithm will be selected based on the
-Accept-Encoding header sent by the client, if one exists.
+the brotli, gzip, and deflate algorithms, although more algorithms may
+be added in the future. The correct algorithm will be selected based
+on the Accept-Encoding header sent by the client, if one exists.
 
 If both brotli and gzip are supported by a client, brotli will be used
-preferentially.
+preferentially. Deflate is only used if it is enabled with
+[`Compression::with_algorithms`]. Codings with a q-value of 0 are
+never used.
+
+When a response is not compressed, it is sent without a content
+coding unless the client forbade that with `identity;q=0`, or with
+`*;q=0` and no entry for `identity`, in which case a successful
+response is replaced with a 406 Not Acceptable.
+
+A `Content-Digest` or `Repr-Digest` response header no longer matches
+a body once it is compressed, so these are removed. With the
+`checksum` crate feature, they are instead recomputed for the
+compressed body with `Conn::with_content_digest` when it is held in
+memory.
+
+Request bodies sent with a `Content-Encoding` can be read with
+[`DecompressionConnExt::request_body_decompressed`], which removes
+each of the listed codings in turn.
 
 */
 #![forbid(unsafe_code)]
@@ -20,7 +37,13 @@ preferentially.
 )]
 #![warn(missing_docs)]
 
-use async_compression::futures::bufread::{BrotliEncoder, GzipEncoder};
+mod compressible_types;
+pub use compressible_types::CompressibleTypes;
+
+mod decompression;
+pub use decompression::{DecompressionConnExt, DecompressionError};
+
+use async_compression::futures::bufread::{BrotliEncoder, GzipEncoder, ZlibEncoder};
 use futures_lite::{
     io::{BufReader, Cursor},
     AsyncReadExt,
@@ -31,8 +54,9 @@ use std::{
     str::FromStr,
 };
 use trillium::{
-    async_trait, conn_try, conn_unwrap, Body, Conn, Handler, HeaderValues,
-    KnownHeaderName::{AcceptEncoding, ContentEncoding, Vary},
+    async_trait, conn_try, conn_unwrap, Body, Conn, Handler,
+    KnownHeaderName::{AcceptEncoding, ContentEncoding, ContentType},
+    Status,
 };
 
 /// Algorithms supported by this crate
@@ -44,6 +68,10 @@ pub enum CompressionAlgorithm {
 
     /// Gzip algorithm
     Gzip,
+
+    /// Deflate algorithm, which is the zlib format. this is not
+    /// enabled by default, as gzip is equivalent and better supported
+    Deflate,
 }
 
 impl CompressionAlgorithm {
@@ -51,6 +79,7 @@ impl CompressionAlgorithm {
         match self {
             CompressionAlgorithm::Brotli => "br",
             CompressionAlgorithm::Gzip => "gzip",
+            CompressionAlgorithm::Deflate => "deflate",
         }
     }
 
@@ -59,6 +88,7 @@ impl CompressionAlgorithm {
             "br" => Some(CompressionAlgorithm::Brotli),
             "gzip" => Some(CompressionAlgorithm::Gzip),
             "x-gzip" => Some(CompressionAlgorithm::Gzip),
+            "deflate" => Some(CompressionAlgorithm::Deflate),
             _ => None,
         }
     }
@@ -92,6 +122,8 @@ Trillium handler for compression
 #[derive(Clone, Debug)]
 pub struct Compression {
     algorithms: BTreeSet<CompressionAlgorithm>,
+    compressible_types: CompressibleTypes,
+    min_length: u64,
 }
 
 impl Default for Compression {
@@ -100,6 +132,8 @@ impl Default for Compression {
             algorithms: [CompressionAlgorithm::Brotli, CompressionAlgorithm::Gzip]
                 .into_iter()
                 .collect(),
+            compressible_types: CompressibleTypes::default(),
+            min_length: 0,
         }
     }
 }
@@ -124,11 +158,58 @@ impl Compression {
         self
     }
 
+    /**
+    sets the policy for which response content types this handler
+    will compress. see [`CompressibleTypes`] for the defaults.
+
+    ```
+    use trillium_compression::{Compression, CompressibleTypes};
+    let compression = Compression::new()
+        .with_compressible_types(CompressibleTypes::default().with_denied("text/csv"));
+    ```
+    */
+    pub fn with_compressible_types(mut self, compressible_types: CompressibleTypes) -> Self {
+        self.compressible_types = compressible_types;
+        self
+    }
+
+    /**
+    sets the minimum length in bytes of a response body that this
+    handler will compress. bodies of unknown length are always
+    compressed. the default is 0, which compresses any body that
+    gets smaller as a result.
+
+    ```
+    use trillium_compression::Compression;
+    let compression = Compression::new().with_min_length(1024);
+    ```
+    */
+    pub fn with_min_length(mut self, min_length: u64) -> Self {
+        self.min_length = min_length;
+        self
+    }
+
+    fn is_compressible(&self, conn: &mut Conn) -> bool {
+        if conn.response_headers_mut().has_header(ContentEncoding) {
+            return false;
+        }
+
+        if matches!(conn.inner().response_body().and_then(Body::len), Some(len) if len < self.min_length)
+        {
+            return false;
+        }
+
+        match conn.response_headers_mut().get_str(ContentType) {
+            Some(content_type) => self.compressible_types.is_compressible(content_type),
+            None => true,
+        }
+    }
+
     fn negotiate(&self, header: &str) -> Option<CompressionAlgorithm> {
         parse_accept_encoding(header)
             .into_iter()
-            .find_map(|(algo, _)| {
-                if self.algorithms.contains(&algo) {
+            .find_map(|(algo, q)| {
+                if q > 0 && self.algorithms.contains(&algo) {
                     Some(algo)
                 } else {
                     None
@@ -163,11 +244,122 @@ fn parse_accept_encoding(header: &str) -> Vec<(CompressionAlgorithm, u8)> {
     vec
 }
 
+/// whether the client has stated that a response without a content
+/// coding is not acceptable, with `identity;q=0`, or with `*;q=0` and
+/// no entry for identity
+fn identity_forbidden(header: &str) -> bool {
+    let mut wildcard_forbidden = false;
+    for coding in header.split(',') {
+        let mut iter = coding.trim().split(';');
+        let coding = iter.next().unwrap_or_default().trim();
+        let forbidden = iter.any(|param| {
+            param
+                .trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+
+        if coding.eq_ignore_ascii_case("identity") {
+            return forbidden;
+        } else if coding == "*" {
+            wildcard_forbidden = forbidden;
+        }
+    }
+    wildcard_forbidden
+}
+
+async fn compress(mut conn: Conn, algo: CompressionAlgorithm) -> Conn {
+    let mut body = conn_unwrap!(conn.inner_mut().take_response_body(), conn);
+    let mut compression_used = false;
+
+    if body.is_static() {
+        match algo {
+            CompressionAlgorithm::Brotli => {
+                let bytes = body.static_bytes().unwrap();
+                let mut data = vec![];
+                let mut encoder = BrotliEncoder::new(Cursor::new(bytes));
+                conn_try!(encoder.read_to_end(&mut data).await, conn);
+                if data.len() < bytes.len() {
+                    log::trace!("brotli'd body from {} to {}", bytes.len(), data.len());
+                    compression_used = true;
+                    body = Body::new_static(data);
+                }
+            }
+
+            CompressionAlgorithm::Gzip => {
+                let bytes = body.static_bytes().unwrap();
+                let mut data = vec![];
+                let mut encoder = GzipEncoder::new(Cursor::new(bytes));
+                conn_try!(encoder.read_to_end(&mut data).await, conn);
+                if data.len() < bytes.len() {
+                    log::trace!("gzipped body from {} to {}", bytes.len(), data.len());
+                    body = Body::new_static(data);
+                    compression_used = true;
+                }
+            }
+
+            CompressionAlgorithm::Deflate => {
+                let bytes = body.static_bytes().unwrap();
+                let mut data = vec![];
+                let mut encoder = ZlibEncoder::new(Cursor::new(bytes));
+                conn_try!(encoder.read_to_end(&mut data).await, conn);
+                if data.len() < bytes.len() {
+                    log::trace!("deflated body from {} to {}", bytes.len(), data.len());
+                    body = Body::new_static(data);
+                    compression_used = true;
+                }
+            }
+        }
+    } else if body.is_streaming() {
+        compression_used = true;
+        match algo {
+            CompressionAlgorithm::Brotli => {
+                body = Body::new_streaming(
+                    BrotliEncoder::new(BufReader::new(body.into_reader())),
+                    None,
+                );
+            }
+
+            CompressionAlgorithm::Gzip => {
+                body =
+                    Body::new_streaming(GzipEncoder::new(BufReader::new(body.into_reader())), None);
+            }
+
+            CompressionAlgorithm::Deflate => {
+                body =
+                    Body::new_streaming(ZlibEncoder::new(BufReader::new(body.into_reader())), None);
+            }
+        }
+    }
+
+    if !compression_used {
+        return conn.with_body(body);
+    }
+
+    conn.response_headers_mut()
+        .insert(ContentEncoding, algo.as_str());
+    conn.add_vary(AcceptEncoding.as_ref());
+
+    // any digest was of the uncompressed body
+    let headers = conn.response_headers_mut();
+    #[cfg_attr(not(feature = "checksum"), allow(unused_variables))]
+    let digested =
+        headers.remove("content-digest").is_some() | headers.remove("repr-digest").is_some();
+
+    conn = conn.with_body(body);
+    #[cfg(feature = "checksum")]
+    if digested {
+        conn = conn.with_content_digest();
+    }
+    conn
+}
+
 #[async_trait]
 impl Handler for Compression {
     async fn run(&self, mut conn: Conn) -> Conn {
         if let Some(header) = conn
-            .headers()
+            .request_headers()
             .get_str(AcceptEncoding)
             .and_then(|h| self.negotiate(h))
         {
@@ -178,68 +370,25 @@ impl Handler for Compression {
 
     async fn before_send(&self, mut conn: Conn) -> Conn {
         if let Some(algo) = conn.state::<CompressionAlgorithm>().copied() {
-            let mut body = conn_unwrap!(conn.inner_mut().take_response_body(), conn);
-            let mut compression_used = false;
-
-            if body.is_static() {
-                match algo {
-                    CompressionAlgorithm::Brotli => {
-                        let bytes = body.static_bytes().unwrap();
-                        let mut data = vec![];
-                        let mut encoder = BrotliEncoder::new(Cursor::new(bytes));
-                        conn_try!(encoder.read_to_end(&mut data).await, conn);
-                        if data.len() < bytes.len() {
-                            log::trace!("brotli'd body from {} to {}", bytes.len(), data.len());
-                            compression_used = true;
-                            body = Body::new_static(data);
-                        }
-                    }
-
-                    CompressionAlgorithm::Gzip => {
-                        let bytes = body.static_bytes().unwrap();
-                        let mut data = vec![];
-                        let mut encoder = GzipEncoder::new(Cursor::new(bytes));
-                        conn_try!(encoder.read_to_end(&mut data).await, conn);
-                        if data.len() < bytes.len() {
-                            log::trace!("gzipped body from {} to {}", bytes.len(), data.len());
-                            body = Body::new_static(data);
-                            compression_used = true;
-                        }
-                    }
-                }
-            } else if body.is_streaming() {
-                compression_used = true;
-                match algo {
-                    CompressionAlgorithm::Brotli => {
-                        body = Body::new_streaming(
-                            BrotliEncoder::new(BufReader::new(body.into_reader())),
-                            None,
-                        );
-                    }
-
-                    CompressionAlgorithm::Gzip => {
-                        body = Body::new_streaming(
-                            GzipEncoder::new(BufReader::new(body.into_reader())),
-                            None,
-                        );
-                    }
-                }
+            if self.is_compressible(&mut conn) {
+                conn = compress(conn, algo).await;
             }
+        }
 
-            if compression_used {
-                let vary = conn
-                    .headers_mut()
-                    .get_str(Vary)
-                    .map(|vary| HeaderValues::from(format!("{}, Accept-Encoding", vary)))
-                    .unwrap_or_else(|| HeaderValues::from("Accept-Encoding"));
-
-                conn.headers_mut().extend([
-                    (ContentEncoding, HeaderValues::from(algo.as_str())),
-                    (Vary, vary),
-                ]);
-            }
+        let identity_forbidden = conn
+            .request_headers()
+            .get_str(AcceptEncoding)
+            .is_some_and(identity_forbidden);
 
-            conn.with_body(body)
+        if identity_forbidden
+            && conn.effective_status().is_success()
+            && conn.has_response_body()
+            && !conn.response_headers_mut().has_header(ContentEncoding)
+        {
+            log::debug!("client does not accept identity, and the response was not compressed");
+            conn.inner_mut().take_response_body();
+            conn.add_vary(AcceptEncoding.as_ref());
+            conn.with_status(Status::NotAcceptable)
         } else {
             conn
         }
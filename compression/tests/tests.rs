@@ -1,4 +1,6 @@
-use trillium::KnownHeaderName::{AcceptEncoding, ContentEncoding, ContentLength, Vary};
+use trillium::KnownHeaderName::{
+    AcceptEncoding, ContentEncoding, ContentLength, ContentType, Vary,
+};
 use trillium_testing::prelude::*;
 
 static COMPRESSIBLE_CONTENT: &str = r#"
@@ -70,3 +72,371 @@ fn test() {
         ContentEncoding => "br"
     );
 }
+
+#[test]
+fn content_types() {
+    use trillium::{Conn, Handler, KnownHeaderName::ContentType};
+    use trillium_compression::{CompressibleTypes, Compression};
+
+    fn with_content_type(content_type: &'static str) -> impl Handler {
+        move |conn: Conn| async move {
+            conn.with_header(ContentType, content_type)
+                .ok(COMPRESSIBLE_CONTENT)
+        }
+    }
+
+    let compression = Compression::new();
+    for (content_type, compressed) in [
+        ("text/plain", true),
+        ("text/html; charset=utf-8", true),
+        ("Application/JSON", true),
+        ("application/ld+json", true),
+        ("image/svg+xml", true),
+        ("image/jpeg", false),
+        ("video/mp4", false),
+        ("application/zip", false),
+        ("text/event-stream", false),
+    ] {
+        let handler = (compression.clone(), with_content_type(content_type));
+        let expected = if compressed { Some("gzip") } else { None };
+        assert_eq!(
+            get("/")
+                .with_request_header(AcceptEncoding, "gzip")
+                .on(&handler)
+                .response_headers_mut()
+                .get_str(ContentEncoding),
+            expected,
+            "content type {}",
+            content_type
+        );
+    }
+
+    let handler = (
+        Compression::new().with_compressible_types(
+            CompressibleTypes::none()
+                .with_allowed("*/*")
+                .with_denied("text/*"),
+        ),
+        with_content_type("image/jpeg"),
+    );
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentEncoding => "gzip"
+    );
+
+    let handler = (
+        Compression::new().with_compressible_types(
+            CompressibleTypes::none()
+                .with_allowed("*/*")
+                .with_denied("text/*"),
+        ),
+        with_content_type("text/plain"),
+    );
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentEncoding => None
+    );
+}
+
+#[test]
+fn already_encoded() {
+    use trillium::Conn;
+    let handler = (
+        trillium_compression::compression(),
+        |conn: Conn| async move {
+            conn.with_header(ContentEncoding, "br")
+                .ok(COMPRESSIBLE_CONTENT)
+        },
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentLength => "500",
+        ContentEncoding => "br",
+        Vary => None,
+    );
+}
+
+#[test]
+fn deflate() {
+    use trillium_compression::{Compression, CompressionAlgorithm};
+    let handler = (
+        Compression::new()
+            .with_algorithms(&[CompressionAlgorithm::Gzip, CompressionAlgorithm::Deflate]),
+        COMPRESSIBLE_CONTENT,
+    );
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "deflate")
+        .on(&handler);
+    assert_headers!(&mut conn, Vary => "Accept-Encoding", ContentEncoding => "deflate");
+    let body = conn.take_response_body().unwrap();
+    let body = trillium_testing::block_on(body.into_bytes()).unwrap();
+    assert!(body.len() < COMPRESSIBLE_CONTENT.len());
+    assert_eq!(body[0], 0x78, "zlib header");
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "deflate, gzip")
+            .on(&handler),
+        ContentEncoding => "gzip"
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "br")
+            .on(&handler),
+        ContentLength => "500",
+        ContentEncoding => None
+    );
+}
+
+#[test]
+fn min_length() {
+    use trillium::{Body, Conn};
+    use trillium_compression::Compression;
+    let compression = Compression::new().with_min_length(501);
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&(compression.clone(), COMPRESSIBLE_CONTENT)),
+        ContentLength => "500",
+        ContentEncoding => None
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&(compression.clone().with_min_length(500), COMPRESSIBLE_CONTENT)),
+        ContentLength => "77",
+        ContentEncoding => "gzip"
+    );
+
+    let streaming = |conn: Conn| async move {
+        conn.ok(Body::new_streaming(
+            trillium_testing::futures_lite::io::Cursor::new(COMPRESSIBLE_CONTENT),
+            None,
+        ))
+    };
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&(compression, streaming)),
+        ContentEncoding => "gzip"
+    );
+}
+
+#[test]
+fn identity_fallback() {
+    use trillium::Conn;
+    use trillium_compression::Compression;
+
+    let disabled = (
+        Compression::new().with_algorithms(&[]),
+        COMPRESSIBLE_CONTENT,
+    );
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip")
+        .on(&disabled);
+    assert_headers!(&mut conn, ContentEncoding => None);
+    assert_ok!(conn, COMPRESSIBLE_CONTENT);
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip, identity;q=0")
+        .on(&disabled);
+    assert_status!(&conn, 406);
+    assert_headers!(&mut conn, Vary => "Accept-Encoding");
+    assert_eq!(conn.take_response_body_string(), None);
+
+    let conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip, *;q=0")
+        .on(&disabled);
+    assert_status!(&conn, 406);
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip, identity, *;q=0")
+        .on(&disabled);
+    assert_headers!(&mut conn, ContentEncoding => None);
+    assert_ok!(conn, COMPRESSIBLE_CONTENT);
+
+    let excluded = (
+        trillium_compression::compression(),
+        |conn: Conn| async move {
+            conn.with_header(ContentType, "image/png")
+                .ok(COMPRESSIBLE_CONTENT)
+        },
+    );
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip")
+        .on(&excluded);
+    assert_headers!(&mut conn, ContentEncoding => None);
+    assert_ok!(conn, COMPRESSIBLE_CONTENT);
+
+    let conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip, identity;q=0")
+        .on(&excluded);
+    assert_status!(&conn, 406);
+
+    let without_status = (
+        trillium_compression::compression(),
+        |conn: Conn| async move {
+            conn.with_header(ContentType, "image/png")
+                .with_body(COMPRESSIBLE_CONTENT)
+        },
+    );
+    let conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip, identity;q=0")
+        .on(&without_status);
+    assert_status!(&conn, 406);
+
+    let handler = (trillium_compression::compression(), COMPRESSIBLE_CONTENT);
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip, identity;q=0")
+            .on(&handler),
+        ContentEncoding => "gzip"
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "br;q=0, gzip")
+            .on(&handler),
+        ContentEncoding => "gzip"
+    );
+}
+
+#[test]
+fn stacked_request_content_encodings() {
+    use async_compression::futures::bufread::{BrotliEncoder, GzipEncoder};
+    use futures_lite::{io::Cursor, AsyncReadExt};
+    use trillium_compression::{DecompressionConnExt, DecompressionError};
+
+    async fn encode(bytes: Vec<u8>, gzip: bool) -> Vec<u8> {
+        let mut encoded = vec![];
+        if gzip {
+            GzipEncoder::new(Cursor::new(bytes))
+                .read_to_end(&mut encoded)
+                .await
+                .unwrap();
+        } else {
+            BrotliEncoder::new(Cursor::new(bytes))
+                .read_to_end(&mut encoded)
+                .await
+                .unwrap();
+        }
+        encoded
+    }
+
+    trillium_testing::block_on(async {
+        let content = COMPRESSIBLE_CONTENT.as_bytes().to_vec();
+
+        // gzip was applied first and brotli second, so brotli is
+        // removed first
+        let body = encode(encode(content.clone(), true).await, false).await;
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "GZIP, br")
+            .with_request_body(body)
+            .on(&());
+        assert_eq!(conn.request_body_decompressed(1024).await.unwrap(), content);
+
+        let body = encode(encode(content.clone(), true).await, true).await;
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "gzip, gzip")
+            .with_request_body(body.clone())
+            .on(&());
+        assert_eq!(conn.request_body_decompressed(1024).await.unwrap(), content);
+
+        // the limit applies to the decoded body
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "gzip, gzip")
+            .with_request_body(body)
+            .on(&());
+        let error = conn.request_body_decompressed(100).await.unwrap_err();
+        assert!(matches!(error, DecompressionError::TooLong(100)));
+        assert_eq!(error.status(), Status::PayloadTooLarge);
+
+        // a coding that does not match the body
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "gzip")
+            .with_request_body(content.clone())
+            .on(&());
+        let error = conn.request_body_decompressed(1024).await.unwrap_err();
+        assert!(matches!(error, DecompressionError::Io(_)));
+        assert_eq!(error.status(), Status::BadRequest);
+
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "gzip, zstd")
+            .with_request_body(content.clone())
+            .on(&());
+        let error = conn.request_body_decompressed(1024).await.unwrap_err();
+        assert!(
+            matches!(&error, DecompressionError::UnsupportedEncoding(coding) if coding == "zstd")
+        );
+        assert_eq!(error.status(), Status::UnsupportedMediaType);
+        assert_eq!(
+            conn.request_body_string().await.unwrap(),
+            COMPRESSIBLE_CONTENT
+        );
+
+        // codings may not be stacked without limit
+        let mut conn = post("/")
+            .with_request_header(ContentEncoding, "gzip, identity, br, gzip")
+            .with_request_body(content.clone())
+            .on(&());
+        let error = conn.request_body_decompressed(1024).await.unwrap_err();
+        assert!(matches!(error, DecompressionError::TooManyEncodings(2)));
+        assert_eq!(error.status(), Status::BadRequest);
+        assert_eq!(
+            conn.request_body_string().await.unwrap(),
+            COMPRESSIBLE_CONTENT
+        );
+
+        let mut conn = post("/").with_request_body(content.clone()).on(&());
+        assert_eq!(conn.request_body_decompressed(1024).await.unwrap(), content);
+    });
+}
+
+#[test]
+fn digests_of_the_uncompressed_body_are_not_sent() {
+    let handler = (
+        trillium_compression::compression(),
+        |conn: trillium::Conn| async move {
+            conn.with_header("content-digest", "sha-256=:uncompressed:")
+                .with_header("repr-digest", "sha-256=:uncompressed:")
+                .ok(COMPRESSIBLE_CONTENT)
+        },
+    );
+
+    let mut conn = get("/")
+        .with_request_header(AcceptEncoding, "gzip")
+        .with_request_header("want-repr-digest", "sha-256=1")
+        .on(&handler);
+    assert_headers!(&mut conn, ContentEncoding => "gzip");
+
+    #[cfg(not(feature = "checksum"))]
+    assert_headers!(&mut conn, "content-digest" => None, "repr-digest" => None);
+
+    #[cfg(feature = "checksum")]
+    {
+        let body = conn.take_response_body().unwrap();
+        let body = trillium_testing::block_on(body.into_bytes()).unwrap();
+        let digest = get("/")
+            .with_request_header("want-repr-digest", "sha-256=1")
+            .on(&move |conn: trillium::Conn| {
+                let body = body.clone();
+                async move { conn.ok(body.to_vec()).with_content_digest() }
+            });
+        let digest = digest.response_headers().get_str("content-digest").unwrap();
+        assert_ne!(digest, "sha-256=:uncompressed:");
+        assert_headers!(&mut conn, "content-digest" => digest, "repr-digest" => digest);
+    }
+}
@@ -183,7 +183,7 @@ impl ConnId {
     );
 
     // assert that the id is a valid uuid, even if we can't assert a specific value
-    assert!(Uuid::parse_str(get("/").on(&app).headers_mut().get_str("x-request-id").unwrap()).is_ok());
+    assert!(Uuid::parse_str(get("/").on(&app).response_headers().get_str("x-request-id").unwrap()).is_ok());
     ```
     */
     pub fn with_id_generator<F>(mut self, id_generator: F) -> Self
@@ -232,12 +232,12 @@ impl Handler for ConnId {
         let id = self
             .request_header
             .as_ref()
-            .and_then(|request_header| conn.headers().get_str(request_header.clone()))
+            .and_then(|request_header| conn.request_headers().get_str(request_header.clone()))
             .map(|request_header| Id(request_header.to_string()))
             .unwrap_or_else(|| self.generate_id());
 
         if let Some(ref response_header) = self.response_header {
-            conn.headers_mut()
+            conn.response_headers_mut()
                 .insert(response_header.clone(), id.to_string());
         }
 
@@ -250,6 +250,30 @@ pub trait ConnIdExt {
     /// Retrieves the id for this conn. This method will panic if it
     /// is run before the [`ConnId`] handler.
     fn id(&self) -> &str;
+
+    /**
+    Retrieves the id for this conn, or `None` if the [`ConnId`]
+    handler has not run. This is useful in handlers that may be
+    mounted without a [`ConnId`].
+
+    ```
+    # use trillium_testing::prelude::*;
+    # use trillium_conn_id::{ConnId, ConnIdExt};
+    let handler = |conn: Conn| async move {
+        let id = conn.request_id().unwrap_or("none").to_string();
+        conn.ok(id)
+    };
+
+    assert_ok!(get("/").on(&handler), "none");
+    assert_ok!(
+        get("/")
+            .with_request_header("x-request-id", "inbound-id")
+            .on(&(ConnId::new(), handler)),
+        "inbound-id"
+    );
+    ```
+    */
+    fn request_id(&self) -> Option<&str>;
 }
 
 impl<ConnLike> ConnIdExt for ConnLike
@@ -261,6 +285,10 @@ where
             .get::<Id>()
             .expect("ConnId handler must be run before calling IdConnExt::id")
     }
+
+    fn request_id(&self) -> Option<&str> {
+        self.as_ref().get::<Id>().map(|id| &**id)
+    }
 }
 
 /// Formatter for the trillium_log crate
@@ -17,9 +17,11 @@ fn test_defaults() {
 
     let conn = get("/").on(&app);
     assert_eq!(conn.id(), "iHxXDjwzU5");
+    assert_eq!(conn.request_id(), Some("iHxXDjwzU5"));
     assert_eq!(log_formatter::conn_id(&conn, true), "iHxXDjwzU5");
 
     let conn = TestConn::build("get", "/", ());
+    assert_eq!(conn.request_id(), None);
     assert_eq!(log_formatter::conn_id(&conn, true), "-");
 }
 
@@ -35,7 +37,7 @@ fn test_settings() {
 
     let mut conn = get("/").on(&app);
 
-    assert!(Uuid::parse_str(conn.headers_mut().get_str("x-something-else").unwrap()).is_ok());
+    assert!(Uuid::parse_str(conn.response_headers().get_str("x-something-else").unwrap()).is_ok());
     assert!(Uuid::parse_str(conn.id()).is_ok());
     assert!(Uuid::parse_str(&log_formatter::conn_id(&conn, true)).is_ok());
 
@@ -58,12 +60,12 @@ fn test_no_headers() {
     );
 
     let mut conn = get("/").on(&app);
-    assert!(conn.headers_mut().get("x-request-id").is_none());
+    assert!(conn.response_headers().get("x-request-id").is_none());
     assert_eq!(conn.id(), "U14baHj9ho");
 
     let mut conn = get("/")
         .with_request_header("x-request-id", "ignored")
         .on(&app);
     assert_eq!(conn.id(), "AawiNNFjGW");
-    assert!(conn.headers_mut().get("x-request-id").is_none());
+    assert!(conn.response_headers().get("x-request-id").is_none());
 }
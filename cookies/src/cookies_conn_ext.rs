@@ -1,15 +1,119 @@
+use crate::request_cookies;
 use cookie::{Cookie, CookieJar};
-use trillium::Conn;
+use trillium::{Conn, KnownHeaderName};
 
 /**
 Extension trait adding cookie capacities to [`Conn`].
 
 Important: The [`CookiesHandler`](crate::CookiesHandler) must be
-called before any of these functions can be called on a conn.
+called before [`CookiesConnExt::cookies`] or
+[`CookiesConnExt::cookies_mut`] can be called on a conn. The other
+functions can be used without it.
 */
 pub trait CookiesConnExt {
-    /// adds a cookie to the cookie jar and returns the conn
+    /**
+    adds a cookie to the response and returns the conn. see
+    [`CookiesConnExt::set_cookie`]
+    */
     fn with_cookie(self, cookie: Cookie<'_>) -> Self;
+
+    /**
+    adds a cookie to the response. if the
+    [`CookiesHandler`](crate::CookiesHandler) has run, the cookie is
+    added to the cookie jar and sent along with any other changes to
+    the jar. otherwise, a `Set-Cookie` header is appended to the
+    response immediately. in either case, other `Set-Cookie` headers
+    on the response are preserved.
+
+    ```
+    use trillium::Conn;
+    use trillium_cookies::{cookie::Cookie, CookiesConnExt};
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: Conn| async move {
+        conn.with_header("set-cookie", "existing=1")
+            .with_cookie(Cookie::new("theme", "dark"))
+            .ok("ok")
+    };
+
+    let conn = get("/").on(&handler);
+    let set_cookie = conn.inner().response_headers().get_values("set-cookie").unwrap();
+    assert_eq!(
+        set_cookie.iter().filter_map(|value| value.as_str()).collect::<Vec<_>>(),
+        ["existing=1", "theme=dark"]
+    );
+    ```
+    */
+    fn set_cookie(&mut self, cookie: Cookie<'static>);
+
+    /**
+    returns the raw value of the named cookie from the request's
+    `Cookie` header, if present. this does not require the
+    [`CookiesHandler`](crate::CookiesHandler), and does not
+    percent-decode the value.
+
+    ```
+    use trillium::Conn;
+    use trillium_cookies::CookiesConnExt;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: Conn| async move {
+        let token = conn.cookie("token").unwrap_or("none").to_string();
+        conn.ok(token)
+    };
+
+    assert_ok!(
+        get("/").with_request_header("cookie", "theme=dark; token=abc==; x=y").on(&handler),
+        "abc=="
+    );
+    assert_ok!(get("/").on(&handler), "none");
+    ```
+    */
+    fn cookie(&self, name: &str) -> Option<&str>;
+
+    /**
+    removes a cookie from the client and returns the conn. see
+    [`CookiesConnExt::remove_cookie`]
+    */
+    fn with_removed_cookie(self, cookie: Cookie<'_>) -> Self;
+
+    /**
+    asks the client to delete a cookie by sending a `Set-Cookie` with
+    the same name, an empty value, `Max-Age=0`, and an `Expires` in
+    the past. a cookie is only deleted if the path and domain match
+    those of the original cookie, so set them on the provided cookie
+    if the original had them. any value on the provided cookie is
+    ignored. like [`CookiesConnExt::set_cookie`], this does not
+    require the [`CookiesHandler`](crate::CookiesHandler).
+
+    ```
+    use trillium::Conn;
+    use trillium_cookies::{cookie::Cookie, CookiesConnExt};
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: Conn| async move {
+        let session = Cookie::build("session", "").path("/").domain("example.com").finish();
+        conn.with_removed_cookie(session).ok("logged out")
+    };
+
+    let conn = get("/").with_request_header("cookie", "session=abc").on(&handler);
+    let set_cookie = conn.inner().response_headers().get_str("set-cookie").unwrap();
+    assert!(set_cookie.starts_with("session=;"));
+    assert!(set_cookie.contains("Path=/"));
+    assert!(set_cookie.contains("Domain=example.com"));
+    assert!(set_cookie.contains("Max-Age=0"));
+    assert!(set_cookie.contains("Expires="));
+
+    let conn = get("/")
+        .with_request_header("cookie", "session=abc")
+        .on(&(trillium_cookies::CookiesHandler::new(), handler));
+    let set_cookie = conn.inner().response_headers().get_str("set-cookie").unwrap();
+    assert!(set_cookie.starts_with("session=;"));
+    assert!(set_cookie.contains("Max-Age=0"));
+    ```
+    */
+    fn remove_cookie(&mut self, cookie: Cookie<'_>);
+
     /// gets a reference to the cookie jar
     fn cookies(&self) -> &CookieJar;
     /// gets a mutable reference to the cookie jar
@@ -23,10 +127,36 @@ impl CookiesConnExt for Conn {
     }
 
     fn with_cookie(mut self, cookie: Cookie<'_>) -> Self {
-        self.cookies_mut().add(cookie.into_owned());
+        self.set_cookie(cookie.into_owned());
+        self
+    }
+
+    fn set_cookie(&mut self, cookie: Cookie<'static>) {
+        match self.state_mut::<CookieJar>() {
+            Some(jar) => jar.add(cookie),
+            None => self
+                .response_headers_mut()
+                .append(KnownHeaderName::SetCookie, cookie.encoded().to_string()),
+        }
+    }
+
+    fn cookie(&self, name: &str) -> Option<&str> {
+        request_cookies::get(self.request_headers(), name)
+    }
+
+    fn with_removed_cookie(mut self, cookie: Cookie<'_>) -> Self {
+        self.remove_cookie(cookie);
         self
     }
 
+    fn remove_cookie(&mut self, cookie: Cookie<'_>) {
+        // this is added rather than removed from the jar, because the
+        // jar only sends a removal for cookies that were in the request
+        let mut cookie = cookie.into_owned();
+        cookie.make_removal();
+        self.set_cookie(cookie);
+    }
+
     fn cookies_mut(&mut self) -> &mut CookieJar {
         self.state_mut()
             .expect("Cookies handler must be executed before calling CookiesExt::cookies_mut")
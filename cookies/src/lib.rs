@@ -52,4 +52,6 @@ pub use cookies_handler::{cookies, CookiesHandler};
 mod cookies_conn_ext;
 pub use cookies_conn_ext::CookiesConnExt;
 
+mod request_cookies;
+
 pub use cookie;
@@ -0,0 +1,29 @@
+use trillium::{HeaderValue, Headers, KnownHeaderName};
+
+/**
+iterates over the name-value pairs in the request's `Cookie`
+headers, without decoding them. pairs are separated by `;`, and each
+pair is split on its first `=`, so values may themselves contain `=`.
+*/
+pub(crate) fn iter(headers: &Headers) -> impl Iterator<Item = (&str, &str)> {
+    headers
+        .get_values(KnownHeaderName::Cookie)
+        .into_iter()
+        .flat_map(|values| values.iter())
+        .filter_map(HeaderValue::as_str)
+        .flat_map(|header| header.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                None
+            } else {
+                Some((name, value.trim()))
+            }
+        })
+}
+
+/// returns the value of the first request cookie with this name
+pub(crate) fn get<'a>(headers: &'a Headers, name: &str) -> Option<&'a str> {
+    iter(headers).find_map(|(cookie_name, value)| (cookie_name == name).then_some(value))
+}
@@ -222,14 +222,7 @@ impl<'a> Forwarded<'a> {
     }
 
     fn parse_forwarded_pair(&mut self, input: &'a str) -> Result<&'a str, ParseError> {
-        let (key, value, rest) = match parse_token(input) {
-            (Some(key), rest) if rest.starts_with('=') => match parse_value(&rest[1..]) {
-                (Some(value), rest) => Some((key, value, rest)),
-                (None, _) => None,
-            },
-            _ => None,
-        }
-        .ok_or_else(|| ParseError::new("parse error in forwarded-pair"))?;
+        let (key, value, rest) = parse_forwarded_pair(input)?;
 
         match key {
             "by" => {
@@ -402,14 +395,26 @@ impl std::fmt::Display for Forwarded<'_> {
     }
 }
 
-fn parse_value(input: &str) -> (Option<Cow<'_, str>>, &str) {
+pub(crate) fn parse_value(input: &str) -> (Option<Cow<'_, str>>, &str) {
     match parse_token(input) {
         (Some(token), rest) => (Some(Cow::Borrowed(token)), rest),
         (None, rest) => parse_quoted_string(rest),
     }
 }
 
-fn format_value(input: &str) -> Cow<'_, str> {
+/// parses a single `key=value` forwarded-pair, returning the key, the
+/// unquoted value, and the remaining input
+pub(crate) fn parse_forwarded_pair(input: &str) -> Result<(&str, Cow<'_, str>, &str), ParseError> {
+    match parse_token(input) {
+        (Some(key), rest) if rest.starts_with('=') => match parse_value(&rest[1..]) {
+            (Some(value), rest) => Ok((key, value, rest)),
+            (None, _) => Err(ParseError::new("parse error in forwarded-pair")),
+        },
+        _ => Err(ParseError::new("parse error in forwarded-pair")),
+    }
+}
+
+pub(crate) fn format_value(input: &str) -> Cow<'_, str> {
     match parse_token(input) {
         (_, "") => input.into(),
         _ => {
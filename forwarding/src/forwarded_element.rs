@@ -0,0 +1,558 @@
+use crate::forwarded::{format_value, parse_forwarded_pair, ParseError};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter, Write},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use trillium::{Headers, KnownHeaderName::Forwarded as ForwardedHeader};
+
+/**
+A single forwarded-element of a [forwarded
+header](https://tools.ietf.org/html/rfc7239#section-4), describing one
+hop of a request through a chain of proxies.
+
+Unlike [`Forwarded`](crate::Forwarded), which flattens all of the
+elements of a header into a single representation, this preserves
+the association between the parameters that each proxy added.
+
+```
+use trillium_forwarding::{ForwardedElement, NodeName, NodePort};
+
+let elements = ForwardedElement::parse_list(
+    r#"for=192.0.2.43;proto=http, for="[2001:db8:cafe::17]:4711";by=_proxy"#
+).unwrap();
+
+assert_eq!(elements.len(), 2);
+assert_eq!(elements[0].proto(), Some("http"));
+assert_eq!(
+    elements[0].forwarded_for().unwrap().ip(),
+    Some("192.0.2.43".parse().unwrap())
+);
+
+let client = elements[1].forwarded_for().unwrap();
+assert_eq!(client.ip(), Some("2001:db8:cafe::17".parse().unwrap()));
+assert_eq!(client.port(), Some(&NodePort::Port(4711)));
+assert_eq!(
+    elements[1].by().unwrap().name(),
+    &NodeName::Obfuscated("_proxy".into())
+);
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedElement<'a> {
+    forwarded_for: Option<Node<'a>>,
+    by: Option<Node<'a>>,
+    host: Option<Cow<'a, str>>,
+    proto: Option<Cow<'a, str>>,
+    extensions: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+}
+
+/**
+A [node](https://tools.ietf.org/html/rfc7239#section-6), as found in
+the `for` and `by` parameters of a [`ForwardedElement`]
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node<'a> {
+    name: NodeName<'a>,
+    port: Option<NodePort<'a>>,
+}
+
+/// The identifier portion of a [`Node`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeName<'a> {
+    /// an ipv4 or ipv6 address
+    Ip(IpAddr),
+
+    /// the `unknown` identifier, used when a proxy does not know or
+    /// does not wish to disclose the identity of the node
+    Unknown,
+
+    /// an [obfuscated
+    /// identifier](https://tools.ietf.org/html/rfc7239#section-6.3),
+    /// including the leading underscore
+    Obfuscated(Cow<'a, str>),
+}
+
+/// The port portion of a [`Node`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodePort<'a> {
+    /// a numeric port
+    Port(u16),
+
+    /// an [obfuscated
+    /// port](https://tools.ietf.org/html/rfc7239#section-6.3),
+    /// including the leading underscore
+    Obfuscated(Cow<'a, str>),
+}
+
+fn is_obfuscated(s: &str) -> bool {
+    match s.strip_prefix('_') {
+        Some(rest) => {
+            !rest.is_empty()
+                && rest
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        }
+        None => false,
+    }
+}
+
+impl<'a> Node<'a> {
+    /**
+    parses a node from the unquoted value of a `for` or `by` parameter
+
+    ```
+    use trillium_forwarding::{Node, NodeName, NodePort};
+
+    let node = Node::parse("192.0.2.43:8080").unwrap();
+    assert_eq!(node.ip(), Some("192.0.2.43".parse().unwrap()));
+    assert_eq!(node.port(), Some(&NodePort::Port(8080)));
+
+    let node = Node::parse("unknown").unwrap();
+    assert!(node.is_unknown());
+
+    let node = Node::parse("_hidden:_port").unwrap();
+    assert_eq!(node.name(), &NodeName::Obfuscated("_hidden".into()));
+    assert_eq!(node.port(), Some(&NodePort::Obfuscated("_port".into())));
+
+    assert!(Node::parse("2001:db8:cafe::17").is_err()); // ipv6 must be bracketed
+    assert!(Node::parse("example.com").is_err());
+    ```
+    */
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let (name, port) = if let Some(rest) = input.strip_prefix('[') {
+            let (ipv6, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| ParseError::new("unterminated ipv6 node"))?;
+            let ipv6: Ipv6Addr = ipv6
+                .parse()
+                .map_err(|_| ParseError::new("invalid ipv6 node"))?;
+            let port = match rest {
+                "" => None,
+                rest => Some(
+                    rest.strip_prefix(':')
+                        .ok_or_else(|| ParseError::new("unexpected character after ipv6 node"))?,
+                ),
+            };
+            (NodeName::Ip(IpAddr::V6(ipv6)), port)
+        } else {
+            let (name, port) = match input.split_once(':') {
+                Some((name, port)) => (name, Some(port)),
+                None => (input, None),
+            };
+
+            let name = if name.eq_ignore_ascii_case("unknown") {
+                NodeName::Unknown
+            } else if is_obfuscated(name) {
+                NodeName::Obfuscated(Cow::Borrowed(name))
+            } else {
+                let ipv4: Ipv4Addr = name
+                    .parse()
+                    .map_err(|_| ParseError::new("invalid node name"))?;
+                NodeName::Ip(IpAddr::V4(ipv4))
+            };
+
+            (name, port)
+        };
+
+        let port = match port {
+            None => None,
+            Some(port) if is_obfuscated(port) => Some(NodePort::Obfuscated(Cow::Borrowed(port))),
+            Some(port) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+                Some(NodePort::Port(
+                    port.parse()
+                        .map_err(|_| ParseError::new("invalid node port"))?,
+                ))
+            }
+            Some(_) => return Err(ParseError::new("invalid node port")),
+        };
+
+        Ok(Self { name, port })
+    }
+
+    /// the identifier of this node
+    pub fn name(&self) -> &NodeName<'a> {
+        &self.name
+    }
+
+    /// the port of this node, if one was provided
+    pub fn port(&self) -> Option<&NodePort<'a>> {
+        self.port.as_ref()
+    }
+
+    /// the ip address of this node, if it is neither unknown nor obfuscated
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self.name {
+            NodeName::Ip(ip) => Some(ip),
+            _ => None,
+        }
+    }
+
+    /// whether this node was the `unknown` identifier
+    pub fn is_unknown(&self) -> bool {
+        self.name == NodeName::Unknown
+    }
+
+    /// Transform a borrowed Node into an owned Node
+    pub fn into_owned(self) -> Node<'static> {
+        Node {
+            name: match self.name {
+                NodeName::Ip(ip) => NodeName::Ip(ip),
+                NodeName::Unknown => NodeName::Unknown,
+                NodeName::Obfuscated(o) => NodeName::Obfuscated(Cow::Owned(o.into_owned())),
+            },
+            port: self.port.map(|port| match port {
+                NodePort::Port(port) => NodePort::Port(port),
+                NodePort::Obfuscated(o) => NodePort::Obfuscated(Cow::Owned(o.into_owned())),
+            }),
+        }
+    }
+}
+
+impl Display for NodeName<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeName::Ip(IpAddr::V4(ip)) => write!(f, "{}", ip),
+            NodeName::Ip(IpAddr::V6(ip)) => write!(f, "[{}]", ip),
+            NodeName::Unknown => f.write_str("unknown"),
+            NodeName::Obfuscated(o) => f.write_str(o),
+        }
+    }
+}
+
+impl Display for NodePort<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NodePort::Port(port) => write!(f, "{}", port),
+            NodePort::Obfuscated(o) => f.write_str(o),
+        }
+    }
+}
+
+impl Display for Node<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(port) = &self.port {
+            write!(f, ":{}", port)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_node(value: Cow<'_, str>) -> Result<Node<'_>, ParseError> {
+    match value {
+        Cow::Borrowed(value) => Node::parse(value),
+        Cow::Owned(value) => Node::parse(&value).map(Node::into_owned),
+    }
+}
+
+impl<'a> ForwardedElement<'a> {
+    /**
+    parses a comma-separated list of forwarded-elements, as found in
+    the value of a forwarded header. empty list elements are ignored.
+    parameter names are case-insensitive, and each parameter may only
+    appear once per element.
+
+    ```
+    use trillium_forwarding::ForwardedElement;
+
+    let elements = ForwardedElement::parse_list("for=unknown;host=example.com, , for=_ab").unwrap();
+    assert_eq!(elements.len(), 2);
+    assert!(elements[0].forwarded_for().unwrap().is_unknown());
+    assert_eq!(elements[0].host(), Some("example.com"));
+
+    assert!(ForwardedElement::parse_list("for=a;for=b").is_err());
+    ```
+    */
+    pub fn parse_list(input: &'a str) -> Result<Vec<Self>, ParseError> {
+        let mut elements = vec![];
+        let mut rest = input;
+
+        loop {
+            rest = rest.trim_start_matches([' ', '\t']);
+            match rest.strip_prefix(',') {
+                Some(after_comma) => rest = after_comma,
+                None if rest.is_empty() => break,
+                None => {
+                    let (element, after_element) = Self::parse_element(rest)?;
+                    elements.push(element);
+                    rest = after_element;
+                }
+            }
+        }
+
+        Ok(elements)
+    }
+
+    fn parse_element(input: &'a str) -> Result<(Self, &'a str), ParseError> {
+        let mut element = Self::default();
+        let mut rest = input;
+
+        loop {
+            let (key, value, after_pair) = parse_forwarded_pair(rest)?;
+            element.set_pair(key, value)?;
+
+            rest = after_pair.trim_start_matches([' ', '\t']);
+            match rest.chars().next() {
+                Some(';') => rest = rest[1..].trim_start_matches([' ', '\t']),
+                Some(',') | None => return Ok((element, rest)),
+                _ => return Err(ParseError::new("unexpected character after forwarded-pair")),
+            }
+        }
+    }
+
+    fn set_pair(&mut self, key: &'a str, value: Cow<'a, str>) -> Result<(), ParseError> {
+        if key.eq_ignore_ascii_case("for") {
+            if self.forwarded_for.is_some() {
+                return Err(ParseError::new("parse error, duplicate `for` key"));
+            }
+            self.forwarded_for = Some(parse_node(value)?);
+        } else if key.eq_ignore_ascii_case("by") {
+            if self.by.is_some() {
+                return Err(ParseError::new("parse error, duplicate `by` key"));
+            }
+            self.by = Some(parse_node(value)?);
+        } else if key.eq_ignore_ascii_case("host") {
+            if self.host.is_some() {
+                return Err(ParseError::new("parse error, duplicate `host` key"));
+            }
+            self.host = Some(value);
+        } else if key.eq_ignore_ascii_case("proto") {
+            if self.proto.is_some() {
+                return Err(ParseError::new("parse error, duplicate `proto` key"));
+            }
+            self.proto = Some(value);
+        } else {
+            if self
+                .extensions
+                .iter()
+                .any(|(existing, _)| existing.eq_ignore_ascii_case(key))
+            {
+                return Err(ParseError::new("parse error, duplicate extension key"));
+            }
+            self.extensions.push((Cow::Borrowed(key), value));
+        }
+
+        Ok(())
+    }
+
+    /**
+    parses all forwarded-elements from every forwarded header in
+    these headers, in order. returns an empty vec if there are no
+    forwarded headers.
+    */
+    pub fn from_headers(headers: &'a Headers) -> Result<Vec<Self>, ParseError> {
+        let mut elements = vec![];
+        if let Some(values) = headers.get_values(ForwardedHeader) {
+            for value in values.iter() {
+                if let Some(value) = value.as_str() {
+                    elements.extend(Self::parse_list(value)?);
+                } else {
+                    return Err(ParseError::new("forwarded header was not utf8"));
+                }
+            }
+        }
+        Ok(elements)
+    }
+
+    /// the `for` parameter, identifying the node that made the
+    /// request to this proxy
+    pub fn forwarded_for(&self) -> Option<&Node<'a>> {
+        self.forwarded_for.as_ref()
+    }
+
+    /// the `by` parameter, identifying the interface on which this
+    /// proxy received the request
+    pub fn by(&self) -> Option<&Node<'a>> {
+        self.by.as_ref()
+    }
+
+    /// the `host` parameter, the host request header as received by this proxy
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// the `proto` parameter, the protocol used to make the request to this proxy
+    pub fn proto(&self) -> Option<&str> {
+        self.proto.as_deref()
+    }
+
+    /// retrieves an extension parameter by case-insensitive name
+    pub fn extension(&self, name: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_ref())
+    }
+
+    /// Transform a borrowed ForwardedElement into an owned ForwardedElement
+    pub fn into_owned(self) -> ForwardedElement<'static> {
+        ForwardedElement {
+            forwarded_for: self.forwarded_for.map(Node::into_owned),
+            by: self.by.map(Node::into_owned),
+            host: self.host.map(|h| Cow::Owned(h.into_owned())),
+            proto: self.proto.map(|p| Cow::Owned(p.into_owned())),
+            extensions: self
+                .extensions
+                .into_iter()
+                .map(|(k, v)| (Cow::Owned(k.into_owned()), Cow::Owned(v.into_owned())))
+                .collect(),
+        }
+    }
+}
+
+impl Display for ForwardedElement<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut pairs = vec![];
+        if let Some(forwarded_for) = &self.forwarded_for {
+            pairs.push(("for", Cow::Owned(forwarded_for.to_string())));
+        }
+        if let Some(by) = &self.by {
+            pairs.push(("by", Cow::Owned(by.to_string())));
+        }
+        if let Some(host) = self.host() {
+            pairs.push(("host", Cow::Borrowed(host)));
+        }
+        if let Some(proto) = self.proto() {
+            pairs.push(("proto", Cow::Borrowed(proto)));
+        }
+        for (key, value) in &self.extensions {
+            pairs.push((key, Cow::Borrowed(value)));
+        }
+
+        for (i, (key, value)) in pairs.into_iter().enumerate() {
+            if i > 0 {
+                f.write_char(';')?;
+            }
+            write!(f, "{}={}", key, format_value(&value))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    type Result = std::result::Result<(), ParseError>;
+
+    #[test]
+    fn rfc_examples() -> Result {
+        let elements = ForwardedElement::parse_list(r#"for="_gazonk""#)?;
+        assert_eq!(
+            elements[0].forwarded_for().unwrap().name(),
+            &NodeName::Obfuscated("_gazonk".into())
+        );
+
+        let elements = ForwardedElement::parse_list(r#"For="[2001:db8:cafe::17]:4711""#)?;
+        let node = elements[0].forwarded_for().unwrap();
+        assert_eq!(node.ip(), Some("2001:db8:cafe::17".parse().unwrap()));
+        assert_eq!(node.port(), Some(&NodePort::Port(4711)));
+
+        let elements = ForwardedElement::parse_list("for=192.0.2.60;proto=http;by=203.0.113.43")?;
+        assert_eq!(elements.len(), 1);
+        assert_eq!(elements[0].proto(), Some("http"));
+        assert_eq!(
+            elements[0].by().unwrap().ip(),
+            Some("203.0.113.43".parse().unwrap())
+        );
+
+        let elements = ForwardedElement::parse_list("for=192.0.2.43, for=198.51.100.17")?;
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            elements[1].forwarded_for().unwrap().ip(),
+            Some("198.51.100.17".parse().unwrap())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parameters_stay_with_their_element() -> Result {
+        let elements = ForwardedElement::parse_list(
+            "for=192.0.2.43;proto=https;host=example.com, for=unknown;by=_internal;proto=http",
+        )?;
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[0].proto(), Some("https"));
+        assert_eq!(elements[0].host(), Some("example.com"));
+        assert!(elements[0].by().is_none());
+        assert!(elements[1].forwarded_for().unwrap().is_unknown());
+        assert_eq!(elements[1].proto(), Some("http"));
+        assert!(elements[1].host().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn quoted_values_and_extensions() -> Result {
+        let elements = ForwardedElement::parse_list(
+            r#"for="192.0.2.43:_port";host="example.com:8080";Secret="a, b; \"c\"""#,
+        )?;
+        let node = elements[0].forwarded_for().unwrap();
+        assert_eq!(node.port(), Some(&NodePort::Obfuscated("_port".into())));
+        assert_eq!(elements[0].host(), Some("example.com:8080"));
+        assert_eq!(elements[0].extension("secret"), Some(r#"a, b; "c""#));
+        Ok(())
+    }
+
+    #[test]
+    fn whitespace() -> Result {
+        let elements = ForwardedElement::parse_list(" for=_a , ,for=_x ; proto=https ,")?;
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[1].proto(), Some("https"));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() {
+        for input in [
+            "for",
+            "for=",
+            "for=a;for=b",
+            "proto=http;PROTO=https",
+            "ext=1;EXT=2",
+            "for=2001:db8::17",
+            r#"for="[2001:db8::17""#,
+            r#"for="[2001:db8::17]4711""#,
+            "for=192.0.2.43:99999",
+            "for=192.0.2.43:port",
+            "for=_",
+            "for=_bad!chars",
+            "for=example.com",
+            "for=192.0.2.43 proto=http",
+            r#"for="unterminated"#,
+        ] {
+            assert!(
+                ForwardedElement::parse_list(input).is_err(),
+                "expected {} to be invalid",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn multiple_headers() -> Result {
+        let mut headers = Headers::new();
+        headers.append("Forwarded", "for=192.0.2.43");
+        headers.append("Forwarded", "for=198.51.100.17;proto=https");
+        let elements = ForwardedElement::from_headers(&headers)?;
+        assert_eq!(elements.len(), 2);
+        assert_eq!(elements[1].proto(), Some("https"));
+
+        assert!(ForwardedElement::from_headers(&Headers::new())?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> Result {
+        let input = r#"for="[2001:db8:cafe::17]:4711";by=_proxy;host=example.com;proto=https;ext="a b", for=unknown"#;
+        let elements = ForwardedElement::parse_list(input)?;
+        let formatted = elements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        assert_eq!(formatted, input);
+        assert_eq!(ForwardedElement::parse_list(&formatted)?, elements);
+        Ok(())
+    }
+}
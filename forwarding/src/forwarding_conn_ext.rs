@@ -0,0 +1,50 @@
+use crate::ForwardedElement;
+use trillium::Conn;
+
+/**
+Extension trait for reading the [forwarded
+header](https://tools.ietf.org/html/rfc7239) from a [`Conn`].
+
+Note that this reads the request headers as they were sent, and
+performs no checks of whether the peer is a trusted proxy. Anyone can
+send a forwarded header, so these values should only be relied upon
+if the request is known to have arrived through a trusted proxy. See
+[`Forwarding`](crate::Forwarding) for a handler that applies trust rules.
+*/
+pub trait ForwardingConnExt {
+    /**
+    returns every forwarded-element in the request's forwarded
+    headers, in the order they were sent. the last element was added
+    by the proxy closest to this server. returns an empty vec if
+    there is no forwarded header, or if it could not be parsed.
+
+    ```
+    use trillium_forwarding::ForwardingConnExt;
+    use trillium_testing::prelude::*;
+
+    let conn = get("/")
+        .with_request_header(
+            "forwarded",
+            r#"for="[2001:db8:cafe::17]:4711";proto=https, for=unknown;by=_proxy"#,
+        )
+        .on(&());
+
+    let forwarded = conn.forwarded();
+    assert_eq!(forwarded.len(), 2);
+    assert_eq!(forwarded[0].proto(), Some("https"));
+    assert!(forwarded[1].forwarded_for().unwrap().is_unknown());
+
+    assert!(get("/").on(&()).forwarded().is_empty());
+    ```
+    */
+    fn forwarded(&self) -> Vec<ForwardedElement<'_>>;
+}
+
+impl ForwardingConnExt for Conn {
+    fn forwarded(&self) -> Vec<ForwardedElement<'_>> {
+        ForwardedElement::from_headers(self.request_headers()).unwrap_or_else(|e| {
+            log::debug!("{}", e);
+            vec![]
+        })
+    }
+}
@@ -12,6 +12,13 @@ The specific headers that are understood by this handler are:
     - [`X-Forwarded-Proto`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/X-Forwarded-Proto)
     - [`X-Forwarded-Host`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/X-Forwarded-Host)
 
+When the peer ip is trusted, the conn is marked secure if the
+forwarded proto is `https`, even though the connection from the proxy
+is plaintext, and the left-most `for` address replaces the peer ip.
+If that address includes a port, it also replaces the peer addr. If
+the address is absent, `unknown`, obfuscated, or otherwise not an ip
+address, the peer ip of the transport is retained.
+
 There are several ways of specifying when to trust a peer ip address,
 and the narrowest possible trust rules should be used for a given
 deployment so as to decrease the chance for a threat actor to generate
@@ -29,9 +36,19 @@ a request with forwarded headers that we mistakenly trust.
 mod forwarded;
 pub use forwarded::Forwarded;
 
+mod forwarded_element;
+pub use forwarded_element::{ForwardedElement, Node, NodeName, NodePort};
+
+mod forwarding_conn_ext;
+pub use forwarding_conn_ext::ForwardingConnExt;
+
 mod parse_utils;
 
-use std::{fmt::Debug, net::IpAddr, ops::Deref};
+use std::{
+    fmt::Debug,
+    net::{IpAddr, SocketAddr},
+    ops::Deref,
+};
 use trillium::{async_trait, conn_unwrap, Conn, Handler};
 
 #[derive(Debug)]
@@ -141,6 +158,23 @@ impl Forwarding {
     }
 }
 
+/// parses the ip address and port, if any, from the left-most `for`
+/// node, which may be a bare ip from `x-forwarded-for`, or a
+/// `forwarded` node with brackets and a port
+fn parse_node(node: &str) -> Option<(IpAddr, Option<u16>)> {
+    let node = node.trim();
+    if let Ok(ip) = node.parse() {
+        return Some((ip, None));
+    }
+
+    let node = Node::parse(node).ok()?;
+    let port = match node.port() {
+        Some(NodePort::Port(port)) => Some(*port),
+        _ => None,
+    };
+    Some((node.ip()?, port))
+}
+
 impl Default for TrustProxy {
     fn default() -> Self {
         Self::Never
@@ -155,8 +189,13 @@ impl Handler for Forwarding {
             return conn;
         }
 
-        let forwarded =
-            conn_unwrap!(Forwarded::from_headers(conn.headers()).ok().flatten(), conn).into_owned();
+        let forwarded = conn_unwrap!(
+            Forwarded::from_headers(conn.request_headers())
+                .ok()
+                .flatten(),
+            conn
+        )
+        .into_owned();
 
         log::debug!("received trusted forwarded {:?}", &forwarded);
 
@@ -167,12 +206,17 @@ impl Handler for Forwarding {
         }
 
         if let Some(proto) = forwarded.proto() {
-            inner_mut.set_secure(proto == "https");
+            // a chain of proxies may each append to x-forwarded-proto,
+            // in which case the left-most is the client's
+            let proto = proto.split(',').next().unwrap_or_default().trim();
+            inner_mut.set_secure(proto.eq_ignore_ascii_case("https"));
         }
 
-        if let Some(ip) = forwarded.forwarded_for().first() {
-            if let Ok(ip_addr) = ip.trim_start_matches('[').trim_end_matches(']').parse() {
-                inner_mut.set_peer_ip(Some(ip_addr));
+        if let Some(node) = forwarded.forwarded_for().first() {
+            match parse_node(node) {
+                Some((ip, Some(port))) => inner_mut.set_peer_addr(Some(SocketAddr::new(ip, port))),
+                Some((ip, None)) => inner_mut.set_peer_ip(Some(ip)),
+                None => log::debug!("ignoring forwarded for {:?}, which is not an ip", node),
             }
         }
 
@@ -107,3 +107,116 @@ fn test_ipranges() {
         "false Some(192.169.1.1) None"
     );
 }
+
+#[test]
+fn test_forwarded_for_nodes() {
+    let app = app(Forwarding::trust_always());
+    let peer_ip: IpAddr = "203.0.113.43".parse().unwrap();
+
+    let cases = [
+        ("forwarded", r#"for="192.0.2.60:8080""#, "Some(192.0.2.60)"),
+        (
+            "forwarded",
+            r#"for="[2001:db8:cafe::17]:4711""#,
+            "Some(2001:db8:cafe::17)",
+        ),
+        (
+            "forwarded",
+            r#"for="[2001:db8:cafe::17]""#,
+            "Some(2001:db8:cafe::17)",
+        ),
+        (
+            "x-forwarded-for",
+            "2001:db8:cafe::17, 10.0.0.1",
+            "Some(2001:db8:cafe::17)",
+        ),
+        (
+            "x-forwarded-for",
+            " 192.0.2.60 , 10.0.0.1",
+            "Some(192.0.2.60)",
+        ),
+        ("forwarded", "for=unknown", "Some(203.0.113.43)"),
+        ("forwarded", "for=_hidden", "Some(203.0.113.43)"),
+        ("x-forwarded-for", "not-an-ip", "Some(203.0.113.43)"),
+        ("x-forwarded-for", "999.0.2.60", "Some(203.0.113.43)"),
+    ];
+
+    for (header, value, expected) in cases {
+        assert_ok!(
+            get("/")
+                .with_request_header(header, value)
+                .with_peer_ip(peer_ip)
+                .on(&app),
+            format!("false {expected} None")
+        );
+    }
+}
+
+#[test]
+fn test_forwarded_proto() {
+    let app = app(Forwarding::trust_ips(["10.10.10.10"]));
+    let trusted: IpAddr = "10.10.10.10".parse().unwrap();
+    let untrusted: IpAddr = "10.10.10.1".parse().unwrap();
+
+    for (proto, secure) in [
+        ("https", true),
+        ("HTTPS", true),
+        ("https, http", true),
+        ("http", false),
+        ("http, https", false),
+    ] {
+        assert_ok!(
+            get("/")
+                .with_request_header("x-forwarded-proto", proto)
+                .with_peer_ip(trusted)
+                .on(&app),
+            format!("{secure} Some(10.10.10.10) None")
+        );
+    }
+
+    assert_ok!(
+        get("/")
+            .with_request_header("x-forwarded-proto", "https")
+            .with_peer_ip(untrusted)
+            .on(&app),
+        "false Some(10.10.10.1) None"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("x-forwarded-ssl", "on")
+            .with_peer_ip(trusted)
+            .on(&app),
+        "true Some(10.10.10.10) None"
+    );
+}
+
+#[test]
+fn test_forwarded_peer_addr() {
+    let handler = (Forwarding::trust_always(), |conn: Conn| async move {
+        let response = format!("{:?} {:?}", conn.peer_ip(), conn.peer_addr());
+        conn.ok(response)
+    });
+    let proxy = "10.0.0.1:41000".parse().unwrap();
+
+    assert_ok!(
+        get("/")
+            .with_request_header("forwarded", r#"for="[2001:db8::17]:4711""#)
+            .with_peer_addr(proxy)
+            .on(&handler),
+        "Some(2001:db8::17) Some([2001:db8::17]:4711)"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("x-forwarded-for", "192.0.2.60")
+            .with_peer_addr(proxy)
+            .on(&handler),
+        "Some(192.0.2.60) None"
+    );
+
+    assert_ok!(
+        get("/").with_peer_addr(proxy).on(&handler),
+        "Some(10.0.0.1) Some(10.0.0.1:41000)"
+    );
+}
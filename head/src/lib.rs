@@ -5,6 +5,10 @@ This simple handler rewrites HEAD requests to be GET requests, and
 then before sending the response, removes the outbound body but sets a
 content length header. Any handlers subsequent to this one see a GET
 request.
+
+Applications that route with `trillium-router` do not need this
+handler, as the router runs the GET handler for a route in response
+to a HEAD request unless a HEAD handler matches.
 */
 #![forbid(unsafe_code)]
 #![deny(
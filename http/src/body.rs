@@ -19,6 +19,15 @@ impl Body {
     /// Construct a new body from a streaming [`AsyncRead`] source. If
     /// you have the body content in memory already, prefer
     /// [`Body::new_static`] or one of the From conversions.
+    ///
+    /// When a response body is sent, the head is flushed before the
+    /// body is first read, and the transport is flushed again
+    /// whenever a read returns `Pending`, so a long-lived body only
+    /// needs to stop producing bytes for what it has produced so far
+    /// to be sent. This matters for transports that buffer writes,
+    /// such as the tls transports from `trillium-rustls` and
+    /// `trillium-native-tls`; a plain tcp stream writes immediately
+    /// and its flush has no effect.
     pub fn new_streaming(
         async_read: impl AsyncRead + Send + Sync + 'static,
         len: Option<u64>,
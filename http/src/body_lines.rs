@@ -0,0 +1,208 @@
+use encoding_rs::Encoding;
+use futures_lite::{ready, AsyncRead, Stream};
+use std::{
+    fmt::{self, Debug, Formatter},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+const READ_BUF_LENGTH: usize = 1024;
+
+/**
+A stream of the lines of a body, as returned by
+[`ReceivedBody::lines`](crate::ReceivedBody::lines).
+
+Lines are separated by `\n`, and a `\r` immediately preceding it is
+removed. A final line without a trailing newline is yielded if it is
+not empty. At most [`BodyLines::max_line_len`] bytes are buffered
+while looking for the end of a line; a longer line yields
+[`Error::LineTooLong`](crate::Error::LineTooLong), after which the
+stream ends.
+*/
+pub struct BodyLines<R> {
+    reader: R,
+    encoding: &'static Encoding,
+    buffer: Vec<u8>,
+    max_line_len: usize,
+    done: bool,
+}
+
+impl<R> Debug for BodyLines<R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BodyLines")
+            .field("encoding", &self.encoding)
+            .field("buffer", &String::from_utf8_lossy(&self.buffer))
+            .field("max_line_len", &self.max_line_len)
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> BodyLines<R> {
+    /// the default maximum line length, eight kilobytes
+    pub const DEFAULT_MAX_LINE_LEN: usize = 8 * 1024;
+
+    pub(crate) fn new(reader: R, encoding: &'static Encoding) -> Self {
+        Self {
+            reader,
+            encoding,
+            buffer: vec![],
+            max_line_len: Self::DEFAULT_MAX_LINE_LEN,
+            done: false,
+        }
+    }
+
+    /// sets the maximum length in bytes of a single line, not
+    /// including the line terminator. defaults to
+    /// [`BodyLines::DEFAULT_MAX_LINE_LEN`]
+    #[must_use]
+    pub const fn with_max_line_len(mut self, max_line_len: usize) -> Self {
+        self.max_line_len = max_line_len;
+        self
+    }
+
+    /// the maximum length in bytes of a single line
+    pub const fn max_line_len(&self) -> usize {
+        self.max_line_len
+    }
+
+    fn decode(&self, line: &[u8]) -> String {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let (line, _, _) = self.encoding.decode(line);
+        line.into_owned()
+    }
+
+    fn too_long(&mut self) -> crate::Error {
+        self.done = true;
+        self.buffer.clear();
+        crate::Error::LineTooLong(self.max_line_len)
+    }
+}
+
+/// the length of a line, not including a trailing `\r`
+fn line_len(line: &[u8]) -> usize {
+    line.strip_suffix(b"\r").unwrap_or(line).len()
+}
+
+impl<R> Stream for BodyLines<R>
+where
+    R: AsyncRead + Unpin,
+{
+    type Item = crate::Result<String>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let mut searched = 0;
+        loop {
+            if let Some(newline) = this.buffer[searched..].iter().position(|&b| b == b'\n') {
+                let newline = searched + newline;
+                if line_len(&this.buffer[..newline]) > this.max_line_len {
+                    return Poll::Ready(Some(Err(this.too_long())));
+                }
+                let line = this.decode(&this.buffer[..newline]);
+                this.buffer.drain(..=newline);
+                return Poll::Ready(Some(Ok(line)));
+            }
+
+            if line_len(&this.buffer) > this.max_line_len {
+                return Poll::Ready(Some(Err(this.too_long())));
+            }
+
+            if this.done {
+                if this.buffer.is_empty() {
+                    return Poll::Ready(None);
+                }
+                let line = this.decode(&this.buffer);
+                this.buffer.clear();
+                return Poll::Ready(Some(Ok(line)));
+            }
+
+            searched = this.buffer.len();
+            let mut bytes = [0; READ_BUF_LENGTH];
+            match ready!(Pin::new(&mut this.reader).poll_read(cx, &mut bytes)) {
+                Ok(0) => this.done = true,
+                Ok(len) => this.buffer.extend_from_slice(&bytes[..len]),
+                Err(e) => {
+                    this.done = true;
+                    this.buffer.clear();
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BodyLines;
+    use crate::Error;
+    use encoding_rs::UTF_8;
+    use futures_lite::{io::Cursor, AsyncRead, StreamExt};
+    use std::{
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    /// a reader that returns at most one byte per read
+    struct Trickle(Cursor<Vec<u8>>);
+
+    impl AsyncRead for Trickle {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<std::io::Result<usize>> {
+            let len = buf.len().min(1);
+            Pin::new(&mut self.0).poll_read(cx, &mut buf[..len])
+        }
+    }
+
+    fn lines<R: AsyncRead + Unpin>(lines: BodyLines<R>) -> Vec<Result<String, String>> {
+        trillium_testing::block_on(lines.map(|line| line.map_err(|e| e.to_string())).collect())
+    }
+
+    #[test]
+    fn splits_lines() {
+        let body = "one\ntwo\r\n\r\nthree";
+        let expected: Vec<Result<String, String>> = vec![
+            Ok("one".into()),
+            Ok("two".into()),
+            Ok(String::new()),
+            Ok("three".into()),
+        ];
+
+        let cursor = Cursor::new(body.as_bytes().to_vec());
+        assert_eq!(lines(BodyLines::new(cursor, UTF_8)), expected);
+
+        let trickle = Trickle(Cursor::new(body.as_bytes().to_vec()));
+        assert_eq!(lines(BodyLines::new(trickle, UTF_8)), expected);
+
+        let cursor = Cursor::new(b"one\n".to_vec());
+        assert_eq!(lines(BodyLines::new(cursor, UTF_8)), vec![Ok("one".into())]);
+    }
+
+    #[test]
+    fn max_line_len() {
+        let cursor = Cursor::new(b"four\r\nfive!\nsix".to_vec());
+        let lines = lines(BodyLines::new(cursor, UTF_8).with_max_line_len(4));
+        assert_eq!(
+            lines,
+            vec![Ok("four".into()), Err(Error::LineTooLong(4).to_string())]
+        );
+
+        // a line without a newline is rejected once it exceeds the
+        // maximum, without reading the rest of the body
+        let mut body = vec![b'a'; 100];
+        body.push(b'\n');
+        let trickle = Trickle(Cursor::new(body));
+        let mut lines = BodyLines::new(trickle, UTF_8).with_max_line_len(10);
+        trillium_testing::block_on(async {
+            assert!(matches!(
+                lines.next().await,
+                Some(Err(Error::LineTooLong(10)))
+            ));
+            assert!(lines.next().await.is_none());
+        });
+        assert_eq!(lines.reader.0.position(), 11);
+    }
+}
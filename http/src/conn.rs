@@ -1,28 +1,41 @@
 use crate::{
     received_body::ReceivedBodyState,
+    reclaimable::Reclaimable,
+    transfer_coding::{self, TransferDecoder},
     util::encoding,
-    Body, ConnectionStatus, Error, HeaderValues, Headers,
-    KnownHeaderName::{Connection, ContentLength, Date, Expect, Host, Server, TransferEncoding},
-    Method, ReceivedBody, Result, StateSet, Status, Stopper, Upgrade, Version,
+    Body, ConnConfig, ConnectionStatus, Error, HeaderValue, HeaderValues, Headers,
+    KnownHeaderName::{
+        Connection, ContentLength, Date, Expect, Host, Server, Te, Trailer, TransferEncoding,
+    },
+    Method, ReceivedBody, Result, Sleep, StateSet, Status, Stopper, Upgrade, Version,
 };
 use encoding_rs::Encoding;
-use futures_lite::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use futures_lite::{
+    future,
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+};
 use httparse::{Request, EMPTY_HEADER};
 use memmem::{Searcher, TwoWaySearcher};
 use std::{
     convert::TryInto,
     fmt::{self, Debug, Formatter},
     future::Future,
-    iter,
-    net::IpAddr,
+    net::{IpAddr, SocketAddr},
+    pin::pin,
     str::FromStr,
-    time::{Instant, SystemTime},
+    time::{Duration, Instant},
 };
 
-const MAX_HEADERS: usize = 128;
-const MAX_HEAD_LENGTH: usize = 2 * 1024;
 const SERVER: &str = concat!("trillium/", env!("CARGO_PKG_VERSION"));
 
+/// the most bytes read from the transport at once while reading a
+/// request head
+const HEAD_READ_LENGTH: usize = 8 * 1024;
+
+/// the size of the buffer that a streaming response body is copied
+/// through on its way to the transport
+const BODY_COPY_LENGTH: usize = 64 * 1024;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum SendStatus {
     Success,
@@ -89,14 +102,21 @@ pub struct Conn<Transport> {
     pub(crate) version: Version,
     pub(crate) state: StateSet,
     pub(crate) response_body: Option<Body>,
-    pub(crate) transport: Transport,
+    pub(crate) transport: Reclaimable<Transport>,
     pub(crate) buffer: Option<Vec<u8>>,
     pub(crate) request_body_state: ReceivedBodyState,
+    pub(crate) request_trailers: Option<Headers>,
+    pub(crate) response_trailers: Option<Headers>,
     pub(crate) secure: bool,
     pub(crate) stopper: Stopper,
+    pub(crate) cancellation: Stopper,
+    pub(crate) synthetic: bool,
+    pub(crate) config: ConnConfig,
+    pub(crate) informational: Vec<Status>,
     pub(crate) after_send: AfterSend,
     pub(crate) start_time: Instant,
     pub(crate) peer_ip: Option<IpAddr>,
+    pub(crate) peer_addr: Option<SocketAddr>,
 }
 
 impl<Transport> Debug for Conn<Transport> {
@@ -116,11 +136,18 @@ impl<Transport> Debug for Conn<Transport> {
                 &self.buffer.as_deref().map(String::from_utf8_lossy),
             )
             .field("request_body_state", &self.request_body_state)
+            .field("request_trailers", &self.request_trailers)
+            .field("response_trailers", &self.response_trailers)
             .field("secure", &self.secure)
             .field("stopper", &self.stopper)
+            .field("cancellation", &self.cancellation)
+            .field("synthetic", &self.synthetic)
+            .field("config", &self.config)
+            .field("informational", &self.informational)
             .field("after_send", &"..")
             .field("start_time", &self.start_time)
             .field("peer_ip", &self.peer_ip)
+            .field("peer_addr", &self.peer_addr)
             .finish()
     }
 }
@@ -144,11 +171,22 @@ where
 
     * there is an io error when reading from the underlying transport
     * headers are too long
-    * we are unable to parse some aspect of the request
-    * the request is an unsupported http version
+    * we are unable to parse some aspect of the request, such as an
+      unrecognized method or an unsupported http version. these are
+      responded to with a `400 Bad Request` before the error is
+      returned.
     * we cannot make sense of the headers, such as if there is a
         `content-length` header as well as a `transfer-encoding: chunked`
         header.
+    * there is more than one `host` header, or none on an http/1.1
+      request, or the `host` does not match the authority of an
+      absolute-form request target. this and the above are
+      responded to with a `400 Bad Request` before the error is
+      returned.
+    * a handler does not return within the
+      [`ConnConfig::handler_timeout`], even after it is cancelled.
+      this is responded to with a `503 Service Unavailable` before
+      the error is returned.
 
     */
 
@@ -162,10 +200,53 @@ where
         F: Fn(Conn<Transport>) -> Fut,
         Fut: Future<Output = Conn<Transport>> + Send,
     {
-        let mut conn = Conn::new(transport, None, stopper).await?;
+        Self::map_with_config(ConnConfig::default(), transport, stopper, handler).await
+    }
+
+    /**
+    the same as [`Conn::map`], but with the provided [`ConnConfig`]
+    applied to every request read from the transport
+
+    # Errors
+
+    This will return an error variant for the same reasons as
+    [`Conn::map`], including a request head that exceeds the limits
+    of the [`ConnConfig`]
+    */
+    pub async fn map_with_config<F, Fut>(
+        config: ConnConfig,
+        transport: Transport,
+        stopper: Stopper,
+        handler: F,
+    ) -> Result<Option<Upgrade<Transport>>>
+    where
+        F: Fn(Conn<Transport>) -> Fut,
+        Fut: Future<Output = Conn<Transport>> + Send,
+    {
+        let mut conn = Conn::new_with_config(config, transport, None, stopper).await?;
+        let mut requests = 0;
 
         loop {
-            conn = match handler(conn).await.send().await? {
+            let mut conn_after_handler = match (config.handler_timeout(), config.sleep()) {
+                (Some(timeout), Some(sleep)) => {
+                    Self::call_with_timeout(conn, &handler, timeout, sleep).await?
+                }
+                _ => handler(conn).await,
+            };
+            requests += 1;
+
+            if !conn_after_handler.should_upgrade()
+                && config
+                    .max_requests_per_connection()
+                    .is_some_and(|max| requests >= max)
+            {
+                log::trace!("reached {requests} requests on this connection, closing");
+                conn_after_handler
+                    .response_headers
+                    .insert(Connection, "close");
+            }
+
+            conn = match conn_after_handler.send().await? {
                 ConnectionStatus::Upgrade(upgrade) => return Ok(Some(upgrade)),
                 ConnectionStatus::Close => return Ok(None),
                 ConnectionStatus::Conn(next) => next,
@@ -173,22 +254,116 @@ where
         }
     }
 
+    /// awaits the handler for at most `timeout`. a handler that runs
+    /// too long is cancelled, and given the same duration again to
+    /// return the conn so that an error response can be written. if
+    /// it does not, the handler is dropped, and the transport is
+    /// reclaimed from its conn to write the error response before the
+    /// connection is closed
+    async fn call_with_timeout<Fut>(
+        mut conn: Self,
+        handler: impl FnOnce(Self) -> Fut,
+        timeout: Duration,
+        sleep: Sleep,
+    ) -> Result<Self>
+    where
+        Fut: Future<Output = Self>,
+    {
+        let cancellation = conn.cancellation.clone();
+        let slot = conn.transport.arm();
+        let timer = || async move {
+            sleep(timeout).await;
+            None
+        };
+
+        // the handler future is dropped at the end of this block,
+        // returning the transport to the slot if it still holds it
+        let (conn, cancelled) = {
+            let mut handler = pin!(handler(conn));
+            if let Some(conn) = future::or(async { Some(handler.as_mut().await) }, timer()).await {
+                (Some(conn), false)
+            } else {
+                log::debug!("handler did not complete within {timeout:?}, cancelling");
+                cancellation.stop();
+                (
+                    future::or(async { Some(handler.await) }, timer()).await,
+                    true,
+                )
+            }
+        };
+
+        let Some(mut conn) = conn else {
+            log::debug!("handler did not return after cancellation, closing");
+            if let Some(mut transport) = slot.reclaim() {
+                Self::send_error_status(&mut transport, Status::ServiceUnavailable).await;
+            }
+            return Err(Error::HandlerTimeout);
+        };
+
+        conn.transport.disarm();
+        if cancelled {
+            conn.status = Some(Status::ServiceUnavailable);
+            conn.response_body = None;
+            conn.response_trailers = None;
+            conn.response_headers = Self::build_response_headers();
+            conn.response_headers.insert(Connection, "close");
+        }
+        Ok(conn)
+    }
+
     async fn send(mut self) -> Result<ConnectionStatus<Transport>> {
+        if let Err(e) = self.write_response().await {
+            log::debug!("unable to send response, cancelling conn: {e}");
+            self.cancellation.stop();
+            return Err(e);
+        }
+
+        let mut after_send = std::mem::take(&mut self.after_send);
+        after_send.call(true.into());
+        std::mem::forget(after_send);
+
+        self.finish().await
+    }
+
+    async fn write_response(&mut self) -> Result<()> {
         self.send_headers().await?;
 
+        // a streaming body may not produce anything for a while, so the
+        // head is flushed rather than left in a buffering transport
+        if self.response_body.as_ref().is_some_and(Body::is_streaming) {
+            self.transport.flush().await?;
+        }
+
         if self.method() != Method::Head
+            && !self.should_upgrade()
             && !matches!(self.status, Some(Status::NotModified | Status::NoContent))
         {
             if let Some(body) = self.response_body.take() {
-                io::copy(body, &mut self.transport).await?;
+                match self.response_trailers.take() {
+                    Some(trailers) => {
+                        write_chunked_with_trailers(&mut *self.transport, body, &trailers).await?;
+                    }
+                    // without chunked encoding, as with http/1.0, a body of
+                    // unknown length is sent as-is and ended by closing
+                    None if body.len().is_none()
+                        && !self
+                            .response_headers
+                            .eq_ignore_ascii_case(TransferEncoding, "chunked") =>
+                    {
+                        copy_body(body.into_reader(), &mut *self.transport).await?;
+                    }
+                    // static content is never reframed, so it can be
+                    // written without copying it through a buffer
+                    None => match body.static_bytes() {
+                        Some(bytes) => self.transport.write_all(bytes).await?,
+                        None => copy_body(body, &mut *self.transport).await?,
+                    },
+                }
             }
         }
 
-        let mut after_send = std::mem::take(&mut self.after_send);
-        after_send.call(true.into());
-        std::mem::forget(after_send);
-
-        self.finish().await
+        self.transport.flush().await?;
+        Ok(())
     }
 
     /// returns a read-only reference to the [state
@@ -216,6 +391,84 @@ where
         &self.request_headers
     }
 
+    /**
+    returns a reference to the trailers that followed a chunked
+    request body, if any were sent. trailers are only available once
+    the request body has been read to the end. fields that are not
+    permitted as trailers, such as `content-length`, are discarded.
+
+    ```
+    # trillium_testing::block_on(async {
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    assert_eq!(conn.request_body().await.read_string().await?, "hello");
+    assert!(conn.request_trailers().is_none());
+    # trillium_http::Result::Ok(()) }).unwrap();
+    ```
+    */
+    pub fn request_trailers(&self) -> Option<&Headers> {
+        self.request_trailers.as_ref()
+    }
+
+    /**
+    whether the client has indicated that it accepts trailers on a
+    chunked response, with `trailers` in the `TE` request header.
+    response trailers are discarded if this is false.
+
+    ```
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    assert!(!conn.accepts_trailers());
+    conn.request_headers_mut().insert("te", "gzip;q=0.5, Trailers");
+    assert!(conn.accepts_trailers());
+    ```
+    */
+    pub fn accepts_trailers(&self) -> bool {
+        self.request_headers.get_values(Te).is_some_and(|values| {
+            values
+                .iter()
+                .filter_map(HeaderValue::as_str)
+                .flat_map(|value| value.split(','))
+                .filter_map(|coding| coding.split(';').next())
+                .any(|coding| coding.trim().eq_ignore_ascii_case("trailers"))
+        })
+    }
+
+    /// returns a reference to the response trailers, if any have been
+    /// set
+    pub fn response_trailers(&self) -> Option<&Headers> {
+        self.response_trailers.as_ref()
+    }
+
+    /**
+    returns a mutable reference to the response trailers, which are
+    sent after the final chunk of a chunked response body.
+
+    trailers are only sent when the response body is chunked, which
+    is to say a streaming body of unknown length on a http/1.1 conn,
+    and the client has indicated that it accepts them (see
+    [`Conn::accepts_trailers`]).
+    fields that are not permitted as trailers, such as
+    `content-length`, `transfer-encoding`, or `host`, are discarded
+    with an error log. if the `trailer` response header has been set,
+    any trailer it does not list is also discarded. otherwise, it is
+    generated from the trailer names.
+
+    ```
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    assert!(conn.response_trailers().is_none());
+    conn.response_trailers_mut().insert("server-timing", "total;dur=12");
+    assert_eq!(
+        conn.response_trailers().unwrap().get_str("server-timing"),
+        Some("total;dur=12")
+    );
+    ```
+    */
+    pub fn response_trailers_mut(&mut self) -> &mut Headers {
+        self.response_trailers.get_or_insert_with(Headers::new)
+    }
+
     /// returns a mutable reference to the response [headers](Headers)
     pub fn request_headers_mut(&mut self) -> &mut Headers {
         &mut self.request_headers
@@ -258,8 +511,26 @@ where
         self.status
     }
 
+    /// the status that this conn's response will be sent with. this
+    /// is the [`Conn::status`] if one has been set. otherwise, a
+    /// handler that set a body but not a status has presumably handled
+    /// the request, so this is `200 Ok`, and only a conn with neither
+    /// is a `404 Not Found`
+    pub fn effective_status(&self) -> Status {
+        self.status.unwrap_or_else(|| {
+            if self.response_body.is_some() {
+                Status::Ok
+            } else {
+                Status::NotFound
+            }
+        })
+    }
+
     /**
-    retrieves the path part of the request url, up to and excluding any query component
+    retrieves the path part of the request url, up to and excluding
+    any query component. for an absolute-form request target such as
+    `http://example.com/some/path`, this is only the path, and the
+    authority is available as the [`Conn::host`]
     ```
     # use trillium_http::{Conn, Method};
     let mut conn = Conn::new_synthetic(Method::Get, "/some/path?and&a=query", ());
@@ -292,11 +563,39 @@ where
         }
     }
 
-    /// get the host for this conn, if it exists
+    /// get the host for this conn, if it exists. this is the authority
+    /// of an absolute-form request target, if the request had one
     pub fn host(&self) -> Option<&str> {
         self.request_headers.get_str(Host)
     }
 
+    /**
+    the host name of the [`Conn::host`], without any port. the
+    brackets around an ipv6 literal are removed, so that it can be
+    parsed as an [`IpAddr`]
+
+    ```
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    conn.set_host("[2001:db8::1]:443".into());
+    assert_eq!(conn.host_name(), Some("2001:db8::1"));
+    assert_eq!(conn.host_port(), Some(443));
+
+    conn.set_host("example.com".into());
+    assert_eq!(conn.host_name(), Some("example.com"));
+    assert_eq!(conn.host_port(), None);
+    ```
+    */
+    pub fn host_name(&self) -> Option<&str> {
+        self.host().map(|host| split_authority(host).0)
+    }
+
+    /// the port of the [`Conn::host`], if it includes a valid one.
+    /// see [`Conn::host_name`]
+    pub fn host_port(&self) -> Option<u16> {
+        self.host().and_then(|host| split_authority(host).1)
+    }
+
     /// set the host for this conn
     pub fn set_host(&mut self, host: String) {
         self.request_headers.insert(Host, host);
@@ -363,7 +662,7 @@ where
     assert_eq!(conn.method(), Method::Get);
     ```
      */
-    pub fn method(&self) -> Method {
+    pub const fn method(&self) -> Method {
         self.method
     }
 
@@ -382,21 +681,32 @@ where
     }
 
     fn needs_100_continue(&self) -> bool {
-        self.request_body_state == ReceivedBodyState::Start
+        self.config.sends_100_continue()
+            && self.request_body_state == ReceivedBodyState::Start
+            && !self.informational.contains(&Status::Continue)
             && self
                 .request_headers
                 .eq_ignore_ascii_case(Expect, "100-continue")
     }
 
+    /// whether the response status is already a client or server
+    /// error, in which case the request body is not solicited
+    fn is_rejected(&self) -> bool {
+        self.status
+            .is_some_and(|status| status.is_client_error() || status.is_server_error())
+    }
+
     fn build_request_body(&mut self) -> ReceivedBody<'_, Transport> {
         ReceivedBody::new(
             self.request_content_length().ok().flatten(),
             &mut self.buffer,
-            &mut self.transport,
+            &mut *self.transport,
             &mut self.request_body_state,
             None,
             encoding(&self.request_headers),
         )
+        .with_trailers(&mut self.request_trailers)
+        .with_max_chunked_len(self.config.max_chunked_body_length())
     }
 
     /**
@@ -435,6 +745,18 @@ where
     returns a [ReceivedBody] that references this conn. the conn
     retains all data and holds the singular transport, but the
     ReceivedBody provides an interface to read body content
+
+    any `gzip` or `deflate` transfer codings that were applied before
+    `chunked` are removed, so the body is read as it was before it
+    was transferred.
+
+    if the client sent `Expect: 100-continue`, this is when `100
+    Continue` is sent to solicit the body, unless the response status
+    has already been set to a client or server error. a handler that
+    rejects a request before reading its body, for example because
+    authentication failed, should set the status first so that the
+    client is not asked to upload a body that will be ignored.
+
     ```
     # async_io::block_on(async {
     # use trillium_http::{Conn, Method};
@@ -446,29 +768,230 @@ where
     ```
     */
     pub async fn request_body(&mut self) -> ReceivedBody<'_, Transport> {
-        if self.needs_100_continue() {
+        if self.needs_100_continue() && !self.is_rejected() {
             self.send_100_continue().await.ok();
         }
 
-        self.build_request_body()
+        let decoder = transfer_coding::parse(&self.request_headers)
+            .ok()
+            .flatten()
+            .and_then(|codings| TransferDecoder::new(&codings));
+
+        let request_body = self.build_request_body();
+        match decoder {
+            Some(decoder) => request_body.with_transfer_decoder(decoder),
+            None => request_body,
+        }
     }
 
     /// returns a clone of the [`stopper::Stopper`] for this Conn. use
     /// this to gracefully stop long-running futures and streams
-    /// inside of handler functions
+    /// inside of handler functions.
+    ///
+    /// once this is stopped, a request that is in flight is completed
+    /// and then the connection is closed, even if any pipelined
+    /// requests have been received, and an idle keep-alive connection
+    /// is closed immediately
     pub fn stopper(&self) -> Stopper {
         self.stopper.clone()
     }
 
-    fn validate_headers(request_headers: &Headers) -> Result<()> {
-        let content_length = request_headers.has_header(ContentLength);
-        let transfer_encoding_chunked =
-            request_headers.eq_ignore_ascii_case(TransferEncoding, "chunked");
+    /**
+    returns a clone of the request-scoped cancellation token for this
+    Conn. this is a [`Stopper`] that is distinct from the
+    server-wide [`Conn::stopper`], and is stopped when:
+
+    * writing the response to the client fails, such as when the
+      client has disconnected. work spawned from a handler that
+      outlives the handler can use this to stop early.
+    * a client disconnect is detected while awaiting a future passed to
+      [`Conn::cancel_on_disconnect`]
+    * any clone of the token is explicitly stopped. to cancel
+      request-scoped work after a timeout, stop the token from a
+      timer with your runtime of choice.
+
+    ```
+    # use trillium_http::{Conn, Method};
+    let conn = Conn::new_synthetic(Method::Get, "/", ());
+    let token = conn.cancellation_token();
+    assert!(!token.is_stopped());
+    conn.cancellation_token().stop();
+    assert!(token.is_stopped());
+    ```
+    */
+    pub fn cancellation_token(&self) -> Stopper {
+        self.cancellation.clone()
+    }
+
+    /**
+    awaits the provided future while watching the transport for a
+    client disconnect. if the client disconnects or the
+    [cancellation token](Conn::cancellation_token) is otherwise
+    stopped before the future completes, the future is dropped, the
+    cancellation token is stopped, and this returns None.
+
+    A disconnect is only detected once the request body has been
+    completely read, because the transport cannot otherwise be read
+    without consuming the body. Only an io error, such as a connection
+    reset, is considered a disconnect. A client may half-close its
+    side of the connection after sending a request while still
+    waiting for the response, so the end of the stream is not a
+    disconnect, and the transport is no longer watched after it.
+    Any bytes the client sends in the meantime, such as
+    a pipelined request, are retained for the next conn. Synthetic
+    conns never detect a disconnect.
+
+    ```
+    # trillium_testing::block_on(async {
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    assert_eq!(conn.cancel_on_disconnect(async { "result" }).await, Some("result"));
+
+    conn.cancellation_token().stop();
+    assert_eq!(conn.cancel_on_disconnect(async { "result" }).await, None);
+    # });
+    ```
+    */
+    pub async fn cancel_on_disconnect<F: Future>(&mut self, future: F) -> Option<F::Output> {
+        let cancellation = self.cancellation.clone();
+        let watch_transport = !self.synthetic && self.request_body_fully_read();
+
+        let disconnect = async {
+            if watch_transport {
+                self.wait_for_disconnect().await;
+                cancellation.stop();
+            }
+            future::pending().await
+        };
+
+        cancellation
+            .stop_future(future::or(future, disconnect))
+            .await
+    }
+
+    /// the number of request body bytes that have not yet been read
+    /// by the handler, if known. this is `None` for a chunked body
+    /// that has been partially read or not read at all
+    fn unread_request_body_len(&self) -> Option<u64> {
+        match self.request_body_state {
+            ReceivedBodyState::End => Some(0),
+            ReceivedBodyState::Start => self.request_content_length().ok().flatten(),
+            ReceivedBodyState::FixedLength {
+                current_index,
+                total_length,
+            } => Some(total_length.saturating_sub(current_index)),
+            _ => None,
+        }
+    }
+
+    /// whether the client asked to wait for `100 Continue` before
+    /// sending a body, and that body was neither solicited nor read.
+    /// the client may send it anyway once it tires of waiting, so the
+    /// connection cannot be reused, as those bytes would otherwise be
+    /// read as the next request
+    fn request_body_unsolicited(&self) -> bool {
+        self.needs_100_continue() && !matches!(self.request_content_length(), Ok(Some(0)))
+    }
+
+    /// whether the handler left more of the request body unread than
+    /// will be drained after responding, in which case the connection
+    /// is closed instead of draining it
+    fn unread_request_body_exceeds_drain_length(&self) -> bool {
+        self.unread_request_body_len()
+            .is_some_and(|len| len > self.config.max_drain_length())
+    }
+
+    /**
+    whether the request body has been read to its end, or there is
+    no request body to read. a request body that the handler leaves
+    unread is drained after the response is sent so that the next
+    request head can be read from the connection, and if more than
+    the [`ConnConfig::max_drain_length`] remains, the connection is
+    closed instead.
+
+    ```
+    # trillium_testing::block_on(async {
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    assert!(!conn.request_body_fully_read());
+    assert_eq!(conn.request_body().await.read_string().await.unwrap(), "hello");
+    assert!(conn.request_body_fully_read());
+
+    let conn = Conn::new_synthetic(Method::Get, "/", ());
+    assert!(conn.request_body_fully_read());
+    # });
+    ```
+    */
+    pub fn request_body_fully_read(&self) -> bool {
+        match self.request_body_state {
+            ReceivedBodyState::End => true,
+            ReceivedBodyState::Start => matches!(self.request_content_length(), Ok(Some(0))),
+            ReceivedBodyState::FixedLength {
+                current_index,
+                total_length,
+            } => current_index >= total_length,
+            _ => false,
+        }
+    }
+
+    async fn wait_for_disconnect(&mut self) {
+        let mut bytes = [0; 1024];
+        loop {
+            match self.transport.read(&mut bytes).await {
+                Ok(0) => {
+                    log::trace!("client closed its side of the connection, no longer watching for disconnect");
+                    future::pending::<()>().await;
+                }
+
+                Ok(n) => {
+                    let buffer = self.buffer.get_or_insert_with(Vec::new);
+                    buffer.extend_from_slice(&bytes[..n]);
+                    if buffer.len() >= self.config.max_head_length() {
+                        log::trace!("buffered too many bytes, no longer watching for disconnect");
+                        future::pending::<()>().await;
+                    }
+                }
+
+                Err(e) => {
+                    log::debug!("client disconnected before response was sent: {e}");
+                    return;
+                }
+            }
+        }
+    }
+
+    fn validate_headers(
+        request_headers: &Headers,
+        version: Version,
+        authority: Option<(&str, u16)>,
+    ) -> Result<()> {
+        let content_length = parse_content_length(request_headers)?.is_some();
+        let transfer_encoding_chunked = transfer_coding::parse(request_headers)?.is_some();
 
         if content_length && transfer_encoding_chunked {
-            Err(Error::UnexpectedHeader("content-length"))
-        } else {
-            Ok(())
+            return Err(Error::UnexpectedHeader("content-length"));
+        }
+
+        // more than one host is a request smuggling vector, and http/1.1
+        // requires exactly one
+        match request_headers
+            .get_values(Host)
+            .map_or(0, |hosts| hosts.len())
+        {
+            0 if version == Version::Http1_1 => return Err(Error::HeaderMissing("host")),
+            0 | 1 => {}
+            _ => return Err(Error::UnexpectedHeader("host")),
+        }
+
+        // a host that disagrees with an absolute-form authority is
+        // ambiguous, so it is rejected rather than ignored
+        match (authority, request_headers.get_str(Host)) {
+            (Some((authority, default_port)), Some(host))
+                if !same_authority(host, authority, default_port) =>
+            {
+                Err(Error::UnexpectedHeader("host"))
+            }
+            _ => Ok(()),
         }
     }
 
@@ -484,11 +1007,18 @@ where
 
     * there is an io error when reading from the underlying transport
     * headers are too long
-    * we are unable to parse some aspect of the request
-    * the request is an unsupported http version
+    * we are unable to parse some aspect of the request, such as an
+      unrecognized method or an unsupported http version. these are
+      responded to with a `400 Bad Request` before the error is
+      returned.
     * we cannot make sense of the headers, such as if there is a
         `content-length` header as well as a `transfer-encoding: chunked`
         header.
+    * there is more than one `host` header, or none on an http/1.1
+      request, or the `host` does not match the authority of an
+      absolute-form request target. this and the above are
+      responded to with a `400 Bad Request` before the error is
+      returned.
 
     */
     #[allow(clippy::missing_errors_doc)] // false positive
@@ -497,59 +1027,96 @@ where
         bytes: Option<Vec<u8>>,
         stopper: Stopper,
     ) -> Result<Self> {
-        let (transport, buf, extra_bytes, start_time) =
-            Self::head(transport, bytes, &stopper).await?;
+        Self::new_with_config(ConnConfig::default(), transport, bytes, stopper).await
+    }
 
-        let buffer = if extra_bytes.is_empty() {
-            None
-        } else {
-            Some(extra_bytes)
-        };
+    /**
+    the same as [`Conn::new`], but with the limits of the provided
+    [`ConnConfig`] applied while reading the request head
+
+    # Errors
+
+    This will return an error variant for the same reasons as
+    [`Conn::new`], including a request head that exceeds the limits
+    of the [`ConnConfig`]
+    */
+    pub async fn new_with_config(
+        config: ConnConfig,
+        transport: Transport,
+        bytes: Option<Vec<u8>>,
+        stopper: Stopper,
+    ) -> Result<Self> {
+        let (mut transport, mut buf, extra_bytes, start_time) =
+            Self::head(transport, bytes, &stopper, &config).await?;
 
-        let mut headers = [EMPTY_HEADER; MAX_HEADERS];
+        let mut headers = vec![EMPTY_HEADER; config.max_headers()];
         let mut httparse_req = Request::new(&mut headers);
 
-        let status = httparse_req.parse(&buf[..])?;
+        let status = match httparse_req.parse(&buf[..]) {
+            Ok(status) => status,
+            Err(httparse::Error::TooManyHeaders) => {
+                Self::send_error_status(&mut transport, Status::RequestHeaderFieldsTooLarge).await;
+                return Err(Error::Httparse(httparse::Error::TooManyHeaders));
+            }
+            // this includes bytes following a request body that do not
+            // form a valid request, such as a body that was longer than
+            // its content-length
+            Err(e) => {
+                Self::send_error_status(&mut transport, Status::BadRequest).await;
+                return Err(e.into());
+            }
+        };
+
         if status.is_partial() {
             log::debug!("partial head content: {}", String::from_utf8_lossy(&buf));
             return Err(Error::PartialHead);
         }
 
-        let method = match httparse_req.method {
-            Some(method) => match method.parse() {
-                Ok(method) => method,
-                Err(_) => return Err(Error::UnrecognizedMethod(method.to_string())),
-            },
-            None => return Err(Error::MissingMethod),
+        let (method, version, target, mut request_headers) = match parse_request(&httparse_req) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                Self::send_error_status(&mut transport, Status::BadRequest).await;
+                return Err(e);
+            }
         };
 
-        let version = match httparse_req.version {
-            Some(0) => Version::Http1_0,
-            Some(1) => Version::Http1_1,
-            Some(version) => return Err(Error::UnsupportedVersion(version)),
-            None => return Err(Error::MissingVersion),
-        };
+        let absolute_form = split_absolute_form(target);
+        let authority = absolute_form
+            .as_ref()
+            .map(|(authority, default_port, _)| (*authority, *default_port));
 
-        let mut request_headers = Headers::with_capacity(httparse_req.headers.len());
-        for header in httparse_req.headers {
-            let header_name = crate::HeaderName::from_str(header.name)?;
-            let header_value = crate::HeaderValue::from(header.value.to_owned());
-            request_headers.append(header_name, header_value);
+        if let Err(e) = Self::validate_headers(&request_headers, version, authority) {
+            let status = match e {
+                Error::UnsupportedEncoding(_) => Status::NotImplemented,
+                _ => Status::BadRequest,
+            };
+            Self::send_error_status(&mut transport, status).await;
+            return Err(e);
         }
 
-        Self::validate_headers(&request_headers)?;
-
         log::trace!("parsed headers: {:#?}", &request_headers);
 
-        let path = httparse_req
-            .path
-            .ok_or(Error::RequestPathMissing)?
-            .to_owned();
+        let path = match absolute_form {
+            // the authority of an absolute-form target takes the
+            // place of the host header, as described in rfc 9112
+            Some((authority, _, path)) => {
+                request_headers.insert(Host, authority.to_owned());
+                path
+            }
+            None => target.to_owned(),
+        };
+
+        // the head buffer is reused to hold any bytes that followed the
+        // head, so that its allocation carries over to the next request
+        // on this connection
+        buf.clear();
+        buf.extend_from_slice(&extra_bytes);
+        let buffer = Some(buf);
 
         let response_headers = Self::build_response_headers();
 
         Ok(Self {
-            transport,
+            transport: Reclaimable::new(transport),
             request_headers,
             method,
             version,
@@ -560,20 +1127,24 @@ where
             state: StateSet::new(),
             response_body: None,
             request_body_state: ReceivedBodyState::Start,
+            request_trailers: None,
+            response_trailers: None,
             secure: false,
             stopper,
+            cancellation: Stopper::new(),
+            synthetic: false,
+            config,
+            informational: vec![],
             after_send: AfterSend::default(),
             start_time,
             peer_ip: None,
+            peer_addr: None,
         })
     }
 
     fn build_response_headers() -> Headers {
         [
-            (
-                Date,
-                HeaderValues::from(httpdate::fmt_http_date(SystemTime::now())),
-            ),
+            (Date, HeaderValues::from(crate::date::http_date())),
             (Server, HeaderValues::from(SERVER)),
         ]
         .into_iter()
@@ -600,10 +1171,18 @@ where
     }
 
     /**
-    calculates any auto-generated headers for this conn prior to sending it
+    calculates any auto-generated headers for this conn prior to sending it,
+    including a `connection` header of either `keep-alive` or `close`
+    that reflects whether the connection will be reused
     */
     pub fn finalize_headers(&mut self) {
-        if self.status == Some(Status::SwitchingProtocols) {
+        if self.should_upgrade() {
+            // rfc 9110 forbids framing headers on a successful
+            // response to CONNECT, as the connection becomes a tunnel
+            if self.method == Method::Connect {
+                self.response_headers.remove(ContentLength);
+                self.response_headers.remove(TransferEncoding);
+            }
             return;
         }
 
@@ -621,14 +1200,90 @@ where
             }
         }
 
-        if self.stopper.is_stopped() {
-            self.response_headers.insert(Connection, "close");
-        } else if !self
-            .request_headers
-            .eq_ignore_ascii_case(Connection, "close")
-            && self.version == Version::Http1_1
+        self.finalize_trailers();
+
+        let keep_alive = !self.stopper.is_stopped()
+            && !self.unread_request_body_exceeds_drain_length()
+            && !self.request_body_unsolicited()
+            && !self
+                .response_headers
+                .eq_ignore_ascii_case(Connection, "close")
+            && match self.version {
+                Version::Http1_1 => !self
+                    .request_headers
+                    .eq_ignore_ascii_case(Connection, "close"),
+                // http/1.0 closes by default, and without chunked
+                // encoding the end of a body of unknown length is
+                // signaled by closing
+                _ => {
+                    self.request_headers
+                        .eq_ignore_ascii_case(Connection, "keep-alive")
+                        && self.response_headers.has_header(ContentLength)
+                }
+            };
+
+        // the decision is always advertised, so that neither end has
+        // to infer it from the version
+        self.response_headers
+            .insert(Connection, if keep_alive { "keep-alive" } else { "close" });
+    }
+
+    fn finalize_trailers(&mut self) {
+        let Some(trailers) = self.response_trailers.take() else {
+            return;
+        };
+
+        if !self
+            .response_headers
+            .eq_ignore_ascii_case(TransferEncoding, "chunked")
         {
-            self.response_headers.try_insert(Connection, "keep-alive");
+            log::error!("trailers can only be sent with a chunked response body, discarding them");
+            self.response_headers.remove(Trailer);
+            return;
+        }
+
+        if !self.accepts_trailers() {
+            log::debug!("request did not include `te: trailers`, discarding trailers");
+            self.response_headers.remove(Trailer);
+            return;
+        }
+
+        let declared = self.response_headers.get_values(Trailer).map(|values| {
+            values
+                .iter()
+                .filter_map(HeaderValue::as_str)
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        });
+
+        let is_declared = |name: &str| match &declared {
+            Some(declared) => declared.iter().any(|d| d.eq_ignore_ascii_case(name)),
+            None => true,
+        };
+
+        let mut valid = Headers::new();
+        for (name, values) in trailers.iter() {
+            if name.is_forbidden_trailer() {
+                log::error!("{name} is not permitted as a trailer, discarding it");
+            } else if !is_declared(name.as_ref()) {
+                log::error!("{name} is not listed in the trailer header, discarding it");
+            } else {
+                valid.insert(name.into_owned(), values.clone());
+            }
+        }
+
+        let names = valid
+            .names()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            self.response_headers.remove(Trailer);
+        } else {
+            self.response_headers.insert(Trailer, names.join(", "));
+            self.response_trailers = Some(valid);
         }
     }
 
@@ -657,32 +1312,104 @@ where
 
     async fn send_100_continue(&mut self) -> Result<()> {
         log::trace!("sending 100-continue");
-        Ok(self
-            .transport
-            .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
-            .await?)
+        self.send_informational(Status::Continue, &Headers::new())
+            .await
+    }
+
+    /**
+    sends an informational (1xx) response ahead of the final
+    response, such as `103 Early Hints` with `Link` headers. any
+    number of informational responses may be sent before the final
+    response, and each is recorded in
+    [`Conn::informational_responses`]. informational responses do not
+    have a body, and the provided headers are sent as-is, without
+    any of the framing headers that are added to the final response.
+
+    `100 Continue` is sent automatically when the request body is
+    read, unless the response status is already an error, and is
+    never sent more than once. as required by rfc 9110,
+    informational responses are not sent to http/1.0 clients, and
+    this returns `Ok(())` without writing anything in that case.
+    synthetic conns record the response without writing it.
+
+    # Errors
+
+    returns [`Error::UnexpectedStatus`] if the status is not 1xx or
+    is `101 Switching Protocols`, which is sent as a final response
+    for upgrades. returns an io error if the transport cannot be
+    written to.
+    */
+    pub async fn send_informational(&mut self, status: Status, headers: &Headers) -> Result<()> {
+        if !status.is_informational() || status == Status::SwitchingProtocols {
+            return Err(Error::UnexpectedStatus(status));
+        }
+
+        if self.version == Version::Http1_0 {
+            log::trace!("not sending {status} to an http/1.0 client");
+            return Ok(());
+        }
+
+        if status == Status::Continue && self.informational.contains(&Status::Continue) {
+            return Ok(());
+        }
+
+        // synthetic conns have no client to send to, but still
+        // record the response
+        if !self.synthetic {
+            write_head(&mut *self.transport, self.version, status, headers).await?;
+        }
+        self.informational.push(status);
+        Ok(())
+    }
+
+    /// the informational (1xx) responses that have been sent ahead
+    /// of the final response for this conn, in the order they were
+    /// sent
+    pub fn informational_responses(&self) -> &[Status] {
+        &self.informational
     }
 
     async fn head(
         mut transport: Transport,
         bytes: Option<Vec<u8>>,
         stopper: &Stopper,
+        config: &ConnConfig,
     ) -> Result<(Transport, Vec<u8>, Vec<u8>, Instant)> {
         let mut buf = bytes.unwrap_or_default();
         let mut len = 0;
         let mut start_with_read = buf.is_empty();
-        let mut instant = None;
+        let mut instant: Option<Instant> = None;
         let searcher = TwoWaySearcher::new(b"\r\n\r\n");
         loop {
             let bytes = if start_with_read {
-                buf.extend(iter::repeat(0).take(1024));
+                // only grow the buffer once the previous reads have
+                // filled it, rather than on every read, and read enough
+                // that most heads arrive in a single read without ever
+                // reading beyond the maximum head length
+                if buf.len() == len {
+                    let read_len =
+                        HEAD_READ_LENGTH.min(config.max_head_length().saturating_sub(len));
+                    buf.resize(len + read_len.max(1), 0);
+                }
                 if len == 0 {
                     stopper
                         .stop_future(transport.read(&mut buf[..]))
                         .await
                         .ok_or(Error::Closed)??
                 } else {
-                    transport.read(&mut buf[len..]).await?
+                    let read = transport.read(&mut buf[len..]);
+                    match (config.head_timeout(), config.sleep(), instant) {
+                        (Some(timeout), Some(sleep), Some(instant)) => {
+                            let timer = async move {
+                                sleep(timeout.saturating_sub(instant.elapsed())).await;
+                                None
+                            };
+                            future::or(async { Some(read.await) }, timer)
+                                .await
+                                .ok_or(Error::HeadTimeout)??
+                        }
+                        _ => read.await?,
+                    }
                 }
             } else {
                 start_with_read = true;
@@ -694,7 +1421,7 @@ where
             }
 
             let search_start = len.max(3) - 3;
-            let search = searcher.search_in(&buf[search_start..]);
+            let search = searcher.search_in(&buf[search_start..len + bytes]);
 
             if let Some(index) = search {
                 buf.truncate(len + bytes);
@@ -726,35 +1453,115 @@ where
                 };
             }
 
-            if len >= MAX_HEAD_LENGTH {
+            if len >= config.max_head_length() {
+                Self::send_error_status(&mut transport, Status::RequestHeaderFieldsTooLarge).await;
                 return Err(Error::HeadersTooLong);
             }
         }
     }
 
-    async fn next(mut self) -> Result<Self> {
-        if !self.needs_100_continue() || self.request_body_state != ReceivedBodyState::Start {
-            self.build_request_body().drain().await?;
+    /// writes a minimal response with this status, such as `431
+    /// Request Header Fields Too Large`, before the connection is
+    /// closed. this is best-effort, as the client may have already
+    /// stopped reading
+    async fn send_error_status(transport: &mut (impl AsyncWrite + Unpin), status: Status) {
+        let mut headers = Self::build_response_headers();
+        headers.insert(Connection, "close");
+        headers.insert(ContentLength, "0");
+
+        if let Err(e) = write_head(transport, Version::Http1_1, status, &headers).await {
+            log::debug!("unable to send {status}: {e}");
+        } else if let Err(e) = transport.flush().await {
+            log::debug!("unable to send {status}: {e}");
         }
-        Conn::new(self.transport, self.buffer, self.stopper).await
     }
 
-    fn should_close(&self) -> bool {
-        let request_connection = self.request_headers.get_lower(Connection);
-        let response_connection = self.response_headers.get_lower(Connection);
+    /// reads and discards whatever the handler left unread of the
+    /// request body, which must be consumed before the next request
+    /// head. a body longer than the drain limit is not drained, and
+    /// the connection is closed instead
+    async fn drain_request_body(&mut self) -> Result<()> {
+        let max_drain_length = self.config.max_drain_length();
+        let mut request_body = self.build_request_body();
+        let mut bytes = [0; 1024];
+        let mut drained = 0;
+        loop {
+            match request_body.read(&mut bytes).await? {
+                0 => return Ok(()),
+                n => drained += n as u64,
+            }
 
-        match (
-            request_connection.as_deref(),
-            response_connection.as_deref(),
-        ) {
-            (Some("keep-alive"), Some("keep-alive")) => false,
-            (Some("close"), _) | (_, Some("close")) => true,
-            _ => self.version == Version::Http1_0,
+            // a chunked body of unknown length is only drained up to
+            // the limit, after which the connection closes
+            if drained > max_drain_length {
+                log::debug!("unread request body exceeded {max_drain_length} bytes, closing");
+                return Err(Error::Closed);
+            }
         }
     }
 
-    fn should_upgrade(&self) -> bool {
-        self.status == Some(Status::SwitchingProtocols)
+    async fn next(mut self) -> Result<Self> {
+        let config = self.config;
+        // the keep-alive timeout includes draining the previous body,
+        // so that a client cannot hold the connection open by
+        // withholding part of a body that nothing is reading
+        let next = async move {
+            self.drain_request_body().await?;
+            Conn::new_with_config(
+                config,
+                self.transport.into_inner(),
+                self.buffer,
+                self.stopper,
+            )
+            .await
+        };
+
+        match (config.keep_alive_timeout(), config.sleep()) {
+            (Some(timeout), Some(sleep)) => {
+                future::or(next, async move {
+                    sleep(timeout).await;
+                    log::trace!("no request within the keep-alive timeout, closing");
+                    Err(Error::Closed)
+                })
+                .await
+            }
+            _ => next.await,
+        }
+    }
+
+    fn should_close(&self) -> bool {
+        if self.stopper.is_stopped() {
+            return true;
+        }
+
+        // a tunnel is closed by either end closing it, rather than by
+        // connection headers, which only apply to the CONNECT itself
+        if self.is_successful_connect() {
+            return false;
+        }
+
+        let request_connection = self.request_headers.get_lower(Connection);
+        let response_connection = self.response_headers.get_lower(Connection);
+
+        match (
+            request_connection.as_deref(),
+            response_connection.as_deref(),
+        ) {
+            (Some("keep-alive"), Some("keep-alive")) => false,
+            (Some("close"), _) | (_, Some("close")) => true,
+            _ => self.version == Version::Http1_0,
+        }
+    }
+
+    /// whether this conn should be handed off as an [`Upgrade`],
+    /// either because the response switched protocols or because it
+    /// accepted a CONNECT, turning the connection into a tunnel
+    fn should_upgrade(&self) -> bool {
+        self.status == Some(Status::SwitchingProtocols) || self.is_successful_connect()
+    }
+
+    fn is_successful_connect(&self) -> bool {
+        self.method == Method::Connect && self.status.is_some_and(|status| status.is_success())
     }
 
     async fn finish(self) -> Result<ConnectionStatus<Transport>> {
@@ -768,24 +1575,41 @@ where
                     log::trace!("connection closed by client");
                     Ok(ConnectionStatus::Close)
                 }
+                // a client that goes away partway through the head of
+                // a subsequent request has not had a request rejected,
+                // and this is routine when keep-alive connections are
+                // recycled, so it is not reported as an error
+                Err(Error::PartialHead) => {
+                    log::debug!("connection closed by client during a request head");
+                    Ok(ConnectionStatus::Close)
+                }
                 Err(e) => Err(e),
                 Ok(conn) => Ok(ConnectionStatus::Conn(conn)),
             }
         }
     }
 
-    fn request_content_length(&self) -> Result<Option<u64>> {
-        if self
-            .request_headers
-            .eq_ignore_ascii_case(TransferEncoding, "chunked")
-        {
+    /**
+    the declared length of the request body, before it is read. this
+    is `None` for a body with a `transfer-encoding`, the length of
+    which is not known until it has been read, and `Some(0)` for a
+    request with neither a `transfer-encoding` nor a
+    `content-length`.
+
+    # Errors
+
+    returns [`Error::MalformedHeader`] if the `content-length` is not
+    a valid length, and an error if the `transfer-encoding` is
+    invalid or unsupported. these requests are rejected before they
+    reach a handler unless the headers have since been modified.
+    */
+    pub fn request_content_length(&self) -> Result<Option<u64>> {
+        if transfer_coding::parse(&self.request_headers)?.is_some() {
             Ok(None)
-        } else if let Some(cl) = self.request_headers.get_str(ContentLength) {
-            cl.parse()
-                .map(Some)
-                .map_err(|_| Error::MalformedHeader("content-length".into()))
         } else {
-            Ok(Some(0))
+            Ok(Some(
+                parse_content_length(&self.request_headers)?.unwrap_or_default(),
+            ))
         }
     }
 
@@ -797,44 +1621,46 @@ where
     }
 
     async fn send_headers(&mut self) -> Result<()> {
-        let status = self.status().unwrap_or(Status::NotFound);
-        let first_line = format!(
-            "{} {} {}\r\n",
-            self.version,
-            status as u16,
-            status.canonical_reason()
-        );
-        log::trace!("sending: {}", &first_line);
-        self.transport.write_all(first_line.as_bytes()).await?;
+        let mut status = self.effective_status();
+
+        // a client keeps waiting for a final response after any 1xx
+        // other than 101, so one of those can never end the exchange
+        if status.is_informational() && status != Status::SwitchingProtocols {
+            log::error!("{status} cannot be sent as a final response, sending 500 instead");
+            status = Status::InternalServerError;
+            self.status = Some(status);
+        }
 
         self.finalize_headers();
 
         log::trace!("response headers:\n{:#?}", &self.response_headers);
 
-        for (header, values) in self.response_headers.iter() {
-            for value in &**values {
-                log::trace!("sending: {}: {}", &header, &value);
-
-                self.transport
-                    .write_all(format!("{}: ", header).as_bytes())
-                    .await?;
-                self.transport.write_all(value.as_ref()).await?;
-                self.transport.write_all(b"\r\n").await?;
-            }
-        }
-
-        self.transport.write_all(b"\r\n").await?;
-
-        Ok(())
+        write_head(
+            &mut *self.transport,
+            self.version,
+            status,
+            &self.response_headers,
+        )
+        .await
     }
 
-    /// applies a mapping function from one transport to another. This
-    /// is particularly useful for boxing the transport. unless you're
+    /// applies a mapping function from one transport to another,
+    /// keeping everything else about the conn, including any request
+    /// body that has been buffered but not yet read. This is
+    /// particularly useful for boxing the transport. unless you're
     /// sure this is what you're looking for, you probably don't want
-    /// to be using this
+    /// to be using this.
+    ///
+    /// To wrap the transport of every conn on a connection, such as in
+    /// an adapter that counts the bytes read and written, wrap it
+    /// before it is passed to [`Conn::map`] instead. Handlers can
+    /// reach the adapter through [`Conn::transport`], or through a
+    /// handle to its counts placed in each conn's
+    /// [state](Conn::state_mut), and an [`after_send`](Conn::after_send)
+    /// hook can read the counts once a response has been written.
     pub fn map_transport<T: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static>(
         self,
-        f: impl Fn(Transport) -> T,
+        f: impl FnOnce(Transport) -> T,
     ) -> Conn<T> {
         let Conn {
             request_headers,
@@ -846,13 +1672,20 @@ where
             transport,
             buffer,
             request_body_state,
+            request_trailers,
+            response_trailers,
             secure,
             method,
             response_body,
             stopper,
+            cancellation,
+            synthetic,
+            config,
+            informational,
             after_send,
             start_time,
             peer_ip,
+            peer_addr,
         } = self;
 
         Conn {
@@ -864,19 +1697,44 @@ where
             status,
             version,
             state,
-            transport: f(transport),
+            transport: transport.map(f),
             buffer,
             request_body_state,
+            request_trailers,
+            response_trailers,
             secure,
             stopper,
+            cancellation,
+            synthetic,
+            config,
+            informational,
             after_send,
             start_time,
             peer_ip,
+            peer_addr,
         }
     }
 
-    /// sets the remote ip address for this conn, if available.
+    /// returns a reference to the transport for this conn
+    pub fn transport(&self) -> &Transport {
+        &self.transport
+    }
+
+    /// returns a mutable reference to the transport for this conn.
+    /// reading from or writing to the transport directly bypasses
+    /// http framing, so this is best suited to transport
+    /// configuration, such as socket options
+    pub fn transport_mut(&mut self) -> &mut Transport {
+        &mut self.transport
+    }
+
+    /// sets the remote ip address for this conn, if available. if
+    /// this differs from the ip of the [`Conn::peer_addr`], the peer
+    /// addr is cleared, as its port no longer applies
     pub fn set_peer_ip(&mut self, peer_ip: Option<IpAddr>) {
+        if self.peer_addr.map(|peer_addr| peer_addr.ip()) != peer_ip {
+            self.peer_addr = None;
+        }
         self.peer_ip = peer_ip;
     }
 
@@ -884,4 +1742,1886 @@ where
     pub fn peer_ip(&self) -> Option<IpAddr> {
         self.peer_ip
     }
+
+    /**
+    sets the remote socket address for this conn, if available. this
+    also sets the [`Conn::peer_ip`]
+
+    ```
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    conn.set_peer_addr(Some("192.0.2.1:54321".parse().unwrap()));
+    assert_eq!(conn.peer_ip(), Some("192.0.2.1".parse().unwrap()));
+    assert_eq!(conn.peer_addr().unwrap().port(), 54321);
+
+    conn.set_peer_ip(Some("203.0.113.7".parse().unwrap()));
+    assert_eq!(conn.peer_addr(), None);
+    ```
+    */
+    pub fn set_peer_addr(&mut self, peer_addr: Option<SocketAddr>) {
+        self.peer_addr = peer_addr;
+        self.peer_ip = peer_addr.map(|peer_addr| peer_addr.ip());
+    }
+
+    /// retrieves the remote socket address, including the port, for
+    /// this conn, if available.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+}
+
+async fn write_head<Transport>(
+    transport: &mut Transport,
+    version: Version,
+    status: Status,
+    headers: &Headers,
+) -> Result<()>
+where
+    Transport: AsyncWrite + Unpin,
+{
+    let first_line = format!(
+        "{} {} {}\r\n",
+        version,
+        status as u16,
+        status.canonical_reason()
+    );
+    log::trace!("sending: {}", &first_line);
+
+    let mut head = first_line.into_bytes();
+    encode_headers(&mut head, headers);
+    transport.write_all(&head).await?;
+    Ok(())
+}
+
+/// appends each header line and the terminating blank line to `buf`,
+/// so that an entire header block can be sent in a single write
+fn encode_headers(buf: &mut Vec<u8>, headers: &Headers) {
+    for (header, values) in headers.iter() {
+        for value in &**values {
+            log::trace!("sending: {}: {}", &header, &value);
+            buf.extend_from_slice(header.as_ref().as_bytes());
+            buf.extend_from_slice(b": ");
+            buf.extend_from_slice(value.as_ref());
+            buf.extend_from_slice(b"\r\n");
+        }
+    }
+
+    buf.extend_from_slice(b"\r\n");
+}
+
+/// extracts the method, version, request target, and headers from a
+/// complete request head. any error here is a malformed request
+fn parse_request<'buf>(
+    httparse_req: &Request<'_, 'buf>,
+) -> Result<(Method, Version, &'buf str, Headers)> {
+    let method = match httparse_req.method {
+        Some(method) => match method.parse() {
+            Ok(method) => method,
+            Err(_) => return Err(Error::UnrecognizedMethod(method.to_string())),
+        },
+        None => return Err(Error::MissingMethod),
+    };
+
+    let version = match httparse_req.version {
+        Some(0) => Version::Http1_0,
+        Some(1) => Version::Http1_1,
+        Some(version) => return Err(Error::UnsupportedVersion(version)),
+        None => return Err(Error::MissingVersion),
+    };
+
+    let mut request_headers = Headers::with_capacity(httparse_req.headers.len());
+    for header in httparse_req.headers.iter() {
+        let header_name = crate::HeaderName::from_str(header.name)?;
+        let header_value = HeaderValue::from(header.value.to_owned());
+        request_headers.append(header_name, header_value);
+    }
+
+    let target = httparse_req.path.ok_or(Error::RequestPathMissing)?;
+    Ok((method, version, target, request_headers))
+}
+
+/// parses the request `content-length`. several values, whether in
+/// separate headers or a comma-separated list, are only accepted if
+/// they are identical, as described in rfc 9110, since a recipient
+/// that picks a different one than we do is a request smuggling vector
+fn parse_content_length(headers: &Headers) -> Result<Option<u64>> {
+    let Some(values) = headers.get_values(ContentLength) else {
+        return Ok(None);
+    };
+
+    let malformed = || Error::MalformedHeader("content-length".into());
+    let mut content_length = None;
+    for value in values.iter() {
+        for len in value.as_str().ok_or_else(malformed)?.split(',') {
+            let len = len.trim().parse::<u64>().map_err(|_| malformed())?;
+            if content_length.is_some_and(|content_length| content_length != len) {
+                return Err(malformed());
+            }
+            content_length = Some(len);
+        }
+    }
+
+    Ok(content_length)
+}
+
+/// splits an absolute-form request target such as
+/// `http://example.com/path?query`, as sent to a proxy, into its
+/// authority, the default port of its scheme, and its origin-form
+/// path and query. returns `None` for any other form of request target
+fn split_absolute_form(target: &str) -> Option<(&str, u16, String)> {
+    let (scheme, rest) = target.split_once("://")?;
+    let default_port = if scheme.eq_ignore_ascii_case("http") {
+        80
+    } else if scheme.eq_ignore_ascii_case("https") {
+        443
+    } else {
+        return None;
+    };
+
+    let (authority, path) = rest.split_at(rest.find(['/', '?']).unwrap_or(rest.len()));
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    if authority.is_empty() {
+        return None;
+    }
+
+    let path = if path.starts_with('/') {
+        path.to_owned()
+    } else {
+        format!("/{path}")
+    };
+
+    Some((authority, default_port, path))
+}
+
+/// compares a host header with an authority, ignoring ascii case and
+/// treating an omitted port as the scheme's default port, such that
+/// `example.com` and `example.com:80` are the same http authority. an
+/// invalid port is never mistaken for the default port
+fn same_authority(host: &str, authority: &str, default_port: u16) -> bool {
+    if host.trim().eq_ignore_ascii_case(authority) {
+        return true;
+    }
+
+    let port = |port: Option<&str>| match port {
+        None | Some("") => Some(default_port),
+        Some(port) => port.parse::<u16>().ok(),
+    };
+
+    let (host_name, host_port) = split_port(host);
+    let (authority_name, authority_port) = split_port(authority);
+    host_name.eq_ignore_ascii_case(authority_name)
+        && port(host_port).is_some()
+        && port(host_port) == port(authority_port)
+}
+
+/// splits an authority into its host and its unparsed port, if it has
+/// one. unlike [`split_authority`], an ipv6 literal keeps its brackets
+fn split_port(authority: &str) -> (&str, Option<&str>) {
+    let authority = authority.trim();
+    let colon = match authority.rfind(']') {
+        Some(bracket) => authority[bracket..].find(':').map(|colon| bracket + colon),
+        None if authority.matches(':').count() == 1 => authority.find(':'),
+        None => None,
+    };
+
+    match colon {
+        Some(colon) => (&authority[..colon], Some(&authority[colon + 1..])),
+        None => (authority, None),
+    }
+}
+
+/// splits an authority such as `example.com:8080` or `[::1]:8080`
+/// into its host, without the brackets of an ipv6 literal, and its
+/// port. a port that is absent or invalid is `None`
+fn split_authority(authority: &str) -> (&str, Option<u16>) {
+    let authority = authority.trim();
+    if let Some(rest) = authority.strip_prefix('[') {
+        if let Some((host, rest)) = rest.split_once(']') {
+            let port = rest.strip_prefix(':').and_then(|port| port.parse().ok());
+            return (host, port);
+        }
+    }
+
+    match authority.rsplit_once(':') {
+        // more than one colon is an unbracketed ipv6 literal, which
+        // cannot include a port
+        Some((host, port)) if !host.contains(':') => (host, port.parse().ok()),
+        _ => (authority, None),
+    }
+}
+
+/// reads from a response body, first flushing the transport if the
+/// body has nothing ready. this is how a streaming body, such as a
+/// stream of server-sent events, ensures that what it has produced so
+/// far reaches the client while it waits to produce more
+async fn read_or_flush<Transport>(
+    body: &mut (impl AsyncRead + Unpin),
+    buf: &mut [u8],
+    transport: &mut Transport,
+) -> Result<usize>
+where
+    Transport: AsyncWrite + Unpin,
+{
+    if let Some(bytes) = future::poll_once(body.read(buf)).await {
+        return Ok(bytes?);
+    }
+
+    transport.flush().await?;
+    Ok(body.read(buf).await?)
+}
+
+/// copies a streaming body to the transport through a single buffer
+/// that is larger than the default for [`futures_lite::io::copy`], so that a large
+/// file is written with fewer, larger writes
+async fn copy_body<Transport>(
+    mut body: impl AsyncRead + Unpin,
+    transport: &mut Transport,
+) -> Result<()>
+where
+    Transport: AsyncWrite + Unpin,
+{
+    let mut buf = vec![0; BODY_COPY_LENGTH];
+    loop {
+        let bytes = read_or_flush(&mut body, &mut buf, transport).await?;
+        if bytes == 0 {
+            break;
+        }
+        transport.write_all(&buf[..bytes]).await?;
+    }
+    Ok(())
+}
+
+async fn write_chunked_with_trailers<Transport>(
+    transport: &mut Transport,
+    body: Body,
+    trailers: &Headers,
+) -> Result<()>
+where
+    Transport: AsyncWrite + Unpin,
+{
+    let mut reader = body.into_reader();
+    let mut buf = vec![0; 8 * 1024];
+    loop {
+        let bytes = read_or_flush(&mut reader, &mut buf, transport).await?;
+        if bytes == 0 {
+            break;
+        }
+        transport
+            .write_all(format!("{bytes:X}\r\n").as_bytes())
+            .await?;
+        transport.write_all(&buf[..bytes]).await?;
+        transport.write_all(b"\r\n").await?;
+    }
+
+    log::trace!("sending trailers:\n{trailers:#?}");
+    let mut last_chunk = b"0\r\n".to_vec();
+    encode_headers(&mut last_chunk, trailers);
+    transport.write_all(&last_chunk).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        transport::BoxedTransport, Body, Conn, ConnConfig, ConnectionStatus, Error, Headers,
+        KnownHeaderName, Method, Status, Stopper, Version,
+    };
+    use async_io::Timer;
+    use futures_lite::{future, io::Cursor, AsyncReadExt, AsyncWriteExt, Future};
+    use std::{pin::Pin, time::Duration};
+    use trillium_testing::{block_on, TestTransport};
+
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async move {
+            Timer::after(duration).await;
+        })
+    }
+
+    fn head_with_headers(count: usize) -> Vec<u8> {
+        let mut head = b"GET / HTTP/1.1\r\nhost: example.com\r\n".to_vec();
+        for n in 1..count {
+            head.extend_from_slice(format!("x-header-{n}: {}\r\n", "a".repeat(64)).as_bytes());
+        }
+        head.extend_from_slice(b"\r\n");
+        head
+    }
+
+    #[test]
+    fn head_length_limit() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client.write_all(&head_with_headers(40)).await.unwrap();
+            let result = Conn::new(server, None, Stopper::new()).await;
+            assert!(matches!(result, Err(Error::HeadersTooLong)));
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+
+            let (mut client, server) = TestTransport::new();
+            client.write_all(&head_with_headers(40)).await.unwrap();
+            let config = ConnConfig::new().with_max_head_length(8 * 1024);
+            let conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            assert_eq!(conn.request_headers().iter().count(), 40);
+        });
+    }
+
+    #[test]
+    fn head_is_read_in_one_read() {
+        use futures_lite::{AsyncRead, AsyncWrite};
+        use std::{
+            io,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        struct CountingReads(Cursor<Vec<u8>>, usize);
+        impl AsyncRead for CountingReads {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                self.1 += 1;
+                Pin::new(&mut self.0).poll_read(cx, buf)
+            }
+        }
+
+        impl AsyncWrite for CountingReads {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Pin::new(&mut self.0).poll_write(cx, buf)
+            }
+
+            fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Pin::new(&mut self.0).poll_flush(cx)
+            }
+
+            fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Pin::new(&mut self.0).poll_close(cx)
+            }
+        }
+
+        block_on(async {
+            let head = head_with_headers(60);
+            assert!(head.len() > 4 * 1024);
+            let config = ConnConfig::new().with_max_head_length(16 * 1024);
+            let transport = CountingReads(Cursor::new(head), 0);
+            let conn = Conn::new_with_config(config, transport, None, Stopper::new())
+                .await
+                .unwrap();
+            assert_eq!(conn.request_headers().iter().count(), 60);
+            assert_eq!(conn.transport.1, 1);
+
+            // a read is bounded by the maximum head length
+            let mut head = head_with_headers(60);
+            head.resize(head.len() + 64 * 1024, b'a');
+            let config = ConnConfig::new().with_max_head_length(1024);
+            let transport = CountingReads(Cursor::new(head), 0);
+            let result = Conn::new_with_config(config, transport, None, Stopper::new()).await;
+            assert!(matches!(result, Err(Error::HeadersTooLong)));
+        });
+    }
+
+    #[test]
+    fn header_count_limit() {
+        block_on(async {
+            let config = ConnConfig::new().with_max_head_length(64 * 1024);
+
+            let (mut client, server) = TestTransport::new();
+            client.write_all(&head_with_headers(129)).await.unwrap();
+            let result = Conn::new_with_config(config, server, None, Stopper::new()).await;
+            assert!(matches!(
+                result,
+                Err(Error::Httparse(httparse::Error::TooManyHeaders))
+            ));
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"));
+
+            let (mut client, server) = TestTransport::new();
+            client.write_all(&head_with_headers(129)).await.unwrap();
+            let config = config.with_max_headers(256);
+            let conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            assert_eq!(conn.request_headers().iter().count(), 129);
+        });
+    }
+
+    #[test]
+    fn unparseable_request_is_a_bad_request() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"FROBNICATE / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+            let result = Conn::new(server, None, Stopper::new()).await;
+            assert!(
+                matches!(result, Err(Error::UnrecognizedMethod(method)) if method == "FROBNICATE")
+            );
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+        });
+    }
+
+    #[test]
+    fn partial_head_after_keep_alive_is_a_close() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET /partial HTTP/1.1\r\nhost: exa")
+                .await
+                .unwrap();
+            client.close();
+            assert!(matches!(
+                Conn::new(server, None, Stopper::new()).await,
+                Err(Error::PartialHead)
+            ));
+
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\nGET /next HTTP/1.1\r\nhost: exa",
+                )
+                .await
+                .unwrap();
+            client.close();
+            let mut conn = Conn::new(server, None, Stopper::new()).await.unwrap();
+            conn.set_status(200);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client.read.to_string().starts_with("HTTP/1.1 200 OK\r\n"));
+        });
+    }
+
+    #[test]
+    fn successful_connect_is_a_tunnel() {
+        block_on(async {
+            let (mut client, mut conn) = conn_for(
+                b"CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\nconnection: close\r\n\r\ntunneled",
+            )
+            .await;
+            assert_eq!(conn.method(), Method::Connect);
+            assert_eq!(conn.path(), "example.com:443");
+            conn.set_status(200);
+            conn.set_response_body("ignored");
+            let ConnectionStatus::Upgrade(mut upgrade) = conn.send().await.unwrap() else {
+                panic!("expected a tunnel")
+            };
+            assert_eq!(upgrade.buffered(), b"tunneled");
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(!response.contains("Content-Length"));
+            assert!(!response.contains("Transfer-Encoding"));
+            assert!(response.ends_with("\r\n\r\n"));
+
+            let mut tunneled = String::new();
+            client.close();
+            upgrade.read_to_string(&mut tunneled).await.unwrap();
+            assert_eq!(tunneled, "tunneled");
+
+            // an unsuccessful CONNECT is an ordinary response
+            let (client, mut conn) = conn_for(
+                b"CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\nconnection: close\r\n\r\n",
+            )
+            .await;
+            conn.set_status(407);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client.read.to_string().contains("Content-Length: 0\r\n"));
+        });
+    }
+
+    #[test]
+    fn keep_alive_timeout() {
+        block_on(async {
+            let config = ConnConfig::new()
+                .with_keep_alive_timeout(Duration::from_millis(10))
+                .with_sleep(sleep);
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+            client
+                .write_all(b"GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            conn.set_status(200);
+            let ConnectionStatus::Conn(mut conn) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(conn.path(), "/next");
+
+            conn.set_status(200);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert_eq!(client.read.to_string().matches("200 OK").count(), 2);
+        });
+    }
+
+    #[test]
+    fn keep_alive_timeout_includes_draining_the_request_body() {
+        block_on(async {
+            let config = ConnConfig::new()
+                .with_keep_alive_timeout(Duration::from_millis(10))
+                .with_sleep(sleep);
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 11\r\n\r\nhello",
+                )
+                .await
+                .unwrap();
+
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            conn.set_status(202);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client
+                .read
+                .to_string()
+                .contains("Connection: keep-alive\r\n"));
+        });
+    }
+
+    #[test]
+    fn max_requests_per_connection() {
+        block_on(async {
+            let config = ConnConfig::new().with_max_requests_per_connection(2);
+            let (mut client, server) = TestTransport::new();
+            for _ in 0..3 {
+                client
+                    .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                    .await
+                    .unwrap();
+            }
+
+            let upgrade = Conn::map_with_config(config, server, Stopper::new(), |mut conn| async {
+                conn.set_status(200);
+                conn
+            })
+            .await
+            .unwrap();
+            assert!(upgrade.is_none());
+
+            let response = client.read.to_string();
+            assert_eq!(response.matches("200 OK").count(), 2);
+            let (first, second) = response.split_at(response.rfind("HTTP/1.1").unwrap());
+            assert!(first.contains("Connection: keep-alive\r\n"));
+            assert!(second.contains("Connection: close\r\n"));
+        });
+    }
+
+    #[test]
+    fn counting_transport() {
+        use futures_lite::{AsyncRead, AsyncWrite};
+        use std::{
+            io,
+            pin::Pin,
+            sync::{
+                atomic::{AtomicUsize, Ordering},
+                Arc, Mutex,
+            },
+            task::{Context, Poll},
+        };
+
+        /// a transport adapter that counts the bytes written through it
+        struct Counting(TestTransport, Arc<AtomicUsize>);
+        impl AsyncRead for Counting {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                Pin::new(&mut self.0).poll_read(cx, buf)
+            }
+        }
+        impl AsyncWrite for Counting {
+            fn poll_write(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                let poll = Pin::new(&mut self.0).poll_write(cx, buf);
+                if let Poll::Ready(Ok(bytes)) = poll {
+                    self.1.fetch_add(bytes, Ordering::SeqCst);
+                }
+                poll
+            }
+
+            fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Pin::new(&mut self.0).poll_flush(cx)
+            }
+
+            fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Pin::new(&mut self.0).poll_close(cx)
+            }
+        }
+
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            for path in ["/short", "/long"] {
+                client
+                    .write_all(
+                        format!("GET {path} HTTP/1.1\r\nhost: example.com\r\n\r\n").as_bytes(),
+                    )
+                    .await
+                    .unwrap();
+            }
+            client.close();
+
+            let written = Arc::new(AtomicUsize::new(0));
+            let per_response = Arc::new(Mutex::new(vec![]));
+            let transport = Counting(server, Arc::clone(&written));
+            Conn::map(transport, Stopper::new(), |mut conn| async {
+                let written = Arc::clone(&conn.transport().1);
+                let before = written.load(Ordering::SeqCst);
+                let per_response = Arc::clone(&per_response);
+                conn.after_send(move |_| {
+                    let bytes = written.load(Ordering::SeqCst) - before;
+                    per_response.lock().unwrap().push(bytes);
+                });
+
+                let body = if conn.path() == "/long" {
+                    "a longer body"
+                } else {
+                    "ok"
+                };
+                conn.set_status(200);
+                conn.set_response_body(body);
+                conn
+            })
+            .await
+            .unwrap();
+
+            let response = client.read.to_string();
+            let (first, second) = response.split_at(response.rfind("HTTP/1.1").unwrap());
+            assert_eq!(*per_response.lock().unwrap(), [first.len(), second.len()]);
+            assert_eq!(written.load(Ordering::SeqCst), response.len());
+        });
+    }
+
+    #[test]
+    fn handler_timeout() {
+        block_on(async {
+            let config = ConnConfig::new()
+                .with_handler_timeout(Duration::from_millis(20))
+                .with_sleep(sleep);
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            let upgrade = Conn::map_with_config(config, server, Stopper::new(), |mut conn| async {
+                let token = conn.cancellation_token();
+                token
+                    .stop_future(Timer::after(Duration::from_secs(30)))
+                    .await;
+                conn.set_status(200);
+                conn.response_headers_mut().insert("x-late", "true");
+                conn.set_response_body("too late");
+                conn
+            })
+            .await
+            .unwrap();
+            assert!(upgrade.is_none());
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+            assert!(!response.contains("x-late"));
+            assert!(!response.contains("too late"));
+
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            let result = Conn::map_with_config(config, server, Stopper::new(), |conn| async {
+                Timer::after(Duration::from_secs(30)).await;
+                conn
+            })
+            .await;
+            assert!(matches!(result, Err(Error::HandlerTimeout)));
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+            assert!(response.contains("Content-Length: 0\r\n"));
+
+            // as with trillium, the transport may be boxed while the
+            // handler holds it
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            let result = Conn::map_with_config(config, server, Stopper::new(), |conn| async {
+                let conn = conn.map_transport(BoxedTransport::new);
+                Timer::after(Duration::from_secs(30)).await;
+                conn.map_transport(|transport| *transport.downcast().unwrap())
+            })
+            .await;
+            assert!(matches!(result, Err(Error::HandlerTimeout)));
+            assert!(client
+                .read
+                .to_string()
+                .starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        });
+    }
+
+    #[test]
+    fn stopping_closes_after_the_in_flight_response() {
+        use futures_lite::AsyncRead;
+        use std::{
+            io,
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        /// a response body that stops the server when it is first read
+        struct StopOnRead(Stopper, Cursor<&'static [u8]>);
+        impl AsyncRead for StopOnRead {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                self.0.stop();
+                Pin::new(&mut self.1).poll_read(cx, buf)
+            }
+        }
+
+        block_on(async {
+            let stopper = Stopper::new();
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n\
+                      GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+                )
+                .await
+                .unwrap();
+
+            let mut conn = Conn::new(server, None, stopper.clone()).await.unwrap();
+            conn.set_status(200);
+            conn.set_response_body(Body::new_streaming(
+                StopOnRead(stopper, Cursor::new(b"hello")),
+                Some(5),
+            ));
+
+            // the response headers were sent before the server was
+            // stopped, but the pipelined request is not handled
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            let response = client.read.to_string();
+            assert!(response.contains("keep-alive"));
+            assert!(response.ends_with("hello"));
+        });
+    }
+
+    async fn trickle(client: &mut TestTransport, bytes: &[u8]) {
+        for byte in bytes {
+            client.write_all(&[*byte]).await.unwrap();
+            Timer::after(Duration::from_millis(2)).await;
+        }
+    }
+
+    #[test]
+    fn head_timeout() {
+        block_on(async {
+            let head = b"GET / HTTP/1.1\r\nhost: example.com\r\nx-slow: aaaaaaaaaaaaaaaa\r\n\r\n";
+
+            let config = ConnConfig::new()
+                .with_head_timeout(Duration::from_millis(20))
+                .with_sleep(sleep);
+            let (mut client, server) = TestTransport::new();
+            let ((), result) = future::zip(
+                trickle(&mut client, head),
+                Conn::new_with_config(config, server, None, Stopper::new()),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::HeadTimeout)));
+
+            let config = ConnConfig::new()
+                .with_head_timeout(Duration::from_secs(30))
+                .with_sleep(sleep);
+            let (mut client, server) = TestTransport::new();
+            let ((), result) = future::zip(
+                trickle(&mut client, head),
+                Conn::new_with_config(config, server, None, Stopper::new()),
+            )
+            .await;
+            assert_eq!(result.unwrap().path(), "/");
+        });
+    }
+
+    #[test]
+    fn cancel_on_disconnect() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut conn = Conn::new(server, None, Stopper::new()).await.unwrap();
+            assert_eq!(
+                conn.cancel_on_disconnect(async { "done" }).await,
+                Some("done")
+            );
+            assert!(!conn.cancellation_token().is_stopped());
+
+            client
+                .write_all(b"GET /pipelined HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+            client.close();
+
+            // a half-closed connection is not a disconnect
+            let work = async {
+                for _ in 0..10 {
+                    future::yield_now().await;
+                }
+                "done"
+            };
+            assert_eq!(conn.cancel_on_disconnect(work).await, Some("done"));
+            assert!(!conn.cancellation_token().is_stopped());
+            assert_eq!(
+                conn.buffer.as_deref(),
+                Some(&b"GET /pipelined HTTP/1.1\r\nhost: example.com\r\n\r\n"[..])
+            );
+        });
+    }
+
+    #[test]
+    fn cancel_on_reset() {
+        use std::{
+            io,
+            task::{Context, Poll},
+        };
+
+        /// reads the provided bytes and then fails as if the connection
+        /// had been reset, discarding anything written
+        struct ResetTransport(Cursor<Vec<u8>>);
+        impl futures_lite::AsyncRead for ResetTransport {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<io::Result<usize>> {
+                match Pin::new(&mut self.0).poll_read(cx, buf) {
+                    Poll::Ready(Ok(0)) => Poll::Ready(Err(io::ErrorKind::ConnectionReset.into())),
+                    other => other,
+                }
+            }
+        }
+        impl futures_lite::AsyncWrite for ResetTransport {
+            fn poll_write(
+                self: Pin<&mut Self>,
+                _: &mut Context<'_>,
+                buf: &[u8],
+            ) -> Poll<io::Result<usize>> {
+                Poll::Ready(Ok(buf.len()))
+            }
+
+            fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+                Poll::Ready(Ok(()))
+            }
+        }
+
+        block_on(async {
+            let transport = ResetTransport(Cursor::new(
+                b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n".to_vec(),
+            ));
+            let mut conn = Conn::new(transport, None, Stopper::new()).await.unwrap();
+            assert_eq!(
+                conn.cancel_on_disconnect(future::pending::<()>()).await,
+                None
+            );
+            assert!(conn.cancellation_token().is_stopped());
+        });
+    }
+
+    #[test]
+    fn unread_request_body_is_not_consumed() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\n\r\n")
+                .await
+                .unwrap();
+
+            let mut conn = Conn::new(server, None, Stopper::new()).await.unwrap();
+            client.write_all(b"hello").await.unwrap();
+            client.close();
+
+            let work = async {
+                for _ in 0..10 {
+                    future::yield_now().await;
+                }
+                "done"
+            };
+            assert_eq!(conn.cancel_on_disconnect(work).await, Some("done"));
+
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello"
+            );
+
+            // the client has only half-closed the connection
+            let work = async {
+                for _ in 0..10 {
+                    future::yield_now().await;
+                }
+                "done"
+            };
+            assert_eq!(conn.cancel_on_disconnect(work).await, Some("done"));
+            assert!(!conn.cancellation_token().is_stopped());
+        });
+    }
+
+    async fn conn_for(request: &[u8]) -> (TestTransport, Conn<TestTransport>) {
+        let (mut client, server) = TestTransport::new();
+        client.write_all(request).await.unwrap();
+        let conn = Conn::new(server, None, Stopper::new()).await.unwrap();
+        (client, conn)
+    }
+
+    #[cfg(feature = "transfer-codings")]
+    #[test]
+    fn transfer_codings_are_removed_from_the_request_body() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        block_on(async {
+            let mut encoder = GzEncoder::new(vec![], Compression::default());
+            encoder.write_all(b"hello hello hello").unwrap();
+            let gzipped = encoder.finish().unwrap();
+
+            let mut request = b"POST / HTTP/1.1\r\nhost: example.com\r\n\
+                transfer-encoding: gzip, chunked\r\n\r\n"
+                .to_vec();
+            for chunk in gzipped.chunks(10) {
+                request.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+                request.extend_from_slice(chunk);
+                request.extend_from_slice(b"\r\n");
+            }
+            request.extend_from_slice(b"0\r\n\r\n");
+
+            let (_client, mut conn) = conn_for(&request).await;
+            assert_eq!(conn.request_content_length().unwrap(), None);
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello hello hello"
+            );
+
+            // a body that is not valid gzip is an error rather than
+            // being read as-is
+            let (_client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\n\
+                  transfer-encoding: gzip, chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+            )
+            .await;
+            assert!(conn.request_body().await.read_string().await.is_err());
+
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"POST / HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: br, chunked\r\n\r\n")
+                .await
+                .unwrap();
+            let result = Conn::new(server, None, Stopper::new()).await;
+            assert!(matches!(result, Err(Error::UnsupportedEncoding(name)) if name == "br"));
+            assert!(client
+                .read
+                .to_string()
+                .starts_with("HTTP/1.1 501 Not Implemented\r\n"));
+        });
+    }
+
+    #[cfg(feature = "transfer-codings")]
+    #[test]
+    fn stacked_transfer_codings_are_decoded_as_they_are_read() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        fn gzip(bytes: &[u8]) -> Vec<u8> {
+            let mut encoder = GzEncoder::new(vec![], Compression::best());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        }
+
+        block_on(async {
+            // 64mb of zeros, compressed to a few hundred bytes
+            let mut inner = GzEncoder::new(vec![], Compression::best());
+            let zeros = vec![0; 1024 * 1024];
+            for _ in 0..64 {
+                inner.write_all(&zeros).unwrap();
+            }
+            let bomb = gzip(&inner.finish().unwrap());
+
+            let mut request = format!(
+                "POST / HTTP/1.1\r\nhost: example.com\r\n\
+                 transfer-encoding: gzip, gzip, chunked\r\n\r\n{:x}\r\n",
+                bomb.len()
+            )
+            .into_bytes();
+            request.extend_from_slice(&bomb);
+            request.extend_from_slice(b"\r\n0\r\n\r\n");
+
+            let (_client, mut conn) = conn_for(&request).await;
+            let result = conn
+                .request_body()
+                .await
+                .read_bytes_with_max_len(1024)
+                .await;
+            assert!(matches!(result, Err(Error::ReceivedBodyTooLong(1024))));
+        });
+    }
+
+    #[test]
+    fn request_body_ends_at_content_length() {
+        block_on(async {
+            let (_client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\n\r\nhello\
+                  GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+            )
+            .await;
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello"
+            );
+            conn.set_status(200);
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.method(), Method::Get);
+            assert_eq!(next.path(), "/next");
+
+            let (client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\n\r\nhello world\r\n\r\n",
+            )
+            .await;
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello"
+            );
+            conn.set_status(200);
+            assert!(matches!(conn.send().await, Err(Error::Httparse(_))));
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.contains("HTTP/1.1 400 Bad Request\r\n"));
+        });
+    }
+
+    #[test]
+    fn unread_request_body_is_drained_after_an_early_response() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"POST /upload HTTP/1.1\r\nhost: example.com\r\ncontent-length: 11\r\n\r\nhello world\
+                  GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+            )
+            .await;
+            assert!(!conn.request_body_fully_read());
+            conn.set_status(202);
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.path(), "/next");
+            assert!(client
+                .read
+                .to_string()
+                .contains("Connection: keep-alive\r\n"));
+
+            let (_client, mut conn) = conn_for(
+                b"POST /upload HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: chunked\r\n\r\n\
+                  5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n\
+                  GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+            )
+            .await;
+            let mut partial = [0; 3];
+            conn.request_body()
+                .await
+                .read_exact(&mut partial)
+                .await
+                .unwrap();
+            assert!(!conn.request_body_fully_read());
+            conn.set_status(202);
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.path(), "/next");
+        });
+    }
+
+    #[test]
+    fn request_body_fully_read() {
+        block_on(async {
+            let (_client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 11\r\n\r\nhello world",
+            )
+            .await;
+            let mut body = [0; 11];
+            conn.request_body()
+                .await
+                .read_exact(&mut body)
+                .await
+                .unwrap();
+            assert!(conn.request_body_fully_read());
+
+            let (_client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: chunked\r\n\r\n\
+                  5\r\nhello\r\n0\r\n\r\n",
+            )
+            .await;
+            assert!(!conn.request_body_fully_read());
+            conn.request_body().await.read_string().await.unwrap();
+            assert!(conn.request_body_fully_read());
+
+            let (_client, conn) = conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n").await;
+            assert!(conn.request_body_fully_read());
+        });
+    }
+
+    #[test]
+    fn unread_request_body_over_the_drain_length_closes() {
+        block_on(async {
+            let config = ConnConfig::new().with_max_drain_length(5);
+
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 11\r\n\r\nhello world")
+                .await
+                .unwrap();
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            conn.set_status(202);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client.read.to_string().contains("Connection: close\r\n"));
+
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: chunked\r\n\r\n\
+                      5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            conn.set_status(202);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+        });
+    }
+
+    #[test]
+    fn informational_responses_precede_the_final_response() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+
+            let mut hints = Headers::new();
+            hints.insert(KnownHeaderName::Link, "</style.css>; rel=preload; as=style");
+            conn.send_informational(Status::EarlyHints, &hints)
+                .await
+                .unwrap();
+            conn.send_informational(Status::EarlyHints, &hints)
+                .await
+                .unwrap();
+            assert_eq!(
+                conn.informational_responses(),
+                &[Status::EarlyHints, Status::EarlyHints]
+            );
+
+            conn.set_status(200);
+            conn.set_response_body("ok");
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            let early_hints = "HTTP/1.1 103 Early Hints\r\n\
+                Link: </style.css>; rel=preload; as=style\r\n\r\n";
+            assert!(response.starts_with(&early_hints.repeat(2)));
+
+            let final_response = &response[early_hints.len() * 2..];
+            assert!(final_response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(final_response.contains("Content-Length: 2\r\n"));
+            assert!(!final_response.contains("Link"));
+            assert!(final_response.ends_with("\r\n\r\nok"));
+        });
+    }
+
+    #[test]
+    fn response_trailers() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\nte: trailers\r\n\r\n",
+            )
+            .await;
+            conn.set_status(200);
+            conn.set_response_body(Body::new_streaming(Cursor::new("hello"), None));
+            let trailers = conn.response_trailers_mut();
+            trailers.insert(KnownHeaderName::ServerTiming, "total;dur=12");
+            trailers.insert(KnownHeaderName::ContentLength, "5");
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+            assert!(response.contains("Trailer: Server-Timing\r\n"));
+            assert!(response.ends_with("5\r\nhello\r\n0\r\nServer-Timing: total;dur=12\r\n\r\n"));
+            assert!(!response.contains("Content-Length"));
+        });
+    }
+
+    #[test]
+    fn response_trailers_must_be_declared() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\nte: trailers\r\n\r\n",
+            )
+            .await;
+            conn.set_status(200);
+            conn.set_response_body(Body::new_streaming(Cursor::new("hello"), None));
+            conn.response_headers_mut()
+                .insert(KnownHeaderName::Trailer, "x-declared, content-type");
+            let trailers = conn.response_trailers_mut();
+            trailers.insert("x-declared", "yes");
+            trailers.insert("x-undeclared", "no");
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            assert!(response.contains("Trailer: x-declared\r\n"));
+            assert!(response.ends_with("0\r\nx-declared: yes\r\n\r\n"));
+            assert!(!response.contains("x-undeclared"));
+        });
+    }
+
+    #[test]
+    fn response_trailers_require_chunked_body() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\nte: trailers\r\n\r\n",
+            )
+            .await;
+            conn.set_status(200);
+            conn.set_response_body("hello");
+            conn.response_headers_mut()
+                .insert(KnownHeaderName::Trailer, "x-trailer");
+            conn.response_trailers_mut().insert("x-trailer", "value");
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            assert!(response.contains("Content-Length: 5\r\n"));
+            assert!(!response.contains("Trailer"));
+            assert!(!response.contains("x-trailer"));
+            assert!(response.ends_with("\r\n\r\nhello"));
+        });
+    }
+
+    #[test]
+    fn continue_is_only_sent_once() {
+        block_on(async {
+            let (mut client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\nexpect: 100-continue\r\ncontent-length: 5\r\n\r\n",
+            )
+            .await;
+
+            drop(conn.request_body().await);
+            conn.send_informational(Status::Continue, &Headers::new())
+                .await
+                .unwrap();
+            client.write_all(b"hello").await.unwrap();
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello"
+            );
+            assert_eq!(conn.informational_responses(), &[Status::Continue]);
+
+            conn.set_status(204);
+            client.close();
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            assert_eq!(response.matches("100 Continue").count(), 1);
+            assert!(
+                response.starts_with("HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 204 No Content\r\n")
+            );
+        });
+    }
+
+    #[test]
+    fn continue_is_not_sent_after_rejection() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"POST / HTTP/1.1\r\nhost: example.com\r\nexpect: 100-continue\r\ncontent-length: 5\r\n\r\n",
+            )
+            .await;
+
+            conn.set_status(401);
+            drop(conn.request_body().await);
+            assert!(conn.informational_responses().is_empty());
+
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+
+            let response = client.read.to_string();
+            assert!(!response.contains("100 Continue"));
+            assert!(response.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+        });
+    }
+
+    #[test]
+    fn unsolicited_continue_body_closes_the_connection() {
+        block_on(async {
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\nexpect: 100-continue\r\ncontent-length: 44\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut conn = Conn::new(server, None, Stopper::new()).await.unwrap();
+
+            // the client stops waiting for 100 Continue and sends a body
+            // that looks like a second request over the same connection
+            client
+                .write_all(b"GET /smuggled HTTP/1.1\r\nhost: example.com\r\n\r\n")
+                .await
+                .unwrap();
+
+            conn.set_status(401);
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.1 401 Unauthorized\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+            assert!(!response.contains("100 Continue"));
+        });
+    }
+
+    #[test]
+    fn continue_can_be_disabled() {
+        block_on(async {
+            let config = ConnConfig::new().without_100_continue();
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\nexpect: 100-continue\r\ncontent-length: 5\r\n\r\nhello\
+                      GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            assert_eq!(
+                conn.request_body().await.read_string().await.unwrap(),
+                "hello"
+            );
+            assert!(conn.informational_responses().is_empty());
+
+            // an unread body is drained rather than assumed to be withheld
+            let (mut client, server) = TestTransport::new();
+            client
+                .write_all(
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\nexpect: 100-continue\r\ncontent-length: 5\r\n\r\nhello\
+                      GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+                )
+                .await
+                .unwrap();
+            let mut conn = Conn::new_with_config(config, server, None, Stopper::new())
+                .await
+                .unwrap();
+            conn.set_status(200);
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.path(), "/next");
+            assert!(!client.read.to_string().contains("100 Continue"));
+        });
+    }
+
+    #[test]
+    fn informational_status_validation() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+
+            for status in [Status::Ok, Status::SwitchingProtocols] {
+                assert!(matches!(
+                    conn.send_informational(status, &Headers::new()).await,
+                    Err(Error::UnexpectedStatus(s)) if s == status
+                ));
+            }
+            assert!(conn.informational_responses().is_empty());
+
+            conn.set_status(Status::EarlyHints);
+            conn.send().await.unwrap();
+            assert!(client
+                .read
+                .to_string()
+                .starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+        });
+    }
+
+    #[test]
+    fn informational_responses_are_not_sent_to_http_1_0() {
+        block_on(async {
+            let (client, mut conn) = conn_for(b"GET / HTTP/1.0\r\n\r\n").await;
+            conn.send_informational(Status::EarlyHints, &Headers::new())
+                .await
+                .unwrap();
+            assert!(conn.informational_responses().is_empty());
+            conn.set_status(200);
+            conn.send().await.unwrap();
+            assert!(client.read.to_string().starts_with("HTTP/1.0 200 OK\r\n"));
+        });
+    }
+
+    #[test]
+    fn default_status() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+            conn.set_response_body("ok");
+            conn.send().await.unwrap();
+            assert!(client.read.to_string().starts_with("HTTP/1.1 200 OK\r\n"));
+
+            let (client, conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+            conn.send().await.unwrap();
+            assert!(client
+                .read
+                .to_string()
+                .starts_with("HTTP/1.1 404 Not Found\r\n"));
+        });
+    }
+
+    #[test]
+    fn http_1_0_closes_by_default() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.0\r\n\r\nGET /next HTTP/1.0\r\n\r\n").await;
+            assert_eq!(conn.http_version(), Version::Http1_0);
+            conn.set_status(200);
+            conn.set_response_body("ok");
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+            assert!(response.contains("Connection: close\r\n"));
+            assert!(!response.contains("Transfer-Encoding"));
+        });
+    }
+
+    #[test]
+    fn http_1_0_keep_alive() {
+        block_on(async {
+            let (client, mut conn) = conn_for(
+                b"GET / HTTP/1.0\r\nconnection: keep-alive\r\n\r\nGET /next HTTP/1.0\r\n\r\n",
+            )
+            .await;
+            conn.set_status(200);
+            conn.set_response_body("ok");
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.path(), "/next");
+            assert_eq!(next.http_version(), Version::Http1_0);
+
+            let response = client.read.to_string();
+            assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+            assert!(response.contains("Connection: keep-alive\r\n"));
+        });
+    }
+
+    #[test]
+    fn connection_header_reflects_the_close_decision() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+            conn.set_status(200);
+            conn.response_headers_mut()
+                .insert("connection", "keep-alive");
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client.read.to_string().contains("Connection: close\r\n"));
+
+            let (client, mut conn) = conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n").await;
+            conn.set_status(200);
+            conn.response_headers_mut().insert("connection", "close");
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+            assert!(client.read.to_string().contains("Connection: close\r\n"));
+        });
+    }
+
+    #[test]
+    fn http_1_0_keep_alive_requires_content_length() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.0\r\nconnection: keep-alive\r\n\r\n").await;
+            conn.set_status(200);
+            conn.set_response_body(Body::new_streaming(Cursor::new("ok"), None));
+            assert!(matches!(
+                conn.send().await.unwrap(),
+                ConnectionStatus::Close
+            ));
+
+            let response = client.read.to_string();
+            assert!(response.contains("Connection: close\r\n"));
+            assert!(response.ends_with("\r\n\r\nok"));
+        });
+    }
+
+    #[derive(Default)]
+    struct CountingWriter {
+        written: Vec<u8>,
+        writes: usize,
+        flushed: Vec<usize>,
+    }
+
+    impl futures_lite::AsyncWrite for CountingWriter {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            self.writes += 1;
+            self.written.extend_from_slice(buf);
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            let len = self.written.len();
+            self.flushed.push(len);
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn head_is_written_at_once() {
+        block_on(async {
+            let mut headers = Headers::new();
+            headers.insert("x-a", "1");
+            headers.append("x-b", "2");
+            headers.append("x-b", "3");
+            headers.insert(KnownHeaderName::ContentLength, "0");
+
+            let mut writer = CountingWriter::default();
+            super::write_head(&mut writer, Version::Http1_1, Status::Ok, &headers)
+                .await
+                .unwrap();
+            assert_eq!(writer.writes, 1);
+
+            let head = String::from_utf8(writer.written).unwrap();
+            assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(head.contains("x-b: 2\r\nx-b: 3\r\n"));
+            assert!(head.contains("Content-Length: 0\r\n"));
+            assert!(head.ends_with("\r\n\r\n"));
+            assert_eq!(head.matches("\r\n").count(), 6);
+        });
+    }
+
+    #[test]
+    fn bodies_are_written_in_large_slices() {
+        block_on(async {
+            let content = vec![b'a'; 200 * 1024];
+
+            let mut writer = CountingWriter::default();
+            let body = Body::new_streaming(Cursor::new(content.clone()), Some(200 * 1024));
+            super::copy_body(body, &mut writer).await.unwrap();
+            assert_eq!(writer.writes, 4);
+            assert_eq!(writer.written, content);
+
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+            conn.set_status(200);
+            conn.set_response_body(content.clone());
+            conn.send().await.unwrap();
+            let response = client.read.to_string();
+            assert!(response.contains("Content-Length: 204800\r\n"));
+            assert!(response.ends_with(&format!("\r\n\r\n{}", "a".repeat(200 * 1024))));
+        });
+    }
+
+    #[test]
+    fn streaming_bodies_flush_while_waiting() {
+        use std::{
+            pin::Pin,
+            task::{Context, Poll},
+        };
+
+        /// yields each chunk after first returning pending once
+        struct Slow(Vec<&'static [u8]>, bool);
+        impl futures_lite::AsyncRead for Slow {
+            fn poll_read(
+                mut self: Pin<&mut Self>,
+                cx: &mut Context<'_>,
+                buf: &mut [u8],
+            ) -> Poll<std::io::Result<usize>> {
+                if self.0.is_empty() {
+                    return Poll::Ready(Ok(0));
+                }
+                self.1 = !self.1;
+                if self.1 {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                let chunk = self.0.remove(0);
+                buf[..chunk.len()].copy_from_slice(chunk);
+                Poll::Ready(Ok(chunk.len()))
+            }
+        }
+
+        block_on(async {
+            let mut writer = CountingWriter::default();
+            let body = Slow(vec![b"one", b"two"], false);
+            super::copy_body(body, &mut writer).await.unwrap();
+            assert_eq!(writer.written, b"onetwo");
+            assert_eq!(writer.flushed, [0, 3]);
+
+            // nothing is flushed while a body has bytes ready
+            let mut writer = CountingWriter::default();
+            super::copy_body(Cursor::new(vec![0; 10]), &mut writer)
+                .await
+                .unwrap();
+            assert!(writer.flushed.is_empty());
+        });
+    }
+
+    #[test]
+    fn response_trailers_require_te_trailers() {
+        block_on(async {
+            let (client, mut conn) =
+                conn_for(b"GET / HTTP/1.1\r\nhost: example.com\r\nconnection: close\r\n\r\n").await;
+            assert!(!conn.accepts_trailers());
+            conn.set_status(200);
+            conn.set_response_body(Body::new_streaming(Cursor::new("hello"), None));
+            conn.response_trailers_mut()
+                .insert(KnownHeaderName::ServerTiming, "total;dur=12");
+            conn.send().await.unwrap();
+
+            let response = client.read.to_string();
+            assert!(response.contains("Transfer-Encoding: chunked\r\n"));
+            assert!(!response.contains("Trailer"));
+            assert!(!response.contains("Server-Timing"));
+            assert!(response.ends_with("5\r\nhello\r\n0\r\n\r\n"));
+        });
+    }
+
+    #[test]
+    fn absolute_form_request_target() {
+        block_on(async {
+            let (_, conn) = conn_for(
+                b"GET http://user@Example.com:8080/some/path?a=b HTTP/1.1\r\nhost: example.com:8080\r\n\r\n",
+            )
+            .await;
+            assert_eq!(conn.path(), "/some/path");
+            assert_eq!(conn.querystring(), "a=b");
+            assert_eq!(conn.host(), Some("Example.com:8080"));
+            assert_eq!(conn.host_name(), Some("Example.com"));
+            assert_eq!(conn.host_port(), Some(8080));
+
+            let (_, conn) = conn_for(b"GET HTTPS://example.com?a=b HTTP/1.0\r\n\r\n").await;
+            assert_eq!(conn.path(), "/");
+            assert_eq!(conn.querystring(), "a=b");
+            assert_eq!(conn.host(), Some("example.com"));
+
+            let (_, conn) =
+                conn_for(b"CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\n\r\n")
+                    .await;
+            assert_eq!(conn.path(), "example.com:443");
+        });
+    }
+
+    #[test]
+    fn ipv6_host() {
+        block_on(async {
+            let (_, conn) = conn_for(b"GET / HTTP/1.1\r\nhost: [2001:db8::1]:443\r\n\r\n").await;
+            assert_eq!(conn.host(), Some("[2001:db8::1]:443"));
+            assert_eq!(conn.host_name(), Some("2001:db8::1"));
+            assert_eq!(conn.host_port(), Some(443));
+
+            let (_, conn) = conn_for(b"GET http://[::1]/ HTTP/1.1\r\nhost: [::1]\r\n\r\n").await;
+            assert_eq!(conn.host_name(), Some("::1"));
+            assert_eq!(conn.host_port(), None);
+
+            let (_, conn) = conn_for(b"GET / HTTP/1.1\r\nhost: example.com:notaport\r\n\r\n").await;
+            assert_eq!(conn.host_name(), Some("example.com"));
+            assert_eq!(conn.host_port(), None);
+        });
+    }
+
+    #[test]
+    fn head_buffer_is_reused_across_requests() {
+        block_on(async {
+            let (_client, mut conn) = conn_for(
+                b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n\
+                  GET /next HTTP/1.1\r\nhost: example.com\r\n\r\n",
+            )
+            .await;
+            let capacity = conn.buffer.as_ref().map_or(0, Vec::capacity);
+            assert!(capacity >= 1024);
+
+            conn.set_status(200);
+            let ConnectionStatus::Conn(next) = conn.send().await.unwrap() else {
+                panic!("expected the connection to be kept alive")
+            };
+            assert_eq!(next.path(), "/next");
+            assert_eq!(next.buffer.as_deref(), Some(&b""[..]));
+            assert_eq!(next.buffer.as_ref().map_or(0, Vec::capacity), capacity);
+        });
+    }
+
+    #[test]
+    fn host_header_validation() {
+        block_on(async {
+            let cases: [(&[u8], Option<&str>); 18] = [
+                (b"GET / HTTP/1.1\r\nhost: example.com\r\n\r\n", None),
+                (b"GET / HTTP/1.0\r\n\r\n", None),
+                (
+                    b"GET / HTTP/1.1\r\n\r\n",
+                    Some("mandatory host header missing"),
+                ),
+                (
+                    b"GET / HTTP/1.1\r\nhost: example.com\r\nHost: attacker.example\r\n\r\n",
+                    Some("unexpected header: host"),
+                ),
+                (
+                    b"GET / HTTP/1.0\r\nhost: example.com\r\nhost: example.com\r\n\r\n",
+                    Some("unexpected header: host"),
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\ncontent-length: 5, 5\r\n\r\n",
+                    None,
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\ncontent-length: 50\r\n\r\n",
+                    Some("malformed http header content-length"),
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5, 6\r\n\r\n",
+                    Some("malformed http header content-length"),
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ncontent-length: -5\r\n\r\n",
+                    Some("malformed http header content-length"),
+                ),
+                (
+                    b"GET http://example.com/ HTTP/1.1\r\nhost: EXAMPLE.com\r\n\r\n",
+                    None,
+                ),
+                (
+                    b"GET http://example.com/ HTTP/1.1\r\nhost: attacker.example\r\n\r\n",
+                    Some("unexpected header: host"),
+                ),
+                (
+                    b"GET http://example.com/ HTTP/1.1\r\nhost: example.com:80\r\n\r\n",
+                    None,
+                ),
+                (
+                    b"GET https://Example.com:443/ HTTP/1.1\r\nhost: example.com\r\n\r\n",
+                    None,
+                ),
+                (
+                    b"GET http://[::1]:80/ HTTP/1.1\r\nhost: [::1]:\r\n\r\n",
+                    None,
+                ),
+                (
+                    b"GET https://example.com/ HTTP/1.1\r\nhost: example.com:80\r\n\r\n",
+                    Some("unexpected header: host"),
+                ),
+                (
+                    b"GET http://example.com/ HTTP/1.1\r\nhost: example.com:notaport\r\n\r\n",
+                    Some("unexpected header: host"),
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: chunked, gzip\r\n\r\n",
+                    Some("malformed http header transfer-encoding"),
+                ),
+                (
+                    b"POST / HTTP/1.1\r\nhost: example.com\r\ntransfer-encoding: identity, chunked\r\ncontent-length: 5\r\n\r\n",
+                    Some("unexpected header: content-length"),
+                ),
+            ];
+
+            for (request, expected_error) in cases {
+                let (mut client, server) = TestTransport::new();
+                client.write_all(request).await.unwrap();
+                let result = Conn::new(server, None, Stopper::new()).await;
+                match expected_error {
+                    None => assert!(result.is_ok()),
+                    Some(expected_error) => {
+                        assert_eq!(result.unwrap_err().to_string(), expected_error);
+                        let response = client.read.to_string();
+                        assert!(response.starts_with("HTTP/1.1 400 Bad Request\r\n"));
+                        assert!(response.contains("Connection: close\r\n"));
+                    }
+                }
+            }
+        });
+    }
 }
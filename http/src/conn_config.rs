@@ -0,0 +1,287 @@
+use std::{future::Future, pin::Pin, time::Duration};
+
+/// a function that returns a future that resolves after the provided
+/// duration, which is used to enforce the timeouts in a
+/// [`ConnConfig`]. this is provided by the async runtime, so that
+/// trillium-http does not depend on any particular timer
+pub type Sleep = fn(Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+/**
+# Limits for serving http requests on a connection
+
+A `ConnConfig` is provided to [`Conn::map_with_config`] or
+[`Conn::new_with_config`] to raise or lower the limits that are
+applied to a connection. The number of headers, the length of the
+request head, and how long it may take to arrive are applied while
+reading each request head. The length of a chunked request body is
+applied while reading that body, and `100 Continue` is sent, if
+enabled, when a handler first reads a body that a client asked to
+send. The handler timeout is applied while the handler runs. After
+each response, the drain length limits how much of an unread request
+body is discarded to keep the connection alive, and the keep-alive
+timeout and the request limit decide whether, and for how long, to
+wait for another request. Timeouts are measured with the [`Sleep`]
+that the runtime adapter provides. The defaults are used by
+[`Conn::map`] and [`Conn::new`].
+
+```
+use std::time::Duration;
+use trillium_http::ConnConfig;
+let config = ConnConfig::new()
+    .with_max_headers(256)
+    .with_max_head_length(16 * 1024)
+    .with_keep_alive_timeout(Duration::from_secs(5))
+    .with_head_timeout(Duration::from_secs(10))
+    .with_max_drain_length(64 * 1024)
+    .with_max_chunked_body_length(10 * 1024 * 1024)
+    .with_handler_timeout(Duration::from_secs(30))
+    .with_max_requests_per_connection(100)
+    .without_100_continue();
+assert_eq!(config.max_headers(), 256);
+assert_eq!(config.max_head_length(), 16 * 1024);
+assert_eq!(config.keep_alive_timeout(), Some(Duration::from_secs(5)));
+assert_eq!(config.head_timeout(), Some(Duration::from_secs(10)));
+assert_eq!(config.max_drain_length(), 64 * 1024);
+assert_eq!(config.max_chunked_body_length(), Some(10 * 1024 * 1024));
+assert_eq!(config.handler_timeout(), Some(Duration::from_secs(30)));
+assert_eq!(config.max_requests_per_connection(), Some(100));
+assert!(!config.sends_100_continue());
+```
+
+[`Conn::map_with_config`]: crate::Conn::map_with_config
+[`Conn::new_with_config`]: crate::Conn::new_with_config
+[`Conn::map`]: crate::Conn::map
+[`Conn::new`]: crate::Conn::new
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct ConnConfig {
+    max_headers: usize,
+    max_head_length: usize,
+    keep_alive_timeout: Option<Duration>,
+    head_timeout: Option<Duration>,
+    max_drain_length: u64,
+    max_chunked_body_length: Option<u64>,
+    handler_timeout: Option<Duration>,
+    max_requests_per_connection: Option<usize>,
+    send_100_continue: bool,
+    sleep: Option<Sleep>,
+}
+
+const DEFAULT_MAX_HEADERS: usize = 128;
+const DEFAULT_MAX_HEAD_LENGTH: usize = 2 * 1024;
+const DEFAULT_MAX_DRAIN_LENGTH: u64 = 1024 * 1024;
+
+impl ConnConfig {
+    /// constructs a new `ConnConfig` with the default limits
+    pub const fn new() -> Self {
+        Self {
+            max_headers: DEFAULT_MAX_HEADERS,
+            max_head_length: DEFAULT_MAX_HEAD_LENGTH,
+            keep_alive_timeout: None,
+            head_timeout: None,
+            max_drain_length: DEFAULT_MAX_DRAIN_LENGTH,
+            max_chunked_body_length: None,
+            handler_timeout: None,
+            max_requests_per_connection: None,
+            send_100_continue: true,
+            sleep: None,
+        }
+    }
+
+    /// chainable setter for the maximum number of request headers.
+    /// requests with more headers than this are rejected. the default
+    /// is 128.
+    #[must_use]
+    pub const fn with_max_headers(mut self, max_headers: usize) -> Self {
+        self.max_headers = max_headers;
+        self
+    }
+
+    /// chainable setter for the maximum length in bytes of the
+    /// request head, including the request line and all
+    /// headers. requests with a longer head are rejected with
+    /// [`Error::HeadersTooLong`](crate::Error::HeadersTooLong). the
+    /// default is 2kb.
+    #[must_use]
+    pub const fn with_max_head_length(mut self, max_head_length: usize) -> Self {
+        self.max_head_length = max_head_length;
+        self
+    }
+
+    /// chainable setter for how long a kept-alive connection may sit
+    /// idle after a response before the next request head has
+    /// arrived, including any time spent draining an unread request
+    /// body. when this elapses, the connection is closed cleanly. by
+    /// default, there is no timeout.
+    #[must_use]
+    pub const fn with_keep_alive_timeout(mut self, keep_alive_timeout: Duration) -> Self {
+        self.keep_alive_timeout = Some(keep_alive_timeout);
+        self
+    }
+
+    /// chainable setter for how long a client may take to send a
+    /// complete request head, measured from its first byte. this
+    /// bounds the whole head rather than each read, so a client
+    /// trickling bytes cannot hold the connection open indefinitely.
+    /// requests that take longer are rejected with
+    /// [`Error::HeadTimeout`](crate::Error::HeadTimeout). by default,
+    /// there is no timeout.
+    #[must_use]
+    pub const fn with_head_timeout(mut self, head_timeout: Duration) -> Self {
+        self.head_timeout = Some(head_timeout);
+        self
+    }
+
+    /// chainable setter for the maximum number of unread request
+    /// body bytes that will be read and discarded after a response
+    /// has been sent, so that the connection can be reused. a
+    /// response to a request with a longer unread body closes the
+    /// connection instead. the default is 1mb.
+    #[must_use]
+    pub const fn with_max_drain_length(mut self, max_drain_length: u64) -> Self {
+        self.max_drain_length = max_drain_length;
+        self
+    }
+
+    /// chainable setter for the maximum decoded length of a chunked
+    /// request body, which unlike a body with a `content-length` is
+    /// not known before it is read. reading more than this from a
+    /// request body is an error wrapping
+    /// [`Error::ReceivedBodyTooLong`](crate::Error::ReceivedBodyTooLong).
+    /// by default, there is no limit.
+    #[must_use]
+    pub const fn with_max_chunked_body_length(mut self, max_chunked_body_length: u64) -> Self {
+        self.max_chunked_body_length = Some(max_chunked_body_length);
+        self
+    }
+
+    /// chainable setter for how long a handler may take to return a
+    /// conn. when this elapses, the conn's [cancellation
+    /// token](crate::Conn::cancellation_token) is stopped and the
+    /// handler is given the same duration again to return. if it
+    /// does, whatever response it built is replaced with `503 Service
+    /// Unavailable` and the connection is closed. a handler that
+    /// still has not returned is dropped, a `503 Service Unavailable`
+    /// is written to the transport it held, and
+    /// [`Error::HandlerTimeout`](crate::Error::HandlerTimeout) is
+    /// returned. by default, there is no timeout.
+    #[must_use]
+    pub const fn with_handler_timeout(mut self, handler_timeout: Duration) -> Self {
+        self.handler_timeout = Some(handler_timeout);
+        self
+    }
+
+    /// chainable setter for the maximum number of requests that are
+    /// read from a single connection, including pipelined requests.
+    /// the response to the last of these is sent with `Connection:
+    /// close` and the connection is then closed. by default, there is
+    /// no limit.
+    #[must_use]
+    pub const fn with_max_requests_per_connection(
+        mut self,
+        max_requests_per_connection: usize,
+    ) -> Self {
+        self.max_requests_per_connection = Some(max_requests_per_connection);
+        self
+    }
+
+    /// never send `100 Continue` in response to `Expect:
+    /// 100-continue`, such as when a proxy in front of this server
+    /// has already done so. a request body is read as usual, and an
+    /// unread body is drained after the response is sent. a client
+    /// that waits for `100 Continue` before sending its body will
+    /// only do so after its own timeout, which is typically about a
+    /// second, so this is best suited to clients that do not wait.
+    #[must_use]
+    pub const fn without_100_continue(mut self) -> Self {
+        self.send_100_continue = false;
+        self
+    }
+
+    /**
+    chainable setter for the timer that the keep-alive, head, and
+    handler timeouts are measured with. timeouts are only enforced
+    once a timer has been provided. the runtime adapters provide one
+    when a server is run, so this is only needed when calling
+    [`Conn::map_with_config`](crate::Conn::map_with_config) directly.
+
+    ```
+    use trillium_http::ConnConfig;
+    let config = ConnConfig::new().with_sleep(|duration| {
+        Box::pin(async move {
+            async_io::Timer::after(duration).await;
+        })
+    });
+    assert!(config.sleep().is_some());
+    ```
+    */
+    #[must_use]
+    pub const fn with_sleep(mut self, sleep: Sleep) -> Self {
+        self.sleep = Some(sleep);
+        self
+    }
+
+    /// the maximum number of request headers
+    pub const fn max_headers(&self) -> usize {
+        self.max_headers
+    }
+
+    /// the maximum length in bytes of the request head
+    pub const fn max_head_length(&self) -> usize {
+        self.max_head_length
+    }
+
+    /// how long to wait for the next request on a kept-alive
+    /// connection, if there is a limit
+    pub const fn keep_alive_timeout(&self) -> Option<Duration> {
+        self.keep_alive_timeout
+    }
+
+    /// how long a client may take to send a request head, if there
+    /// is a limit
+    pub const fn head_timeout(&self) -> Option<Duration> {
+        self.head_timeout
+    }
+
+    /// the maximum number of unread request body bytes to discard
+    /// before reusing the connection
+    pub const fn max_drain_length(&self) -> u64 {
+        self.max_drain_length
+    }
+
+    /// the maximum decoded length of a chunked request body, if there
+    /// is a limit
+    pub const fn max_chunked_body_length(&self) -> Option<u64> {
+        self.max_chunked_body_length
+    }
+
+    /// how long a handler may take to return a conn, if there is a
+    /// limit
+    pub const fn handler_timeout(&self) -> Option<Duration> {
+        self.handler_timeout
+    }
+
+    /// the maximum number of requests read from a single connection,
+    /// if there is a limit
+    pub const fn max_requests_per_connection(&self) -> Option<usize> {
+        self.max_requests_per_connection
+    }
+
+    /// whether `100 Continue` is sent to a client that asks for it
+    /// before its request body is read
+    pub const fn sends_100_continue(&self) -> bool {
+        self.send_100_continue
+    }
+
+    /// the timer that timeouts are measured with, if one has been
+    /// provided
+    pub const fn sleep(&self) -> Option<Sleep> {
+        self.sleep
+    }
+}
+
+impl Default for ConnConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
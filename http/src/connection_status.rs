@@ -1,6 +1,7 @@
 use crate::{Conn, Upgrade};
 /// This represents the next state after a response on a conn transport.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)] // this is returned once per request, never stored
 pub enum ConnectionStatus<Transport> {
     /// The transport has been closed, either by the client or by us
     Close,
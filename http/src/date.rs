@@ -0,0 +1,95 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// the last second that can be represented in an http date,
+/// 9999-12-31T23:59:59Z
+const MAX_HTTP_DATE_SECS: u64 = 253_402_300_799;
+
+/// the most recently formatted date, along with the unix second it
+/// was formatted for
+static CACHED_DATE: Mutex<Option<(u64, String)>> = Mutex::new(None);
+
+/// returns the current time formatted for a `Date` header. the date
+/// only has one-second precision, so it is formatted at most once per
+/// second and shared between all responses in that second
+pub fn http_date() -> String {
+    let now = SystemTime::now();
+    let second = now
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    let mut cached = CACHED_DATE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+    match &*cached {
+        Some((cached_second, date)) if *cached_second == second => date.clone(),
+        _ => {
+            let date = format_http_date(now);
+            *cached = Some((second, date.clone()));
+            date
+        }
+    }
+}
+
+/**
+formats a time as an http date, such as `Sun, 06 Nov 1994 08:49:37
+GMT`, as used by the `Date` and `Last-Modified` headers. http dates
+have one-second precision, so any fraction of a second is truncated.
+times before the unix epoch are formatted as the epoch, and times
+after the year 9999 as the last second of that year.
+
+```
+use std::time::{Duration, UNIX_EPOCH};
+use trillium_http::format_http_date;
+let time = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+assert_eq!(format_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+assert_eq!(
+    format_http_date(UNIX_EPOCH - Duration::from_secs(1)),
+    "Thu, 01 Jan 1970 00:00:00 GMT"
+);
+```
+*/
+pub fn format_http_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+        .min(MAX_HTTP_DATE_SECS);
+    httpdate::fmt_http_date(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_http_date, http_date, MAX_HTTP_DATE_SECS};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn matches_current_time() {
+        let before = SystemTime::now();
+        let date = httpdate::parse_http_date(&http_date()).unwrap();
+        let after = SystemTime::now();
+        assert!(date <= after);
+        assert!(before.duration_since(date).unwrap_or_default().as_secs() < 1);
+        assert_eq!(http_date().len(), "Sun, 06 Nov 1994 08:49:37 GMT".len());
+    }
+
+    #[test]
+    fn clamps_out_of_range_times() {
+        assert_eq!(
+            format_http_date(UNIX_EPOCH - Duration::from_secs(1)),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+        assert_eq!(
+            format_http_date(UNIX_EPOCH + Duration::from_secs(MAX_HTTP_DATE_SECS + 60)),
+            "Fri, 31 Dec 9999 23:59:59 GMT"
+        );
+
+        let now = SystemTime::now();
+        let parsed = httpdate::parse_http_date(&format_http_date(now)).unwrap();
+        assert!(parsed <= now);
+        assert!(now.duration_since(parsed).unwrap().as_secs() < 1);
+    }
+}
@@ -38,7 +38,9 @@ pub enum Error {
     #[error(transparent)]
     TryFromIntError(#[from] TryFromIntError),
 
-    /// an incomplete http head
+    /// an incomplete http head. this is only returned for the first
+    /// request on a connection, as an incomplete head on a subsequent
+    /// request is treated as the client closing the connection
     #[error("partial http head")]
     PartialHead,
 
@@ -80,10 +82,61 @@ pub enum Error {
     #[error("unexpected header: {0}")]
     UnexpectedHeader(&'static str),
 
-    /// for security reasons, we do not allow request headers beyond
-    /// 8kb.
-    #[error("Head byte length should be less than 8kb")]
+    /// for security reasons, we do not allow heads longer than a
+    /// maximum length, which for a request is
+    /// [`ConnConfig::max_head_length`](crate::ConnConfig::max_head_length)
+    #[error("Head byte length exceeds the maximum")]
     HeadersTooLong,
+
+    /// the request head was not completely received within the
+    /// [`ConnConfig::head_timeout`](crate::ConnConfig::head_timeout)
+    /// of its first byte
+    #[error("timed out reading the request head")]
+    HeadTimeout,
+
+    /// the handler neither completed within the
+    /// [`ConnConfig::handler_timeout`](crate::ConnConfig::handler_timeout)
+    /// nor returned promptly once its cancellation token was stopped,
+    /// so it was dropped. if the transport was still held by its
+    /// conn, a `503 Service Unavailable` was sent before closing
+    #[error("handler did not complete within the handler timeout")]
+    HandlerTimeout,
+
+    /// the request body exceeded the maximum length that the
+    /// application was willing to read
+    #[error("received body longer than maximum of {0} bytes")]
+    ReceivedBodyTooLong(u64),
+
+    /// a line of a request body exceeded the maximum length that the
+    /// application was willing to buffer. see
+    /// [`BodyLines::with_max_line_len`](crate::BodyLines::with_max_line_len)
+    #[error("received line longer than maximum of {0} bytes")]
+    LineTooLong(usize),
+
+    /// the received body did not match the checksum declared in the
+    /// named header or trailer
+    #[error("received body does not match {0} checksum")]
+    ChecksumMismatch(&'static str),
+
+    /// the request used a transfer coding that is not supported. this
+    /// is responded to with `501 Not Implemented`
+    #[error("unsupported transfer coding {0}")]
+    UnsupportedEncoding(String),
+
+    /// a status was used where it is not permitted, such as sending
+    /// a non-1xx status as an informational response
+    #[error("unexpected status {0}")]
+    UnexpectedStatus(crate::Status),
+
+    /// one of several destinations for a request body failed to
+    /// accept a write. the index identifies which destination failed.
+    #[error("failed to write received body to destination {index}: {source}")]
+    ReceivedBodyDestination {
+        /// the position of the failed destination
+        index: usize,
+        /// the underlying io error
+        source: std::io::Error,
+    },
 }
 
 /// this crate's result type
@@ -43,6 +43,60 @@ impl<'a> HeaderName<'a> {
     }
 }
 
+impl HeaderName<'_> {
+    /// whether this header is prohibited from being sent as a trailer,
+    /// as it controls message framing, routing, authentication,
+    /// caching, or the interpretation of the body, per rfc 9110 §6.5.1
+    pub(crate) fn is_forbidden_trailer(&self) -> bool {
+        use KnownHeaderName::{
+            Age, Authorization, CacheControl, Connection, ContentEncoding, ContentLength,
+            ContentRange, ContentType, Date, Expect, Expires, Host, IfMatch, IfModifiedSince,
+            IfNoneMatch, IfRange, IfUnmodifiedSince, KeepAlive, Location, MaxForwards, Pragma,
+            ProxyAuthenticate, ProxyAuthorization, ProxyConnection, Range, RetryAfter, SetCookie,
+            Te, Trailer, TransferEncoding, Upgrade, Vary, Warning, WwwAuthenticate,
+        };
+
+        matches!(
+            self.0,
+            KnownHeader(
+                Age | Authorization
+                    | CacheControl
+                    | Connection
+                    | ContentEncoding
+                    | ContentLength
+                    | ContentRange
+                    | ContentType
+                    | Date
+                    | Expect
+                    | Expires
+                    | Host
+                    | IfMatch
+                    | IfModifiedSince
+                    | IfNoneMatch
+                    | IfRange
+                    | IfUnmodifiedSince
+                    | KeepAlive
+                    | Location
+                    | MaxForwards
+                    | Pragma
+                    | ProxyAuthenticate
+                    | ProxyAuthorization
+                    | ProxyConnection
+                    | Range
+                    | RetryAfter
+                    | SetCookie
+                    | Te
+                    | Trailer
+                    | TransferEncoding
+                    | Upgrade
+                    | Vary
+                    | Warning
+                    | WwwAuthenticate
+            )
+        )
+    }
+}
+
 impl PartialEq for HeaderName<'_> {
     fn eq(&self, other: &Self) -> bool {
         match (&self.0, &other.0) {
@@ -213,6 +267,7 @@ known_headers! {
     ("Accept-Charset", AcceptCharset),
     ("Accept-Encoding", AcceptEncoding),
     ("Accept-Language", AcceptLanguage),
+    ("Accept-Patch", AcceptPatch),
     ("Accept-Push-Policy", AcceptPushPolicy),
     ("Accept-Ranges", AcceptRanges),
     ("Accept-Signature", AcceptSignature),
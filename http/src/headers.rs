@@ -54,6 +54,13 @@ impl Headers {
             }))
     }
 
+    /// Return an iterator over borrowed header names, without cloning
+    /// them. First yields the known headers and then the unknown
+    /// headers, if any.
+    pub fn names(&self) -> impl Iterator<Item = HeaderName<'_>> {
+        self.iter().map(|(name, _)| name)
+    }
+
     /// add the header value or header values into this header map. If
     /// there is already a header with the same name, the new values
     /// will be added to the existing ones. To replace any existing
@@ -146,6 +153,67 @@ impl Headers {
         }
     }
 
+    /**
+    Removes the hop-by-hop headers, which describe a single
+    connection rather than the message and so must not be forwarded
+    by a proxy, as described in rfc 7230 §6.1. These are
+    `connection`, `keep-alive`, `proxy-connection`,
+    `proxy-authorization`, `te`, `trailer`, `transfer-encoding`, and
+    `upgrade`, as well as any header named in the `connection`
+    header, compared case-insensitively.
+
+    ```
+    # use trillium_http::Headers;
+    let mut headers = Headers::new();
+    headers.insert("connection", "keep-alive, X-Hop");
+    headers.insert("x-hop", "removed");
+    headers.insert("keep-alive", "timeout=5");
+    headers.insert("x-end-to-end", "kept");
+    headers.remove_hop_by_hop_headers();
+    assert_eq!(headers.names().count(), 1);
+    assert_eq!(headers.get_str("x-end-to-end"), Some("kept"));
+    ```
+    */
+    pub fn remove_hop_by_hop_headers(&mut self) {
+        let connection_options = self
+            .remove(KnownHeaderName::Connection)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(HeaderValue::as_str)
+                    .flat_map(|value| value.split(','))
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        for name in &connection_options {
+            if let Ok(known) = name.parse::<KnownHeaderName>() {
+                self.known.remove(&known);
+            }
+        }
+
+        self.unknown.retain(|name, _| {
+            !connection_options
+                .iter()
+                .any(|option| option.eq_ignore_ascii_case(name))
+        });
+
+        for name in [
+            KnownHeaderName::KeepAlive,
+            KnownHeaderName::ProxyConnection,
+            KnownHeaderName::ProxyAuthorization,
+            KnownHeaderName::Te,
+            KnownHeaderName::Trailer,
+            KnownHeaderName::TransferEncoding,
+            KnownHeaderName::Upgrade,
+        ] {
+            self.known.remove(&name);
+        }
+    }
+
     /// Retrieves a reference to all header values with the provided
     /// header name. If you expect there to be only one value, use
     /// [`Headers::get`].
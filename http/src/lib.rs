@@ -74,6 +74,9 @@ server_handle.await?; // wait for the server to shut down
 mod received_body;
 pub use received_body::ReceivedBody;
 
+mod body_lines;
+pub use body_lines::BodyLines;
+
 #[cfg(feature = "unstable")]
 pub use received_body::ReceivedBodyState;
 
@@ -83,6 +86,9 @@ pub use error::{Error, Result};
 mod conn;
 pub use conn::Conn;
 
+mod conn_config;
+pub use conn_config::{ConnConfig, Sleep};
+
 mod connection_status;
 pub use connection_status::ConnectionStatus;
 
@@ -97,7 +103,15 @@ pub use stopper::Stopper;
 mod mut_cow;
 pub(crate) use mut_cow::MutCow;
 
+mod transfer_coding;
+
+mod reclaimable;
+
+mod date;
+pub use date::format_http_date;
+
 mod util;
+pub use util::header_param;
 
 mod body;
 pub use body::Body;
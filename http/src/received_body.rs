@@ -1,4 +1,6 @@
-use crate::{Body, MutCow};
+use crate::{
+    transfer_coding::TransferDecoder, Body, BodyLines, HeaderName, HeaderValue, Headers, MutCow,
+};
 use encoding_rs::Encoding;
 use futures_lite::{io, ready, AsyncRead, AsyncReadExt, AsyncWrite, Stream};
 use httparse::Status;
@@ -8,11 +10,12 @@ use std::{
     io::ErrorKind,
     iter,
     pin::Pin,
+    str::FromStr,
     task::{Context, Poll},
 };
 
 use Poll::{Pending, Ready};
-use ReceivedBodyState::{Chunked, End, FixedLength, Start};
+use ReceivedBodyState::{Chunked, End, FixedLength, Start, Trailers};
 
 macro_rules! trace {
     ($s:literal, $($arg:tt)+) => (
@@ -43,6 +46,9 @@ pub struct ReceivedBody<'conn, Transport> {
     state: MutCow<'conn, ReceivedBodyState>,
     on_completion: Option<Box<dyn Fn(Transport) + Send + Sync + 'static>>,
     encoding: &'static Encoding,
+    trailers: MutCow<'conn, Option<Headers>>,
+    transfer_decoder: Option<TransferDecoder>,
+    max_chunked_len: Option<u64>,
 }
 
 impl<'conn, Transport> ReceivedBody<'conn, Transport>
@@ -66,9 +72,36 @@ where
             state: state.into(),
             on_completion,
             encoding,
+            trailers: MutCow::Owned(None),
+            transfer_decoder: None,
+            max_chunked_len: None,
         }
     }
 
+    /// limits the decoded length of a chunked body, which is
+    /// otherwise unbounded. reading more than this is an error
+    #[must_use]
+    pub(crate) const fn with_max_chunked_len(mut self, max_chunked_len: Option<u64>) -> Self {
+        self.max_chunked_len = max_chunked_len;
+        self
+    }
+
+    /// removes these transfer codings, such as gzip, from the body
+    /// after it has been dechunked
+    #[must_use]
+    pub(crate) fn with_transfer_decoder(mut self, transfer_decoder: TransferDecoder) -> Self {
+        self.transfer_decoder = Some(transfer_decoder);
+        self
+    }
+
+    #[allow(missing_docs)]
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_trailers(mut self, trailers: impl Into<MutCow<'conn, Option<Headers>>>) -> Self {
+        self.trailers = trailers.into();
+        self
+    }
+
     /**
     Returns the content-length of this body, if available. This
     usually is derived from the content-length header. If the http
@@ -88,6 +121,15 @@ where
         self.content_length
     }
 
+    /**
+    Returns the trailers that were sent after the final chunk of a
+    chunked body, if there were any. These are only available once
+    the body has been read to the end.
+    */
+    pub fn trailers(&self) -> Option<&Headers> {
+        self.trailers.as_ref()
+    }
+
     /**
     Reads the entire body to string, using the encoding determined by
     the content-type (mime) charset. If an encoding problem is
@@ -144,6 +186,107 @@ where
         Ok(vec)
     }
 
+    /**
+    Similar to [`ReceivedBody::read_string`], but stops reading and
+    returns an error once more than `max_len` bytes have been
+    received. This is appropriate for bodies from untrusted clients,
+    because at most `max_len` bytes are ever buffered, regardless of
+    the content-length.
+
+    # Errors
+
+    This will return an error if there is an IO error on the
+    underlying transport such as a disconnect, or
+    [`Error::ReceivedBodyTooLong`](crate::Error::ReceivedBodyTooLong)
+    if either the content-length or the number of bytes actually read
+    exceeds `max_len`
+
+    ```rust
+    # trillium_testing::block_on(async {
+    # use trillium_http::{Method, Conn, Error};
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    let body = conn.request_body().await;
+    assert_eq!(body.read_string_with_max_len(5).await?, "hello");
+
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    let body = conn.request_body().await;
+    assert!(matches!(
+        body.read_string_with_max_len(4).await,
+        Err(Error::ReceivedBodyTooLong(4))
+    ));
+    # trillium_http::Result::Ok(()) }).unwrap();
+    ```
+    */
+    pub async fn read_string_with_max_len(self, max_len: u64) -> crate::Result<String> {
+        let encoding = self.encoding();
+        let bytes = self.read_bytes_with_max_len(max_len).await?;
+        let (s, _, _) = encoding.decode(&bytes);
+        Ok(s.to_string())
+    }
+
+    /**
+    Similar to [`ReceivedBody::read_bytes`], but stops reading and
+    returns an error once more than `max_len` bytes have been
+    received. A content-length longer than `max_len` is rejected
+    before anything is read.
+
+    # Errors
+
+    This will return an error if there is an IO error on the
+    underlying transport such as a disconnect, or
+    [`Error::ReceivedBodyTooLong`](crate::Error::ReceivedBodyTooLong)
+    if either the content-length or the number of bytes actually read
+    exceeds `max_len`
+    */
+    pub async fn read_bytes_with_max_len(mut self, max_len: u64) -> crate::Result<Vec<u8>> {
+        let capacity = match self.content_length {
+            Some(len) if len > max_len => return Err(crate::Error::ReceivedBodyTooLong(max_len)),
+            Some(len) => len.try_into().unwrap_or_default(),
+            None => 0,
+        };
+
+        let mut vec = Vec::with_capacity(capacity);
+        let mut buf = vec![0; 4096];
+        loop {
+            let bytes = self.read(&mut buf).await?;
+            if bytes == 0 {
+                break Ok(vec);
+            }
+
+            if (vec.len() + bytes) as u64 > max_len {
+                break Err(crate::Error::ReceivedBodyTooLong(max_len));
+            }
+
+            vec.extend_from_slice(&buf[..bytes]);
+        }
+    }
+
+    /**
+    Returns a stream of the lines of this body, decoded with
+    [`ReceivedBody::encoding`]. Each line is limited to
+    [`BodyLines::DEFAULT_MAX_LINE_LEN`] bytes unless configured with
+    [`BodyLines::with_max_line_len`], so that a body without newlines
+    cannot be buffered without bound.
+
+    ```
+    # trillium_testing::block_on(async {
+    # use trillium_http::{Conn, Error, Method};
+    use futures_lite::StreamExt;
+
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "first\r\nsecond\nthird line");
+    let mut lines = conn.request_body().await.lines().with_max_line_len(6);
+    assert_eq!(lines.next().await.unwrap()?, "first");
+    assert_eq!(lines.next().await.unwrap()?, "second");
+    assert!(matches!(lines.next().await, Some(Err(Error::LineTooLong(6)))));
+    assert!(lines.next().await.is_none());
+    # trillium_http::Result::Ok(()) }).unwrap();
+    ```
+    */
+    pub fn lines(self) -> BodyLines<Self> {
+        let encoding = self.encoding;
+        BodyLines::new(self, encoding)
+    }
+
     /**
     returns the character encoding of this body, usually
     determined from the content type (mime-type) of the associated
@@ -161,6 +304,45 @@ where
         }
     }
 
+    /// reads directly into the buffer until the trailer section is
+    /// complete, returning any bytes that followed it
+    fn poll_trailers(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Option<Vec<u8>>>> {
+        loop {
+            let buffer = self.buffer.get_or_insert_with(Vec::new);
+            if let Some((trailer_len, trailers)) = parse_trailers(buffer)? {
+                let unused = buffer.split_off(trailer_len);
+                *self.buffer = None;
+                *self.trailers = trailers;
+                return Ready(Ok(if unused.is_empty() {
+                    None
+                } else {
+                    Some(unused)
+                }));
+            }
+
+            let mut bytes = [0; 128];
+            let transport = match self.transport.as_mut() {
+                Some(transport) => &mut **transport,
+                None => return Ready(Err(ErrorKind::NotConnected.into())),
+            };
+
+            match ready!(Pin::new(transport).poll_read(cx, &mut bytes))? {
+                // a body that ends without the final empty line is
+                // tolerated as having no trailers
+                0 if b"\r\n".starts_with(self.buffer.as_deref().unwrap_or_default()) => {
+                    *self.buffer = None;
+                    return Ready(Ok(None));
+                }
+
+                0 => return Ready(Err(ErrorKind::UnexpectedEof.into())),
+                len => self
+                    .buffer
+                    .get_or_insert_with(Vec::new)
+                    .extend_from_slice(&bytes[..len]),
+            }
+        }
+    }
+
     /**
     Consumes the remainder of this body from the underlying transport
     by reading it to the end and discarding the contents. This is
@@ -228,14 +410,16 @@ where
     // the clippy::only_used_in_recursion seems like a false positive,
     // it thinks `total` is unused
     clippy::only_used_in_recursion,
-    clippy::cast_possible_truncation
+    clippy::cast_possible_truncation,
+    clippy::type_complexity
 )]
 fn chunk_decode(
     remaining: usize,
     mut total: usize,
     buf: &mut [u8],
-) -> io::Result<(ReceivedBodyState, usize, Option<Vec<u8>>)> {
+) -> io::Result<(ReceivedBodyState, usize, Option<Vec<u8>>, Option<Headers>)> {
     let mut ranges_to_keep = vec![];
+    let mut trailers = None;
     let mut chunk_start = 0;
     let mut chunk_end = remaining;
     let (request_body_state, unused) = loop {
@@ -269,6 +453,19 @@ fn chunk_decode(
                 chunk_end = 2 + chunk_start + chunk_size as usize;
 
                 if chunk_size == 0 {
+                    if chunk_start >= buf.len() {
+                        break (Trailers, None);
+                    }
+
+                    match parse_trailers(&buf[chunk_start..])? {
+                        Some((trailer_len, parsed)) => {
+                            chunk_end = chunk_start + trailer_len;
+                            trailers = parsed;
+                        }
+
+                        None => break (Trailers, Some(buf[chunk_start..].to_vec())),
+                    }
+
                     break (
                         End,
                         if chunk_end < buf.len() {
@@ -280,6 +477,13 @@ fn chunk_decode(
                 }
             }
 
+            Ok(Status::Partial) if buf.len() - chunk_start >= MAX_CHUNK_SIZE_LINE_LENGTH => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "chunk size line too long",
+                ));
+            }
+
             Ok(Status::Partial) => {
                 break (
                     Chunked {
@@ -311,7 +515,53 @@ fn chunk_decode(
         bytes = new_bytes;
     }
 
-    Ok((request_body_state, bytes, unused))
+    Ok((request_body_state, bytes, unused, trailers))
+}
+
+/// the maximum length of a chunk size line, including any chunk
+/// extensions and the terminating crlf
+const MAX_CHUNK_SIZE_LINE_LENGTH: usize = 1024;
+
+const MAX_TRAILERS: usize = 32;
+const MAX_TRAILERS_LENGTH: usize = 2 * 1024;
+
+/// parses the trailer section following the final chunk, including
+/// the terminating empty line. returns None if the section is not yet
+/// complete, and the number of bytes consumed along with any
+/// trailers if it is. fields that are not permitted as trailers, such
+/// as `content-length` or `host`, are discarded rather than exposed
+/// alongside the permitted trailers.
+fn parse_trailers(buf: &[u8]) -> io::Result<Option<(usize, Option<Headers>)>> {
+    let invalid = |message| io::Error::new(ErrorKind::InvalidData, message);
+
+    let mut trailers = [httparse::EMPTY_HEADER; MAX_TRAILERS];
+    match httparse::parse_headers(buf, &mut trailers) {
+        Ok(Status::Complete((trailer_len, parsed))) => {
+            if parsed.is_empty() {
+                return Ok(Some((trailer_len, None)));
+            }
+
+            let mut headers = Headers::with_capacity(parsed.len());
+            let mut permitted = 0;
+            for trailer in parsed {
+                let name =
+                    HeaderName::from_str(trailer.name).map_err(|_| invalid("invalid trailer"))?;
+                if name.is_forbidden_trailer() {
+                    log::debug!("{name} is not permitted as a trailer, discarding it");
+                    continue;
+                }
+                headers.append(name, HeaderValue::from(trailer.value.to_owned()));
+                permitted += 1;
+            }
+            Ok(Some((trailer_len, (permitted > 0).then_some(headers))))
+        }
+
+        Ok(Status::Partial) if buf.len() > MAX_TRAILERS_LENGTH => Err(invalid("trailers too long")),
+
+        Ok(Status::Partial) => Ok(None),
+
+        Err(_) => Err(invalid("invalid trailer")),
+    }
 }
 
 const STREAM_READ_BUF_LENGTH: usize = 128;
@@ -350,11 +600,46 @@ impl<'conn, Transport> AsyncRead for ReceivedBody<'conn, Transport>
 where
     Transport: AsyncRead + Unpin + Send + Sync + 'static,
 {
-    #[allow(clippy::cast_possible_truncation)]
     fn poll_read(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.transfer_decoder.is_none() {
+            return self.poll_read_framed(cx, buf);
+        }
+
+        if buf.is_empty() {
+            return Ready(Ok(0));
+        }
+
+        let mut framed = [0; 4096];
+        loop {
+            if let Some(decoder) = &mut self.transfer_decoder {
+                if let Some(bytes) = decoder.read(buf)? {
+                    return Ready(Ok(bytes));
+                }
+            }
+
+            let bytes = ready!(self.poll_read_framed(cx, &mut framed))?;
+            if let Some(decoder) = &mut self.transfer_decoder {
+                decoder.feed(&framed[..bytes]);
+            }
+        }
+    }
+}
+
+impl<Transport> ReceivedBody<'_, Transport>
+where
+    Transport: AsyncRead + Unpin + Send + Sync + 'static,
+{
+    /// reads the body as framed by its content-length or chunked
+    /// encoding, without removing any other transfer coding
+    #[allow(clippy::cast_possible_truncation)]
+    fn poll_read_framed(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
         trace!("polling received body with state {:?}", &*self.state);
         let (new_body_state, bytes, unused) = match *self.state {
@@ -378,7 +663,23 @@ where
 
             Chunked { remaining, total } => {
                 let bytes = ready!(self.read_raw(cx, buf)?);
-                chunk_decode(remaining, total, &mut buf[..bytes])?
+                let (state, bytes, unused, trailers) =
+                    chunk_decode(remaining, total, &mut buf[..bytes])?;
+
+                if let Some(max) = self
+                    .max_chunked_len
+                    .filter(|max| (total + bytes) as u64 > *max)
+                {
+                    return Ready(Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        crate::Error::ReceivedBodyTooLong(max),
+                    )));
+                }
+
+                if trailers.is_some() {
+                    *self.trailers = trailers;
+                }
+                (state, bytes, unused)
             }
 
             FixedLength {
@@ -409,12 +710,19 @@ where
                 (state, bytes, None)
             }
 
+            Trailers => {
+                let unused = ready!(self.poll_trailers(cx))?;
+                (End, 0, unused)
+            }
+
             End => (End, 0, None),
         };
 
         if let Some(unused) = unused {
             if let Some(existing) = &mut *self.buffer {
-                existing.extend_from_slice(&unused);
+                // any existing buffer is what remained after this read,
+                // so the unused bytes belong before it
+                existing.splice(0..0, unused);
             } else {
                 *self.buffer = Some(unused);
             }
@@ -448,6 +756,8 @@ impl<'conn, Transport> fmt::Debug for ReceivedBody<'conn, Transport> {
                 &self.buffer.as_deref().map(String::from_utf8_lossy),
             )
             .field("on_completion", &self.on_completion.is_some())
+            .field("trailers", &*self.trailers)
+            .field("transfer_decoder", &self.transfer_decoder)
             .finish()
     }
 }
@@ -481,6 +791,11 @@ pub enum ReceivedBodyState {
         total_length: u64,
     },
 
+    /// read state for the trailers following the final chunk of a
+    /// chunked-encoded body. the partial trailer section is held in
+    /// the buffer until it is complete.
+    Trailers,
+
     /// the terminal read state
     End,
 }
@@ -542,7 +857,7 @@ mod chunk_decode {
 
         let mut buf = input_data.to_string().into_bytes();
 
-        let (output_state, bytes, unused) = chunk_decode(remaining, 0, &mut buf).unwrap();
+        let (output_state, bytes, unused, _) = chunk_decode(remaining, 0, &mut buf).unwrap();
 
         assert_eq!(
             (
@@ -630,4 +945,118 @@ mod chunk_decode {
         );
         assert_decoded((7, "hello\r\n0\r\n\r\n"), (None, "hello", None));
     }
+
+    #[test]
+    fn test_trailers() {
+        let mut buf = b"3\r\nabc\r\n0\r\nx-checksum: 1\r\n\r\nnext request".to_vec();
+        let (state, bytes, unused, trailers) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::End);
+        assert_eq!(&buf[..bytes], b"abc");
+        assert_eq!(unused.as_deref(), Some(&b"next request"[..]));
+        assert_eq!(trailers.unwrap().get_str("x-checksum"), Some("1"));
+
+        let mut buf = b"3\r\nabc\r\n0\r\nx-check".to_vec();
+        let (state, bytes, unused, trailers) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::Trailers);
+        assert_eq!(&buf[..bytes], b"abc");
+        assert_eq!(unused.as_deref(), Some(&b"x-check"[..]));
+        assert!(trailers.is_none());
+
+        let mut buf = b"0\r\n\r\n".to_vec();
+        let (state, _, unused, trailers) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::End);
+        assert!(unused.is_none());
+        assert!(trailers.is_none());
+
+        let mut buf = b"0\r\nnot a trailer\r\n\r\n".to_vec();
+        assert!(chunk_decode(0, 0, &mut buf).is_err());
+
+        let mut buf =
+            b"0\r\ncontent-length: 5\r\nx-checksum: 1\r\nHost: example.com\r\n\r\n".to_vec();
+        let (state, _, _, trailers) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::End);
+        let trailers = trailers.unwrap();
+        assert_eq!(trailers.get_str("x-checksum"), Some("1"));
+        assert!(!trailers.has_header("content-length"));
+        assert!(!trailers.has_header("host"));
+
+        let mut buf = b"0\r\ntransfer-encoding: chunked\r\n\r\n".to_vec();
+        let (state, _, _, trailers) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::End);
+        assert!(trailers.is_none());
+    }
+
+    #[test]
+    fn chunk_size_line_too_long() {
+        let mut buf = format!("1;{}", "a".repeat(1100)).into_bytes();
+        assert!(chunk_decode(0, 0, &mut buf).is_err());
+
+        let mut buf = format!("1;{}\r\nX\r\n0\r\n\r\n", "a".repeat(100)).into_bytes();
+        let (state, bytes, _, _) = chunk_decode(0, 0, &mut buf).unwrap();
+        assert_eq!(state, ReceivedBodyState::End);
+        assert_eq!(&buf[..bytes], b"X");
+    }
+
+    #[test]
+    fn test_full_decode_with_trailers() {
+        for size in 3..50 {
+            let input = "5\r\n12345\r\n3\r\nabc\r\n0\r\nx-a: 1\r\nx-b: 2\r\n\r\n";
+            let (output, rb) = full_decode_with_size(input, size).unwrap();
+            assert_eq!(output, "12345abc", "size: {size}");
+            let trailers = rb.trailers().unwrap();
+            assert_eq!(trailers.get_str("x-a"), Some("1"), "size: {size}");
+            assert_eq!(trailers.get_str("x-b"), Some("2"), "size: {size}");
+        }
+    }
+
+    fn chunked_body(input: &str) -> ReceivedBody<'static, Cursor<&str>> {
+        ReceivedBody::new(
+            None,
+            None,
+            Cursor::new(input),
+            ReceivedBodyState::Chunked {
+                remaining: 0,
+                total: 0,
+            },
+            None,
+            UTF_8,
+        )
+    }
+
+    #[test]
+    fn test_read_with_max_len() {
+        let input = "5\r\n12345\r\n1\r\na\r\n2\r\nbc\r\n3\r\ndef\r\n0\r\n";
+        trillium_testing::block_on(async {
+            let output = chunked_body(input).read_string_with_max_len(11).await;
+            assert_eq!(output.unwrap(), "12345abcdef");
+
+            let output = chunked_body(input).read_bytes_with_max_len(10).await;
+            assert!(matches!(output, Err(crate::Error::ReceivedBodyTooLong(10))));
+
+            let output = chunked_body(input).read_bytes_with_max_len(0).await;
+            assert!(matches!(output, Err(crate::Error::ReceivedBodyTooLong(0))));
+        });
+    }
+
+    #[test]
+    fn test_max_chunked_len() {
+        let input = "5\r\n12345\r\n1\r\na\r\n2\r\nbc\r\n3\r\ndef\r\n0\r\n";
+        trillium_testing::block_on(async {
+            let output = chunked_body(input)
+                .with_max_chunked_len(Some(11))
+                .read_string()
+                .await;
+            assert_eq!(output.unwrap(), "12345abcdef");
+
+            let error = chunked_body(input)
+                .with_max_chunked_len(Some(10))
+                .read_string()
+                .await
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                "received body longer than maximum of 10 bytes"
+            );
+        });
+    }
 }
@@ -0,0 +1,110 @@
+use futures_lite::AsyncWrite;
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// a transport that has been reclaimed, which may be of any type
+/// that the conn's transport has since been mapped to
+pub(crate) type Reclaimed = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// boxes a transport of a particular type as [`Reclaimed`]
+type Boxer<Transport> = fn(Transport) -> Reclaimed;
+
+/// a place that a dropped [`Reclaimable`] transport is returned to
+#[derive(Clone, Default)]
+pub(crate) struct Slot(Arc<Mutex<Option<Reclaimed>>>);
+
+impl Slot {
+    /// takes a transport that was returned to this slot, if any
+    pub(crate) fn reclaim(&self) -> Option<Reclaimed> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+}
+
+/// holds the transport of a conn. while a [`Slot`] is armed, a
+/// transport that is dropped along with its conn is placed in the
+/// slot instead of being closed, so that a response can still be
+/// written after a handler future that owns the conn is dropped
+pub(crate) struct Reclaimable<Transport> {
+    transport: Option<Transport>,
+    slot: Option<(Slot, Boxer<Transport>)>,
+}
+
+fn boxed<Transport>(transport: Transport) -> Reclaimed
+where
+    Transport: AsyncWrite + Send + Unpin + 'static,
+{
+    Box::new(transport)
+}
+
+impl<Transport> Reclaimable<Transport> {
+    pub(crate) fn new(transport: Transport) -> Self {
+        Self {
+            transport: Some(transport),
+            slot: None,
+        }
+    }
+
+    /// returns the transport. it is not reclaimed after this, even
+    /// if a slot was armed
+    pub(crate) fn into_inner(mut self) -> Transport {
+        self.transport
+            .take()
+            .expect("transport is only taken by into_inner")
+    }
+
+    /// applies a mapping function to the transport, keeping any armed
+    /// slot, which the mapped transport is returned to instead
+    pub(crate) fn map<T>(mut self, f: impl FnOnce(Transport) -> T) -> Reclaimable<T>
+    where
+        T: AsyncWrite + Send + Unpin + 'static,
+    {
+        let slot = self.slot.take();
+        Reclaimable {
+            transport: Some(f(self.into_inner())),
+            slot: slot.map(|(slot, _)| (slot, boxed::<T> as Boxer<T>)),
+        }
+    }
+
+    /// arms a slot that the transport will be returned to if it is
+    /// dropped before [`Reclaimable::disarm`]
+    pub(crate) fn arm(&mut self) -> Slot
+    where
+        Transport: AsyncWrite + Send + Unpin + 'static,
+    {
+        let slot = Slot::default();
+        self.slot = Some((slot.clone(), boxed::<Transport>));
+        slot
+    }
+
+    pub(crate) fn disarm(&mut self) {
+        self.slot = None;
+    }
+}
+
+impl<Transport> Deref for Reclaimable<Transport> {
+    type Target = Transport;
+
+    fn deref(&self) -> &Transport {
+        self.transport
+            .as_ref()
+            .expect("transport is only taken by into_inner")
+    }
+}
+
+impl<Transport> DerefMut for Reclaimable<Transport> {
+    fn deref_mut(&mut self) -> &mut Transport {
+        self.transport
+            .as_mut()
+            .expect("transport is only taken by into_inner")
+    }
+}
+
+impl<Transport> Drop for Reclaimable<Transport> {
+    fn drop(&mut self) {
+        if let (Some(transport), Some((slot, boxed))) = (self.transport.take(), self.slot.take()) {
+            *slot.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(boxed(transport));
+        }
+    }
+}
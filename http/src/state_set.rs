@@ -60,6 +60,16 @@ impl StateSet {
             .and_then(|boxed| (boxed as Box<dyn Any>).downcast().ok().map(|boxed| *boxed))
     }
 
+    /// Remove every value from this `StateSet`.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Check if this `StateSet` contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     /// Gets a value from this `StateSet` or populates it with the
     /// provided default.
     pub fn get_or_insert<T: Send + Sync + 'static>(&mut self, default: T) -> &mut T {
@@ -132,5 +142,10 @@ mod tests {
 
         assert_eq!(map.get::<bool>(), None);
         assert_eq!(map.get(), Some(&MyType(10)));
+
+        assert!(!map.is_empty());
+        map.clear();
+        assert!(map.is_empty());
+        assert!(!map.contains::<MyType>());
     }
 }
@@ -1,6 +1,6 @@
 use crate::{
-    conn::AfterSend, received_body::ReceivedBodyState, Conn, Headers, KnownHeaderName, Method,
-    StateSet, Stopper, Version,
+    conn::AfterSend, received_body::ReceivedBodyState, reclaimable::Reclaimable, Conn, ConnConfig,
+    Headers, KnownHeaderName, Method, StateSet, Stopper, Version,
 };
 use futures_lite::io::{AsyncRead, AsyncWrite, Result};
 use std::{
@@ -47,15 +47,17 @@ impl AsyncWrite for Synthetic {
 
 impl AsyncRead for Synthetic {
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         _cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<Result<usize>> {
-        match &self.0 {
+        let Self(body, read) = &mut *self;
+        match body {
             Some(bytes) => {
-                let bytes_left = bytes.len() - self.1;
+                let bytes_left = bytes.len() - *read;
                 let bytes_to_read = bytes_left.min(buf.len());
-                buf.copy_from_slice(&bytes[self.1..self.1 + bytes_to_read]);
+                buf[..bytes_to_read].copy_from_slice(&bytes[*read..*read + bytes_to_read]);
+                *read += bytes_to_read;
                 Poll::Ready(Ok(bytes_to_read))
             }
             None => Poll::Ready(Ok(0)),
@@ -122,7 +124,7 @@ impl Conn<Synthetic> {
         );
 
         Self {
-            transport,
+            transport: Reclaimable::new(transport),
             request_headers,
             response_headers: Headers::new(),
             path: path.into(),
@@ -133,11 +135,18 @@ impl Conn<Synthetic> {
             response_body: None,
             buffer: None,
             request_body_state: ReceivedBodyState::Start,
+            request_trailers: None,
+            response_trailers: None,
             secure: false,
             stopper: Stopper::new(),
+            cancellation: Stopper::new(),
+            synthetic: true,
+            config: ConnConfig::default(),
+            informational: vec![],
             after_send: AfterSend::default(),
             start_time: Instant::now(),
             peer_ip: None,
+            peer_addr: None,
         }
     }
 
@@ -150,7 +159,8 @@ impl Conn<Synthetic> {
             KnownHeaderName::ContentLength,
             transport.len().unwrap_or_default().to_string(),
         );
-        self.transport = transport;
+        *self.transport = transport;
         self.request_body_state = ReceivedBodyState::default();
+        self.request_trailers = None;
     }
 }
@@ -0,0 +1,238 @@
+use crate::{Error, Headers, KnownHeaderName::TransferEncoding, Result};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind, Read},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// the most transfer codings, other than `chunked`, that may be
+/// applied to a single request body. each coding multiplies the work
+/// of decoding the body, and there is no reason to apply more than
+/// one compression
+const MAX_TRANSFER_CODINGS: usize = 2;
+
+/// a transfer coding that can be removed from a request body, other
+/// than the `chunked` framing. these require the default
+/// `transfer-codings` crate feature, without which they are
+/// unsupported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransferCoding {
+    #[cfg(feature = "transfer-codings")]
+    Gzip,
+    #[cfg(feature = "transfer-codings")]
+    Deflate,
+}
+
+/**
+parses the `transfer-encoding` request header into the codings that
+were applied before `chunked`, in the order they were applied.
+returns `Ok(None)` if there is no transfer coding other than
+`identity`, in which case the body is framed by its content-length.
+
+as required by rfc 9112, `chunked` must be the final coding and may
+only be applied once.
+*/
+pub(crate) fn parse(headers: &Headers) -> Result<Option<Vec<TransferCoding>>> {
+    let Some(values) = headers.get_values(TransferEncoding) else {
+        return Ok(None);
+    };
+
+    #[cfg_attr(not(feature = "transfer-codings"), allow(unused_mut))]
+    let mut codings = vec![];
+    let mut chunked = false;
+    for value in values.iter() {
+        let value = value
+            .as_str()
+            .ok_or_else(|| Error::MalformedHeader("transfer-encoding".into()))?;
+
+        for name in value.split(',').map(str::trim) {
+            match &*name.to_ascii_lowercase() {
+                "" | "identity" => {}
+                _ if chunked => return Err(Error::MalformedHeader("transfer-encoding".into())),
+                "chunked" => chunked = true,
+                #[cfg(feature = "transfer-codings")]
+                "gzip" | "x-gzip" => codings.push(TransferCoding::Gzip),
+                #[cfg(feature = "transfer-codings")]
+                "deflate" => codings.push(TransferCoding::Deflate),
+                _ => return Err(Error::UnsupportedEncoding(name.to_string())),
+            }
+        }
+    }
+
+    if codings.len() > MAX_TRANSFER_CODINGS {
+        return Err(Error::MalformedHeader("transfer-encoding".into()));
+    }
+
+    match (codings.is_empty(), chunked) {
+        (true, false) => Ok(None),
+        (_, true) => Ok(Some(codings)),
+        (false, false) => Err(Error::MalformedHeader("transfer-encoding".into())),
+    }
+}
+
+/// the dechunked body, as it is fed to the innermost decoder. a read
+/// with nothing fed is `WouldBlock` until the end of the body has been
+/// fed, which the decoders leave their state intact for
+#[derive(Default)]
+struct Input {
+    bytes: Vec<u8>,
+    position: usize,
+    ended: bool,
+}
+
+#[derive(Clone, Default)]
+struct SharedInput(Arc<Mutex<Input>>);
+
+impl SharedInput {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Input> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Read for SharedInput {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut input = self.lock();
+        let available = &input.bytes[input.position..];
+        if available.is_empty() {
+            return if input.ended {
+                Ok(0)
+            } else {
+                Err(ErrorKind::WouldBlock.into())
+            };
+        }
+
+        let len = buf.len().min(available.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        input.position += len;
+        Ok(len)
+    }
+}
+
+/// removes the transfer codings from a request body after it has
+/// been dechunked. decoding is pulled by reads of the decoded body,
+/// so no more is decoded than is read, and a small body that
+/// decompresses to something very large is never held in memory
+pub(crate) struct TransferDecoder {
+    input: SharedInput,
+    decoded: Box<dyn Read + Send + Sync>,
+}
+
+impl Debug for TransferDecoder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let input = self.input.lock();
+        f.debug_struct("TransferDecoder")
+            .field("input", &(input.bytes.len() - input.position))
+            .field("ended", &input.ended)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TransferDecoder {
+    /// a decoder for these codings, or `None` if there are none to
+    /// remove
+    pub(crate) fn new(codings: &[TransferCoding]) -> Option<Self> {
+        if codings.is_empty() {
+            return None;
+        }
+
+        let input = SharedInput::default();
+        #[cfg_attr(not(feature = "transfer-codings"), allow(unused_mut))]
+        let mut decoded: Box<dyn Read + Send + Sync> = Box::new(input.clone());
+
+        #[cfg(feature = "transfer-codings")]
+        for coding in codings.iter().rev() {
+            use flate2::bufread::{GzDecoder, ZlibDecoder};
+            let reader = io::BufReader::new(decoded);
+            decoded = match coding {
+                TransferCoding::Gzip => Box::new(GzDecoder::new(reader)),
+                TransferCoding::Deflate => Box::new(ZlibDecoder::new(reader)),
+            };
+        }
+
+        #[cfg(not(feature = "transfer-codings"))]
+        if let Some(coding) = codings.first() {
+            match *coding {}
+        }
+
+        Some(Self { input, decoded })
+    }
+
+    /// feeds more of the dechunked body to the decoders. an empty
+    /// slice indicates the end of the body
+    pub(crate) fn feed(&mut self, bytes: &[u8]) {
+        let mut input = self.input.lock();
+        let position = input.position;
+        input.bytes.drain(..position);
+        input.position = 0;
+        input.bytes.extend_from_slice(bytes);
+        input.ended = bytes.is_empty();
+    }
+
+    /// decodes into `buf`, returning the number of bytes decoded, or
+    /// `None` if more of the body must be fed first. zero indicates
+    /// the end of the decoded body. an incomplete or invalid coding
+    /// is an error
+    pub(crate) fn read(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        match self.decoded.read(buf) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::{Error, Headers};
+
+    fn parse_values(values: &[&'static str]) -> Result<Option<Vec<super::TransferCoding>>, String> {
+        let mut headers = Headers::new();
+        for value in values {
+            headers.append("transfer-encoding", *value);
+        }
+        parse(&headers).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn parsing() {
+        assert_eq!(parse(&Headers::new()).unwrap(), None);
+        assert_eq!(parse_values(&["identity"]), Ok(None));
+        assert_eq!(parse_values(&["Chunked"]), Ok(Some(vec![])));
+
+        let malformed = Error::MalformedHeader("transfer-encoding".into()).to_string();
+        assert_eq!(parse_values(&["chunked, gzip"]), Err(malformed.clone()));
+        assert_eq!(parse_values(&["chunked", "chunked"]), Err(malformed));
+        assert_eq!(
+            parse_values(&["br, chunked"]),
+            Err(Error::UnsupportedEncoding("br".into()).to_string())
+        );
+    }
+
+    #[cfg(feature = "transfer-codings")]
+    #[test]
+    fn parsing_codings() {
+        use super::TransferCoding::*;
+        assert_eq!(
+            parse_values(&["gzip, identity, chunked"]),
+            Ok(Some(vec![Gzip]))
+        );
+        assert_eq!(
+            parse_values(&["deflate", "x-gzip ,chunked"]),
+            Ok(Some(vec![Deflate, Gzip]))
+        );
+
+        let malformed = Error::MalformedHeader("transfer-encoding".into()).to_string();
+        assert_eq!(parse_values(&["gzip"]), Err(malformed.clone()));
+        assert_eq!(parse_values(&["gzip, gzip, gzip, chunked"]), Err(malformed));
+    }
+
+    #[cfg(not(feature = "transfer-codings"))]
+    #[test]
+    fn codings_are_unsupported_without_the_feature() {
+        assert_eq!(
+            parse_values(&["gzip, chunked"]),
+            Err(Error::UnsupportedEncoding("gzip".into()).to_string())
+        );
+    }
+}
@@ -72,6 +72,34 @@ impl BoxedTransport {
         let inner: Box<dyn Any> = self.0.as_box_any();
         inner.downcast().ok()
     }
+
+    /**
+    Attempt to borrow the trait object as a specific transport T,
+    without taking it. This will only succeed if T is the type that
+    was originally passed to [`BoxedTransport::new`], and will return
+    None otherwise
+
+    ```
+    use trillium_http::transport::BoxedTransport;
+    use trillium_testing::TestTransport;
+    let (test_transport, _) = TestTransport::new();
+    let boxed = BoxedTransport::new(test_transport);
+    assert!(boxed.downcast_ref::<TestTransport>().is_some());
+    assert!(boxed.downcast_ref::<async_net::TcpStream>().is_none());
+    ```
+    */
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        // this must not auto-ref the box, which is itself a Transport
+        (*self.0).as_any().downcast_ref()
+    }
+
+    /// Attempt to mutably borrow the trait object as a specific
+    /// transport T, without taking it. This will only succeed if T is
+    /// the type that was originally passed to [`BoxedTransport::new`],
+    /// and will return None otherwise
+    pub fn downcast_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        (*self.0).as_mut_any().downcast_mut()
+    }
 }
 
 impl Deref for BoxedTransport {
@@ -36,6 +36,16 @@ pub trait Transport: Any + AsyncRead + AsyncWrite + Send + Sync + Unpin {
     /// in order to support downcasting from a `Box<dyn Transport>`,
     /// Transport requires implementing an `as_box_any` function.
     fn as_box_any(self: Box<Self>) -> Box<dyn Any>;
+
+    /// in order to support downcasting a `&dyn Transport` by
+    /// reference, Transport requires implementing an `as_any`
+    /// function.
+    fn as_any(&self) -> &dyn Any;
+
+    /// in order to support downcasting a `&mut dyn Transport` by
+    /// reference, Transport requires implementing an `as_mut_any`
+    /// function.
+    fn as_mut_any(&mut self) -> &mut dyn Any;
 }
 
 impl<T> Transport for T
@@ -45,4 +55,12 @@ where
     fn as_box_any(self: Box<Self>) -> Box<dyn Any> {
         self
     }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
 }
@@ -11,13 +11,22 @@ use std::{
 /**
 This open (pub fields) struct represents a http upgrade. It contains
 all of the data available on a Conn, as well as owning the underlying
-transport.
+transport. A conn becomes an upgrade when its response is `101
+Switching Protocols`, or when it is a `CONNECT` request that received
+a successful response, in which case the upgrade is the tunnel and
+bytes should be relayed in both directions.
 
 Important implementation note: When reading directly from the
 transport, ensure that you read from `buffer` first if there are bytes
 in it. Alternatively, read directly from the Upgrade, as that
 [`AsyncRead`] implementation will drain the buffer first before
 reading from the transport.
+
+The Upgrade is itself the supported upgraded transport: it implements
+[`AsyncRead`] and [`AsyncWrite`], and any bytes that the client sent
+after the request headers, such as the first websocket frame, are
+read before any fresh bytes from the transport. Use
+[`Upgrade::into_transport`] only if the buffer has been handled.
 */
 pub struct Upgrade<Transport> {
     /// The http request headers
@@ -75,11 +84,25 @@ impl<Transport> Upgrade<Transport> {
         &self.state
     }
 
+    /// the bytes that have been read from the transport but not yet
+    /// consumed, which precede any bytes read from the transport
+    pub fn buffered(&self) -> &[u8] {
+        self.buffer.as_deref().unwrap_or_default()
+    }
+
+    /// takes the underlying transport, discarding any request data.
+    /// any bytes returned by [`Upgrade::buffered`] are not included,
+    /// so this is only appropriate if that is empty or has already
+    /// been handled. prefer reading from and writing to the Upgrade
+    pub fn into_transport(self) -> Transport {
+        self.transport
+    }
+
     /// Modify the transport type of this upgrade. This is useful for
     /// boxing the transport in order to erase the type argument.
     pub fn map_transport<T: AsyncRead + AsyncWrite + Send + Sync + Unpin + 'static>(
         self,
-        f: impl Fn(Transport) -> T,
+        f: impl FnOnce(Transport) -> T,
     ) -> Upgrade<T> {
         Upgrade {
             transport: f(self.transport),
@@ -125,7 +148,7 @@ impl<Transport> From<Conn<Transport>> for Upgrade<Transport> {
             path,
             method,
             state,
-            transport,
+            transport: transport.into_inner(),
             buffer,
             stopper,
         }
@@ -157,8 +180,14 @@ impl<Transport: AsyncRead + Unpin> AsyncRead for Upgrade<Transport> {
                     self.buffer = None;
                     match Pin::new(&mut self.transport).poll_read(cx, &mut buf[len..]) {
                         Poll::Ready(Ok(e)) => Poll::Ready(Ok(e + len)),
+                        // the buffered bytes have already been copied into
+                        // buf, so an error is deferred until the next read
+                        // rather than discarding them
+                        Poll::Ready(Err(e)) => {
+                            log::debug!("deferring transport error after buffered bytes: {e}");
+                            Poll::Ready(Ok(len))
+                        }
                         Poll::Pending => Poll::Ready(Ok(len)),
-                        Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
                     }
                 }
             }
@@ -185,3 +214,62 @@ impl<Transport: AsyncWrite + Unpin> AsyncWrite for Upgrade<Transport> {
         Pin::new(&mut self.transport).poll_close(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Upgrade;
+    use crate::{Headers, Method, StateSet, Stopper};
+    use futures_lite::{io::Cursor, AsyncRead, AsyncReadExt};
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    fn build<T>(transport: T, buffer: &[u8]) -> Upgrade<T> {
+        Upgrade {
+            request_headers: Headers::new(),
+            path: "/".into(),
+            method: Method::Get,
+            state: StateSet::new(),
+            transport,
+            buffer: Some(buffer.to_vec()),
+            stopper: Stopper::new(),
+        }
+    }
+
+    struct Failing;
+    impl AsyncRead for Failing {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(Err(io::ErrorKind::ConnectionReset.into()))
+        }
+    }
+
+    #[test]
+    fn buffered_bytes_are_read_first() {
+        trillium_testing::block_on(async {
+            let mut upgrade = build(Cursor::new(b" world".to_vec()), b"hello");
+            assert_eq!(upgrade.buffered(), b"hello");
+            let mut string = String::new();
+            upgrade.read_to_string(&mut string).await.unwrap();
+            assert_eq!(string, "hello world");
+            assert!(upgrade.buffered().is_empty());
+
+            let mut upgrade = build(Cursor::new(b"!".to_vec()), b"hello");
+            let mut buf = [0; 3];
+            assert_eq!(upgrade.read(&mut buf).await.unwrap(), 3);
+            assert_eq!(&buf, b"hel");
+            assert_eq!(upgrade.buffered(), b"lo");
+
+            let mut upgrade = build(Failing, b"hello");
+            let mut buf = [0; 10];
+            assert_eq!(upgrade.read(&mut buf).await.unwrap(), 5);
+            assert_eq!(&buf[..5], b"hello");
+            assert!(upgrade.read(&mut buf).await.is_err());
+        });
+    }
+}
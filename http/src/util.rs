@@ -1,18 +1,108 @@
 use crate::{Headers, KnownHeaderName};
 use encoding_rs::Encoding;
-use mime::Mime;
-use std::str::FromStr;
+use std::borrow::Cow;
 
 /// a utility function for extracting a character encoding from a set
 /// of [`Headers`][trillium::Headers]
 pub fn encoding(headers: &Headers) -> &'static Encoding {
     headers
         .get_str(KnownHeaderName::ContentType)
-        .and_then(|c| Mime::from_str(c).ok())
-        .and_then(|m| {
-            m.params()
-                .find(|(name, _)| name.as_str() == "charset")
-                .and_then(|(_, v)| Encoding::for_label(v.as_str().as_bytes()))
-        })
+        .and_then(|content_type| header_param(content_type, "charset"))
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
         .unwrap_or(encoding_rs::WINDOWS_1252)
 }
+
+/**
+extracts a named parameter from a header value of the form
+`value; name=param; other="quoted param"`, such as a `content-type`
+or `content-disposition`. parameter names are compared
+case-insensitively, and quoted values are unquoted. the value is only
+copied if it contains escaped characters.
+
+```
+use trillium_http::header_param;
+let content_type = r#"multipart/form-data; Boundary="a \"b\" c"; charset=utf-8"#;
+assert_eq!(header_param(content_type, "boundary").as_deref(), Some(r#"a "b" c"#));
+assert_eq!(header_param(content_type, "CHARSET").as_deref(), Some("utf-8"));
+assert_eq!(header_param(content_type, "version"), None);
+```
+*/
+pub fn header_param<'a>(value: &'a str, name: &str) -> Option<Cow<'a, str>> {
+    split_params(value).into_iter().skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| unquote(value.trim()))
+    })
+}
+
+/// splits on semicolons that are not within a quoted string
+fn split_params(value: &str) -> Vec<&str> {
+    let mut params = vec![];
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                params.push(&value[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params
+}
+
+fn unquote(value: &str) -> Cow<'_, str> {
+    match value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        Some(quoted) if quoted.contains('\\') => {
+            let mut unquoted = String::with_capacity(quoted.len());
+            let mut chars = quoted.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => unquoted.extend(chars.next()),
+                    c => unquoted.push(c),
+                }
+            }
+            Cow::Owned(unquoted)
+        }
+        Some(quoted) => Cow::Borrowed(quoted),
+        None => Cow::Borrowed(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{header_param, unquote};
+
+    #[test]
+    fn unquoting() {
+        assert_eq!(unquote(r#""a\\b""#), r"a\b");
+        assert_eq!(unquote(r#""a b""#), "a b");
+        assert_eq!(unquote("plain"), "plain");
+    }
+
+    #[test]
+    fn params() {
+        let value = r#"form-data; name="upload"; filename="a; b \"c\".txt""#;
+        assert_eq!(header_param(value, "name").as_deref(), Some("upload"));
+        assert_eq!(
+            header_param(value, "filename").as_deref(),
+            Some(r#"a; b "c".txt"#)
+        );
+        assert_eq!(header_param("text/plain", "charset"), None);
+        assert_eq!(header_param("text/plain;charset", "charset"), None);
+        assert_eq!(
+            header_param("text/plain ; CharSet = utf-8 ", "charset").as_deref(),
+            Some("utf-8")
+        );
+    }
+}
@@ -132,7 +132,7 @@ mod status_mod {
     | ???  | white  |
     */
     pub fn status(conn: &Conn, color: bool) -> StatusOutput {
-        StatusOutput(conn.status().unwrap_or(Status::NotFound), color)
+        StatusOutput(conn.effective_status(), color)
     }
 }
 
@@ -154,7 +154,10 @@ called with a header name
 */
 pub fn header(header_name: &'static str) -> impl LogFormatter {
     move |conn: &Conn, _color: bool| {
-        format!("{:?}", conn.headers().get_str(header_name).unwrap_or(""))
+        format!(
+            "{:?}",
+            conn.request_headers().get_str(header_name).unwrap_or("")
+        )
     }
 }
 
@@ -5,9 +5,18 @@ This allows http clients that are unable to generate http methods
 other than `GET` and `POST` to use `POST` requests that are
 interpreted as other methods such as `PUT`, `PATCH`, or `DELETE`.
 
-This is currently supported with a querystring parameter of
-`_method`. To change the querystring parameter's name, use
-[`MethodOverride::with_param_name`]
+The requested method is read from, in order of preference:
+
+* if enabled with [`MethodOverride::with_header`], an
+  `X-HTTP-Method-Override` request header. To use a different header
+  name, use [`MethodOverride::with_header_name`]
+* a querystring parameter of `_method`. To change the parameter's
+  name, use [`MethodOverride::with_param_name`]
+* if enabled with [`MethodOverride::with_form_body`], a form field
+  of the same name in an `application/x-www-form-urlencoded` request
+  body, such as a hidden input in an html form. The body is buffered
+  with [`Conn::buffer_request_body`], so it can still be read by
+  subsequent handlers
 
 By default, the only methods allowed are `PUT`, `PATCH`, and
 `DELETE`. To override this, use
@@ -28,7 +37,8 @@ POST.
 
 use querystrong::QueryStrong;
 use std::{collections::HashSet, convert::TryInto, fmt::Debug, iter::FromIterator};
-use trillium::{async_trait, conn_unwrap, Conn, Handler, Method};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status};
+use trillium_http::Error;
 
 /**
 Trillium method override handler
@@ -38,6 +48,8 @@ See crate-level docs for an explanation
 #[derive(Clone, Debug)]
 pub struct MethodOverride {
     param: &'static str,
+    header: Option<&'static str>,
+    form_body_limit: Option<u64>,
     allowed_methods: HashSet<Method>,
 }
 
@@ -45,6 +57,8 @@ impl Default for MethodOverride {
     fn default() -> Self {
         Self {
             param: "_method",
+            header: None,
+            form_body_limit: None,
             allowed_methods: HashSet::from_iter([Method::Put, Method::Patch, Method::Delete]),
         }
     }
@@ -89,6 +103,99 @@ impl MethodOverride {
         self.param = param_name;
         self
     }
+
+    /**
+    also read the method from an `x-http-method-override` request
+    header, which is preferred to the querystring
+
+    default: disabled
+    ```
+    # use trillium_method_override::MethodOverride;
+    let handler = MethodOverride::new().with_header();
+    ```
+    */
+    pub fn with_header(self) -> Self {
+        self.with_header_name("x-http-method-override")
+    }
+
+    /**
+    also read the method from the provided request header, which is
+    preferred to the querystring
+
+    default: disabled
+    ```
+    # use trillium_method_override::MethodOverride;
+    let handler = MethodOverride::new().with_header_name("x-method-override");
+    ```
+    */
+    pub fn with_header_name(mut self, header_name: &'static str) -> Self {
+        self.header = Some(header_name);
+        self
+    }
+
+    /**
+    also read the method from a field of an
+    `application/x-www-form-urlencoded` request body, using the same
+    name as the querystring parameter. a body with a content-length
+    longer than `max_len` bytes is not read, and a chunked body that
+    turns out to be longer than `max_len` is halted with `413 Payload
+    Too Large`, as it cannot be read by subsequent handlers once it
+    has been consumed. otherwise, the body is buffered on the conn,
+    so subsequent handlers can read it as usual.
+
+    default: disabled
+    ```
+    # use trillium_method_override::MethodOverride;
+    let handler = MethodOverride::new().with_form_body(16 * 1024);
+    ```
+    */
+    pub fn with_form_body(mut self, max_len: u64) -> Self {
+        self.form_body_limit = Some(max_len);
+        self
+    }
+
+    fn header_method(&self, conn: &Conn) -> Option<Method> {
+        conn.request_headers()
+            .get_str(self.header?)?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    fn query_method(&self, conn: &Conn) -> Option<Method> {
+        let qs = QueryStrong::parse(conn.querystring()).ok()?;
+        qs.get_str(self.param)?.try_into().ok()
+    }
+
+    /// an error indicates that the body could not be read, either
+    /// because it was longer than the limit or because of a transport
+    /// error, and by then some or all of it has been consumed
+    async fn form_body_method(&self, conn: &mut Conn) -> Result<Option<Method>, Error> {
+        let Some(max_len) = self.form_body_limit else {
+            return Ok(None);
+        };
+
+        let is_form = conn
+            .request_headers()
+            .get_str(KnownHeaderName::ContentType)
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|mime| {
+                mime.trim()
+                    .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+            });
+        if !is_form {
+            return Ok(None);
+        }
+
+        let Some(body) = conn.buffer_request_body(max_len).await? else {
+            return Ok(None);
+        };
+
+        Ok(std::str::from_utf8(body)
+            .ok()
+            .and_then(|body| QueryStrong::parse(body).ok())
+            .and_then(|qs| qs.get_str(self.param)?.try_into().ok()))
+    }
 }
 
 #[async_trait]
@@ -97,10 +204,26 @@ impl Handler for MethodOverride {
         if conn.method() != Method::Post {
             return conn;
         }
-        let qs = conn_unwrap!(QueryStrong::parse(conn.querystring()).ok(), conn);
-        let method_str = conn_unwrap!(qs.get_str(self.param), conn);
-        let method: Method = conn_unwrap!(method_str.try_into().ok(), conn);
-        if self.allowed_methods.contains(&method) {
+
+        let method = match self
+            .header_method(&conn)
+            .or_else(|| self.query_method(&conn))
+        {
+            Some(method) => Some(method),
+            None => match self.form_body_method(&mut conn).await {
+                Ok(method) => method,
+                Err(Error::ReceivedBodyTooLong(max_len)) => {
+                    log::debug!("form body exceeded {max_len} bytes");
+                    return conn.with_status(Status::PayloadTooLarge).halt();
+                }
+                Err(e) => {
+                    log::debug!("unable to read form body: {e}");
+                    return conn.with_status(Status::BadRequest).halt();
+                }
+            },
+        };
+
+        if let Some(method) = method.filter(|method| self.allowed_methods.contains(method)) {
             conn.inner_mut().set_method(method);
         }
         conn
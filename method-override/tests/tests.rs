@@ -47,3 +47,101 @@ fn with_a_different_param_name() {
     assert_ok!(post("/?verb=delete").on(&app), "you did a delete");
     assert_ok!(post("/?_method=delete").on(&app), "it was a post");
 }
+
+#[test]
+fn with_a_header() {
+    let app = (MethodOverride::new().with_header(), test_handler);
+    assert_ok!(
+        post("/")
+            .with_request_header("x-http-method-override", "PATCH")
+            .on(&app),
+        "adams"
+    );
+
+    // the header is preferred to the querystring
+    assert_ok!(
+        post("/?_method=delete")
+            .with_request_header("x-http-method-override", "put")
+            .on(&app),
+        "put and call"
+    );
+
+    assert_ok!(
+        post("/")
+            .with_request_header("x-http-method-override", "connect")
+            .on(&app),
+        "it was a post"
+    );
+
+    let app = (
+        MethodOverride::new().with_header_name("x-verb"),
+        test_handler,
+    );
+    assert_ok!(
+        post("/").with_request_header("x-verb", "delete").on(&app),
+        "you did a delete"
+    );
+
+    // the header is ignored unless it is enabled
+    let app = (MethodOverride::new(), test_handler);
+    assert_ok!(
+        post("/")
+            .with_request_header("x-http-method-override", "delete")
+            .on(&app),
+        "it was a post"
+    );
+}
+
+#[test]
+fn with_a_form_body() {
+    let app = (
+        MethodOverride::new().with_form_body(1024),
+        |mut conn: Conn| async move {
+            let body = conn.request_body_string().await.unwrap();
+            let method = conn.method();
+            conn.ok(format!("{method} {body}"))
+        },
+    );
+
+    let form = |body: &'static str| {
+        post("/")
+            .with_request_header("content-type", "application/x-www-form-urlencoded")
+            .with_request_body(body)
+    };
+
+    assert_ok!(
+        form("name=trillium&_method=put").on(&app),
+        "PUT name=trillium&_method=put"
+    );
+    assert_ok!(form("name=trillium").on(&app), "POST name=trillium");
+    assert_ok!(form("_method=connect").on(&app), "POST _method=connect");
+
+    // a body longer than the limit is not read for the override
+    let long_body = format!("_method=delete&padding={}", "a".repeat(2048));
+    let mut conn = post("/")
+        .with_request_header("content-type", "application/x-www-form-urlencoded")
+        .with_request_body(long_body.clone())
+        .on(&app);
+    assert_status!(&conn, 200);
+    assert_body!(&mut conn, format!("POST {long_body}"));
+
+    // a chunked body is only found to be too long once it has been
+    // read, so it cannot be passed on
+    let conn = post("/")
+        .with_request_header("content-type", "application/x-www-form-urlencoded")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_body(format!("{:x}\r\n{long_body}\r\n0\r\n\r\n", long_body.len()))
+        .on(&app);
+    assert_status!(&conn, 413);
+    assert!(conn.is_halted());
+
+    // other content types are not read
+    assert_ok!(
+        post("/").with_request_body("_method=delete").on(&app),
+        "POST _method=delete"
+    );
+
+    // the form body is only read when enabled
+    let app = (MethodOverride::new(), test_handler);
+    assert_ok!(form("_method=delete").on(&app), "it was a post");
+}
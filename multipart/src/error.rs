@@ -0,0 +1,63 @@
+use std::io;
+
+/// Concrete errors that occur while parsing a multipart body
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the request content-type was not `multipart/form-data` with a
+    /// valid boundary
+    #[error("request is not multipart/form-data with a valid boundary")]
+    NotMultipart,
+
+    /// a field exceeded its configured maximum length. `index` is the
+    /// zero-based position of the field in the body, and `name` is the
+    /// field name from its content-disposition, if any.
+    #[error(
+        "multipart field {index} ({}) exceeded the maximum length of {limit} bytes",
+        .name.as_deref().unwrap_or("unnamed")
+    )]
+    FieldTooLong {
+        /// the zero-based position of the field in the body
+        index: usize,
+        /// the name of the field, if it had one
+        name: Option<String>,
+        /// the maximum length of this field, in bytes
+        limit: u64,
+    },
+
+    /// the body as a whole exceeded the configured maximum length
+    #[error("multipart body exceeded the maximum length of {0} bytes")]
+    BodyTooLong(u64),
+
+    /// the headers for a single field exceeded the maximum length
+    #[error("multipart field headers exceeded the maximum length of {0} bytes")]
+    HeadersTooLong(usize),
+
+    /// the headers for a field could not be parsed
+    #[error("malformed multipart field headers: {0}")]
+    MalformedHeaders(#[from] httparse::Error),
+
+    /// the body did not follow the multipart syntax
+    #[error("malformed multipart body: {0}")]
+    Malformed(&'static str),
+
+    /// the body ended before the closing boundary
+    #[error("multipart body ended before the closing boundary")]
+    UnexpectedEof,
+
+    /// an io error from the underlying body
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// this crate's result type
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Io(io) => io,
+            other => io::Error::new(io::ErrorKind::Other, other),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+/*!
+streaming `multipart/form-data` parsing for trillium, with size
+limits per field and for the body as a whole.
+
+Fields are read one at a time and each field's content is available
+as an [`AsyncRead`](futures_lite::AsyncRead), so uploads do not need
+to be held in memory. [`MultipartLimits`] are enforced against the
+bytes received as the body is streamed, and the
+[`Error::FieldTooLong`] variant identifies the field that exceeded
+its limit.
+
+```
+use trillium::{Conn, Status};
+use trillium_multipart::{Error, MultipartConnExt, MultipartLimits};
+use trillium_testing::prelude::*;
+
+async fn read_fields(conn: &mut Conn) -> trillium_multipart::Result<Vec<String>> {
+    let limits = MultipartLimits::new().with_max_field_len(8);
+    let mut multipart = conn.multipart_with_limits(limits).await?;
+    let mut fields = vec![];
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        fields.push(format!("{}={}", name, field.text().await?));
+    }
+    Ok(fields)
+}
+
+async fn upload(mut conn: Conn) -> Conn {
+    match read_fields(&mut conn).await {
+        Ok(fields) => conn.ok(fields.join("&")),
+        Err(e @ Error::FieldTooLong { .. }) => conn
+            .with_status(Status::PayloadTooLarge)
+            .with_body(e.to_string())
+            .halt(),
+        Err(_) => conn.with_status(Status::BadRequest).halt(),
+    }
+}
+
+let body = "--boundary\r\n\
+    content-disposition: form-data; name=\"a\"\r\n\r\n\
+    short\r\n\
+    --boundary\r\n\
+    content-disposition: form-data; name=\"b\"\r\n\r\n\
+    too long for the limit\r\n\
+    --boundary--\r\n";
+
+assert_response!(
+    post("/")
+        .with_request_header("content-type", "multipart/form-data; boundary=boundary")
+        .with_request_body(body)
+        .on(&upload),
+    Status::PayloadTooLarge,
+    "multipart field 1 (b) exceeded the maximum length of 8 bytes"
+);
+```
+*/
+
+mod error;
+pub use error::{Error, Result};
+
+mod limits;
+pub use limits::MultipartLimits;
+
+mod multipart;
+pub use multipart::{Field, Multipart};
+
+mod multipart_conn_ext;
+pub use multipart_conn_ext::MultipartConnExt;
@@ -0,0 +1,93 @@
+/**
+Size limits for a multipart body, enforced as the body is read.
+
+Limits are enforced against the bytes actually received, not against
+any declared length, so a client cannot evade them by omitting or
+misrepresenting a content-length.
+
+```
+use trillium_multipart::MultipartLimits;
+let limits = MultipartLimits::new()
+    .with_max_field_len(64 * 1024)
+    .with_max_field_len_for("avatar", 2 * 1024 * 1024)
+    .with_max_body_len(4 * 1024 * 1024);
+
+assert_eq!(limits.max_field_len_for(Some("comment")), 64 * 1024);
+assert_eq!(limits.max_field_len_for(Some("avatar")), 2 * 1024 * 1024);
+assert_eq!(limits.max_body_len(), 4 * 1024 * 1024);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct MultipartLimits {
+    max_field_len: u64,
+    max_body_len: u64,
+    field_overrides: Vec<(String, u64)>,
+}
+
+impl Default for MultipartLimits {
+    fn default() -> Self {
+        Self {
+            max_field_len: Self::DEFAULT_MAX_FIELD_LEN,
+            max_body_len: Self::DEFAULT_MAX_BODY_LEN,
+            field_overrides: vec![],
+        }
+    }
+}
+
+impl MultipartLimits {
+    /// the default maximum length of a single field, one megabyte
+    pub const DEFAULT_MAX_FIELD_LEN: u64 = 1024 * 1024;
+
+    /// the default maximum length of an entire multipart body, ten megabytes
+    pub const DEFAULT_MAX_BODY_LEN: u64 = 10 * 1024 * 1024;
+
+    /// constructs the default limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets the maximum length of any field that does not have a
+    /// per-name limit. defaults to
+    /// [`MultipartLimits::DEFAULT_MAX_FIELD_LEN`]
+    #[must_use]
+    pub fn with_max_field_len(mut self, max_field_len: u64) -> Self {
+        self.max_field_len = max_field_len;
+        self
+    }
+
+    /// sets the maximum length of fields with this name, replacing
+    /// any previous limit for this name
+    #[must_use]
+    pub fn with_max_field_len_for(mut self, name: impl Into<String>, max_field_len: u64) -> Self {
+        let name = name.into();
+        self.field_overrides
+            .retain(|(existing, _)| *existing != name);
+        self.field_overrides.push((name, max_field_len));
+        self
+    }
+
+    /// sets the maximum length of the entire multipart body, including
+    /// boundaries and field headers. defaults to
+    /// [`MultipartLimits::DEFAULT_MAX_BODY_LEN`]
+    #[must_use]
+    pub fn with_max_body_len(mut self, max_body_len: u64) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+
+    /// the maximum length of a field with this name
+    pub fn max_field_len_for(&self, name: Option<&str>) -> u64 {
+        name.and_then(|name| {
+            self.field_overrides
+                .iter()
+                .find(|(existing, _)| existing == name)
+                .map(|(_, limit)| *limit)
+        })
+        .unwrap_or(self.max_field_len)
+    }
+
+    /// the maximum length of the entire multipart body
+    pub fn max_body_len(&self) -> u64 {
+        self.max_body_len
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{Error, MultipartLimits, Result};
+use futures_lite::{future, io::AsyncRead, ready};
+use memmem::{Searcher, TwoWaySearcher};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use trillium::{header_param, HeaderName, HeaderValue, Headers, KnownHeaderName};
+
+/// the maximum length of the headers for a single field
+const MAX_HEADERS_LEN: usize = 8 * 1024;
+
+/// the maximum number of headers for a single field
+const MAX_HEADERS: usize = 16;
+
+/// how many bytes to request from the underlying reader at a time
+const READ_LEN: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// searching for the next delimiter, discarding anything before it
+    Delimiter,
+    /// the delimiter has been consumed, and the next field's headers
+    /// are expected
+    Headers,
+    /// reading the body of the current field
+    Body,
+    /// the closing delimiter has been read
+    Done,
+}
+
+/**
+A streaming `multipart/form-data` body.
+
+Fields are read one at a time with [`Multipart::next_field`], and each
+[`Field`] is itself a streaming [`AsyncRead`], so no more than a small
+buffer of the body is held in memory at once. Any unread portion of a
+field is discarded when the next field is requested.
+
+The [`MultipartLimits`] are enforced as bytes are read. Reading past a
+field's limit returns [`Error::FieldTooLong`] identifying the field,
+and reading past the body limit returns [`Error::BodyTooLong`].
+*/
+pub struct Multipart<'a> {
+    reader: Box<dyn AsyncRead + Unpin + Send + Sync + 'a>,
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    reader_done: bool,
+    state: State,
+    limits: MultipartLimits,
+    body_len: u64,
+    field_index: usize,
+    field_name: Option<String>,
+    field_len: u64,
+    field_limit: u64,
+}
+
+impl Debug for Multipart<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multipart")
+            .field("reader", &"..")
+            .field("delimiter", &String::from_utf8_lossy(&self.delimiter))
+            .field("state", &self.state)
+            .field("limits", &self.limits)
+            .field("body_len", &self.body_len)
+            .field("field_index", &self.field_index)
+            .finish()
+    }
+}
+
+impl<'a> Multipart<'a> {
+    /**
+    builds a new Multipart from an async reader, the boundary from the
+    request's content-type (see [`Multipart::boundary`]), and limits
+
+    ```
+    # trillium_testing::block_on(async {
+    use trillium_multipart::{Multipart, MultipartLimits};
+    let body = "--XyZ\r\n\
+        content-disposition: form-data; name=\"greeting\"\r\n\r\n\
+        hello\r\n\
+        --XyZ--\r\n";
+
+    let mut multipart = Multipart::new(body.as_bytes(), "XyZ", MultipartLimits::new());
+    let field = multipart.next_field().await?.unwrap();
+    assert_eq!(field.name(), Some("greeting"));
+    assert_eq!(field.text().await?, "hello");
+    assert!(multipart.next_field().await?.is_none());
+    # trillium_multipart::Result::Ok(()) }).unwrap();
+    ```
+    */
+    pub fn new(
+        reader: impl AsyncRead + Unpin + Send + Sync + 'a,
+        boundary: &str,
+        limits: MultipartLimits,
+    ) -> Self {
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        Self {
+            reader: Box::new(reader),
+            delimiter,
+            // the first delimiter is not required to be preceded by a
+            // newline, so we pretend that the body starts with one
+            buffer: b"\r\n".to_vec(),
+            reader_done: false,
+            state: State::Delimiter,
+            limits,
+            body_len: 0,
+            field_index: 0,
+            field_name: None,
+            field_len: 0,
+            field_limit: 0,
+        }
+    }
+
+    /**
+    extracts the boundary from a `multipart/form-data` content-type
+    header value. as described in [rfc 2046 section
+    5.1.1](https://www.rfc-editor.org/rfc/rfc2046#section-5.1.1), a
+    boundary is one to seventy characters from a limited set, and does
+    not end with a space. any other boundary is rejected, so that a
+    body is never searched for a delimiter that a well-formed body could
+    not contain.
+
+    ```
+    use trillium_multipart::Multipart;
+    assert_eq!(
+        Multipart::boundary("multipart/form-data; boundary=abc123"),
+        Some("abc123".into())
+    );
+    assert_eq!(
+        Multipart::boundary(r#"Multipart/Form-Data; charset=utf-8; boundary="a b:c""#),
+        Some("a b:c".into())
+    );
+    assert_eq!(Multipart::boundary("application/json"), None);
+    assert_eq!(Multipart::boundary("multipart/form-data"), None);
+    assert_eq!(Multipart::boundary(r#"multipart/form-data; boundary="a@b""#), None);
+    assert_eq!(Multipart::boundary(r#"multipart/form-data; boundary="ends with ""#), None);
+    ```
+    */
+    pub fn boundary(content_type: &str) -> Option<String> {
+        let essence = content_type.split(';').next()?.trim();
+        if !essence.eq_ignore_ascii_case("multipart/form-data") {
+            return None;
+        }
+
+        header_param(content_type, "boundary")
+            .filter(|boundary| is_valid_boundary(boundary))
+            .map(Cow::into_owned)
+    }
+
+    /// the limits for this multipart body
+    pub fn limits(&self) -> &MultipartLimits {
+        &self.limits
+    }
+
+    /**
+    returns the next field, or None if the closing boundary has been
+    reached. any unread content from the previous field is read and
+    discarded, and counts toward that field's limit.
+
+    # Errors
+
+    returns an error if the body is malformed, if a limit is
+    exceeded, or if there is an io error reading the body
+    */
+    pub async fn next_field(&mut self) -> Result<Option<Field<'_, 'a>>> {
+        future::poll_fn(|cx| self.poll_next_field(cx)).await?;
+        if self.state == State::Done {
+            return Ok(None);
+        }
+
+        let headers = future::poll_fn(|cx| self.poll_headers(cx)).await?;
+
+        let (name, file_name) = headers
+            .get_str(KnownHeaderName::ContentDisposition)
+            .map(parse_content_disposition)
+            .unwrap_or_default();
+
+        self.field_limit = self.limits.max_field_len_for(name.as_deref());
+        self.field_name = name.clone();
+        self.field_len = 0;
+        self.state = State::Body;
+        let index = self.field_index;
+        self.field_index += 1;
+
+        Ok(Some(Field {
+            multipart: self,
+            index,
+            name,
+            file_name,
+            headers,
+        }))
+    }
+
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.reader_done {
+            return Poll::Ready(Err(Error::UnexpectedEof));
+        }
+
+        let start = self.buffer.len();
+        self.buffer.resize(start + READ_LEN, 0);
+        let result = Pin::new(&mut self.reader).poll_read(cx, &mut self.buffer[start..]);
+        let bytes = match result {
+            Poll::Ready(Ok(bytes)) => bytes,
+            Poll::Ready(Err(e)) => {
+                self.buffer.truncate(start);
+                return Poll::Ready(Err(e.into()));
+            }
+            Poll::Pending => {
+                self.buffer.truncate(start);
+                return Poll::Pending;
+            }
+        };
+
+        self.buffer.truncate(start + bytes);
+        if bytes == 0 {
+            self.reader_done = true;
+            return Poll::Ready(Err(Error::UnexpectedEof));
+        }
+
+        self.body_len += bytes as u64;
+        if self.body_len > self.limits.max_body_len() {
+            log::debug!(
+                "multipart body exceeded {} bytes",
+                self.limits.max_body_len()
+            );
+            return Poll::Ready(Err(Error::BodyTooLong(self.limits.max_body_len())));
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn find_delimiter(&self) -> Option<usize> {
+        TwoWaySearcher::new(&self.delimiter).search_in(&self.buffer)
+    }
+
+    /// advances to the state after the next delimiter, either
+    /// `Headers` or `Done`
+    fn poll_next_field(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut discard = [0; READ_LEN];
+        loop {
+            match self.state {
+                State::Done | State::Headers => return Poll::Ready(Ok(())),
+
+                State::Body => {
+                    ready!(self.poll_field_read(cx, &mut discard))?;
+                }
+
+                State::Delimiter => match self.find_delimiter() {
+                    Some(index) if self.buffer.len() >= index + self.delimiter.len() + 2 => {
+                        let after = index + self.delimiter.len();
+                        if &self.buffer[after..after + 2] == b"--" {
+                            self.buffer.clear();
+                            self.state = State::Done;
+                        } else {
+                            self.buffer.drain(..after);
+                            self.state = State::Headers;
+                        }
+                    }
+
+                    Some(_) => ready!(self.poll_fill(cx))?,
+
+                    None => {
+                        let keep = self.delimiter.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.drain(..self.buffer.len() - keep);
+                        }
+                        ready!(self.poll_fill(cx))?;
+                    }
+                },
+            }
+        }
+    }
+
+    /// reads the remainder of the delimiter line and the field headers
+    fn poll_headers(&mut self, cx: &mut Context<'_>) -> Poll<Result<Headers>> {
+        loop {
+            // transport padding is permitted between the delimiter and the newline
+            let padding = self
+                .buffer
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t'))
+                .count();
+
+            let line_end = TwoWaySearcher::new(b"\r\n").search_in(&self.buffer[padding..]);
+            match line_end {
+                Some(0) => {}
+                Some(_) => {
+                    return Poll::Ready(Err(Error::Malformed("expected newline after boundary")))
+                }
+                None if self.buffer.len() - padding > MAX_HEADERS_LEN => {
+                    return Poll::Ready(Err(Error::Malformed("expected newline after boundary")))
+                }
+                None => {
+                    ready!(self.poll_fill(cx))?;
+                    continue;
+                }
+            }
+
+            let headers_start = padding + 2;
+            let headers = &self.buffer[headers_start..];
+            let end = if headers.starts_with(b"\r\n") {
+                Some(2)
+            } else {
+                TwoWaySearcher::new(b"\r\n\r\n")
+                    .search_in(headers)
+                    .map(|index| index + 4)
+            };
+
+            match end {
+                Some(end) if end > MAX_HEADERS_LEN => {
+                    return Poll::Ready(Err(Error::HeadersTooLong(MAX_HEADERS_LEN)))
+                }
+
+                Some(end) => {
+                    let headers = parse_headers(&headers[..end])?;
+                    self.buffer.drain(..headers_start + end);
+                    return Poll::Ready(Ok(headers));
+                }
+
+                None if headers.len() > MAX_HEADERS_LEN => {
+                    return Poll::Ready(Err(Error::HeadersTooLong(MAX_HEADERS_LEN)))
+                }
+
+                None => ready!(self.poll_fill(cx))?,
+            }
+        }
+    }
+
+    fn poll_field_read(&mut self, cx: &mut Context<'_>, out: &mut [u8]) -> Poll<Result<usize>> {
+        if self.state != State::Body {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            let available = match self.find_delimiter() {
+                Some(0) => {
+                    self.state = State::Delimiter;
+                    return Poll::Ready(Ok(0));
+                }
+                Some(index) => index,
+                // the end of the buffer might be the start of a delimiter
+                None => self.buffer.len().saturating_sub(self.delimiter.len() - 1),
+            };
+
+            if available == 0 {
+                ready!(self.poll_fill(cx))?;
+                continue;
+            }
+
+            let bytes = available.min(out.len());
+            if self.field_len + bytes as u64 > self.field_limit {
+                let index = self.field_index - 1;
+                log::debug!(
+                    "multipart field {} exceeded {} bytes",
+                    index,
+                    self.field_limit
+                );
+                return Poll::Ready(Err(Error::FieldTooLong {
+                    index,
+                    name: self.field_name.clone(),
+                    limit: self.field_limit,
+                }));
+            }
+
+            out[..bytes].copy_from_slice(&self.buffer[..bytes]);
+            self.buffer.drain(..bytes);
+            self.field_len += bytes as u64;
+            return Poll::Ready(Ok(bytes));
+        }
+    }
+}
+
+/**
+A single field of a [`Multipart`] body.
+
+The field's content is read with its [`AsyncRead`] implementation,
+or with [`Field::bytes`] or [`Field::text`]. Errors from the
+`AsyncRead` implementation are [`io::Error`]s that wrap this crate's
+[`Error`], so a limit violation can be identified with
+`io_error.get_ref().and_then(|e| e.downcast_ref::<Error>())`.
+*/
+pub struct Field<'m, 'a> {
+    multipart: &'m mut Multipart<'a>,
+    index: usize,
+    name: Option<String>,
+    file_name: Option<String>,
+    headers: Headers,
+}
+
+impl Debug for Field<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("index", &self.index)
+            .field("name", &self.name)
+            .field("file_name", &self.file_name)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+impl Field<'_, '_> {
+    /// the zero-based position of this field in the body
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// the `name` parameter of this field's content-disposition
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// the `filename` parameter of this field's content-disposition,
+    /// if this field is a file upload. this is provided by the client
+    /// and should not be used as a filesystem path without sanitizing it.
+    pub fn file_name(&self) -> Option<&str> {
+        self.file_name.as_deref()
+    }
+
+    /// the content-type of this field, if provided
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.get_str(KnownHeaderName::ContentType)
+    }
+
+    /// all of the headers for this field
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    /// the maximum length of this field, from the [`MultipartLimits`]
+    pub fn limit(&self) -> u64 {
+        self.multipart.field_limit
+    }
+
+    /**
+    reads the rest of this field into memory
+
+    # Errors
+
+    returns an error if this field exceeds its limit or if the body
+    cannot be read
+    */
+    pub async fn bytes(self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        let mut chunk = [0; READ_LEN];
+        loop {
+            match future::poll_fn(|cx| self.multipart.poll_field_read(cx, &mut chunk)).await? {
+                0 => return Ok(bytes),
+                n => bytes.extend_from_slice(&chunk[..n]),
+            }
+        }
+    }
+
+    /**
+    reads the rest of this field into a string, replacing any invalid
+    utf8 with replacement characters
+
+    # Errors
+
+    returns an error if this field exceeds its limit or if the body
+    cannot be read
+    */
+    pub async fn text(self) -> Result<String> {
+        let bytes = self.bytes().await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+impl AsyncRead for Field<'_, '_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.multipart
+            .poll_field_read(cx, buf)
+            .map_err(io::Error::from)
+    }
+}
+
+fn parse_headers(bytes: &[u8]) -> Result<Headers> {
+    let mut parsed = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    match httparse::parse_headers(bytes, &mut parsed)? {
+        httparse::Status::Complete((_, parsed)) => {
+            let mut headers = Headers::new();
+            for header in parsed {
+                let name = HeaderName::from(header.name.to_string());
+                headers.append(name, HeaderValue::from(header.value.to_vec()));
+            }
+            Ok(headers)
+        }
+        httparse::Status::Partial => Err(Error::Malformed("incomplete field headers")),
+    }
+}
+
+fn parse_content_disposition(value: &str) -> (Option<String>, Option<String>) {
+    (
+        header_param(value, "name").map(Cow::into_owned),
+        header_param(value, "filename").map(Cow::into_owned),
+    )
+}
+
+/// whether this boundary consists of one to seventy `bchars` and
+/// does not end with a space
+fn is_valid_boundary(boundary: &str) -> bool {
+    (1..=70).contains(&boundary.len())
+        && !boundary.ends_with(' ')
+        && boundary
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"'()+_,-./:=? ".contains(&byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_content_disposition;
+
+    #[test]
+    fn content_disposition() {
+        assert_eq!(
+            parse_content_disposition(r#"form-data; name="field""#),
+            (Some("field".into()), None)
+        );
+        assert_eq!(
+            parse_content_disposition(r#"form-data; name="upload"; filename="a; b \"c\".txt""#),
+            (Some("upload".into()), Some(r#"a; b "c".txt"#.into()))
+        );
+        assert_eq!(
+            parse_content_disposition("form-data; NAME=unquoted"),
+            (Some("unquoted".into()), None)
+        );
+        assert_eq!(parse_content_disposition("form-data"), (None, None));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{Error, Multipart, MultipartLimits, Result};
+use trillium::{async_trait, Conn, KnownHeaderName};
+
+/**
+Extension trait to read a `multipart/form-data` request body from a
+[`trillium::Conn`]
+*/
+#[async_trait]
+pub trait MultipartConnExt {
+    /**
+    streams the request body as multipart/form-data with the default
+    [`MultipartLimits`]
+
+    # Errors
+
+    returns [`Error::NotMultipart`] if the request content-type is not
+    multipart/form-data with a valid boundary, and
+    [`Error::BodyTooLong`] if the request declares a content-length
+    that exceeds the body limit
+    */
+    async fn multipart(&mut self) -> Result<Multipart<'_>>;
+
+    /**
+    streams the request body as multipart/form-data with the provided
+    [`MultipartLimits`]
+
+    # Errors
+
+    returns [`Error::NotMultipart`] if the request content-type is not
+    multipart/form-data with a valid boundary, and
+    [`Error::BodyTooLong`] if the request declares a content-length
+    that exceeds the body limit
+    */
+    async fn multipart_with_limits(&mut self, limits: MultipartLimits) -> Result<Multipart<'_>>;
+}
+
+#[async_trait]
+impl MultipartConnExt for Conn {
+    async fn multipart(&mut self) -> Result<Multipart<'_>> {
+        self.multipart_with_limits(MultipartLimits::default()).await
+    }
+
+    async fn multipart_with_limits(&mut self, limits: MultipartLimits) -> Result<Multipart<'_>> {
+        let boundary = self
+            .request_headers()
+            .get_str(KnownHeaderName::ContentType)
+            .and_then(Multipart::boundary)
+            .ok_or(Error::NotMultipart)?;
+
+        let body = self.request_body().await;
+
+        // this is only an early rejection. limits are also enforced
+        // while reading, since the declared length may be absent
+        if body
+            .content_length()
+            .is_some_and(|len| len > limits.max_body_len())
+        {
+            return Err(Error::BodyTooLong(limits.max_body_len()));
+        }
+
+        Ok(Multipart::new(body, &boundary, limits))
+    }
+}
@@ -0,0 +1,251 @@
+use futures_lite::{io::Cursor, AsyncRead, AsyncReadExt};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use trillium::{Conn, Status};
+use trillium_multipart::{Error, Multipart, MultipartConnExt, MultipartLimits};
+use trillium_testing::{block_on, prelude::*};
+
+const BODY: &str = "preamble to be ignored\r\n\
+    --boundary\r\n\
+    Content-Disposition: form-data; name=\"title\"\r\n\
+    \r\n\
+    hello\r\n\
+    --boundary\r\n\
+    Content-Disposition: form-data; name=\"upload\"; filename=\"hello.txt\"\r\n\
+    Content-Type: text/plain\r\n\
+    \r\n\
+    line one\r\n\
+    line two\r\n\
+    --boundary\r\n\
+    Content-Disposition: form-data; name=\"empty\"\r\n\
+    \r\n\
+    \r\n\
+    --boundary--\r\n\
+    epilogue";
+
+/// yields a few bytes at a time, and never declares a length
+struct Trickle(Cursor<Vec<u8>>);
+
+impl AsyncRead for Trickle {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let len = buf.len().min(3);
+        Pin::new(&mut self.0).poll_read(cx, &mut buf[..len])
+    }
+}
+
+async fn collect(mut multipart: Multipart<'_>) -> trillium_multipart::Result<Vec<String>> {
+    let mut fields = vec![];
+    while let Some(field) = multipart.next_field().await? {
+        let description = format!(
+            "{}:{}:{}:{}",
+            field.index(),
+            field.name().unwrap_or_default(),
+            field.file_name().unwrap_or_default(),
+            field.content_type().unwrap_or_default()
+        );
+        fields.push(format!("{}={}", description, field.text().await?));
+    }
+    Ok(fields)
+}
+
+#[test]
+fn parses_fields() {
+    block_on(async {
+        let multipart = Multipart::new(BODY.as_bytes(), "boundary", MultipartLimits::new());
+        assert_eq!(
+            collect(multipart).await.unwrap(),
+            vec![
+                "0:title::=hello",
+                "1:upload:hello.txt:text/plain=line one\r\nline two",
+                "2:empty::="
+            ]
+        );
+    });
+}
+
+#[test]
+fn parses_fields_across_short_reads() {
+    block_on(async {
+        let reader = Trickle(Cursor::new(BODY.as_bytes().to_vec()));
+        let multipart = Multipart::new(reader, "boundary", MultipartLimits::new());
+        assert_eq!(collect(multipart).await.unwrap().len(), 3);
+    });
+}
+
+#[test]
+fn skipping_unread_fields() {
+    block_on(async {
+        let mut multipart = Multipart::new(BODY.as_bytes(), "boundary", MultipartLimits::new());
+        multipart.next_field().await.unwrap().unwrap();
+        let mut field = multipart.next_field().await.unwrap().unwrap();
+        let mut partial = [0; 4];
+        field.read_exact(&mut partial).await.unwrap();
+        assert_eq!(&partial, b"line");
+        let field = multipart.next_field().await.unwrap().unwrap();
+        assert_eq!(field.name(), Some("empty"));
+        assert!(multipart.next_field().await.unwrap().is_none());
+    });
+}
+
+#[test]
+fn field_limit_names_the_field() {
+    block_on(async {
+        let limits = MultipartLimits::new().with_max_field_len(10);
+        let reader = Trickle(Cursor::new(BODY.as_bytes().to_vec()));
+        match collect(Multipart::new(reader, "boundary", limits)).await {
+            Err(Error::FieldTooLong { index, name, limit }) => {
+                assert_eq!(index, 1);
+                assert_eq!(name.as_deref(), Some("upload"));
+                assert_eq!(limit, 10);
+            }
+            other => panic!("expected FieldTooLong, got {:?}", other),
+        }
+    });
+}
+
+#[test]
+fn per_field_overrides() {
+    block_on(async {
+        let limits = MultipartLimits::new()
+            .with_max_field_len(5)
+            .with_max_field_len_for("upload", 100);
+        let multipart = Multipart::new(BODY.as_bytes(), "boundary", limits);
+        assert_eq!(collect(multipart).await.unwrap().len(), 3);
+    });
+}
+
+#[test]
+fn field_limit_applies_to_skipped_fields() {
+    block_on(async {
+        let limits = MultipartLimits::new().with_max_field_len(10);
+        let mut multipart = Multipart::new(BODY.as_bytes(), "boundary", limits);
+        multipart.next_field().await.unwrap().unwrap();
+        multipart.next_field().await.unwrap().unwrap();
+        assert!(matches!(
+            multipart.next_field().await,
+            Err(Error::FieldTooLong { index: 1, .. })
+        ));
+    });
+}
+
+#[test]
+fn body_limit_is_enforced_while_streaming() {
+    block_on(async {
+        let limits = MultipartLimits::new().with_max_body_len(64);
+        let reader = Trickle(Cursor::new(BODY.as_bytes().to_vec()));
+        assert!(matches!(
+            collect(Multipart::new(reader, "boundary", limits)).await,
+            Err(Error::BodyTooLong(64))
+        ));
+    });
+}
+
+#[test]
+fn truncated_body() {
+    block_on(async {
+        let body = &BODY[..BODY.find("line two").unwrap()];
+        assert!(matches!(
+            collect(Multipart::new(
+                body.as_bytes(),
+                "boundary",
+                MultipartLimits::new()
+            ))
+            .await,
+            Err(Error::UnexpectedEof)
+        ));
+    });
+}
+
+#[test]
+fn malformed_boundary_line() {
+    block_on(async {
+        let body = "--boundaryjunk\r\n\r\nvalue\r\n--boundary--";
+        assert!(matches!(
+            collect(Multipart::new(
+                body.as_bytes(),
+                "boundary",
+                MultipartLimits::new()
+            ))
+            .await,
+            Err(Error::Malformed(_))
+        ));
+    });
+}
+
+async fn handler(mut conn: Conn) -> Conn {
+    let limits = MultipartLimits::new().with_max_body_len(128);
+    let result = match conn.multipart_with_limits(limits).await {
+        Ok(multipart) => collect(multipart).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(fields) => conn.ok(fields.join("\n")),
+        Err(e @ Error::NotMultipart) => conn
+            .with_status(Status::UnsupportedMediaType)
+            .with_body(e.to_string()),
+        Err(e) => conn
+            .with_status(Status::PayloadTooLarge)
+            .with_body(e.to_string()),
+    }
+}
+
+#[test]
+fn conn_ext() {
+    let body = "--b\r\ncontent-disposition: form-data; name=\"a\"\r\n\r\n1\r\n--b--\r\n";
+    assert_ok!(
+        post("/")
+            .with_request_header("content-type", "multipart/form-data; boundary=b")
+            .with_request_body(body)
+            .on(&handler),
+        "0:a::=1"
+    );
+
+    assert_response!(
+        post("/").with_request_body(body).on(&handler),
+        Status::UnsupportedMediaType
+    );
+
+    assert_response!(
+        post("/")
+            .with_request_header("content-type", "multipart/form-data; boundary=b")
+            .with_request_body(BODY)
+            .on(&handler),
+        Status::PayloadTooLarge,
+        "multipart body exceeded the maximum length of 128 bytes"
+    );
+}
+
+#[test]
+fn invalid_boundaries_are_rejected() {
+    let body = "--b\r\ncontent-disposition: form-data; name=\"a\"\r\n\r\n1\r\n--b--\r\n";
+    for content_type in [
+        "multipart/form-data; boundary=\"\"",
+        "multipart/form-data; boundary=\"trailing space \"",
+        "multipart/form-data; boundary=\"semi;colon\"",
+    ] {
+        assert_response!(
+            post("/")
+                .with_request_header("content-type", content_type)
+                .with_request_body(body)
+                .on(&handler),
+            Status::UnsupportedMediaType
+        );
+    }
+
+    let too_long = format!("multipart/form-data; boundary={}", "b".repeat(71));
+    assert_response!(
+        post("/")
+            .with_request_header("content-type", too_long)
+            .with_request_body(body)
+            .on(&handler),
+        Status::UnsupportedMediaType
+    );
+}
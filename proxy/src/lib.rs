@@ -182,9 +182,14 @@ impl<C: Connector> Handler for Proxy<C> {
         log::debug!("proxying to {}", request_url);
 
         let mut client_conn = self.client.build_conn(conn.method(), request_url);
+        let mut request_headers = conn.request_headers().clone();
+        // an upgrade needs its connection and upgrade headers to reach
+        // the upstream server
+        if !request_headers.contains_ignore_ascii_case(KnownHeaderName::Connection, "upgrade") {
+            request_headers.remove_hop_by_hop_headers();
+        }
         client_conn.request_headers().extend(
-            conn.headers()
-                .clone()
+            request_headers
                 .into_iter()
                 .filter(|(name, _)| name != KnownHeaderName::Host),
         );
@@ -210,7 +215,7 @@ impl<C: Connector> Handler for Proxy<C> {
 
         let conn = match client_conn.status() {
             Some(SwitchingProtocols) => {
-                conn.headers_mut()
+                conn.response_headers_mut()
                     .extend(std::mem::take(client_conn.response_headers_mut()).into_iter());
 
                 conn.with_state(UpstreamUpgrade(client_conn.into()))
@@ -223,8 +228,10 @@ impl<C: Connector> Handler for Proxy<C> {
             }
 
             Some(status) => {
-                conn.headers_mut()
-                    .extend(std::mem::take(client_conn.response_headers_mut()).into_iter());
+                let mut response_headers = std::mem::take(client_conn.response_headers_mut());
+                response_headers.remove_hop_by_hop_headers();
+                conn.response_headers_mut()
+                    .extend(response_headers.into_iter());
                 conn.with_body(client_conn).with_status(status)
             }
 
@@ -0,0 +1,281 @@
+use std::fmt::{self, Display, Formatter};
+use trillium::{
+    structured_field::{BareItem, Item},
+    Conn, Status,
+};
+
+/// the name of the `Cache-Status` response header
+const CACHE_STATUS: &str = "cache-status";
+
+/**
+The reason a cache forwarded a request rather than serving it from
+storage, as listed in [rfc
+9211](https://www.rfc-editor.org/rfc/rfc9211#section-2.2).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ForwardReason {
+    /// the cache was configured not to handle this request
+    Bypass,
+
+    /// the request method's semantics require the request to be forwarded
+    Method,
+
+    /// the cache did not contain any responses that matched the
+    /// request uri
+    UriMiss,
+
+    /// the cache contained a response for the request uri, but the
+    /// request headers did not match the response's `Vary` header
+    VaryMiss,
+
+    /// the cache did not contain any responses that could be used to
+    /// satisfy this request
+    Miss,
+
+    /// the cache was able to select a fresh response, but the request's
+    /// semantics, such as a `Cache-Control: no-cache` directive, did
+    /// not allow its use
+    Request,
+
+    /// the cache was able to select a response, but it was stale
+    Stale,
+
+    /// the cache was able to select a partial response, but it did
+    /// not contain all of the requested ranges
+    Partial,
+}
+
+impl ForwardReason {
+    /// the token for this reason, such as `uri-miss`
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bypass => "bypass",
+            Self::Method => "method",
+            Self::UriMiss => "uri-miss",
+            Self::VaryMiss => "vary-miss",
+            Self::Miss => "miss",
+            Self::Request => "request",
+            Self::Stale => "stale",
+            Self::Partial => "partial",
+        }
+    }
+}
+
+impl Display for ForwardReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/**
+# A single member of a `Cache-Status` response header
+
+[Rfc 9211](https://www.rfc-editor.org/rfc/rfc9211) describes how each
+cache that handles a response appends a member to the `Cache-Status`
+header, naming the cache and describing whether the response was a
+hit or was forwarded, and why. [`ResponseCache`](crate::ResponseCache)
+emits these when configured with
+[`ResponseCache::with_cache_status`](crate::ResponseCache::with_cache_status),
+and other caches can use [`CacheStatusConnExt`] to do the same.
+
+The cache name is serialized as a token if it is a valid token, and
+as a quoted string otherwise.
+
+```
+use trillium_response_cache::{CacheStatus, ForwardReason};
+
+let hit = CacheStatus::hit("ExampleCache").with_ttl(376);
+assert_eq!(hit.to_string(), "ExampleCache;hit;ttl=376");
+
+let miss = CacheStatus::forward("example cache", ForwardReason::UriMiss).with_stored();
+assert_eq!(miss.to_string(), r#""example cache";fwd=uri-miss;stored"#);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheStatus {
+    cache: String,
+    forward: Option<ForwardReason>,
+    forward_status: Option<Status>,
+    ttl: Option<i64>,
+    stored: bool,
+    collapsed: bool,
+    key: Option<String>,
+    detail: Option<String>,
+}
+
+impl CacheStatus {
+    fn new(cache: impl Into<String>, forward: Option<ForwardReason>) -> Self {
+        Self {
+            cache: cache.into(),
+            forward,
+            forward_status: None,
+            ttl: None,
+            stored: false,
+            collapsed: false,
+            key: None,
+            detail: None,
+        }
+    }
+
+    /// a response that was served from the named cache without
+    /// contacting the next hop
+    pub fn hit(cache: impl Into<String>) -> Self {
+        Self::new(cache, None)
+    }
+
+    /// a request that the named cache forwarded to the next hop
+    pub fn forward(cache: impl Into<String>, reason: ForwardReason) -> Self {
+        Self::new(cache, Some(reason))
+    }
+
+    /// chainable setter for the status that the next hop responded
+    /// with, when forwarded
+    #[must_use]
+    pub fn with_forward_status(mut self, status: Status) -> Self {
+        self.forward_status = Some(status);
+        self
+    }
+
+    /// chainable setter for the remaining freshness lifetime of the
+    /// response in seconds. this is negative for a stale response
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: i64) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// indicates that the forwarded response was stored by the cache
+    #[must_use]
+    pub fn with_stored(mut self) -> Self {
+        self.stored = true;
+        self
+    }
+
+    /// indicates that the forwarded request was collapsed with at
+    /// least one other request
+    #[must_use]
+    pub fn with_collapsed(mut self) -> Self {
+        self.collapsed = true;
+        self
+    }
+
+    /// chainable setter for an implementation-specific
+    /// representation of the cache key
+    #[must_use]
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// chainable setter for implementation-specific additional detail
+    #[must_use]
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// the name of the cache
+    pub fn cache(&self) -> &str {
+        &self.cache
+    }
+
+    /// whether the response was served from the cache
+    pub fn is_hit(&self) -> bool {
+        self.forward.is_none()
+    }
+
+    /// the reason the request was forwarded, if it was
+    pub fn forward_reason(&self) -> Option<ForwardReason> {
+        self.forward
+    }
+
+    /// the remaining freshness lifetime of the response in seconds,
+    /// if known
+    pub fn ttl(&self) -> Option<i64> {
+        self.ttl
+    }
+
+    /// whether the forwarded response was stored
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// builds the structured field item for this member
+    pub fn to_item(&self) -> Item {
+        let cache = if is_token(&self.cache) {
+            BareItem::Token(self.cache.clone())
+        } else {
+            BareItem::String(self.cache.clone())
+        };
+
+        let mut item = Item::new(cache);
+        item = match self.forward {
+            None => item.with_parameter("hit", true),
+            Some(reason) => item.with_parameter("fwd", BareItem::Token(reason.as_str().into())),
+        };
+
+        if let Some(status) = self.forward_status {
+            item = item.with_parameter("fwd-status", i64::from(status as u16));
+        }
+
+        if let Some(ttl) = self.ttl {
+            item = item.with_parameter("ttl", ttl);
+        }
+
+        if self.stored {
+            item = item.with_parameter("stored", true);
+        }
+
+        if self.collapsed {
+            item = item.with_parameter("collapsed", true);
+        }
+
+        if let Some(key) = &self.key {
+            item = item.with_parameter("key", BareItem::String(key.clone()));
+        }
+
+        if let Some(detail) = &self.detail {
+            item = item.with_parameter("detail", BareItem::String(detail.clone()));
+        }
+
+        item
+    }
+}
+
+impl Display for CacheStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_item().fmt(f)
+    }
+}
+
+/// whether this cache name can be serialized as a structured field
+/// token rather than a string
+fn is_token(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    bytes
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == b'*')
+        && bytes.all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&byte))
+}
+
+/// Provides an extension trait for emitting `Cache-Status` on
+/// [`trillium::Conn`]
+pub trait CacheStatusConnExt: Sized {
+    /// appends a member to the `Cache-Status` response header,
+    /// after any members added by caches closer to the origin
+    fn append_cache_status(&mut self, cache_status: CacheStatus);
+
+    /// chainable alternative to [`CacheStatusConnExt::append_cache_status`]
+    #[must_use]
+    fn with_cache_status(mut self, cache_status: CacheStatus) -> Self {
+        self.append_cache_status(cache_status);
+        self
+    }
+}
+
+impl CacheStatusConnExt for Conn {
+    fn append_cache_status(&mut self, cache_status: CacheStatus) {
+        self.append_header(CACHE_STATUS, cache_status.to_string());
+    }
+}
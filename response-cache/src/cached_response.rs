@@ -0,0 +1,157 @@
+use std::time::{Duration, SystemTime};
+use trillium::{
+    Body, Conn, HeaderName, Headers,
+    KnownHeaderName::{Age, Host},
+    Method, Status,
+};
+
+/**
+The primary key that responses are stored under.
+
+A cache key is computed from the request method, the `Host` request
+header, the request path, and the full querystring, without any
+normalization. Each key may have several stored responses that
+differ by the request headers named in their `Vary` header. See
+[`CachedResponse::matches`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    method: Method,
+    host: Option<String>,
+    path: String,
+    querystring: String,
+}
+
+impl CacheKey {
+    pub(crate) fn new(conn: &Conn, method: Method) -> Self {
+        Self {
+            method,
+            host: conn.request_headers().get_str(Host).map(String::from),
+            path: conn.path().to_string(),
+            querystring: conn.querystring().to_string(),
+        }
+    }
+
+    /// the request method for this key
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// the `Host` request header for this key, if there was one
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// the request path for this key
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// the request querystring for this key
+    pub fn querystring(&self) -> &str {
+        &self.querystring
+    }
+}
+
+/**
+A response captured by [`ResponseCache`](crate::ResponseCache), along
+with the request header values that it varies on and its freshness
+lifetime.
+*/
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub(crate) status: Status,
+    pub(crate) headers: Headers,
+    pub(crate) body: Vec<u8>,
+    pub(crate) vary: Vec<(HeaderName<'static>, Option<String>)>,
+    pub(crate) stored_at: SystemTime,
+    pub(crate) expires_at: SystemTime,
+}
+
+impl CachedResponse {
+    /// the response status
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// the response headers
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    /// the response body
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// the time at which this response was stored
+    pub fn stored_at(&self) -> SystemTime {
+        self.stored_at
+    }
+
+    /// the time after which this response is stale and will not be served
+    pub fn expires_at(&self) -> SystemTime {
+        self.expires_at
+    }
+
+    /// whether this response can still be served at `now`
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        now < self.expires_at
+    }
+
+    /// how long ago this response was stored
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.stored_at).unwrap_or_default()
+    }
+
+    /// the request header names in this response's `Vary` header,
+    /// along with the value of each of those headers on the request
+    /// that this response was stored for
+    pub fn vary(&self) -> &[(HeaderName<'static>, Option<String>)] {
+        &self.vary
+    }
+
+    /**
+    whether this response can be served for a request with these
+    request headers. this is true if, for each of the header names in
+    [`CachedResponse::vary`], the request header value is identical
+    to the value it had on the original request. a request header that
+    was absent on the original request must also be absent.
+    */
+    pub fn matches(&self, request_headers: &Headers) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| request_header(request_headers, name) == *value)
+    }
+
+    /// whether this response has the same vary values as another,
+    /// and should therefore replace it in a store
+    pub fn same_variant(&self, other: &CachedResponse) -> bool {
+        self.vary == other.vary
+    }
+
+    pub(crate) fn respond(&self, mut conn: Conn, now: SystemTime) -> Conn {
+        let headers = conn.response_headers_mut();
+        headers.extend(self.headers.clone());
+        headers.insert(Age, self.age(now).as_secs().to_string());
+        conn.with_status(self.status)
+            .with_body(Body::new_static(self.body.clone()))
+            .halt()
+    }
+}
+
+/// looks up a request header by name, ignoring ascii case for
+/// unknown header names. a header with several values is combined
+/// into one, as a comma-separated list
+pub(crate) fn request_header(headers: &Headers, name: &HeaderName<'_>) -> Option<String> {
+    headers
+        .iter()
+        .find(|(header_name, _)| header_name == name)
+        .map(|(_, values)| {
+            values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+}
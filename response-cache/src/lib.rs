@@ -0,0 +1,421 @@
+/*!
+# In-process http response caching for trillium.rs
+
+This crate provides [`ResponseCache`], a handler that stores
+responses in a pluggable [`CacheStore`] and replays them for
+subsequent matching requests while they are fresh.
+
+## Which responses are stored
+
+A response is only stored if all of the following are true:
+
+* the request method is GET and the request does not have an
+  `Authorization` header or a `Cache-Control: no-store` directive
+* the status is one of 200, 203, 204, 300, 301, 308, 404, 405, 410,
+  414, or 501
+* the response has a `Cache-Control` header with a nonzero `s-maxage`
+  or `max-age`, and does not include `no-store`, `no-cache`, or
+  `private`. because this cache is shared between all clients,
+  `s-maxage` takes precedence over `max-age`.
+* the response does not have a `Set-Cookie` header or a `Vary: *` header
+* the body is static, or is streaming with a known length that does
+  not exceed [`ResponseCache::with_max_body_len`]
+
+The freshness lifetime is the `s-maxage` or `max-age`, reduced by any
+`Age` header already on the response. Responses are never
+revalidated; once stale, they are no longer served and the request is
+passed along to the rest of the handler sequence.
+
+## Cache keys and `Vary`
+
+Responses are stored under a [`CacheKey`] computed from the request
+method, `Host` header, path, and querystring. For each request header
+named in a response's `Vary` header, the request's value is stored
+alongside the response, and that response is only replayed for
+requests that have identical values for those headers. Stored
+variants with the same vary values replace each other.
+
+## Requests that bypass the cache
+
+A request with `Cache-Control: no-cache` or `no-store` is never
+served from the cache, and a request with `Cache-Control: max-age`
+is only served a response that is no older than that max-age. A
+successful request with an unsafe method such as POST or DELETE
+removes the stored responses for a GET to the same key.
+
+## `Cache-Status`
+
+When configured with [`ResponseCache::with_cache_status`], each
+response carries a [`Cache-Status`](CacheStatus) member naming this
+cache. A replayed response is marked `hit` with its remaining `ttl`
+in seconds, and any other response is marked with the
+[`ForwardReason`] it was not served from the cache, along with
+`stored` and its `ttl` if it was stored.
+
+## Handler placement
+
+Place this handler after any handlers that transform the response
+body, such as compression, so that untransformed responses are stored
+and the transformation is applied to replayed responses as well.
+
+```
+use trillium::Conn;
+use trillium_caching_headers::{CacheControlDirective::MaxAge, CachingHeadersExt};
+use trillium_response_cache::ResponseCache;
+use trillium_testing::prelude::*;
+use std::{sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+
+let counter = std::sync::Arc::new(AtomicUsize::new(0));
+let handler = (ResponseCache::default(), move |conn: Conn| {
+    let count = counter.fetch_add(1, Ordering::SeqCst);
+    async move {
+        conn.with_cache_control(MaxAge(Duration::from_secs(60)))
+            .ok(format!("response {}", count))
+    }
+});
+
+assert_ok!(get("/").on(&handler), "response 0");
+assert_ok!(get("/").on(&handler), "response 0", "age" => "0");
+assert_ok!(get("/other").on(&handler), "response 1");
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod cached_response;
+use cached_response::request_header;
+pub use cached_response::{CacheKey, CachedResponse};
+
+mod cache_status;
+pub use cache_status::{CacheStatus, CacheStatusConnExt, ForwardReason};
+
+mod store;
+pub use store::{CacheStore, MemoryStore};
+
+use std::time::{Duration, SystemTime};
+use trillium::{
+    async_trait, Body, Conn, Handler, HeaderName,
+    KnownHeaderName::{
+        Age, Authorization, Connection, ContentLength, SetCookie, TransferEncoding, Vary,
+    },
+    Method, Status,
+};
+use trillium_caching_headers::CachingHeadersExt;
+
+/**
+Trillium handler that caches responses in a [`CacheStore`].
+
+See the crate-level docs for which responses are stored and when
+they are served.
+*/
+#[derive(Debug)]
+pub struct ResponseCache<S = MemoryStore> {
+    store: S,
+    max_body_len: u64,
+    cache_status: Option<String>,
+}
+
+impl Default for ResponseCache<MemoryStore> {
+    fn default() -> Self {
+        Self::new(MemoryStore::default())
+    }
+}
+
+/// Alias for [`ResponseCache::new`]
+pub fn response_cache<S: CacheStore>(store: S) -> ResponseCache<S> {
+    ResponseCache::new(store)
+}
+
+/// the request was served from the cache
+struct CacheHit;
+
+/// the request was not served from the cache, for this reason
+struct CacheMiss(ForwardReason);
+
+/// the response to this request may be stored under this key
+struct CacheCandidate(CacheKey);
+
+/// a successful response to this request invalidates this key
+struct Invalidation(CacheKey);
+
+impl<S: CacheStore> ResponseCache<S> {
+    /// the default maximum body length that will be stored, one megabyte
+    pub const DEFAULT_MAX_BODY_LEN: u64 = 1024 * 1024;
+
+    /// constructs a new ResponseCache with the provided store
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            max_body_len: Self::DEFAULT_MAX_BODY_LEN,
+            cache_status: None,
+        }
+    }
+
+    /// emits a [`CacheStatus`] member with this cache name on every
+    /// response. see the crate-level docs for the parameters that are
+    /// included. by default, no `Cache-Status` header is sent
+    #[must_use]
+    pub fn with_cache_status(mut self, cache_name: impl Into<String>) -> Self {
+        self.cache_status = Some(cache_name.into());
+        self
+    }
+
+    /// sets the maximum length of a response body that will be
+    /// stored. defaults to [`ResponseCache::DEFAULT_MAX_BODY_LEN`]
+    #[must_use]
+    pub fn with_max_body_len(mut self, max_body_len: u64) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+
+    fn append_cache_status(&self, conn: &mut Conn, cache_status: impl FnOnce(&str) -> CacheStatus) {
+        if let Some(name) = &self.cache_status {
+            conn.append_cache_status(cache_status(name));
+        }
+    }
+
+    /// borrow the store for this cache
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// stores the response if possible, returning its freshness
+    /// lifetime if it was stored
+    async fn capture(&self, mut conn: Conn, key: CacheKey) -> (Conn, Option<Duration>) {
+        let status = conn.effective_status();
+        if !is_cacheable_status(status) {
+            return (conn, None);
+        }
+
+        let response_headers = conn.inner().response_headers();
+        if response_headers.has_header(SetCookie) {
+            return (conn, None);
+        }
+
+        let freshness = match response_headers.cache_control() {
+            Some(cc) if !cc.is_no_store() && !cc.is_no_cache() && !cc.is_private() => {
+                match cc.s_maxage().or_else(|| cc.max_age()) {
+                    Some(freshness) => freshness,
+                    None => return (conn, None),
+                }
+            }
+            _ => return (conn, None),
+        };
+
+        let age = response_headers
+            .get_str(Age)
+            .and_then(|age| age.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+
+        let freshness = match freshness.checked_sub(age) {
+            Some(freshness) if !freshness.is_zero() => freshness,
+            _ => return (conn, None),
+        };
+
+        // a response may have several vary headers, each of which
+        // may list several header names
+        let mut vary_names = vec![];
+        for vary in response_headers
+            .get_values(Vary)
+            .into_iter()
+            .flat_map(|values| values.iter())
+        {
+            let vary = vary.to_string();
+            let names = vary
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty());
+            vary_names.extend(names.map(|name| HeaderName::from(name.to_string())));
+        }
+
+        if vary_names.iter().any(|name| name.as_ref() == "*") {
+            return (conn, None);
+        }
+
+        let body = match conn.inner_mut().take_response_body() {
+            None => vec![],
+            Some(body) if body.is_static() => {
+                let bytes = body.static_bytes().unwrap_or_default().to_vec();
+                conn.inner_mut().set_response_body(body);
+                bytes
+            }
+            Some(body) if body.len().is_some_and(|len| len <= self.max_body_len) => {
+                match body.into_bytes().await {
+                    Ok(bytes) => {
+                        conn.inner_mut()
+                            .set_response_body(Body::new_static(bytes.to_vec()));
+                        bytes.into_owned()
+                    }
+                    Err(e) => {
+                        log::error!("unable to read response body for caching: {}", e);
+                        return (conn.with_status(500), None);
+                    }
+                }
+            }
+            Some(body) => {
+                conn.inner_mut().set_response_body(body);
+                return (conn, None);
+            }
+        };
+
+        let vary = vary_names
+            .into_iter()
+            .map(|name| {
+                let value = request_header(conn.request_headers(), &name);
+                (name, value)
+            })
+            .collect();
+
+        let mut headers = conn.inner().response_headers().clone();
+        for name in [Age, ContentLength, TransferEncoding, Connection] {
+            headers.remove(name);
+        }
+
+        let stored_at = SystemTime::now();
+        log::trace!("storing response for {:?} for {:?}", key, freshness);
+        self.store
+            .insert(
+                key,
+                CachedResponse {
+                    status,
+                    headers,
+                    body,
+                    vary,
+                    stored_at,
+                    expires_at: stored_at + freshness,
+                },
+            )
+            .await;
+
+        (conn, Some(freshness))
+    }
+}
+
+fn is_cacheable_status(status: Status) -> bool {
+    matches!(
+        status as u16,
+        200 | 203 | 204 | 300 | 301 | 308 | 404 | 405 | 410 | 414 | 501
+    )
+}
+
+#[async_trait]
+impl<S: CacheStore> Handler for ResponseCache<S> {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let method = conn.method();
+        if method != Method::Get {
+            if !method.is_safe() {
+                let key = CacheKey::new(&conn, Method::Get);
+                conn.set_state(Invalidation(key));
+            }
+            conn.set_state(CacheMiss(ForwardReason::Method));
+            return conn;
+        }
+
+        if conn.request_headers().has_header(Authorization) {
+            conn.set_state(CacheMiss(ForwardReason::Bypass));
+            return conn;
+        }
+
+        let request_cache_control = conn.cache_control();
+        let no_store = request_cache_control
+            .as_ref()
+            .is_some_and(|cc| cc.is_no_store());
+        let no_cache = request_cache_control
+            .as_ref()
+            .is_some_and(|cc| cc.is_no_cache());
+        let max_age = request_cache_control.and_then(|cc| cc.max_age());
+
+        let key = CacheKey::new(&conn, Method::Get);
+        let now = SystemTime::now();
+        let stored = self.store.get(&key).await;
+        let mut reason = if stored.is_empty() {
+            ForwardReason::UriMiss
+        } else {
+            ForwardReason::VaryMiss
+        };
+
+        for response in stored {
+            if !response.matches(conn.request_headers()) {
+                continue;
+            }
+
+            if !response.is_fresh(now) {
+                if reason != ForwardReason::Request {
+                    reason = ForwardReason::Stale;
+                }
+                continue;
+            }
+
+            if no_store || no_cache || max_age.is_some_and(|max_age| response.age(now) > max_age) {
+                reason = ForwardReason::Request;
+                continue;
+            }
+
+            log::trace!("serving cached response for {:?}", key);
+            conn.set_state(CacheHit);
+            let ttl = response
+                .expires_at()
+                .duration_since(now)
+                .unwrap_or_default();
+            let mut conn = response.respond(conn, now);
+            self.append_cache_status(&mut conn, |name| {
+                CacheStatus::hit(name).with_ttl(seconds(ttl))
+            });
+            return conn;
+        }
+
+        conn.set_state(CacheMiss(reason));
+        if !no_store {
+            conn.set_state(CacheCandidate(key));
+        }
+
+        conn
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        if conn.take_state::<CacheHit>().is_some() {
+            return conn;
+        }
+
+        let reason = conn.take_state::<CacheMiss>();
+
+        let mut stored = None;
+        if let Some(CacheCandidate(key)) = conn.take_state() {
+            (conn, stored) = self.capture(conn, key).await;
+        }
+
+        if let Some(Invalidation(key)) = conn.take_state() {
+            let successful = conn
+                .status()
+                .is_some_and(|status| (200..400).contains(&(status as u16)));
+            if successful {
+                log::trace!("invalidating cached responses for {:?}", key);
+                self.store.remove(&key).await;
+            }
+        }
+
+        if let Some(CacheMiss(reason)) = reason {
+            self.append_cache_status(&mut conn, |name| {
+                let cache_status = CacheStatus::forward(name, reason);
+                match stored {
+                    Some(freshness) => cache_status.with_stored().with_ttl(seconds(freshness)),
+                    None => cache_status,
+                }
+            });
+        }
+
+        conn
+    }
+}
+
+fn seconds(duration: Duration) -> i64 {
+    i64::try_from(duration.as_secs()).unwrap_or(i64::MAX)
+}
@@ -0,0 +1,157 @@
+use crate::{CacheKey, CachedResponse};
+use std::{collections::HashMap, sync::Mutex, time::SystemTime};
+use trillium::async_trait;
+
+/**
+A storage backend for [`ResponseCache`](crate::ResponseCache).
+
+Each [`CacheKey`] may have several [`CachedResponse`] variants that
+differ by the request headers named in their `Vary` header. The
+handler is responsible for selecting a fresh variant that matches the
+current request, so stores only need to hold variants and decide when
+to discard them.
+*/
+#[async_trait]
+pub trait CacheStore: Send + Sync + 'static {
+    /// returns the stored variants for this key. stores may omit
+    /// stale variants, but are not required to.
+    async fn get(&self, key: &CacheKey) -> Vec<CachedResponse>;
+
+    /// stores a response for this key, replacing any existing variant
+    /// for which [`CachedResponse::same_variant`] is true
+    async fn insert(&self, key: CacheKey, response: CachedResponse);
+
+    /// removes all variants for this key
+    async fn remove(&self, key: &CacheKey);
+}
+
+/**
+An in-memory [`CacheStore`].
+
+Stale variants are dropped when their key is next read, and whenever
+an insertion would exceed the configured maximum number of stored
+responses. If the store is still full after dropping stale variants,
+the variants that expire soonest are evicted first.
+*/
+#[derive(Debug)]
+pub struct MemoryStore {
+    entries: Mutex<HashMap<CacheKey, Vec<CachedResponse>>>,
+    max_responses: usize,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::default(),
+            max_responses: Self::DEFAULT_MAX_RESPONSES,
+        }
+    }
+}
+
+impl MemoryStore {
+    /// the default maximum number of stored responses, counting each
+    /// variant separately
+    pub const DEFAULT_MAX_RESPONSES: usize = 1000;
+
+    /// constructs a new empty MemoryStore
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets the maximum number of stored responses, counting each
+    /// variant separately. defaults to
+    /// [`MemoryStore::DEFAULT_MAX_RESPONSES`]
+    #[must_use]
+    pub fn with_max_responses(mut self, max_responses: usize) -> Self {
+        self.max_responses = max_responses;
+        self
+    }
+
+    /// the number of responses currently stored, including stale
+    /// responses that have not yet been dropped
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().values().map(Vec::len).sum()
+    }
+
+    /// whether this store is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn make_room(entries: &mut HashMap<CacheKey, Vec<CachedResponse>>, max_responses: usize) {
+    let count = |entries: &HashMap<CacheKey, Vec<CachedResponse>>| -> usize {
+        entries.values().map(Vec::len).sum()
+    };
+
+    if count(entries) < max_responses {
+        return;
+    }
+
+    let now = SystemTime::now();
+    for variants in entries.values_mut() {
+        variants.retain(|variant| variant.is_fresh(now));
+    }
+    entries.retain(|_, variants| !variants.is_empty());
+
+    while count(entries) >= max_responses {
+        let soonest = entries
+            .iter()
+            .flat_map(|(key, variants)| {
+                variants
+                    .iter()
+                    .enumerate()
+                    .map(move |(index, variant)| (variant.expires_at(), key, index))
+            })
+            .min_by_key(|(expires_at, _, _)| *expires_at)
+            .map(|(_, key, index)| (key.clone(), index));
+
+        match soonest {
+            Some((key, index)) => {
+                if let Some(variants) = entries.get_mut(&key) {
+                    variants.remove(index);
+                    if variants.is_empty() {
+                        entries.remove(&key);
+                    }
+                }
+            }
+            None => break,
+        }
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &CacheKey) -> Vec<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = SystemTime::now();
+        match entries.get_mut(key) {
+            Some(variants) => {
+                variants.retain(|variant| variant.is_fresh(now));
+                let variants = variants.clone();
+                if variants.is_empty() {
+                    entries.remove(key);
+                }
+                variants
+            }
+            None => vec![],
+        }
+    }
+
+    async fn insert(&self, key: CacheKey, response: CachedResponse) {
+        if self.max_responses == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(variants) = entries.get_mut(&key) {
+            variants.retain(|variant| !variant.same_variant(&response));
+        }
+        make_room(&mut entries, self.max_responses);
+        entries.entry(key).or_default().push(response);
+    }
+
+    async fn remove(&self, key: &CacheKey) {
+        self.entries.lock().unwrap().remove(key);
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use trillium::{Body, Conn, Handler, KnownHeaderName};
+use trillium_caching_headers::{
+    CacheControlDirective::{MaxAge, NoStore, Private, SMaxage},
+    CacheControlHeader, CachingHeadersExt,
+};
+use trillium_response_cache::*;
+use trillium_testing::prelude::*;
+
+fn counting(f: impl Fn(Conn) -> Conn + Send + Sync + 'static) -> (Arc<AtomicUsize>, impl Handler) {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handler_counter = Arc::clone(&counter);
+    let handler = move |conn: Conn| {
+        let count = handler_counter.fetch_add(1, Ordering::SeqCst);
+        let conn = f(conn);
+        async move { conn.ok(format!("response {}", count)) }
+    };
+    (counter, handler)
+}
+
+fn max_age(conn: Conn) -> Conn {
+    conn.with_cache_control(MaxAge(Duration::from_secs(60)))
+}
+
+#[test]
+fn caches_fresh_responses() {
+    let (counter, app) = counting(max_age);
+    let app = (ResponseCache::default(), app);
+
+    assert_ok!(get("/").on(&app), "response 0");
+    assert_ok!(get("/").on(&app), "response 0", "age" => "0", "cache-control" => "max-age=60");
+    assert_ok!(get("/?a=b").on(&app), "response 1");
+    assert_ok!(
+        get("/")
+            .with_request_header(KnownHeaderName::Host, "other.example")
+            .on(&app),
+        "response 2"
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn caches_a_body_without_a_status() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let handler_counter = Arc::clone(&counter);
+    let app = (ResponseCache::default(), move |conn: Conn| {
+        let count = handler_counter.fetch_add(1, Ordering::SeqCst);
+        async move { max_age(conn).with_body(format!("response {count}")) }
+    });
+
+    assert_body!(get("/").on(&app), "response 0");
+    assert_ok!(get("/").on(&app), "response 0", "age" => "0");
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn uncacheable_responses() {
+    let cases: Vec<fn(Conn) -> Conn> = vec![
+        |conn| conn,
+        |conn| conn.with_cache_control(NoStore),
+        |conn| conn.with_cache_control([MaxAge(Duration::from_secs(60)), Private]),
+        |conn| conn.with_cache_control(MaxAge(Duration::from_secs(0))),
+        |conn| max_age(conn).with_header(KnownHeaderName::SetCookie, "a=b"),
+        |conn| max_age(conn).with_header(KnownHeaderName::Vary, "*"),
+        |conn| max_age(conn).with_header(KnownHeaderName::Age, "60"),
+    ];
+
+    for case in cases {
+        let (counter, app) = counting(case);
+        let app = (ResponseCache::default(), app);
+        assert_ok!(get("/").on(&app), "response 0");
+        assert_ok!(get("/").on(&app), "response 1");
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+}
+
+#[test]
+fn s_maxage_takes_precedence() {
+    let (_, app) = counting(|conn| {
+        conn.with_cache_control(CacheControlHeader::from([
+            MaxAge(Duration::from_secs(60)),
+            SMaxage(Duration::from_secs(0)),
+        ]))
+    });
+    let app = (ResponseCache::default(), app);
+    assert_ok!(get("/").on(&app), "response 0");
+    assert_ok!(get("/").on(&app), "response 1");
+}
+
+#[test]
+fn vary() {
+    let (counter, app) =
+        counting(|conn| max_age(conn).with_vary([KnownHeaderName::AcceptLanguage]));
+    let app = (ResponseCache::default(), app);
+
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "en")
+            .on(&app),
+        "response 0"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "fr")
+            .on(&app),
+        "response 1"
+    );
+    assert_ok!(get("/").on(&app), "response 2");
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "en")
+            .on(&app),
+        "response 0"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "fr")
+            .on(&app),
+        "response 1"
+    );
+    assert_ok!(get("/").on(&app), "response 2");
+    assert_eq!(counter.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn vary_with_several_values() {
+    let (counter, app) = counting(|conn| {
+        max_age(conn).with_header(
+            KnownHeaderName::Vary,
+            vec!["accept-language", "accept, dnt"],
+        )
+    });
+    let app = (ResponseCache::default(), app);
+    let request = |accept: Vec<&'static str>| {
+        get("/")
+            .with_request_header("accept-language", "en")
+            .with_request_header("accept", accept)
+            .with_request_header("dnt", "1")
+    };
+
+    assert_ok!(request(vec!["text/html", "*/*"]).on(&app), "response 0");
+    assert_ok!(request(vec!["text/html", "*/*"]).on(&app), "response 0");
+    assert_ok!(request(vec!["text/html"]).on(&app), "response 1");
+    assert_ok!(
+        request(vec!["text/html", "*/*"])
+            .with_request_header("dnt", "0")
+            .on(&app),
+        "response 2"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "en")
+            .on(&app),
+        "response 3"
+    );
+    assert_eq!(counter.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn request_directives_bypass_cache() {
+    let (_, app) = counting(max_age);
+    let app = (ResponseCache::default(), app);
+
+    assert_ok!(get("/").on(&app), "response 0");
+    assert_ok!(
+        get("/")
+            .with_request_header("cache-control", "no-cache")
+            .on(&app),
+        "response 1"
+    );
+    // the no-cache response replaced the stored response
+    assert_ok!(get("/").on(&app), "response 1");
+    assert_ok!(
+        get("/")
+            .with_request_header("cache-control", "no-store")
+            .on(&app),
+        "response 2"
+    );
+    assert_ok!(get("/").on(&app), "response 1");
+    assert_ok!(
+        get("/")
+            .with_request_header(KnownHeaderName::Authorization, "Basic dXNlcjpwYXNz")
+            .on(&app),
+        "response 3"
+    );
+}
+
+#[test]
+fn unsafe_methods_invalidate() {
+    let (_, app) = counting(max_age);
+    let app = (ResponseCache::default(), app);
+
+    assert_ok!(get("/").on(&app), "response 0");
+    assert_ok!(get("/").on(&app), "response 0");
+    assert_ok!(post("/").on(&app), "response 1");
+    assert_ok!(get("/").on(&app), "response 2");
+    assert_ok!(get("/").on(&app), "response 2");
+}
+
+#[test]
+fn streaming_bodies() {
+    let app = (
+        ResponseCache::default().with_max_body_len(10),
+        |conn: Conn| async move {
+            let body = conn.path().trim_start_matches('/').to_string();
+            let len = body.len() as u64;
+            max_age(conn).ok(Body::new_streaming(
+                futures_lite::io::Cursor::new(body),
+                Some(len),
+            ))
+        },
+    );
+
+    assert_ok!(get("/short").on(&app), "short");
+    assert_eq!(app.0.store().len(), 1);
+
+    assert_ok!(get("/much-too-long").on(&app), "much-too-long");
+    assert_eq!(app.0.store().len(), 1);
+}
+
+#[test]
+fn memory_store_eviction() {
+    let app = (
+        ResponseCache::new(MemoryStore::new().with_max_responses(2)),
+        |conn: Conn| async move {
+            let seconds = conn.path().trim_start_matches('/').parse().unwrap();
+            conn.with_cache_control(MaxAge(Duration::from_secs(seconds)))
+                .ok("ok")
+        },
+    );
+
+    assert_ok!(get("/30").on(&app));
+    assert_ok!(get("/10").on(&app));
+    assert_ok!(get("/20").on(&app));
+    assert_eq!(app.0.store().len(), 2);
+
+    // the response that expires soonest was evicted
+    assert_headers!(get("/30").on(&app), "age" => "0");
+    assert_headers!(get("/20").on(&app), "age" => "0");
+    assert_headers!(get("/10").on(&app), "age" => None);
+}
+
+#[test]
+fn cache_status() {
+    let (_, app) = counting(|conn| max_age(conn).with_vary([KnownHeaderName::AcceptLanguage]));
+    let app = (ResponseCache::default().with_cache_status("test"), app);
+
+    assert_ok!(
+        get("/").on(&app),
+        "response 0",
+        "cache-status" => "test;fwd=uri-miss;ttl=60;stored"
+    );
+    assert_ok!(get("/").on(&app), "response 0", "cache-status" => "test;hit;ttl=59");
+    assert_ok!(
+        get("/")
+            .with_request_header("accept-language", "en")
+            .on(&app),
+        "response 1",
+        "cache-status" => "test;fwd=vary-miss;ttl=60;stored"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header("cache-control", "no-store")
+            .on(&app),
+        "response 2",
+        "cache-status" => "test;fwd=request"
+    );
+    assert_ok!(
+        post("/").on(&app),
+        "response 3",
+        "cache-status" => "test;fwd=method"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header(KnownHeaderName::Authorization, "Basic dXNlcjpwYXNz")
+            .on(&app),
+        "response 4",
+        "cache-status" => "test;fwd=bypass"
+    );
+
+    let (_, app) = counting(|conn| conn.with_header("cache-status", "origin;fwd=uri-miss"));
+    let app = (ResponseCache::default().with_cache_status("test"), app);
+    let conn = get("/").on(&app);
+    let cache_status = conn
+        .inner()
+        .response_headers()
+        .get_values("cache-status")
+        .unwrap();
+    assert_eq!(cache_status.len(), 2);
+    assert_eq!(cache_status[1], "test;fwd=uri-miss");
+}
+
+#[test]
+fn no_cache_status_by_default() {
+    let (_, app) = counting(max_age);
+    let app = (ResponseCache::default(), app);
+    assert_headers!(get("/").on(&app), "cache-status" => None);
+    assert_headers!(get("/").on(&app), "cache-status" => None);
+}
@@ -55,6 +55,64 @@ or an `any` handler.
 To disable the default OPTIONS behavior, use
 [`Router::without_options_handling`] or
 [`RouterRef::set_options_handling`]
+
+Whenever a request path matches routes for other methods but not the
+request method, the router registers those methods with
+[`Conn::add_allowed_methods`](trillium::Conn::add_allowed_methods)
+before passing the conn along. This allows a
+[`trillium::AutoOptions`] placed after the router to respond to
+OPTIONS requests, or to respond with a 405 Method Not Allowed, using
+the same `Allow` header as the router's own OPTIONS handling.
+
+```
+use trillium::AutoOptions;
+use trillium_router::Router;
+use trillium_testing::{prelude::*, TestConn};
+
+let handler = (
+    Router::new()
+        .without_options_handling()
+        .get("/resource", "ok")
+        .delete("/resource", "deleted"),
+    AutoOptions::new().with_method_not_allowed(),
+);
+
+let mut conn = TestConn::build(Method::Options, "/resource", ()).on(&handler);
+assert_status!(&conn, 200);
+assert_headers!(&mut conn, "allow" => "DELETE, GET");
+
+let mut conn = post("/resource").on(&handler);
+assert_status!(&conn, 405);
+assert_headers!(&mut conn, "allow" => "DELETE, GET");
+
+assert_not_handled!(post("/other").on(&handler));
+```
+
+## Head handling
+
+By default, a HEAD request is routed to the GET handler for the same
+path when no HEAD handler matches, as HEAD is [defined to
+be](https://www.rfc-editor.org/rfc/rfc9110#section-9.3.2) identical to
+GET without the response body. The handler sees a HEAD request and
+can respond exactly as it would to GET: the `Content-Length` of that
+body is sent, but the body itself is not. An explicit HEAD handler
+takes precedence.
+
+To disable this, use [`Router::without_head_handling`] or
+[`RouterRef::set_head_handling`]
+
+```
+use trillium_router::Router;
+use trillium_testing::{prelude::*, TestConn};
+
+let router = Router::new().get("/", "hello");
+let mut conn = TestConn::build(Method::Head, "/", ()).on(&router);
+assert_status!(&conn, 200);
+assert_headers!(&mut conn, "content-length" => "5");
+
+let router = Router::new().without_head_handling().get("/", "hello");
+assert_not_handled!(TestConn::build(Method::Head, "/", ()).on(&router));
+```
 */
 
 mod router;
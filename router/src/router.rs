@@ -6,7 +6,7 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     mem,
 };
-use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName, Method, Upgrade};
+use trillium::{async_trait, Conn, Handler, Info, Method, Upgrade};
 
 const ALL_METHODS: [Method; 5] = [
     Method::Delete,
@@ -134,6 +134,7 @@ See crate level docs for more, as this is the primary type in this crate.
 pub struct Router {
     routefinder: MethodRoutefinder,
     handle_options: bool,
+    handle_head: bool,
 }
 
 impl Default for Router {
@@ -141,6 +142,7 @@ impl Default for Router {
         Self {
             routefinder: MethodRoutefinder::default(),
             handle_options: true,
+            handle_head: true,
         }
     }
 }
@@ -238,6 +240,24 @@ impl Router {
         self.handle_options = options_enabled;
     }
 
+    /**
+    Disable the default behavior of running the GET handler for a
+    route in response to a HEAD request when no HEAD handler matches
+    */
+    pub fn without_head_handling(mut self) -> Self {
+        self.set_head_handling(false);
+        self
+    }
+
+    /**
+    enable or disable the router's behavior of running the GET handler for a route in response to HEAD requests.
+
+    default: enabled
+     */
+    pub(crate) fn set_head_handling(&mut self, head_enabled: bool) {
+        self.handle_head = head_enabled;
+    }
+
     /**
     Another way to build a router, if you don't like the chainable
     interface described in [`Router::new`]. Note that the argument to
@@ -278,7 +298,13 @@ impl Router {
         method: Method,
         path: &'b str,
     ) -> Option<Match<'a, 'b, (MethodSelection, Box<dyn Handler>)>> {
-        self.routefinder.best_match(method, path)
+        self.routefinder.best_match(method, path).or_else(|| {
+            if method == Method::Head && self.handle_head {
+                self.routefinder.best_match(Method::Get, path)
+            } else {
+                None
+            }
+        })
     }
 
     /**
@@ -409,7 +435,7 @@ impl Router {
 
 #[async_trait]
 impl Handler for Router {
-    async fn run(&self, conn: Conn) -> Conn {
+    async fn run(&self, mut conn: Conn) -> Conn {
         let method = conn.method();
         let path = conn.path();
 
@@ -436,20 +462,14 @@ impl Handler for Router {
 
             new_conn
         } else if method == Method::Options && self.handle_options {
-            let allow = self
-                .routefinder
-                .methods_matching(path)
-                .iter()
-                .map(|m| m.as_ref())
-                .collect::<Vec<_>>()
-                .join(", ");
-
-            return conn
-                .with_header(KnownHeaderName::Allow, allow)
-                .with_status(200)
-                .halt();
+            let allow = self.routefinder.methods_matching(path);
+            return conn.with_allow_header(allow).with_status(200).halt();
         } else {
             log::debug!("{} did not match any route", conn.path());
+            let allow = self.routefinder.methods_matching(path);
+            if !allow.is_empty() {
+                conn.add_allowed_methods(allow);
+            }
             conn
         }
     }
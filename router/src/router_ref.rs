@@ -180,4 +180,16 @@ impl<'r> RouterRef<'r> {
     pub fn set_options_handling(&mut self, options_enabled: bool) {
         self.0.set_options_handling(options_enabled);
     }
+
+    /**
+    enable or disable the router's behavior of running the GET
+    handler for a route in response to HEAD requests.
+
+    default: enabled
+
+    see crate-level docs for further explanation of the default behavior.
+     */
+    pub fn set_head_handling(&mut self, head_enabled: bool) {
+        self.0.set_head_handling(head_enabled);
+    }
 }
@@ -0,0 +1,35 @@
+use trillium::{Conn, Method};
+use trillium_router::*;
+use trillium_testing::{prelude::*, TestConn};
+
+#[test]
+fn head_runs_the_get_handler() {
+    let router = Router::new()
+        .get("/", |conn: Conn| async move {
+            let method = conn.method().to_string();
+            conn.ok("hello").with_header("x-method", method)
+        })
+        .get("/explicit", "get")
+        .with_route(Method::Head, "/explicit", |conn: Conn| async move {
+            conn.with_header("x-head", "handled").with_status(200)
+        });
+
+    let mut conn = TestConn::build(Method::Head, "/", ()).on(&router);
+    assert_status!(&conn, 200);
+    assert_headers!(&mut conn, "content-length" => "5", "x-method" => "HEAD");
+
+    let mut conn = TestConn::build(Method::Head, "/explicit", ()).on(&router);
+    assert_status!(&conn, 200);
+    assert_headers!(&mut conn, "x-head" => "handled", "content-length" => "0");
+
+    assert_not_handled!(TestConn::build(Method::Head, "/other", ()).on(&router));
+}
+
+#[test]
+fn when_head_handling_is_disabled() {
+    let router = Router::build(|mut router| {
+        router.set_head_handling(false);
+        router.get("/", "hello");
+    });
+    assert_not_handled!(TestConn::build(Method::Head, "/", ()).on(&router));
+}
@@ -65,3 +65,26 @@ fn nested_router() {
     assert_headers!(TestConn::build("options", "/nested/here", ()).on(&router), "allow" => "GET, POST");
     assert_headers!(TestConn::build("options", "*", ()).on(&router), "allow" => "DELETE, GET, PATCH, POST, PUT");
 }
+
+#[test]
+fn auto_options_after_routers_without_options_handling() {
+    let handler = (
+        Router::new().without_options_handling().get("/a", "ok"),
+        Router::new()
+            .without_options_handling()
+            .post("/a", "ok")
+            .put("/b", "ok"),
+        trillium::AutoOptions::new().with_method_not_allowed(),
+    );
+
+    let mut conn = TestConn::build("options", "/a", ()).on(&handler);
+    assert_status!(&conn, 200);
+    assert_headers!(&mut conn, "allow" => "GET, POST");
+
+    let mut conn = TestConn::build("delete", "/b", ()).on(&handler);
+    assert_status!(&conn, 405);
+    assert_headers!(&mut conn, "allow" => "PUT");
+
+    assert_ok!(get("/a").on(&handler), "ok");
+    assert_not_handled!(TestConn::build("options", "/c", ()).on(&handler));
+}
@@ -0,0 +1,25 @@
+use trillium::Conn;
+use trillium_router::*;
+use trillium_testing::prelude::*;
+
+fn prefix(conn: Conn) -> impl std::future::Future<Output = Conn> {
+    async move {
+        let prefix = conn.route_prefix().to_string();
+        conn.ok(prefix)
+    }
+}
+
+#[test]
+fn route_prefix_in_nested_routers() {
+    let router = Router::new()
+        .get("/", prefix)
+        .get(
+            "/api/*",
+            Router::new().get("/v1/*", Router::new().get("/users", prefix)),
+        )
+        .get("/pages/*", prefix);
+
+    assert_ok!(get("/").on(&router), "");
+    assert_ok!(get("/api/v1/users").on(&router), "/api/v1");
+    assert_ok!(get("/pages/a/b").on(&router), "/pages");
+}
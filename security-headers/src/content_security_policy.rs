@@ -0,0 +1,233 @@
+use std::fmt::{self, Display, Formatter};
+use trillium::{
+    async_trait, Conn, Handler,
+    KnownHeaderName::{ContentSecurityPolicy as CspHeader, ContentSecurityPolicyReportOnly},
+};
+
+/**
+A per-request random value for use in a `Content-Security-Policy`
+[nonce source](https://www.w3.org/TR/CSP3/#grammardef-nonce-source).
+
+The nonce is 128 bits from the operating system's cryptographically
+secure random number generator, base64-encoded. A new nonce is
+generated for each request by [`ContentSecurityPolicy`] and stored
+in the conn's state, where it can be retrieved with
+[`SecurityHeadersConnExt::csp_nonce`](crate::SecurityHeadersConnExt::csp_nonce)
+and rendered into the `nonce` attribute of inline `<script>` and
+`<style>` tags.
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    /// the number of random bytes in a nonce
+    pub const LEN: usize = 16;
+
+    /**
+    generates a new random nonce
+
+    # Panics
+
+    panics if the operating system's random number generator is unavailable
+    */
+    pub fn generate() -> Self {
+        let mut bytes = [0; Self::LEN];
+        getrandom::getrandom(&mut bytes).expect("unable to generate a random csp nonce");
+        Self(base64::encode(bytes))
+    }
+
+    /// borrow the base64-encoded nonce
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for CspNonce {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for CspNonce {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/**
+Trillium handler that sets a `Content-Security-Policy` header with a
+fresh [`CspNonce`] for every request.
+
+The nonce is generated when this handler runs, so it should be placed
+before any handlers that render inline scripts or styles. The header
+is applied when the response is sent, with `'nonce-...'` appended to
+each nonce directive. By default, the nonce directives are
+`script-src` and `style-src`. If the response already has a
+`Content-Security-Policy` header, it is left untouched.
+
+```
+use trillium::Conn;
+use trillium_security_headers::{ContentSecurityPolicy, SecurityHeadersConnExt};
+use trillium_testing::prelude::*;
+
+let handler = (
+    ContentSecurityPolicy::new()
+        .with_directive("default-src", "'self'")
+        .with_directive("script-src", "'self'"),
+    |conn: Conn| async move {
+        let nonce = conn.csp_nonce().unwrap().to_string();
+        conn.ok(format!(r#"<script nonce="{}">alert("hi")</script>"#, nonce))
+    },
+);
+
+let mut conn = get("/").on(&handler);
+let body = conn.take_response_body_string().unwrap();
+let nonce = body.split('"').nth(1).unwrap();
+let expected = format!(
+    "default-src 'self'; script-src 'self' 'nonce-{}'; style-src 'nonce-{}'",
+    nonce, nonce
+);
+assert_headers!(&conn, "content-security-policy" => (expected.as_str()));
+```
+*/
+#[derive(Debug, Clone)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, String)>,
+    nonce_directives: Vec<String>,
+    report_only: bool,
+}
+
+impl Default for ContentSecurityPolicy {
+    fn default() -> Self {
+        Self {
+            directives: vec![],
+            nonce_directives: vec!["script-src".into(), "style-src".into()],
+            report_only: false,
+        }
+    }
+}
+
+impl ContentSecurityPolicy {
+    /// constructs a new policy with no directives other than a nonce
+    /// for `script-src` and `style-src`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sets the sources for a directive, replacing any previous
+    /// sources for it. a nonce is still appended if this is a nonce
+    /// directive.
+    #[must_use]
+    pub fn with_directive(mut self, name: impl Into<String>, sources: impl Into<String>) -> Self {
+        let (name, sources) = (name.into(), sources.into());
+        match self
+            .directives
+            .iter_mut()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(&name))
+        {
+            Some((_, existing)) => *existing = sources,
+            None => self.directives.push((name, sources)),
+        }
+        self
+    }
+
+    /// appends the nonce to this directive
+    #[must_use]
+    pub fn with_nonce_directive(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if !self.is_nonce_directive(&name) {
+            self.nonce_directives.push(name);
+        }
+        self
+    }
+
+    /// does not append the nonce to this directive
+    #[must_use]
+    pub fn without_nonce_directive(mut self, name: &str) -> Self {
+        self.nonce_directives
+            .retain(|existing| !existing.eq_ignore_ascii_case(name));
+        self
+    }
+
+    /// sends the policy as `Content-Security-Policy-Report-Only`
+    /// instead of `Content-Security-Policy`
+    #[must_use]
+    pub fn with_report_only(mut self) -> Self {
+        self.report_only = true;
+        self
+    }
+
+    fn is_nonce_directive(&self, name: &str) -> bool {
+        self.nonce_directives
+            .iter()
+            .any(|existing| existing.eq_ignore_ascii_case(name))
+    }
+
+    /**
+    formats this policy as a header value with the provided nonce
+
+    ```
+    use trillium_security_headers::{ContentSecurityPolicy, CspNonce};
+    let nonce = CspNonce::generate();
+    let policy = ContentSecurityPolicy::new()
+        .with_directive("script-src", "'strict-dynamic'")
+        .without_nonce_directive("style-src")
+        .with_directive("object-src", "'none'");
+    assert_eq!(
+        policy.header_value(&nonce),
+        format!("script-src 'strict-dynamic' 'nonce-{}'; object-src 'none'", nonce)
+    );
+    ```
+    */
+    pub fn header_value(&self, nonce: &CspNonce) -> String {
+        let mut directives = self
+            .directives
+            .iter()
+            .map(|(name, sources)| {
+                if !self.is_nonce_directive(name) {
+                    format!("{} {}", name, sources)
+                } else if sources.is_empty() {
+                    format!("{} 'nonce-{}'", name, nonce)
+                } else {
+                    format!("{} {} 'nonce-{}'", name, sources, nonce)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for name in &self.nonce_directives {
+            if !self
+                .directives
+                .iter()
+                .any(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            {
+                directives.push(format!("{} 'nonce-{}'", name, nonce));
+            }
+        }
+
+        directives.join("; ")
+    }
+}
+
+#[async_trait]
+impl Handler for ContentSecurityPolicy {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        if conn.state::<CspNonce>().is_none() {
+            conn.set_state(CspNonce::generate());
+        }
+        conn
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        if let Some(nonce) = conn.state::<CspNonce>() {
+            let header_value = self.header_value(nonce);
+            let header_name = if self.report_only {
+                ContentSecurityPolicyReportOnly
+            } else {
+                CspHeader
+            };
+            conn.response_headers_mut()
+                .try_insert(header_name, header_value);
+        }
+        conn
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+/**
+A `Strict-Transport-Security` policy.
+
+```
+use std::time::Duration;
+use trillium_security_headers::Hsts;
+
+let hsts = Hsts::new(Duration::from_secs(63072000))
+    .with_include_subdomains()
+    .with_preload();
+assert_eq!(
+    hsts.to_string(),
+    "max-age=63072000; includeSubDomains; preload"
+);
+assert!(hsts.preload_problems().is_empty());
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hsts {
+    max_age: Duration,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+/**
+A reason that an [`Hsts`] policy with the `preload` directive would
+not be accepted by browser preload lists, as described at
+<https://hstspreload.org/#deployment-recommendations>
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PreloadProblem {
+    /// the max-age is shorter than [`Hsts::PRELOAD_MINIMUM_MAX_AGE`]
+    MaxAgeTooShort,
+
+    /// the policy does not include the `includeSubDomains` directive
+    MissingIncludeSubdomains,
+}
+
+impl Display for PreloadProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PreloadProblem::MaxAgeTooShort => write!(
+                f,
+                "max-age must be at least {} seconds",
+                Hsts::PRELOAD_MINIMUM_MAX_AGE.as_secs()
+            ),
+            PreloadProblem::MissingIncludeSubdomains => {
+                f.write_str("the includeSubDomains directive must be specified")
+            }
+        }
+    }
+}
+
+impl Hsts {
+    /// the shortest max-age accepted by browser preload lists, one year
+    pub const PRELOAD_MINIMUM_MAX_AGE: Duration = Duration::from_secs(31_536_000);
+
+    /// constructs a new policy with the provided max-age, without
+    /// `includeSubDomains` or `preload`
+    pub const fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            include_subdomains: false,
+            preload: false,
+        }
+    }
+
+    /// chainable setter to add the `includeSubDomains` directive
+    #[must_use]
+    pub const fn with_include_subdomains(mut self) -> Self {
+        self.include_subdomains = true;
+        self
+    }
+
+    /// chainable setter to add the `preload` directive. see
+    /// [`Hsts::preload_problems`]
+    #[must_use]
+    pub const fn with_preload(mut self) -> Self {
+        self.preload = true;
+        self
+    }
+
+    /// the max-age of this policy
+    pub const fn max_age(&self) -> Duration {
+        self.max_age
+    }
+
+    /// whether this policy includes the `includeSubDomains` directive
+    pub const fn include_subdomains(&self) -> bool {
+        self.include_subdomains
+    }
+
+    /// whether this policy includes the `preload` directive
+    pub const fn preload(&self) -> bool {
+        self.preload
+    }
+
+    /**
+    Returns the reasons this policy would be rejected by browser
+    preload lists. This is always empty for a policy that does not
+    include the `preload` directive.
+
+    ```
+    use std::time::Duration;
+    use trillium_security_headers::{Hsts, PreloadProblem};
+
+    let hsts = Hsts::new(Duration::from_secs(300)).with_preload();
+    assert_eq!(
+        hsts.preload_problems(),
+        vec![
+            PreloadProblem::MaxAgeTooShort,
+            PreloadProblem::MissingIncludeSubdomains
+        ]
+    );
+
+    assert!(Hsts::new(Duration::from_secs(300)).preload_problems().is_empty());
+    ```
+    */
+    pub fn preload_problems(&self) -> Vec<PreloadProblem> {
+        let mut problems = vec![];
+        if self.preload {
+            if self.max_age < Self::PRELOAD_MINIMUM_MAX_AGE {
+                problems.push(PreloadProblem::MaxAgeTooShort);
+            }
+
+            if !self.include_subdomains {
+                problems.push(PreloadProblem::MissingIncludeSubdomains);
+            }
+        }
+        problems
+    }
+
+    /// logs each of [`Hsts::preload_problems`] at the provided level
+    pub(crate) fn log_preload_problems(&self, level: log::Level) {
+        for problem in self.preload_problems() {
+            log::log!(
+                level,
+                "Strict-Transport-Security header `{}` will not be accepted for preloading: {}",
+                self,
+                problem
+            );
+        }
+    }
+}
+
+impl Display for Hsts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "max-age={}", self.max_age.as_secs())?;
+        if self.include_subdomains {
+            f.write_str("; includeSubDomains")?;
+        }
+        if self.preload {
+            f.write_str("; preload")?;
+        }
+        Ok(())
+    }
+}
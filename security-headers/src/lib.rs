@@ -0,0 +1,211 @@
+/*!
+# Trillium handler for common security headers
+
+This crate provides a [`SecurityHeaders`] handler that applies a
+conservative set of response headers to every response, an [`Hsts`]
+type for building `Strict-Transport-Security` policies, and a
+[`SecurityHeadersConnExt`] for setting those headers on an individual
+[`trillium::Conn`]. For a `Content-Security-Policy` with a fresh nonce
+for each request, see [`ContentSecurityPolicy`].
+
+```
+use std::time::Duration;
+use trillium_security_headers::{Hsts, SecurityHeaders};
+use trillium_testing::prelude::*;
+
+let handler = (
+    SecurityHeaders::new().with_hsts(Hsts::new(Duration::from_secs(31536000))),
+    "ok",
+);
+
+assert_ok!(
+    get("/").secure().on(&handler),
+    "ok",
+    "x-content-type-options" => "nosniff",
+    "x-frame-options" => "SAMEORIGIN",
+    "referrer-policy" => "strict-origin-when-cross-origin",
+    "strict-transport-security" => "max-age=31536000"
+);
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod content_security_policy;
+pub use content_security_policy::{ContentSecurityPolicy, CspNonce};
+
+mod hsts;
+pub use hsts::{Hsts, PreloadProblem};
+
+mod security_headers_conn_ext;
+pub use security_headers_conn_ext::SecurityHeadersConnExt;
+
+use trillium::{
+    async_trait, Conn, Handler, HeaderName, HeaderValues, Headers,
+    KnownHeaderName::{ReferrerPolicy, StrictTransportSecurity},
+};
+
+/**
+Trillium handler that applies security headers to every response.
+
+Each header is only applied if the response does not already contain
+a header of the same name, so individual handlers can override any of
+these defaults.
+
+By default, the following headers are applied:
+
+* `X-Content-Type-Options: nosniff`
+* `X-Frame-Options: SAMEORIGIN`
+* `Referrer-Policy: strict-origin-when-cross-origin`
+
+`Strict-Transport-Security` is opt-in with
+[`SecurityHeaders::with_hsts`], and per [rfc6797 §7.2][rfc] is only
+sent on conns that are [secure](trillium::Conn::is_secure).
+
+[rfc]: https://datatracker.ietf.org/doc/html/rfc6797#section-7.2
+*/
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: Headers,
+    hsts: Option<Hsts>,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        let mut headers = Headers::new();
+        headers.insert("x-content-type-options", "nosniff");
+        headers.insert("x-frame-options", "SAMEORIGIN");
+        headers.insert(ReferrerPolicy, "strict-origin-when-cross-origin");
+        Self {
+            headers,
+            hsts: None,
+        }
+    }
+}
+
+impl SecurityHeaders {
+    /// constructs a new SecurityHeaders handler with the default headers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    chainable setter to apply a `Strict-Transport-Security` header to
+    secure conns. if the policy includes `preload` but does not meet
+    browser preload list requirements, a warning is logged for each
+    unmet requirement.
+
+    ```
+    use std::time::Duration;
+    use trillium_security_headers::{Hsts, SecurityHeaders};
+    use trillium_testing::prelude::*;
+
+    let hsts = Hsts::new(Duration::from_secs(31536000))
+        .with_include_subdomains()
+        .with_preload();
+    let handler = (SecurityHeaders::new().with_hsts(hsts), "ok");
+
+    assert_ok!(
+        get("/").secure().on(&handler),
+        "ok",
+        "strict-transport-security" => "max-age=31536000; includeSubDomains; preload"
+    );
+
+    assert_headers!(
+        get("/").on(&handler),
+        "strict-transport-security" => None
+    );
+    ```
+    */
+    #[must_use]
+    pub fn with_hsts(mut self, hsts: Hsts) -> Self {
+        hsts.log_preload_problems(log::Level::Warn);
+        self.hsts = Some(hsts);
+        self
+    }
+
+    /**
+    chainable setter to apply an additional header, or to replace
+    one of the defaults
+
+    ```
+    use trillium_security_headers::SecurityHeaders;
+    use trillium_testing::prelude::*;
+
+    let handler = (
+        SecurityHeaders::new()
+            .with_header("x-frame-options", "DENY")
+            .with_header("cross-origin-opener-policy", "same-origin"),
+        "ok",
+    );
+
+    assert_ok!(
+        get("/").on(&handler),
+        "ok",
+        "x-frame-options" => "DENY",
+        "cross-origin-opener-policy" => "same-origin"
+    );
+    ```
+    */
+    #[must_use]
+    pub fn with_header(
+        mut self,
+        name: impl Into<HeaderName<'static>>,
+        value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /**
+    chainable setter to stop applying one of the default headers
+
+    ```
+    use trillium_security_headers::SecurityHeaders;
+    use trillium_testing::prelude::*;
+
+    let handler = (SecurityHeaders::new().without_header("x-frame-options"), "ok");
+    assert_headers!(get("/").on(&handler), "x-frame-options" => None);
+    ```
+    */
+    #[must_use]
+    pub fn without_header(mut self, name: impl Into<HeaderName<'static>>) -> Self {
+        self.headers.remove(name);
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for SecurityHeaders {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        let secure = conn.is_secure();
+        let response_headers = conn.response_headers_mut();
+        for (name, values) in self.headers.iter() {
+            response_headers.try_insert(name.into_owned(), values.clone());
+        }
+
+        if let Some(hsts) = &self.hsts {
+            if secure {
+                response_headers.try_insert(StrictTransportSecurity, hsts.to_string());
+            }
+        }
+
+        conn
+    }
+}
+
+/// Alias for [`SecurityHeaders::new`]
+pub fn security_headers() -> SecurityHeaders {
+    SecurityHeaders::new()
+}
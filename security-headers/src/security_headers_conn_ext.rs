@@ -0,0 +1,68 @@
+use crate::{CspNonce, Hsts};
+use std::time::Duration;
+use trillium::{Conn, KnownHeaderName::StrictTransportSecurity};
+
+/// Extension trait for setting security headers on a [`trillium::Conn`]
+pub trait SecurityHeadersConnExt {
+    /**
+    sets a `Strict-Transport-Security` response header, replacing any
+    previously set value. if `preload` is requested but the policy
+    does not meet browser preload list requirements, each unmet
+    requirement is logged at debug level, as this runs on every
+    request. use [`Hsts::preload_problems`](crate::Hsts::preload_problems)
+    to check a policy once instead.
+
+    ```
+    use std::time::Duration;
+    use trillium_security_headers::SecurityHeadersConnExt;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: trillium::Conn| async move {
+        conn.with_hsts(Duration::from_secs(31536000), true, true)
+            .ok("ok")
+    };
+
+    assert_ok!(
+        get("/").secure().on(&handler),
+        "ok",
+        "strict-transport-security" => "max-age=31536000; includeSubDomains; preload"
+    );
+    ```
+    */
+    fn with_hsts(self, max_age: Duration, include_subdomains: bool, preload: bool) -> Self;
+
+    /// sets a `Strict-Transport-Security` response header from an
+    /// [`Hsts`], replacing any previously set value. see
+    /// [`SecurityHeadersConnExt::with_hsts`]
+    fn set_hsts(&mut self, hsts: Hsts);
+
+    /// returns the [`CspNonce`] for this request, if a
+    /// [`ContentSecurityPolicy`](crate::ContentSecurityPolicy) handler
+    /// has run. see [`ContentSecurityPolicy`](crate::ContentSecurityPolicy)
+    /// for an example.
+    fn csp_nonce(&self) -> Option<&str>;
+}
+
+impl SecurityHeadersConnExt for Conn {
+    fn with_hsts(mut self, max_age: Duration, include_subdomains: bool, preload: bool) -> Self {
+        let mut hsts = Hsts::new(max_age);
+        if include_subdomains {
+            hsts = hsts.with_include_subdomains();
+        }
+        if preload {
+            hsts = hsts.with_preload();
+        }
+        self.set_hsts(hsts);
+        self
+    }
+
+    fn set_hsts(&mut self, hsts: Hsts) {
+        hsts.log_preload_problems(log::Level::Debug);
+        self.response_headers_mut()
+            .insert(StrictTransportSecurity, hsts.to_string());
+    }
+
+    fn csp_nonce(&self) -> Option<&str> {
+        self.state::<CspNonce>().map(CspNonce::as_str)
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+use trillium::{Conn, KnownHeaderName};
+use trillium_security_headers::*;
+use trillium_testing::prelude::*;
+
+#[test]
+fn defaults_do_not_override_handler_headers() {
+    let app = (SecurityHeaders::new(), |conn: Conn| async move {
+        conn.with_header("x-frame-options", "DENY")
+            .with_header(KnownHeaderName::ReferrerPolicy, "no-referrer")
+            .ok("ok")
+    });
+
+    assert_ok!(
+        get("/").on(&app),
+        "ok",
+        "x-frame-options" => "DENY",
+        "referrer-policy" => "no-referrer",
+        "x-content-type-options" => "nosniff"
+    );
+}
+
+#[test]
+fn applies_to_unhandled_conns() {
+    let app = security_headers();
+    assert_headers!(
+        &mut get("/").on(&app),
+        "x-content-type-options" => "nosniff"
+    );
+}
+
+#[test]
+fn hsts_only_on_secure_conns() {
+    let app = (
+        SecurityHeaders::new().with_hsts(Hsts::new(Duration::from_secs(600))),
+        "ok",
+    );
+
+    assert_headers!(get("/").on(&app), "strict-transport-security" => None);
+    assert_headers!(
+        get("/").secure().on(&app),
+        "strict-transport-security" => "max-age=600"
+    );
+}
+
+#[test]
+fn conn_hsts_overrides_handler_hsts() {
+    let app = (
+        SecurityHeaders::new().with_hsts(Hsts::new(Duration::from_secs(600))),
+        |conn: Conn| async move {
+            conn.with_hsts(Duration::from_secs(0), false, false)
+                .ok("ok")
+        },
+    );
+
+    assert_ok!(
+        get("/").secure().on(&app),
+        "ok",
+        "strict-transport-security" => "max-age=0"
+    );
+}
+
+#[test]
+fn preload_problems() {
+    let year = Hsts::PRELOAD_MINIMUM_MAX_AGE;
+
+    assert!(Hsts::new(year)
+        .with_include_subdomains()
+        .with_preload()
+        .preload_problems()
+        .is_empty());
+
+    assert_eq!(
+        Hsts::new(year).with_preload().preload_problems(),
+        vec![PreloadProblem::MissingIncludeSubdomains]
+    );
+
+    assert_eq!(
+        Hsts::new(year - Duration::from_secs(1))
+            .with_include_subdomains()
+            .with_preload()
+            .preload_problems(),
+        vec![PreloadProblem::MaxAgeTooShort]
+    );
+
+    assert!(Hsts::new(Duration::from_secs(1))
+        .preload_problems()
+        .is_empty());
+}
+
+#[test]
+fn csp_nonce_is_fresh_for_each_request() {
+    let app = (ContentSecurityPolicy::new(), |conn: Conn| async move {
+        let nonce = conn.csp_nonce().unwrap().to_string();
+        conn.ok(nonce)
+    });
+
+    let mut first = get("/").on(&app);
+    let mut second = get("/").on(&app);
+    let first_nonce = first.take_response_body_string().unwrap();
+    let second_nonce = second.take_response_body_string().unwrap();
+
+    assert_ne!(first_nonce, second_nonce);
+    assert_eq!(base64::decode(&first_nonce).unwrap().len(), CspNonce::LEN);
+    let expected = format!(
+        "script-src 'nonce-{}'; style-src 'nonce-{}'",
+        first_nonce, first_nonce
+    );
+    assert_headers!(&first, "content-security-policy" => (expected.as_str()));
+}
+
+#[test]
+fn csp_report_only_and_existing_headers() {
+    let app = (ContentSecurityPolicy::new().with_report_only(), "ok");
+    let conn = get("/").on(&app);
+    let response_headers = conn.inner().response_headers();
+    assert!(response_headers.has_header("content-security-policy-report-only"));
+    assert!(!response_headers.has_header("content-security-policy"));
+
+    let app = (ContentSecurityPolicy::new(), |conn: Conn| async move {
+        conn.with_header(KnownHeaderName::ContentSecurityPolicy, "default-src 'none'")
+            .ok("ok")
+    });
+    assert_headers!(get("/").on(&app), "content-security-policy" => "default-src 'none'");
+
+    assert!(get("/").on(&"ok").csp_nonce().is_none());
+}
@@ -1,7 +1,7 @@
 use crate::{CloneCounter, Server};
 use std::marker::PhantomData;
 use trillium::Handler;
-use trillium_http::Stopper;
+use trillium_http::{ConnConfig, Stopper};
 use trillium_tls_common::Acceptor;
 
 /**
@@ -63,6 +63,7 @@ pub struct Config<ServerType: ?Sized, AcceptorType> {
     pub(crate) counter: CloneCounter,
     pub(crate) register_signals: bool,
     pub(crate) max_connections: Option<usize>,
+    pub(crate) conn_config: ConnConfig,
     server: PhantomData<ServerType>,
 }
 
@@ -137,6 +138,7 @@ where
             counter: self.counter,
             register_signals: self.register_signals,
             max_connections: self.max_connections,
+            conn_config: self.conn_config,
         }
     }
 
@@ -155,6 +157,14 @@ where
         self.max_connections = max_connections;
         self
     }
+
+    /// Configures the limits applied while reading each request
+    /// head, such as the maximum number of headers. See
+    /// [`ConnConfig`] for the defaults.
+    pub fn with_conn_config(mut self, conn_config: ConnConfig) -> Self {
+        self.conn_config = conn_config;
+        self
+    }
 }
 
 impl<ServerType> Config<ServerType, ()> {
@@ -176,6 +186,7 @@ impl<ServerType: ?Sized, AcceptorType: Clone> Clone for Config<ServerType, Accep
             counter: self.counter.clone(),
             register_signals: self.register_signals,
             max_connections: self.max_connections,
+            conn_config: self.conn_config,
         }
     }
 }
@@ -205,6 +216,7 @@ impl<ServerType> Default for Config<ServerType, ()> {
             counter: CloneCounter::new(),
             register_signals: cfg!(unix),
             max_connections,
+            conn_config: ConnConfig::default(),
         }
     }
 }
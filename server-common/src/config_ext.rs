@@ -160,6 +160,7 @@ where
 
         ServerType::set_nodelay(&mut stream, self.nodelay);
 
+        let peer_addr = ServerType::peer_addr(&stream);
         let peer_ip = ServerType::peer_ip(&stream);
 
         let stream = match self.acceptor.accept(stream).await {
@@ -170,13 +171,27 @@ where
             }
         };
 
-        let result = HttpConn::map(stream, self.stopper.clone(), |mut conn| async {
-            conn.set_peer_ip(peer_ip);
-            let conn = handler.run(conn.into()).await;
-            let conn = handler.before_send(conn).await;
+        let conn_config = if self.conn_config.sleep().is_some() {
+            self.conn_config
+        } else {
+            self.conn_config.with_sleep(ServerType::sleep)
+        };
+
+        let result = HttpConn::map_with_config(
+            conn_config,
+            stream,
+            self.stopper.clone(),
+            |mut conn| async {
+                match peer_addr {
+                    Some(peer_addr) => conn.set_peer_addr(Some(peer_addr)),
+                    None => conn.set_peer_ip(peer_ip),
+                }
+                let conn = handler.run(conn.into()).await;
+                let conn = handler.before_send(conn).await;
 
-            conn.into_inner()
-        })
+                conn.into_inner()
+            },
+        )
         .await;
 
         match result {
@@ -3,9 +3,10 @@ use futures_lite::{AsyncRead, AsyncWrite};
 use std::{
     future::{ready, Future},
     io::Result,
-    net::IpAddr,
+    net::{IpAddr, SocketAddr},
     pin::Pin,
     sync::Arc,
+    time::Duration,
 };
 use trillium::{Handler, Info};
 use trillium_http::Stopper;
@@ -33,14 +34,21 @@ pub trait Server: Send + 'static {
         listener: &mut Self::Listener,
     ) -> Pin<Box<dyn Future<Output = Result<Self::Transport>> + Send + '_>>;
 
-    /// Optional method to return a peer ip address from a
+    /// Optional method to return a peer socket address from a
     /// `Self::Transport`, if applicable. The default implementation
     /// returns None.
     #[allow(unused_variables)]
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
         None
     }
 
+    /// Optional method to return a peer ip address from a
+    /// `Self::Transport`, if applicable. The default implementation
+    /// returns the ip of [`Server::peer_addr`].
+    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
+        Self::peer_addr(transport).map(|socket_addr| socket_addr.ip())
+    }
+
     /// Optional method to set tcp nodelay to the provided value on
     /// the Self::Transport
     #[allow(unused_variables)]
@@ -134,6 +142,11 @@ pub trait Server: Send + 'static {
     /// Runtime implementation hook for blocking on a top level future.
     fn block_on(fut: impl Future<Output = ()> + 'static);
 
+    /// Runtime implementation hook for a timer that resolves after
+    /// the provided duration. This is used to enforce the timeouts
+    /// in [`ConnConfig`](trillium_http::ConnConfig).
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
     /// Run a trillium application from a sync context
     fn run<A, H>(config: Config<Self, A>, handler: H)
     where
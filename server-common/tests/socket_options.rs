@@ -0,0 +1,98 @@
+use std::{
+    future::{pending, Future},
+    io::Result,
+    pin::Pin,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use trillium::Info;
+use trillium_server_common::{Acceptor, Config, ConfigExt, Server, Stopper};
+use trillium_smol::{async_global_executor, async_io};
+use trillium_testing::TestTransport;
+
+const CONNECTIONS: usize = 3;
+static NODELAY: AtomicUsize = AtomicUsize::new(0);
+
+/// a listener that accepts a fixed number of test transports and then
+/// stops the server
+struct RecordingListener {
+    transports: Vec<TestTransport>,
+    stopper: Stopper,
+}
+
+/// a server that records the socket options that are set on each
+/// transport instead of applying them
+struct RecordingServer;
+
+impl Server for RecordingServer {
+    type Listener = RecordingListener;
+    type Transport = TestTransport;
+    const DESCRIPTION: &'static str = "recording server";
+
+    fn build_listener<A>(config: &Config<Self, A>) -> Self::Listener
+    where
+        A: Acceptor<Self::Transport>,
+    {
+        let transports = (0..CONNECTIONS)
+            .map(|_| {
+                let (mut client, server) = TestTransport::new();
+                client.close();
+                server
+            })
+            .collect();
+
+        RecordingListener {
+            transports,
+            stopper: config.stopper(),
+        }
+    }
+
+    fn accept(
+        listener: &mut Self::Listener,
+    ) -> Pin<Box<dyn Future<Output = Result<Self::Transport>> + Send + '_>> {
+        Box::pin(async move {
+            match listener.transports.pop() {
+                Some(transport) => Ok(transport),
+                None => {
+                    listener.stopper.stop();
+                    pending().await
+                }
+            }
+        })
+    }
+
+    fn info(_listener: &Self::Listener) -> Info {
+        "recording listener".into()
+    }
+
+    fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
+        async_global_executor::spawn(fut).detach();
+    }
+
+    fn block_on(fut: impl Future<Output = ()> + 'static) {
+        async_global_executor::block_on(fut);
+    }
+
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async move {
+            async_io::Timer::after(duration).await;
+        })
+    }
+
+    fn set_nodelay(_transport: &mut Self::Transport, nodelay: bool) {
+        if nodelay {
+            NODELAY.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn socket_options_are_applied_to_each_transport() {
+    Config::<RecordingServer, ()>::new()
+        .with_nodelay()
+        .with_max_connections(None)
+        .without_signals()
+        .run(());
+
+    assert_eq!(NODELAY.load(Ordering::SeqCst), CONNECTIONS);
+}
@@ -3,7 +3,7 @@ use async_global_executor::{block_on, spawn};
 use async_net::unix::{UnixListener, UnixStream};
 use async_net::{TcpListener, TcpStream};
 use futures_lite::prelude::*;
-use std::{convert::TryInto, env, io::Result, net::IpAddr, pin::Pin};
+use std::{convert::TryInto, env, io::Result, net::SocketAddr, pin::Pin, time::Duration};
 use trillium::{log_error, Info};
 use trillium_server_common::Server;
 #[cfg(unix)]
@@ -58,12 +58,9 @@ impl Server for Smol {
         })
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
         match transport {
-            Tcp(transport) => transport
-                .peer_addr()
-                .ok()
-                .map(|socket_addr| socket_addr.ip()),
+            Tcp(transport) => transport.peer_addr().ok(),
 
             Unix(_) => None,
         }
@@ -92,6 +89,12 @@ impl Server for Smol {
         block_on(fut)
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async move {
+            async_io::Timer::after(duration).await;
+        })
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         if let Tcp(transport) = transport {
             log_error!(transport.set_nodelay(nodelay));
@@ -130,11 +133,8 @@ impl Server for Smol {
         Box::pin(async move { listener.accept().await.map(|(t, _)| t) })
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
-        transport
-            .peer_addr()
-            .ok()
-            .map(|socket_addr| socket_addr.ip())
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
+        transport.peer_addr().ok()
     }
 
     fn listener_from_tcp(tcp: std::net::TcpListener) -> Self::Listener {
@@ -153,6 +153,12 @@ impl Server for Smol {
         block_on(fut)
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(async move {
+            async_io::Timer::after(duration).await;
+        })
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         log_error!(transport.set_nodelay(nodelay));
     }
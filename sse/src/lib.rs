@@ -11,6 +11,10 @@ Often, you will want this stream to be something like a channel, but
 the specifics of that are dependent on the event fanout
 characteristics of your application.
 
+For long-lived streams that pass through proxies, use
+[`with_sse_stream_and_heartbeat`](crate::SseConnExt::with_sse_stream_and_heartbeat)
+to send a [`Heartbeat`] comment while the stream is idle.
+
 This crate implements [`Eventable`] for an [`Event`] type that you can
 use in your application, for `String`, and for `&'static str`. You can
 also implement [`Eventable`] for any type in your application.
@@ -65,7 +69,8 @@ fn main() {
 )]
 #![warn(missing_docs)]
 
-use futures_lite::{stream::Stream, AsyncRead};
+use async_io::Timer;
+use futures_lite::{stream::Stream, AsyncRead, Future};
 use std::{
     borrow::Cow,
     fmt::Write,
@@ -73,12 +78,80 @@ use std::{
     marker::PhantomData,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use trillium::{Body, Conn, KnownHeaderName, Status};
 
+/**
+# A periodic keepalive for server sent event streams
+
+Proxies and load balancers often close connections that have been
+idle for some time. When a stream has not produced an event within
+the interval, a heartbeat sends an sse comment, which clients ignore,
+to keep the connection active. Heartbeats are only written between
+events, never within one. See
+[`SseConnExt::with_sse_stream_and_heartbeat`].
+
+```
+use std::time::Duration;
+use trillium_sse::Heartbeat;
+let heartbeat = Heartbeat::new(Duration::from_secs(15)).with_comment("ping");
+assert_eq!(heartbeat.interval(), Duration::from_secs(15));
+assert_eq!(heartbeat.comment(), "ping");
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    interval: Duration,
+    comment: Cow<'static, str>,
+}
+
+impl Heartbeat {
+    /// constructs a new heartbeat that is sent after `interval` without
+    /// an event. the default comment is `keepalive`
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            comment: Cow::Borrowed("keepalive"),
+        }
+    }
+
+    /// chainable setter for the comment text sent as the heartbeat.
+    /// each line is sent as a separate sse comment line
+    pub fn with_comment(mut self, comment: impl Into<Cow<'static, str>>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// the idle interval after which a heartbeat is sent
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// the comment text sent as the heartbeat
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut output = String::new();
+        for line in self.comment.lines() {
+            writeln!(&mut output, ": {}", line).unwrap();
+        }
+
+        if output.is_empty() {
+            output.push_str(":\n");
+        }
+
+        writeln!(output).unwrap();
+        output.into_bytes()
+    }
+}
+
 struct SseBody<S, E> {
     stream: S,
     buffer: Vec<u8>,
+    heartbeat: Option<(Vec<u8>, Duration, Timer)>,
     event: PhantomData<E>,
 }
 
@@ -91,9 +164,30 @@ where
         Self {
             stream,
             buffer: Vec::new(),
+            heartbeat: None,
             event: PhantomData::default(),
         }
     }
+
+    fn with_heartbeat(mut self, heartbeat: &Heartbeat) -> Self {
+        self.heartbeat = Some((
+            heartbeat.encode(),
+            heartbeat.interval,
+            Timer::after(heartbeat.interval),
+        ));
+        self
+    }
+}
+
+/// copies as much of `data` as fits into `buf`, keeping the rest in
+/// `buffer` to be read before anything else
+fn write_buffered(data: &[u8], buf: &mut [u8], buffer: &mut Vec<u8>) -> usize {
+    let writable_len = data.len().min(buf.len());
+    buf[0..writable_len].copy_from_slice(&data[0..writable_len]);
+    if writable_len < data.len() {
+        buffer.extend_from_slice(&data[writable_len..]);
+    }
+    writable_len
 }
 
 fn encode(event: impl Eventable) -> String {
@@ -125,8 +219,15 @@ where
         cx: &mut Context<'_>,
         buf: &mut [u8],
     ) -> Poll<io::Result<usize>> {
-        let Self { buffer, stream, .. } = self.get_mut();
+        let Self {
+            buffer,
+            stream,
+            heartbeat,
+            ..
+        } = self.get_mut();
 
+        // the remainder of a partially written event or heartbeat is
+        // always sent first, so that they are never interleaved
         let buffer_read = buffer.len().min(buf.len());
         if buffer_read > 0 {
             buf[0..buffer_read].copy_from_slice(&buffer[0..buffer_read]);
@@ -135,15 +236,25 @@ where
         }
 
         match Pin::new(stream).poll_next(cx) {
-            Poll::Pending => Poll::Pending,
+            Poll::Pending => {
+                let Some((data, interval, timer)) = heartbeat else {
+                    return Poll::Pending;
+                };
+
+                if Pin::new(&mut *timer).poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                timer.set_after(*interval);
+                Poll::Ready(Ok(write_buffered(data, buf, buffer)))
+            }
+
             Poll::Ready(Some(item)) => {
-                let data = encode(item).into_bytes();
-                let writable_len = data.len().min(buf.len());
-                buf[0..writable_len].copy_from_slice(&data[0..writable_len]);
-                if writable_len < data.len() {
-                    buffer.extend_from_slice(&data[writable_len..]);
+                if let Some((_, interval, timer)) = heartbeat {
+                    timer.set_after(*interval);
                 }
-                Poll::Ready(Ok(writable_len))
+                let data = encode(item).into_bytes();
+                Poll::Ready(Ok(write_buffered(&data, buf, buffer)))
             }
 
             Poll::Ready(None) => Poll::Ready(Ok(0)),
@@ -177,6 +288,30 @@ pub trait SseConnExt {
     where
         S: Stream<Item = E> + Unpin + Send + Sync + 'static,
         E: Eventable;
+
+    /**
+    like [`SseConnExt::with_sse_stream`], but also sends a
+    [`Heartbeat`] comment whenever the stream has not produced an
+    event within the heartbeat's interval, to keep intermediaries
+    from closing an idle connection
+
+    ```
+    use std::time::Duration;
+    use trillium::Conn;
+    use trillium_sse::{Heartbeat, SseConnExt};
+
+    let handler = |conn: Conn| async move {
+        let events = futures_lite::stream::iter(["hello"]);
+        conn.with_sse_stream_and_heartbeat(events, Heartbeat::new(Duration::from_secs(30)))
+    };
+    # use trillium_testing::prelude::*;
+    assert_ok!(get("/").on(&handler), "data: hello\n\n");
+    ```
+    */
+    fn with_sse_stream_and_heartbeat<S, E>(self, sse_stream: S, heartbeat: Heartbeat) -> Self
+    where
+        S: Stream<Item = E> + Unpin + Send + Sync + 'static,
+        E: Eventable;
 }
 
 impl SseConnExt for Conn {
@@ -186,12 +321,30 @@ impl SseConnExt for Conn {
         E: Eventable,
     {
         let body = SseBody::new(self.inner().stopper().stop_stream(sse_stream));
-        self.with_header(KnownHeaderName::ContentType, "text/event-stream")
-            .with_header(KnownHeaderName::CacheControl, "no-cache")
-            .with_body(body)
-            .with_status(Status::Ok)
-            .halt()
+        with_sse_body(self, body)
     }
+
+    fn with_sse_stream_and_heartbeat<S, E>(self, sse_stream: S, heartbeat: Heartbeat) -> Self
+    where
+        S: Stream<Item = E> + Unpin + Send + Sync + 'static,
+        E: Eventable,
+    {
+        let body =
+            SseBody::new(self.inner().stopper().stop_stream(sse_stream)).with_heartbeat(&heartbeat);
+        with_sse_body(self, body)
+    }
+}
+
+fn with_sse_body<S, E>(conn: Conn, body: SseBody<S, E>) -> Conn
+where
+    S: Stream<Item = E> + Unpin + Send + Sync + 'static,
+    E: Eventable,
+{
+    conn.with_header(KnownHeaderName::ContentType, "text/event-stream")
+        .with_header(KnownHeaderName::CacheControl, "no-cache")
+        .with_body(body)
+        .with_status(Status::Ok)
+        .halt()
 }
 
 /**
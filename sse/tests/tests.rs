@@ -0,0 +1,80 @@
+use async_io::Timer;
+use futures_lite::{stream, AsyncReadExt, Stream};
+use std::{pin::Pin, time::Duration};
+use trillium::Conn;
+use trillium_sse::{Heartbeat, SseConnExt};
+use trillium_testing::{block_on, prelude::*, TestConn};
+
+type Events = Pin<Box<dyn Stream<Item = &'static str> + Send + Sync>>;
+
+/// yields each event after a delay, and then ends
+fn delayed(events: Vec<(u64, &'static str)>) -> Events {
+    Box::pin(stream::unfold(
+        events.into_iter(),
+        |mut events| async move {
+            let (delay, event) = events.next()?;
+            Timer::after(Duration::from_millis(delay)).await;
+            Some((event, events))
+        },
+    ))
+}
+
+async fn read_in_small_pieces(mut conn: TestConn) -> String {
+    let mut body = conn.take_response_body().unwrap().into_reader();
+    let mut output = vec![];
+    let mut bytes = [0; 3];
+    loop {
+        match body.read(&mut bytes).await.unwrap() {
+            0 => break String::from_utf8(output).unwrap(),
+            n => output.extend_from_slice(&bytes[..n]),
+        }
+        Timer::after(Duration::from_millis(2)).await;
+    }
+}
+
+#[test]
+fn heartbeats_are_sent_when_idle() {
+    let handler = |conn: Conn| async move {
+        conn.with_sse_stream_and_heartbeat(
+            delayed(vec![(0, "first"), (100, "second")]),
+            Heartbeat::new(Duration::from_millis(20)).with_comment("ping"),
+        )
+    };
+
+    let body = block_on(read_in_small_pieces(get("/").on(&handler)));
+    assert!(body.starts_with("data: first\n\n: ping\n\n"));
+    assert!(body.ends_with(": ping\n\ndata: second\n\n"));
+    for frame in body.split_terminator("\n\n") {
+        assert!(
+            ["data: first", "data: second", ": ping"].contains(&frame),
+            "unexpected frame {frame:?} in {body:?}"
+        );
+    }
+}
+
+#[test]
+fn heartbeats_do_not_interrupt_events() {
+    let event = "a long event that takes many small reads to send";
+    let handler = move |conn: Conn| async move {
+        conn.with_sse_stream_and_heartbeat(
+            delayed(vec![(0, event), (0, event)]),
+            Heartbeat::new(Duration::from_millis(1)),
+        )
+    };
+
+    let body = block_on(read_in_small_pieces(get("/").on(&handler)));
+    for frame in body.split_terminator("\n\n") {
+        assert!(
+            frame == ": keepalive" || frame == format!("data: {event}"),
+            "unexpected frame {frame:?} in {body:?}"
+        );
+    }
+    assert_eq!(body.matches(event).count(), 2);
+}
+
+#[test]
+fn without_heartbeat() {
+    let handler = |conn: Conn| async move { conn.with_sse_stream(delayed(vec![(30, "only")])) };
+    let body = block_on(read_in_small_pieces(get("/").on(&handler)));
+    assert_eq!(body, "data: only\n\n");
+}
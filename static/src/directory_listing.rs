@@ -0,0 +1,176 @@
+use crate::fs_shims::{fs, read_dir};
+use serde::Serialize;
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+use trillium::{
+    Conn,
+    KnownHeaderName::{self, ContentType},
+};
+
+/// The representation of a directory listing, negotiated from the
+/// request `Accept` header. Html is the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListingFormat {
+    Html,
+    Json,
+}
+
+impl ListingFormat {
+    fn negotiate(conn: &Conn) -> Self {
+        match conn.negotiate_content_type(&["text/html", "application/json"]) {
+            Some("application/json") => Self::Json,
+            _ => Self::Html,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+/// reads the entries of `dir`, omitting dotfiles and any entry that
+/// does not resolve to a path within `root`, such as a symlink that
+/// points elsewhere on the filesystem
+async fn entries(dir: &Path, root: &Path) -> Option<Vec<Entry>> {
+    let mut entries = vec![];
+    for path in read_dir(dir).await.ok()? {
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if !name.starts_with('.') => name.to_string(),
+            _ => continue,
+        };
+
+        let resolved: PathBuf = match fs::canonicalize(&path).await.map(Into::into) {
+            Ok(resolved) => resolved,
+            Err(_) => continue,
+        };
+
+        if !resolved.starts_with(root) {
+            log::trace!(
+                "omitting {:?} from listing, it resolves outside of {:?}",
+                path,
+                root
+            );
+            continue;
+        }
+
+        let is_dir = fs::metadata(&resolved)
+            .await
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or_default();
+
+        entries.push(Entry { name, is_dir });
+    }
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Some(entries)
+}
+
+/// builds a link to an entry that is relative to the current request
+/// path, so that listings continue to work within nested routers
+fn href(url_path: &str, entry: &Entry) -> String {
+    let mut href = if url_path.ends_with('/') {
+        String::new()
+    } else {
+        let last_segment = url_path.rsplit('/').next().unwrap_or_default();
+        format!("{}/", last_segment)
+    };
+
+    for byte in entry.name.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            href.push(byte as char);
+        } else {
+            write!(href, "%{:02X}", byte).unwrap();
+        }
+    }
+
+    if entry.is_dir {
+        href.push('/');
+    }
+
+    href
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render_html(url_path: &str, entries: &[Entry]) -> String {
+    let title = escape_html(url_path);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Index of {title}</title></head>\n<body>\n<h1>Index of {title}</h1>\n<ul>\n"
+    );
+
+    for entry in entries {
+        writeln!(
+            html,
+            "<li><a href=\"{}\">{}{}</a></li>",
+            escape_html(&href(url_path, entry)),
+            escape_html(&entry.name),
+            if entry.is_dir { "/" } else { "" }
+        )
+        .unwrap();
+    }
+
+    html.push_str("</ul>\n</body>\n</html>\n");
+    html
+}
+
+#[derive(Serialize)]
+struct JsonListing<'a> {
+    path: &'a str,
+    entries: Vec<JsonEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonEntry<'a> {
+    name: &'a str,
+    href: String,
+    #[serde(rename = "type")]
+    entry_type: &'static str,
+}
+
+fn render_json(url_path: &str, entries: &[Entry]) -> String {
+    let listing = JsonListing {
+        path: url_path,
+        entries: entries
+            .iter()
+            .map(|entry| JsonEntry {
+                name: &entry.name,
+                href: href(url_path, entry),
+                entry_type: if entry.is_dir { "directory" } else { "file" },
+            })
+            .collect(),
+    };
+
+    serde_json::to_string(&listing).expect("a directory listing is always serializable")
+}
+
+/// sends a listing of `dir`, which must already have been resolved
+/// to a canonical path within `root`
+pub(crate) async fn send(mut conn: Conn, dir: &Path, root: &Path) -> Conn {
+    let entries = trillium::conn_unwrap!(entries(dir, root).await, conn.with_status(404));
+    let url_path = conn.path();
+
+    let (body, content_type) = match ListingFormat::negotiate(&conn) {
+        ListingFormat::Html => (render_html(url_path, &entries), "text/html; charset=utf-8"),
+        ListingFormat::Json => (render_json(url_path, &entries), "application/json"),
+    };
+
+    conn.add_vary(KnownHeaderName::Accept.as_ref());
+    conn.with_header(ContentType, content_type).ok(body)
+}
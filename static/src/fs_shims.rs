@@ -19,3 +19,36 @@ will not require the use of cargo feature flags."
 );
     }
 }
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "smol")] {
+        pub(crate) async fn read_dir(path: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+            use futures_lite::StreamExt;
+            let mut entries = vec![];
+            let mut read_dir = fs::read_dir(path).await?;
+            while let Some(entry) = read_dir.next().await {
+                entries.push(entry?.path());
+            }
+            Ok(entries)
+        }
+    } else if #[cfg(feature = "tokio")] {
+        pub(crate) async fn read_dir(path: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+            let mut entries = vec![];
+            let mut read_dir = fs::read_dir(path).await?;
+            while let Some(entry) = read_dir.next_entry().await? {
+                entries.push(entry.path());
+            }
+            Ok(entries)
+        }
+    } else if #[cfg(feature = "async-std")] {
+        pub(crate) async fn read_dir(path: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+            use futures_lite::StreamExt;
+            let mut entries = vec![];
+            let mut read_dir = fs::read_dir(path).await?;
+            while let Some(entry) = read_dir.next().await {
+                entries.push(entry?.path().into());
+            }
+            Ok(entries)
+        }
+    }
+}
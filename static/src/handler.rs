@@ -1,10 +1,11 @@
 use crate::{
+    directory_listing,
     fs_shims::{fs, File},
     options::StaticOptions,
-    StaticConnExt,
+    precompressed, safe_join, StaticConnExt,
 };
 use std::path::{Path, PathBuf};
-use trillium::{async_trait, conn_unwrap, Conn, Handler};
+use trillium::{async_trait, Conn, Handler};
 
 /**
 trillium handler to serve static files from the filesystem
@@ -14,6 +15,7 @@ pub struct StaticFileHandler {
     fs_root: PathBuf,
     index_file: Option<String>,
     root_is_file: bool,
+    directory_listing: bool,
     options: StaticOptions,
 }
 
@@ -25,28 +27,18 @@ enum Record {
 
 impl StaticFileHandler {
     async fn resolve_fs_path(&self, url_path: &str) -> Option<PathBuf> {
-        let mut file_path = self.fs_root.clone();
         log::trace!(
             "attempting to resolve {} relative to {}",
             url_path,
-            file_path.to_str().unwrap()
+            self.fs_root.to_str().unwrap()
         );
-        for segment in Path::new(url_path) {
-            match segment.to_str() {
-                Some("/") => {}
-                Some(".") => {}
-                Some("..") => {
-                    file_path.pop();
-                }
-                _ => {
-                    file_path.push(segment);
-                }
-            };
-        }
 
-        if file_path.starts_with(&self.fs_root) {
-            fs::canonicalize(file_path).await.ok().map(Into::into)
+        let file_path = safe_join(&self.fs_root, url_path)?;
+        let canonical: PathBuf = fs::canonicalize(file_path).await.map(Into::into).ok()?;
+        if canonical.starts_with(&self.fs_root) {
+            Some(canonical)
         } else {
+            log::trace!("{} resolves outside of {:?}", url_path, self.fs_root);
             None
         }
     }
@@ -67,6 +59,14 @@ impl StaticFileHandler {
         }
     }
 
+    async fn send_precompressed(&self, conn: Conn, path: &Path) -> Result<Conn, Conn> {
+        if self.options.precompressed {
+            precompressed::send(conn, path, &self.options, Some(&self.fs_root)).await
+        } else {
+            Err(conn)
+        }
+    }
+
     /**
     builds a new StaticFileHandler
 
@@ -97,6 +97,7 @@ impl StaticFileHandler {
             fs_root,
             index_file: None,
             root_is_file: false,
+            directory_listing: false,
             options: StaticOptions::default(),
         }
     }
@@ -113,6 +114,47 @@ impl StaticFileHandler {
         self
     }
 
+    /**
+    serves a precompressed sibling of a requested file, such as
+    `app.js.br` or `app.js.gz` for `app.js`, if one exists and the
+    request's accept-encoding header allows it. brotli is preferred
+    over gzip when both are equally acceptable. The response has the
+    content-type of the original file, the content-length of the
+    compressed file, and a content-encoding header. This is disabled
+    by default.
+
+    ```
+    # #[cfg(not(unix))] fn main() {}
+    # #[cfg(unix)] fn main() {
+    use trillium_static::{StaticFileHandler, crate_relative_path};
+
+    let handler = StaticFileHandler::new(crate_relative_path!("examples/files"))
+        .with_precompressed();
+
+    use trillium_testing::prelude::*;
+    let conn = get("/compressed.txt")
+        .with_request_header("accept-encoding", "gzip")
+        .on(&handler);
+    assert_status!(&conn, 200);
+    assert_headers!(
+        &conn,
+        "content-encoding" => "gzip",
+        "content-length" => "57",
+        "content-type" => "text/plain; charset=utf-8",
+        "vary" => "Accept-Encoding"
+    );
+
+    let mut conn = get("/compressed.txt").on(&handler);
+    assert_ok!(&mut conn, "this file also has a gzipped sibling\n");
+    assert_headers!(&conn, "content-encoding" => None);
+    # }
+    ```
+    */
+    pub fn with_precompressed(mut self) -> Self {
+        self.options = self.options.with_precompressed();
+        self
+    }
+
     /**
     sets the index file on this StaticFileHandler
     ```
@@ -133,6 +175,53 @@ impl StaticFileHandler {
         self.index_file = Some(file.to_string());
         self
     }
+
+    /**
+    serves a generated listing of directory contents for any
+    directory that does not contain an index file. This is disabled
+    by default. The listing is html unless the request's accept
+    header prefers `application/json`. Dotfiles and any entries that
+    resolve outside of the fs root (such as symlinks) are omitted.
+
+    ```
+    # #[cfg(not(unix))] fn main() {}
+    # #[cfg(unix)] fn main() {
+    use trillium_static::{StaticFileHandler, crate_relative_path};
+
+    let handler = StaticFileHandler::new(crate_relative_path!("examples/files"))
+        .with_index_file("index.html")
+        .with_directory_listing();
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/subdir").on(&handler), "subdir index.html");
+
+    let mut conn = get("/subdir_with_no_index").on(&handler);
+    assert_status!(&conn, 200);
+    let body = assert_body_contains!(
+        &mut conn,
+        r#"<a href="subdir_with_no_index/plaintext.txt">plaintext.txt</a>"#
+    );
+    assert!(!body.contains(".gitkeep"));
+
+    assert_ok!(
+        get("/subdir_with_no_index/")
+            .with_request_header("accept", "application/json")
+            .on(&handler),
+        r#"{"path":"/subdir_with_no_index/","entries":[{"name":"plaintext.txt","href":"plaintext.txt","type":"file"}]}"#,
+        "content-type" => "application/json"
+    );
+
+    let conn = get("/subdir_with_no_index/")
+        .with_request_header("accept", "text/html;q=0.5, application/json")
+        .on(&handler);
+    assert_headers!(conn, "content-type" => "application/json", "vary" => "Accept");
+    # }
+    ```
+    */
+    pub fn with_directory_listing(mut self) -> Self {
+        self.directory_listing = true;
+        self
+    }
 }
 
 #[async_trait]
@@ -161,15 +250,34 @@ impl Handler for StaticFileHandler {
 
     async fn run(&self, conn: Conn) -> Conn {
         match self.resolve(conn.path()).await {
-            Some(Record::File(path, file)) => conn.send_file(file).await.with_mime_from_path(path),
+            Some(Record::File(path, file)) => {
+                let conn = match self.send_precompressed(conn, &path).await {
+                    Ok(conn) => return conn,
+                    Err(conn) => conn,
+                };
+                conn.send_file(file).await.with_mime_from_path(path)
+            }
 
             Some(Record::Dir(path)) => {
-                let index = conn_unwrap!(self.index_file.as_ref(), conn);
-                let path = path.join(index);
-                let file = conn_unwrap!(File::open(path.to_str().unwrap()).await.ok(), conn);
-                conn.send_file_with_options(file, &self.options)
-                    .await
-                    .with_mime_from_path(path)
+                if let Some(index) = &self.index_file {
+                    let index_path = path.join(index);
+                    if let Ok(file) = File::open(index_path.to_str().unwrap()).await {
+                        let conn = match self.send_precompressed(conn, &index_path).await {
+                            Ok(conn) => return conn,
+                            Err(conn) => conn,
+                        };
+                        return conn
+                            .send_file_with_options(file, &self.options)
+                            .await
+                            .with_mime_from_path(index_path);
+                    }
+                }
+
+                if self.directory_listing {
+                    directory_listing::send(conn, &path, &self.fs_root).await
+                } else {
+                    conn
+                }
             }
 
             _ => conn,
@@ -86,13 +86,17 @@ serves all files from disk every time, with no in-memory caching.
 
 cfg_if::cfg_if! {
    if #[cfg(any(feature = "smol", feature = "tokio", feature = "async-std"))] {
+       mod directory_listing;
        mod fs_shims;
        mod handler;
        mod options;
+       mod precompressed;
+       mod safe_join;
        mod static_conn_ext;
 
        pub use handler::StaticFileHandler;
        pub use relative_path;
+       pub use safe_join::safe_join;
        pub use static_conn_ext::StaticConnExt;
 
        /// a convenient helper macro to build a str relative to the crate root
@@ -2,6 +2,7 @@
 pub struct StaticOptions {
     pub(crate) etag: bool,
     pub(crate) modified: bool,
+    pub(crate) precompressed: bool,
 }
 
 impl StaticOptions {
@@ -14,6 +15,11 @@ impl StaticOptions {
         self.modified = false;
         self
     }
+
+    pub fn with_precompressed(mut self) -> Self {
+        self.precompressed = true;
+        self
+    }
 }
 
 impl Default for StaticOptions {
@@ -21,6 +27,7 @@ impl Default for StaticOptions {
         Self {
             etag: true,
             modified: true,
+            precompressed: false,
         }
     }
 }
@@ -0,0 +1,141 @@
+use crate::{
+    fs_shims::{fs, File},
+    options::StaticOptions,
+    StaticConnExt,
+};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+use trillium::{
+    Conn,
+    KnownHeaderName::{AcceptEncoding, ContentEncoding},
+};
+
+/// content-codings that we look for precompressed siblings of, in
+/// order of preference, along with the file extension for each
+const ENCODINGS: [(&str, &str); 2] = [("br", "br"), ("gzip", "gz")];
+
+/// returns the quality the accept-encoding header assigns to this
+/// content-coding, or zero if it is not acceptable
+fn quality(accept_encoding: &str, encoding: &str) -> f32 {
+    let mut wildcard = None;
+    for item in accept_encoding.split(',') {
+        let mut parts = item.split(';');
+        let name = parts.next().unwrap_or_default().trim();
+        let q = parts
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())
+            .unwrap_or(0.0);
+
+        if name.eq_ignore_ascii_case(encoding)
+            || (encoding == "gzip" && name.eq_ignore_ascii_case("x-gzip"))
+        {
+            return q;
+        } else if name == "*" {
+            wildcard = Some(q);
+        }
+    }
+    wildcard.unwrap_or(0.0)
+}
+
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut sibling = OsString::from(path.as_os_str());
+    sibling.push(".");
+    sibling.push(extension);
+    sibling.into()
+}
+
+/**
+opens the most preferred precompressed sibling of the file at `path`
+(`path.br` or `path.gz`) that the accept-encoding header allows,
+returning the content-coding and the file. If `root` is provided,
+siblings that resolve outside of it are ignored.
+*/
+async fn open(
+    path: &Path,
+    accept_encoding: Option<&str>,
+    root: Option<&Path>,
+) -> Option<(&'static str, File)> {
+    let accept_encoding = accept_encoding?;
+
+    let mut candidates = ENCODINGS
+        .iter()
+        .map(|&(encoding, extension)| (encoding, extension, quality(accept_encoding, encoding)))
+        .filter(|(_, _, q)| *q > 0.0)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|(_, _, a), (_, _, b)| b.total_cmp(a));
+
+    for (encoding, extension, _) in candidates {
+        let sibling = sibling(path, extension);
+
+        if let Some(root) = root {
+            let canonical: Option<PathBuf> = fs::canonicalize(&sibling).await.map(Into::into).ok();
+            if !canonical.is_some_and(|canonical| canonical.starts_with(root)) {
+                log::trace!(
+                    "{:?} does not exist or resolves outside of {:?}",
+                    sibling,
+                    root
+                );
+                continue;
+            }
+        }
+
+        if !fs::metadata(&sibling)
+            .await
+            .is_ok_and(|metadata| metadata.is_file())
+        {
+            continue;
+        }
+
+        if let Ok(file) = File::open(&sibling).await {
+            log::trace!("serving {:?} for {:?}", sibling, path);
+            return Some((encoding, file));
+        }
+    }
+
+    None
+}
+
+/**
+sends the most preferred acceptable precompressed sibling of the
+file at `path`, with a content-type guessed from `path` and a
+content-encoding for the sibling. Because the response depends on
+the accept-encoding header whether or not a sibling is sent, this
+also adds `Accept-Encoding` to the vary header. Returns the conn as
+an `Err` if no sibling was sent, so the caller can send the original.
+*/
+pub(crate) async fn send(
+    mut conn: Conn,
+    path: &Path,
+    options: &StaticOptions,
+    root: Option<&Path>,
+) -> Result<Conn, Conn> {
+    conn.add_vary(AcceptEncoding.as_ref());
+    match open(path, conn.request_headers().get_str(AcceptEncoding), root).await {
+        Some((encoding, file)) => Ok(conn
+            .send_file_with_options(file, options)
+            .await
+            .with_mime_from_path(path)
+            .with_header(ContentEncoding, encoding)),
+        None => Err(conn),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::quality;
+
+    #[test]
+    fn accept_encoding_quality() {
+        assert_eq!(quality("gzip, br", "br"), 1.0);
+        assert_eq!(quality("gzip;q=0.5, br;q=0.8", "gzip"), 0.5);
+        assert_eq!(quality("gzip;q=0", "gzip"), 0.0);
+        assert_eq!(quality("GZIP", "gzip"), 1.0);
+        assert_eq!(quality("x-gzip", "gzip"), 1.0);
+        assert_eq!(quality("deflate", "br"), 0.0);
+        assert_eq!(quality("*;q=0.3, gzip;q=0", "br"), 0.3);
+        assert_eq!(quality("*;q=0.3, gzip;q=0", "gzip"), 0.0);
+        assert_eq!(quality("identity", "gzip"), 0.0);
+    }
+}
@@ -0,0 +1,178 @@
+use percent_encoding::percent_decode_str;
+use std::path::{Component, Path, PathBuf};
+
+/**
+Maps a request path onto a filesystem path within `root`, returning
+None if the request path cannot be safely represented within root.
+
+The request path is percent-decoded exactly once and then split on
+both `/` and `\`, so that encoded separators and windows-style
+separators are treated the same as `/`. Empty segments and `.` are
+ignored, leading slashes are interpreted relative to root, and the
+following are rejected:
+
+* any `..` segment, including encoded forms like `%2e%2e`
+* NUL bytes
+* segments that the platform would interpret as a root or prefix,
+  such as windows drive letters
+* percent-encoded sequences that are malformed or not utf8
+
+Note that this is a purely lexical operation and does not touch the
+filesystem. A symlink within root may still point elsewhere, so if
+the returned path will be followed, canonicalize it and confirm that
+it still starts with the canonicalized root.
+
+```
+use std::path::Path;
+use trillium_static::safe_join;
+
+let root = Path::new("/srv/www");
+assert_eq!(
+    safe_join(root, "/css/site%20styles.css"),
+    Some(root.join("css").join("site styles.css"))
+);
+assert_eq!(safe_join(root, "/"), Some(root.to_path_buf()));
+assert_eq!(safe_join(root, "/../etc/passwd"), None);
+assert_eq!(safe_join(root, "/%2e%2e/etc/passwd"), None);
+assert_eq!(safe_join(root, "/..%5c..%5cwindows"), None);
+```
+*/
+pub fn safe_join(root: impl AsRef<Path>, request_path: &str) -> Option<PathBuf> {
+    let root = root.as_ref();
+    let decoded = percent_decode(request_path)?;
+    let mut path = root.to_path_buf();
+
+    for segment in decoded.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => return None,
+            segment if segment.contains('\0') => return None,
+            #[cfg(windows)]
+            segment if segment.contains(':') => return None,
+            segment => {
+                let mut components = Path::new(segment).components();
+                match (components.next(), components.next()) {
+                    (Some(Component::Normal(normal)), None) => path.push(normal),
+                    _ => return None,
+                }
+            }
+        }
+    }
+
+    if path.starts_with(root) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+fn percent_decode(s: &str) -> Option<String> {
+    let well_formed = s.split('%').skip(1).all(|escape| {
+        escape
+            .as_bytes()
+            .get(..2)
+            .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    });
+
+    if well_formed {
+        percent_decode_str(s).decode_utf8().ok().map(String::from)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::safe_join;
+    use std::path::{Path, PathBuf};
+
+    fn root() -> &'static Path {
+        Path::new("/srv/www")
+    }
+
+    fn joined(segments: &[&str]) -> Option<PathBuf> {
+        Some(segments.iter().fold(root().to_path_buf(), |p, s| p.join(s)))
+    }
+
+    #[test]
+    fn allowed() {
+        assert_eq!(safe_join(root(), ""), joined(&[]));
+        assert_eq!(safe_join(root(), "/"), joined(&[]));
+        assert_eq!(safe_join(root(), "/index.html"), joined(&["index.html"]));
+        assert_eq!(safe_join(root(), "index.html"), joined(&["index.html"]));
+        assert_eq!(
+            safe_join(root(), "/a/b/c.txt"),
+            joined(&["a", "b", "c.txt"])
+        );
+        assert_eq!(safe_join(root(), "/a/./b/"), joined(&["a", "b"]));
+        assert_eq!(safe_join(root(), "//a//b"), joined(&["a", "b"]));
+        assert_eq!(safe_join(root(), "/a%20b.txt"), joined(&["a b.txt"]));
+        assert_eq!(safe_join(root(), "/%2Fa%2fb"), joined(&["a", "b"]));
+        assert_eq!(safe_join(root(), "/a\\b"), joined(&["a", "b"]));
+        assert_eq!(safe_join(root(), "/%e2%9c%93"), joined(&["✓"]));
+        assert_eq!(safe_join(root(), "/..a/b.."), joined(&["..a", "b.."]));
+        assert_eq!(safe_join(root(), "/.../"), joined(&["..."]));
+        assert_eq!(safe_join(root(), "/.hidden"), joined(&[".hidden"]));
+
+        // only one layer of decoding is applied, so this is a file
+        // literally named %2e%2e, not a parent directory
+        assert_eq!(safe_join(root(), "/%252e%252e/x"), joined(&["%2e%2e", "x"]));
+    }
+
+    #[test]
+    fn traversal() {
+        let payloads = [
+            "..",
+            "/..",
+            "/../",
+            "/../etc/passwd",
+            "/a/../../etc/passwd",
+            "/a/b/../c",
+            "/./../",
+            "../../../../../../etc/passwd",
+            "/%2e%2e/etc/passwd",
+            "/%2E%2E/etc/passwd",
+            "/.%2e/etc/passwd",
+            "/%2e./etc/passwd",
+            "/%2e%2e%2fetc%2fpasswd",
+            "/..%2fetc%2fpasswd",
+            "/..%2Fetc%2Fpasswd",
+            "/..\\etc\\passwd",
+            "/..%5cetc%5cpasswd",
+            "/..%5Cetc%5Cpasswd",
+            "\\..\\..\\windows\\win.ini",
+            "/a/%2e%2e/%2e%2e/etc",
+            "/a\\..\\..\\etc",
+        ];
+
+        for payload in payloads {
+            assert_eq!(safe_join(root(), payload), None, "payload: {}", payload);
+        }
+    }
+
+    #[test]
+    fn nul_bytes() {
+        assert_eq!(safe_join(root(), "/index.html%00.png"), None);
+        assert_eq!(safe_join(root(), "/%00"), None);
+        assert_eq!(safe_join(root(), "/a\0b"), None);
+    }
+
+    #[test]
+    fn malformed_encoding() {
+        assert_eq!(safe_join(root(), "/%"), None);
+        assert_eq!(safe_join(root(), "/%2"), None);
+        assert_eq!(safe_join(root(), "/%zz"), None);
+        assert_eq!(safe_join(root(), "/%+1"), None);
+        assert_eq!(safe_join(root(), "/%-1"), None);
+        assert_eq!(safe_join(root(), "/%c0%ae%c0%ae/etc"), None);
+        assert_eq!(safe_join(root(), "/%ff"), None);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_prefixes() {
+        assert_eq!(safe_join(root(), "/C:/windows/win.ini"), None);
+        assert_eq!(safe_join(root(), "/C:%5cwindows"), None);
+        assert_eq!(safe_join(root(), "/a/file.txt:stream"), None);
+    }
+}
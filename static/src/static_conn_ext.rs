@@ -1,11 +1,19 @@
-use crate::{fs_shims::File, options::StaticOptions};
+use crate::{
+    directory_listing,
+    fs_shims::{fs, File},
+    options::StaticOptions,
+    precompressed,
+};
 use etag::EntityTag;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use trillium::{
-    Body, Conn,
+    Body, Conn, HeaderName,
     KnownHeaderName::{self, ContentType},
 };
 
+/// the header that nginx reads for internal redirects
+const X_ACCEL_REDIRECT: &str = "x-accel-redirect";
+
 /// conn extension trait to facilitate sending individual files and
 /// paths
 #[trillium::async_trait]
@@ -30,6 +38,60 @@ pub trait StaticConnExt {
         options: &StaticOptions,
     ) -> Self;
 
+    /// Send a listing of the directory at the provided path, as html
+    /// or as json if the request's accept header prefers
+    /// `application/json`. Dotfiles and any entries that resolve
+    /// outside of this directory (such as symlinks) are omitted. Will
+    /// send a 404 if the directory cannot be read.
+    async fn send_directory_listing<A: AsRef<Path> + Send>(self, path: A) -> Self;
+
+    /**
+    Hand off serving this response to a front-end proxy by setting an
+    `X-Accel-Redirect` header with the provided path, as supported by
+    nginx. This clears any response body, sets a 200 status, and halts
+    the conn. The proxy is responsible for serving the content at
+    that path, and the path is interpreted according to the proxy's
+    configuration. To use a different header, see
+    [`StaticConnExt::internal_redirect_with_header`].
+
+    ```
+    use trillium_static::StaticConnExt;
+    use trillium_testing::prelude::*;
+    let mut conn = get("/download").on(&|conn: Conn| async move {
+        conn.with_body("this will be removed")
+            .internal_redirect("/protected/report.pdf")
+    });
+
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "x-accel-redirect" => "/protected/report.pdf");
+    assert!(conn.is_halted());
+    assert!(conn.take_response_body().is_none());
+    ```
+    */
+    fn internal_redirect(self, path: impl Into<String>) -> Self;
+
+    /**
+    Hand off serving this response to a front-end proxy with a
+    custom header name, such as `X-Sendfile` for apache's
+    mod_xsendfile or lighttpd. Otherwise identical to
+    [`StaticConnExt::internal_redirect`].
+
+    ```
+    use trillium_static::StaticConnExt;
+    use trillium_testing::prelude::*;
+    let conn = get("/download").on(&|conn: Conn| async move {
+        conn.internal_redirect_with_header("x-sendfile", "/var/files/report.pdf")
+    });
+
+    assert_headers!(&conn, "x-sendfile" => "/var/files/report.pdf");
+    ```
+    */
+    fn internal_redirect_with_header(
+        self,
+        header_name: impl Into<HeaderName<'static>>,
+        path: impl Into<String>,
+    ) -> Self;
+
     /// Guess the mime type for this fs path using
     /// [`mime_guess`](https://docs.rs/mime_guess/) and set the
     /// content-type header
@@ -54,6 +116,13 @@ impl StaticConnExt for Conn {
         options: &StaticOptions,
     ) -> Self {
         let path = path.as_ref().to_path_buf();
+        if options.precompressed {
+            match precompressed::send(self, &path, options, None).await {
+                Ok(conn) => return conn,
+                Err(conn) => self = conn,
+            }
+        }
+
         let file = trillium::conn_try!(File::open(&path).await, self.with_status(404));
         self.send_file_with_options(file, options)
             .await
@@ -65,7 +134,7 @@ impl StaticConnExt for Conn {
 
         if options.modified {
             if let Ok(last_modified) = metadata.modified() {
-                self.headers_mut().try_insert(
+                self.response_headers_mut().try_insert(
                     KnownHeaderName::LastModified,
                     httpdate::fmt_http_date(last_modified),
                 );
@@ -74,7 +143,7 @@ impl StaticConnExt for Conn {
 
         if options.etag {
             let etag = EntityTag::from_file_meta(&metadata);
-            self.headers_mut()
+            self.response_headers_mut()
                 .try_insert(KnownHeaderName::Etag, etag.to_string());
         }
 
@@ -84,6 +153,29 @@ impl StaticConnExt for Conn {
         self.ok(Body::new_streaming(file, Some(metadata.len())))
     }
 
+    async fn send_directory_listing<A: AsRef<Path> + Send>(self, path: A) -> Self {
+        let dir: PathBuf = trillium::conn_try!(
+            fs::canonicalize(path.as_ref()).await.map(Into::into),
+            self.with_status(404)
+        );
+        directory_listing::send(self, &dir, &dir).await
+    }
+
+    fn internal_redirect(self, path: impl Into<String>) -> Self {
+        self.internal_redirect_with_header(X_ACCEL_REDIRECT, path)
+    }
+
+    fn internal_redirect_with_header(
+        mut self,
+        header_name: impl Into<HeaderName<'static>>,
+        path: impl Into<String>,
+    ) -> Self {
+        self.take_response_body();
+        self.with_header(header_name, path.into())
+            .with_status(200)
+            .halt()
+    }
+
     fn with_mime_from_path(self, path: impl AsRef<Path>) -> Self {
         if let Some(mime) = mime_guess::from_path(path).first() {
             use mime_guess::mime::{APPLICATION, HTML, JAVASCRIPT, TEXT};
@@ -63,7 +63,7 @@ impl TeraConnExt for Conn {
             Ok(string) => {
                 if let Some(extension) = PathBuf::from(template_name).extension() {
                     if let Some(mime) = mime_db::lookup(extension.to_string_lossy()) {
-                        self.headers_mut()
+                        self.response_headers_mut()
                             .insert(KnownHeaderName::ContentType, mime);
                     }
                 }
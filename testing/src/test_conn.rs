@@ -2,7 +2,7 @@ use crate::block_on;
 use std::{
     convert::TryInto,
     fmt::Debug,
-    net::IpAddr,
+    net::{IpAddr, SocketAddr},
     ops::{Deref, DerefMut},
 };
 use trillium::{Conn, Handler, HeaderName, HeaderValues, Method};
@@ -43,7 +43,7 @@ impl TestConn {
     use trillium_testing::TestConn;
     let conn = TestConn::build("get", "/", "body")
         .with_request_header("some-header", "value");
-    assert_eq!(conn.headers().get_str("some-header"), Some("value"));
+    assert_eq!(conn.request_headers().get_str("some-header"), Some("value"));
     ```
     */
 
@@ -87,6 +87,13 @@ impl TestConn {
         self
     }
 
+    /// sets the peer socket address, and therefore the peer ip, for
+    /// this test conn
+    pub fn with_peer_addr(mut self, addr: SocketAddr) -> Self {
+        self.inner_mut().set_peer_addr(Some(addr));
+        self
+    }
+
     /// set the test conn to be secure
     pub fn secure(mut self) -> Self {
         self.inner_mut().set_secure(true);
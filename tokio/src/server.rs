@@ -1,5 +1,7 @@
 use async_compat::Compat;
-use std::{convert::TryInto, future::Future, io::Result, net::IpAddr, pin::Pin};
+use std::{
+    convert::TryInto, future::Future, io::Result, net::SocketAddr, pin::Pin, time::Duration,
+};
 #[cfg(unix)]
 use tokio::net::{UnixListener, UnixStream};
 use tokio::{
@@ -60,13 +62,9 @@ impl Server for TokioServer {
         })
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
         match transport {
-            Tcp(transport) => transport
-                .get_ref()
-                .peer_addr()
-                .ok()
-                .map(|socket_addr| socket_addr.ip()),
+            Tcp(transport) => transport.get_ref().peer_addr().ok(),
 
             Unix(_) => None,
         }
@@ -87,6 +85,10 @@ impl Server for TokioServer {
         crate::block_on(fut)
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         if let Tcp(transport) = transport {
             log_error!(transport.get_mut().set_nodelay(nodelay));
@@ -137,12 +139,8 @@ impl Server for TokioServer {
         tcp.try_into().unwrap()
     }
 
-    fn peer_ip(transport: &Self::Transport) -> Option<IpAddr> {
-        transport
-            .get_ref()
-            .peer_addr()
-            .ok()
-            .map(|socket_addr| socket_addr.ip())
+    fn peer_addr(transport: &Self::Transport) -> Option<SocketAddr> {
+        transport.get_ref().peer_addr().ok()
     }
 
     fn info(listener: &Self::Listener) -> Info {
@@ -157,6 +155,10 @@ impl Server for TokioServer {
         crate::block_on(fut);
     }
 
+    fn sleep(duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
     fn set_nodelay(transport: &mut Self::Transport, nodelay: bool) {
         log_error!(transport.get_mut().set_nodelay(nodelay));
     }
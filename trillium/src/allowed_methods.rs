@@ -0,0 +1,183 @@
+use crate::{async_trait, Conn, Handler, Method, Status};
+use std::{
+    collections::{btree_set, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+/**
+# The set of http methods supported at a path
+
+Routers record the methods they support for the current request path
+by calling [`Conn::add_allowed_methods`], which accumulates them into
+an `AllowedMethods` in the conn's state. Handlers later in the
+sequence, such as [`AutoOptions`], can read this set with
+[`Conn::allowed_methods`] without knowing anything about how routing
+was performed. Because each registration is merged into the existing
+set, several routers can contribute to the same conn.
+
+```
+use trillium::{AllowedMethods, Method};
+let allowed: AllowedMethods = [Method::Post, Method::Get].into_iter().collect();
+assert!(allowed.contains(Method::Get));
+assert_eq!(allowed.to_string(), "GET, POST");
+```
+*/
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AllowedMethods(BTreeSet<Method>);
+
+impl AllowedMethods {
+    /// constructs a new empty `AllowedMethods`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a method to this set
+    pub fn insert(&mut self, method: Method) {
+        self.0.insert(method);
+    }
+
+    /// determines whether a method is in this set
+    pub fn contains(&self, method: Method) -> bool {
+        self.0.contains(&method)
+    }
+
+    /// iterates over the methods in this set, in alphabetical order
+    pub fn iter(&self) -> impl Iterator<Item = Method> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// the number of methods in this set
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// determines whether this set is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Display for AllowedMethods {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, method) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(method.as_ref())?;
+        }
+        Ok(())
+    }
+}
+
+impl Extend<Method> for AllowedMethods {
+    fn extend<T: IntoIterator<Item = Method>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl FromIterator<Method> for AllowedMethods {
+    fn from_iter<T: IntoIterator<Item = Method>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for AllowedMethods {
+    type Item = Method;
+    type IntoIter = btree_set::IntoIter<Method>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/**
+# A handler that responds to OPTIONS requests from registered methods
+
+`AutoOptions` reads the [`AllowedMethods`] registered by earlier
+handlers in the sequence, and for any conn that has not yet been
+halted:
+
+* responds to an OPTIONS request with a 200 and an `Allow` header
+  listing the registered methods, if any methods were registered
+* optionally, with [`AutoOptions::with_method_not_allowed`], responds
+  to a request for any other method with a 405 and an `Allow` header
+  if methods were registered but the request method was not handled
+
+Place this handler after any routers. Conns for which no methods were
+registered are passed along unchanged.
+
+```
+use trillium::{AutoOptions, Conn, Method};
+use trillium_testing::{prelude::*, TestConn};
+
+let handler = (
+    |mut conn: Conn| async move {
+        if conn.path() == "/resource" {
+            conn.add_allowed_methods([Method::Get, Method::Delete]);
+            if conn.is_get() {
+                return conn.ok("resource");
+            }
+        }
+        conn
+    },
+    AutoOptions::new().with_method_not_allowed(),
+);
+
+let mut conn = TestConn::build(Method::Options, "/resource", ()).on(&handler);
+assert_status!(&conn, 200);
+assert_headers!(&mut conn, "allow" => "DELETE, GET");
+
+let mut conn = post("/resource").on(&handler);
+assert_status!(&conn, 405);
+assert_headers!(&mut conn, "allow" => "DELETE, GET");
+
+assert_ok!(get("/resource").on(&handler), "resource");
+assert_not_handled!(TestConn::build(Method::Options, "/other", ()).on(&handler));
+```
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoOptions {
+    method_not_allowed: bool,
+}
+
+impl AutoOptions {
+    /// constructs a new `AutoOptions` handler that only responds to
+    /// OPTIONS requests
+    pub const fn new() -> Self {
+        Self {
+            method_not_allowed: false,
+        }
+    }
+
+    /// chainable setter to also respond with a 405 Method Not Allowed
+    /// for unhandled requests at a path that has registered methods
+    #[must_use]
+    pub const fn with_method_not_allowed(mut self) -> Self {
+        self.method_not_allowed = true;
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for AutoOptions {
+    async fn run(&self, conn: Conn) -> Conn {
+        if conn.is_halted() {
+            return conn;
+        }
+
+        let allowed = match conn.allowed_methods() {
+            Some(allowed) => allowed.clone(),
+            None => return conn,
+        };
+
+        if conn.is_options() {
+            conn.with_allow_header(allowed)
+                .with_status(Status::Ok)
+                .halt()
+        } else if self.method_not_allowed && !allowed.contains(conn.method()) {
+            conn.with_method_not_allowed(allowed)
+        } else {
+            conn
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    time::Duration,
+};
+
+/**
+# An alternative service for the `Alt-Svc` header
+
+Each `AltSvc` advertises that the same resources are also available
+over another protocol, and optionally on another host or port, as
+described in [rfc 7838](https://www.rfc-editor.org/rfc/rfc7838). It
+is most commonly used to advertise an http/3 endpoint. Use
+[`Conn::with_alt_svc`](crate::Conn::with_alt_svc) to send one or more
+alternatives.
+
+```
+use std::time::Duration;
+use trillium::AltSvc;
+
+let alt_svc = AltSvc::new("h3", ":443").with_max_age(Duration::from_secs(86400));
+assert_eq!(alt_svc.to_string(), r#"h3=":443"; ma=86400"#);
+
+let alt_svc = AltSvc::new("h2", "alt.example.com:8443").with_persist();
+assert_eq!(alt_svc.to_string(), r#"h2="alt.example.com:8443"; persist=1"#);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltSvc {
+    protocol_id: String,
+    authority: String,
+    max_age: Option<Duration>,
+    persist: bool,
+}
+
+impl AltSvc {
+    /// constructs a new alternative service from an alpn protocol id
+    /// such as `h3`, and an authority such as `:443` or
+    /// `alt.example.com:443`. an authority without a host refers to
+    /// the same host as the request.
+    pub fn new(protocol_id: impl Into<String>, authority: impl Into<String>) -> Self {
+        Self {
+            protocol_id: protocol_id.into(),
+            authority: authority.into(),
+            max_age: None,
+            persist: false,
+        }
+    }
+
+    /// chainable setter for how long the client may consider this
+    /// alternative fresh. the default, when this is not set, is 24
+    /// hours. this is sent with one-second precision.
+    #[must_use]
+    pub const fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// chainable setter to ask clients to keep this alternative
+    /// across network changes
+    #[must_use]
+    pub const fn with_persist(mut self) -> Self {
+        self.persist = true;
+        self
+    }
+
+    /// the alpn protocol id for this alternative
+    pub fn protocol_id(&self) -> &str {
+        &self.protocol_id
+    }
+
+    /// the authority for this alternative
+    pub fn authority(&self) -> &str {
+        &self.authority
+    }
+
+    /// the max age for this alternative, if one was set
+    pub const fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+}
+
+const fn is_tchar(c: u8) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            b'!' | b'#'
+                | b'$'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+impl Display for AltSvc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // protocol ids are tokens, with anything else percent-encoded
+        for &byte in self.protocol_id.as_bytes() {
+            if is_tchar(byte) {
+                f.write_char(char::from(byte))?;
+            } else {
+                write!(f, "%{byte:02X}")?;
+            }
+        }
+
+        f.write_str("=\"")?;
+        for c in self.authority.chars() {
+            if matches!(c, '"' | '\\') {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        f.write_char('"')?;
+
+        if let Some(max_age) = self.max_age {
+            write!(f, "; ma={}", max_age.as_secs())?;
+        }
+
+        if self.persist {
+            f.write_str("; persist=1")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use futures_lite::AsyncRead;
+use std::{
+    io::Result,
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+/// a reader over the chunks yielded by a synchronous iterator. the
+/// iterator is held in a mutex only so that the reader is `Sync`;
+/// it is never locked, since reading requires exclusive access
+pub struct IterReader<I> {
+    iter: Mutex<I>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl<I> IterReader<I> {
+    pub const fn new(iter: I) -> Self {
+        Self {
+            iter: Mutex::new(iter),
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+// the iterator is never pinned, so the reader can move regardless of
+// whether the iterator is Unpin
+impl<I> Unpin for IterReader<I> {}
+
+impl<I> AsyncRead for IterReader<I>
+where
+    I: Iterator<Item = Vec<u8>>,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let Self {
+            iter,
+            chunk,
+            position,
+        } = &mut *self;
+
+        while *position == chunk.len() {
+            match iter
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .next()
+            {
+                Some(next) => {
+                    *chunk = next;
+                    *position = 0;
+                }
+                None => return Poll::Ready(Ok(0)),
+            }
+        }
+
+        let len = buf.len().min(chunk.len() - *position);
+        buf[..len].copy_from_slice(&chunk[*position..*position + len]);
+        *position += len;
+        Poll::Ready(Ok(len))
+    }
+}
@@ -0,0 +1,78 @@
+use crate::structured_field::{parse_dictionary, serialize_dictionary, BareItem, Item, Member};
+use sha2::{Digest, Sha256, Sha512};
+use trillium_http::{Error, Headers};
+
+type Hasher = fn(&[u8]) -> Vec<u8>;
+
+/// the checksum headers that can be verified, as used by
+/// s3-compatible uploads. each checksum is the base64 encoding of the
+/// digest of the entire body.
+const CHECKSUMS: [(&str, Hasher); 2] = [
+    ("x-amz-checksum-crc32", |body| {
+        crc32fast::hash(body).to_be_bytes().to_vec()
+    }),
+    ("x-amz-checksum-sha256", |body| {
+        Sha256::digest(body).to_vec()
+    }),
+];
+
+/// verifies every supported checksum that was declared, preferring
+/// trailers over headers
+pub fn verify(body: &[u8], trailers: Option<&Headers>, headers: &Headers) -> Result<(), Error> {
+    for (name, digest) in CHECKSUMS {
+        let declared = trailers
+            .and_then(|trailers| trailers.get_str(name))
+            .or_else(|| headers.get_str(name));
+
+        if let Some(declared) = declared {
+            if base64::encode(digest(body)) != declared.trim() {
+                return Err(Error::ChecksumMismatch(name));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// the algorithms that can be sent in `Content-Digest` and
+/// `Repr-Digest`, as registered by rfc 9530, in order of preference
+/// when the client does not express one
+const DIGESTS: [(&str, Hasher); 2] = [
+    ("sha-256", |body| Sha256::digest(body).to_vec()),
+    ("sha-512", |body| Sha512::digest(body).to_vec()),
+];
+
+/// selects the supported algorithm that a `Want-Content-Digest` or
+/// `Want-Repr-Digest` header prefers most, or sha-256 if there is no
+/// such header. an algorithm with a preference of zero is never
+/// selected
+fn select(want: Option<&str>) -> Option<(&'static str, Hasher)> {
+    let Some(want) = want.and_then(parse_dictionary) else {
+        return Some(DIGESTS[0]);
+    };
+
+    let mut selected: Option<(i64, (&'static str, Hasher))> = None;
+    for (key, member) in want {
+        let Some(&BareItem::Integer(preference)) = member.as_item().map(Item::bare_item) else {
+            continue;
+        };
+
+        let Some(digest) = DIGESTS.into_iter().find(|(name, _)| *name == key) else {
+            continue;
+        };
+
+        if preference > 0 && selected.is_none_or(|(selected, _)| selected < preference) {
+            selected = Some((preference, digest));
+        }
+    }
+
+    selected.map(|(_, digest)| digest)
+}
+
+/// the value of a digest header for this content, using the algorithm
+/// selected from the corresponding `Want-` header
+pub fn digest_header(body: &[u8], want: Option<&str>) -> Option<String> {
+    let (name, digest) = select(want)?;
+    let item = Item::new(BareItem::ByteSequence(digest(body)));
+    Some(serialize_dictionary(&[(name.into(), Member::Item(item))]))
+}
@@ -1,13 +1,88 @@
+use crate::{
+    body_iter::IterReader,
+    content_negotiation,
+    percent_decoding::{decode_path, form_decode},
+    range::{SeekingReader, SkippingReader},
+    AllowedMethods, AltSvc, ConnParts, Priority, RangeSet,
+};
+use encoding_rs::Encoding;
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncWrite, AsyncWriteExt};
 use std::{
+    borrow::Cow,
     convert::TryInto,
     fmt::{self, Debug, Formatter},
-    net::IpAddr,
+    future::Future,
+    net::{IpAddr, SocketAddr},
 };
 use trillium_http::{
     transport::{BoxedTransport, Transport},
-    Body, HeaderName, HeaderValues, Headers, Method, ReceivedBody, StateSet, Status,
+    Body, Error, HeaderName, HeaderValue, HeaderValues, Headers, KnownHeaderName, Method,
+    ReceivedBody, StateSet, Status, Stopper,
 };
 
+/**
+the error returned by [`Conn::request_body_json`], distinguishing a
+failure to read the body from a failure to deserialize it. Only
+available when the `json` crate feature is enabled.
+*/
+#[cfg(feature = "json")]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum JsonError {
+    /// the request body could not be read, or was not valid utf8
+    #[error(transparent)]
+    Http(#[from] Error),
+
+    /// the request body was not valid json for the requested type
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/**
+the error returned by [`Conn::request_body_form`], distinguishing a
+request that is not a form, a failure to read the body, and a
+failure to deserialize it. Only available when the `json` crate
+feature is enabled.
+*/
+#[cfg(feature = "json")]
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum FormError {
+    /// the request content-type was not
+    /// `application/x-www-form-urlencoded`
+    #[error("request is not application/x-www-form-urlencoded")]
+    ContentType,
+
+    /// the request body could not be read, or was not valid utf8
+    #[error(transparent)]
+    Http(#[from] Error),
+
+    /// the request body was not a valid form for the requested type
+    #[error(transparent)]
+    Form(#[from] serde_urlencoded::de::Error),
+}
+
+/// a request body read into memory by [`Conn::buffer_request_body`]
+struct BufferedRequestBody {
+    bytes: Vec<u8>,
+    encoding: &'static Encoding,
+}
+
+impl BufferedRequestBody {
+    fn decode(&self) -> String {
+        let (s, _, _) = self.encoding.decode(&self.bytes);
+        s.into_owned()
+    }
+
+    const fn check_len(&self, max: usize) -> trillium_http::Result<&Self> {
+        if self.bytes.len() > max {
+            Err(Error::ReceivedBodyTooLong(max as u64))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
 /**
 # A Trillium HTTP connection.
 
@@ -110,6 +185,41 @@ impl Conn {
         self.with_status(200).with_body(body).halt()
     }
 
+    /**
+    a convenience function for responding with a status and halting
+    in one call, such as from error handling middleware. It is exactly
+    identical to `conn.with_status(status).halt()`
+    ```
+    use trillium::{Conn, Status};
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: Conn| async move { conn.halt_with_status(Status::Forbidden) });
+    assert_status!(&conn, 403);
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn halt_with_status(self, status: impl TryInto<Status>) -> Self {
+        self.with_status(status).halt()
+    }
+
+    /**
+    like [`Conn::ok`], but with any status. It is exactly identical to
+    `conn.with_status(status).with_body(body).halt()`
+    ```
+    use trillium::Conn;
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: Conn| async move {
+        conn.halt_with_body(404, "no such page")
+    });
+    assert_response!(&mut conn, 404, "no such page");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn halt_with_body(self, status: impl TryInto<Status>, body: impl Into<Body>) -> Self {
+        self.with_status(status).with_body(body).halt()
+    }
+
     /**
     returns the response status for this `Conn`, if it has been set.
     ```
@@ -124,6 +234,23 @@ impl Conn {
         self.inner.status()
     }
 
+    /**
+    returns the status that the response for this `Conn` will be sent
+    with: the [`Conn::status`] if it has been set, or otherwise `200
+    Ok` if a body has been set and `404 Not Found` if not.
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&());
+    assert_eq!(conn.effective_status(), Status::NotFound);
+
+    let conn = get("/").on(&|conn: Conn| async move { conn.with_body("hello") });
+    assert_eq!(conn.effective_status(), Status::Ok);
+    ```
+     */
+    pub fn effective_status(&self) -> Status {
+        self.inner.effective_status()
+    }
+
     /// assigns a status to this response. see [`Conn::status`] for example usage
     pub fn set_status(&mut self, status: impl TryInto<Status>) {
         self.inner.set_status(status);
@@ -172,6 +299,60 @@ impl Conn {
         self
     }
 
+    /**
+    Sets the response body from an [`AsyncRead`] and returns the
+    `Conn` for fluent chaining. The body is streamed to the client
+    without being read into memory. When `len` is provided, it is
+    sent as the `Content-Length`, and otherwise the body is sent with
+    chunked transfer encoding. Note that this does not set the
+    response status or halted.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: Conn| async move {
+        let reader = futures_lite::io::Cursor::new("hello");
+        conn.with_body_stream(reader, Some(5))
+    });
+    assert_eq!(conn.response_len(), Some(5));
+    assert_body!(&mut conn, "hello");
+    ```
+    */
+    #[must_use]
+    pub fn with_body_stream(
+        self,
+        reader: impl AsyncRead + Send + Sync + 'static,
+        len: Option<u64>,
+    ) -> Self {
+        self.with_body(Body::new_streaming(reader, len))
+    }
+
+    /**
+    Sets the response body from a synchronous iterator of byte chunks
+    and returns the `Conn` for fluent chaining. The chunks are sent in
+    order with chunked transfer encoding, as the length is not known
+    in advance. Each chunk is produced as the body is written, on the
+    task that writes it, so an iterator that does a lot of work for
+    each chunk will block that task. Note that this does not set the
+    response status or halted.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: Conn| async move {
+        let rows = (1..=3).map(|n| format!("{n},{}\n", n * n).into_bytes());
+        conn.with_body_iter(rows).with_status(200)
+    });
+    assert_eq!(conn.response_len(), None);
+    assert_body!(&mut conn, "1,1\n2,4\n3,9\n");
+    ```
+    */
+    #[must_use]
+    pub fn with_body_iter<I>(self, iter: I) -> Self
+    where
+        I: Iterator<Item = Vec<u8>> + Send + 'static,
+    {
+        self.with_body_stream(IterReader::new(iter), None)
+    }
+
     /**
     Sets the response body from any `impl Into<Body>`. Note that this does not set the response
     status or halted.
@@ -246,6 +427,31 @@ impl Conn {
         self.inner.state_mut().take()
     }
 
+    /// Returns whether the state set contains a value of this type
+    pub fn has_state<T: 'static>(&self) -> bool {
+        self.inner.state().contains::<T>()
+    }
+
+    /**
+    Removes every value from the state set
+
+    ```
+    use trillium_testing::prelude::*;
+    struct Hello;
+    let mut conn = get("/").on(&());
+    conn.set_state(Hello);
+    conn.set_state(1u8);
+    assert!(conn.has_state::<Hello>());
+
+    conn.clear_state();
+    assert!(!conn.has_state::<Hello>());
+    assert!(!conn.has_state::<u8>());
+    ```
+    */
+    pub fn clear_state(&mut self) {
+        self.inner.state_mut().clear();
+    }
+
     /**
     Either returns the current &mut T from the state set, or
     inserts a new one with the provided default function and
@@ -259,6 +465,27 @@ impl Conn {
         self.inner.state_mut().get_or_insert_with(default)
     }
 
+    /**
+    Puts a new type into the state set, replacing any previous
+    instance of this type, and returns a mutable reference to the
+    inserted value
+
+    ```
+    use trillium_testing::prelude::*;
+    struct Visits(Vec<&'static str>);
+    let mut conn = get("/").on(&());
+    conn.set_state(Visits(vec!["stale"]));
+
+    let visits = conn.insert_state_and_get_mut(Visits(vec![]));
+    visits.0.push("home");
+    assert_eq!(conn.state::<Visits>().unwrap().0, ["home"]);
+    ```
+    */
+    pub fn insert_state_and_get_mut<T: Send + Sync + 'static>(&mut self, val: T) -> &mut T {
+        self.take_state::<T>();
+        self.mut_state_or_insert_with(|| val)
+    }
+
     /**
     Returns a [ReceivedBody] that references this `Conn`. The `Conn`
     retains all data and holds the singular transport, but the
@@ -308,157 +535,2061 @@ impl Conn {
     */
     #[allow(clippy::missing_errors_doc)] // this is a false positive
     pub async fn request_body_string(&mut self) -> trillium_http::Result<String> {
+        if let Some(buffered) = self.state::<BufferedRequestBody>() {
+            return Ok(buffered.decode());
+        }
         self.request_body().await.read_string().await
     }
 
     /**
-    if there is a response body for this conn and it has a known
-    fixed length, it is returned from this function
+
+    Convenience function to read the content of a request body as
+    bytes, without any utf8 validation.
+
+    # Errors
+
+    This will return an error variant if there is an IO failure on the
+    underlying transport.
+
+    # Examples
 
     ```
     use trillium_testing::prelude::*;
-    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
-        conn.with_body("hello")
-    });
+    let mut conn = get("/").with_request_body(vec![0xde, 0xad, 0xbe, 0xef]).on(&());
 
-    assert_eq!(conn.response_len(), Some(5));
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.request_body_bytes().await.unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+    # });
     ```
     */
-    pub fn response_len(&self) -> Option<u64> {
-        self.inner.response_body().and_then(Body::len)
+    #[allow(clippy::missing_errors_doc)] // this is a false positive
+    pub async fn request_body_bytes(&mut self) -> trillium_http::Result<Vec<u8>> {
+        if let Some(buffered) = self.state::<BufferedRequestBody>() {
+            return Ok(buffered.bytes.clone());
+        }
+        self.request_body().await.read_bytes().await
     }
 
     /**
-    returns the request method for this conn.
+    Reads the content of a request body as a `String`, as with
+    [`Conn::request_body_string`], but reads at most `max` bytes from
+    the client. Use this instead of `request_body_string` for
+    endpoints that accept bodies from untrusted clients.
+
+    # Errors
+
+    This will return an error variant if there is an IO failure on
+    the underlying transport, or
+    [`Error::ReceivedBodyTooLong`](trillium_http::Error::ReceivedBodyTooLong)
+    if the request content-length or the body itself is longer than
+    `max` bytes. A body that is too long is never read into memory in
+    full.
+
+    # Examples
+
     ```
     use trillium_testing::prelude::*;
-    let mut conn = get("/").on(&());
+    let mut conn = post("/").with_request_body("request body").on(&());
 
-    assert_eq!(conn.method(), Method::Get);
-    ```
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.request_body_string_with_limit(1024).await.unwrap(), "request body");
+    # });
 
+    let mut conn = post("/").with_request_body("request body").on(&());
+    # trillium_testing::block_on(async {
+    assert!(conn.request_body_string_with_limit(5).await.is_err());
+    # });
+    ```
     */
-    pub fn method(&self) -> Method {
-        self.inner.method()
+    pub async fn request_body_string_with_limit(
+        &mut self,
+        max: usize,
+    ) -> trillium_http::Result<String> {
+        if let Some(buffered) = self.state::<BufferedRequestBody>() {
+            return buffered.check_len(max).map(BufferedRequestBody::decode);
+        }
+        self.request_body()
+            .await
+            .read_string_with_max_len(max as u64)
+            .await
     }
 
-    /// returns the request headers
-    ///
-    /// stability note: this may become `request_headers` at some point
-    pub fn headers(&self) -> &Headers {
-        self.inner.request_headers()
-    }
+    /**
+    Reads the content of a request body as bytes, as with
+    [`Conn::request_body_bytes`], but reads at most `max` bytes from
+    the client.
 
-    /// returns the mutable response headers
-    ///
-    /// stability note: this may become `response_headers` at some point
-    pub fn headers_mut(&mut self) -> &mut Headers {
-        self.inner.response_headers_mut()
+    # Errors
+
+    This will return an error variant if there is an IO failure on
+    the underlying transport, or
+    [`Error::ReceivedBodyTooLong`](trillium_http::Error::ReceivedBodyTooLong)
+    if the request content-length or the body itself is longer than
+    `max` bytes.
+    */
+    pub async fn request_body_bytes_with_limit(
+        &mut self,
+        max: usize,
+    ) -> trillium_http::Result<Vec<u8>> {
+        if let Some(buffered) = self.state::<BufferedRequestBody>() {
+            return buffered
+                .check_len(max)
+                .map(|buffered| buffered.bytes.clone());
+        }
+        self.request_body()
+            .await
+            .read_bytes_with_max_len(max as u64)
+            .await
     }
 
     /**
-    insert a header name and value/values into the response headers
-    and return the conn. for a slight performance improvement, use a
-    [`KnownHeaderName`](crate::KnownHeaderName) as the first argument instead of a
-    str.
+    Reads the request body into memory so that it can be read again
+    by later handlers, but only if it is no longer than `threshold`
+    bytes. Once buffered, [`Conn::request_body_bytes`],
+    [`Conn::request_body_string`] and their `_with_limit` variants
+    return the buffered body rather than reading from the transport,
+    so any number of handlers can read it. [`Conn::request_body`]
+    still reads from the transport, and is empty once the body has
+    been buffered.
+
+    The decision to buffer consults the declared content-length: a
+    body that declares a content-length longer than `threshold` is
+    not read at all, and this returns `Ok(None)` so that the body
+    can still be streamed. A body of unknown length, such as a
+    chunked body, is read up to `threshold` bytes, and if it turns
+    out to be longer this returns
+    [`Error::ReceivedBodyTooLong`](trillium_http::Error::ReceivedBodyTooLong)
+    without buffering. The bytes already read are discarded, so the
+    body cannot be streamed after that error. If the body has already
+    been buffered, this returns it regardless of `threshold`.
+
+    # Errors
+
+    This will return an error variant if there is an IO failure on
+    the underlying transport, if a body of unknown length is longer
+    than `threshold`, or if the request's framing headers are
+    invalid, as described in
+    [`Conn::request_content_length`].
+
+    # Examples
 
     ```
     use trillium_testing::prelude::*;
-    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
-        conn.with_header("content-type", "application/html")
-    });
+    let mut conn = post("/").with_request_body("request body").on(&());
+
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.buffer_request_body(1024).await.unwrap(), Some(&b"request body"[..]));
+    assert_eq!(conn.request_body_string().await.unwrap(), "request body");
+    assert_eq!(conn.request_body_string().await.unwrap(), "request body");
+    # });
+
+    let mut conn = post("/").with_request_body("request body").on(&());
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.buffer_request_body(5).await.unwrap(), None);
+    assert_eq!(conn.request_body_string().await.unwrap(), "request body");
+    # });
     ```
     */
-    #[must_use]
-    pub fn with_header(
-        mut self,
-        header_name: impl Into<HeaderName<'static>>,
-        header_value: impl Into<HeaderValues>,
-    ) -> Self {
-        self.headers_mut().insert(header_name, header_value);
-        self
+    pub async fn buffer_request_body(
+        &mut self,
+        threshold: u64,
+    ) -> trillium_http::Result<Option<&[u8]>> {
+        if self.state::<BufferedRequestBody>().is_none() {
+            let declared_too_long = self
+                .request_content_length()?
+                .is_some_and(|len| len > threshold);
+
+            if declared_too_long {
+                return Ok(None);
+            }
+
+            let request_body = self.request_body().await;
+            let encoding = request_body.encoding();
+            let bytes = request_body.read_bytes_with_max_len(threshold).await?;
+            self.set_state(BufferedRequestBody { bytes, encoding });
+        }
+
+        Ok(self.buffered_request_body())
+    }
+
+    /// returns the request body if it has been buffered with
+    /// [`Conn::buffer_request_body`]
+    pub fn buffered_request_body(&self) -> Option<&[u8]> {
+        self.state::<BufferedRequestBody>()
+            .map(|buffered| &*buffered.bytes)
     }
 
     /**
-    returns the path for this request. note that this may not
-    represent the entire http request path if running nested
-    routers.
+    returns the trailers that followed a chunked request body, if any
+    were sent. these are only available once the request body has
+    been read to the end.
     */
-    pub fn path(&self) -> &str {
-        self.path.last().map_or_else(|| self.inner.path(), |p| &**p)
+    pub fn request_trailers(&self) -> Option<&Headers> {
+        self.inner.request_trailers()
     }
 
     /**
-    returns query part of the request path
+    Reads the content of a request body as bytes, as with
+    [`Conn::request_body_bytes`], and verifies it against any checksum
+    the client declared in either a trailer or a request header. This
+    requires the `checksum` crate feature to be enabled.
+
+    The checksums currently supported are those used by s3-compatible
+    uploads, `x-amz-checksum-crc32` and `x-amz-checksum-sha256`, each
+    of which is the base64 encoding of the digest of the entire body.
+    Other checksums are ignored.
+
+    # Errors
+
+    This will return an error variant if there is an IO failure on the
+    underlying transport, or
+    [`Error::ChecksumMismatch`](trillium_http::Error::ChecksumMismatch)
+    if a declared checksum does not match the body.
+
+    # Examples
 
     ```
     use trillium_testing::prelude::*;
-    let conn = get("/a/b?c&d=e").on(&());
-    assert_eq!(conn.querystring(), "c&d=e");
+    let mut conn = post("/")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_header("x-amz-trailer", "x-amz-checksum-crc32")
+        .with_request_body("5\r\nhello\r\n0\r\nx-amz-checksum-crc32: NhCmhg==\r\n\r\n")
+        .on(&());
 
-    let conn = get("/a/b").on(&());
-    assert_eq!(conn.querystring(), "");
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.request_body_bytes_verified().await.unwrap(), b"hello");
+    assert_eq!(
+        conn.request_trailers().unwrap().get_str("x-amz-checksum-crc32"),
+        Some("NhCmhg==")
+    );
+    # });
     ```
     */
-    pub fn querystring(&self) -> &str {
-        self.inner.querystring()
+    #[cfg(feature = "checksum")]
+    pub async fn request_body_bytes_verified(&mut self) -> trillium_http::Result<Vec<u8>> {
+        let bytes = self.request_body_bytes().await?;
+        crate::checksum::verify(&bytes, self.request_trailers(), self.request_headers())?;
+        Ok(bytes)
     }
 
     /**
-    sets the `halted` attribute of this conn, preventing later
-    processing in a given tuple handler. returns
-    the conn for fluent chaining
+    sets the `Content-Digest` response header described in [rfc
+    9530](https://www.rfc-editor.org/rfc/rfc9530) to a digest of the
+    response body, which must already be set and held in memory.
+    This requires the `checksum` crate feature to be enabled.
+
+    The algorithm is the one the client most prefers in its
+    `Want-Content-Digest` request header among those supported,
+    `sha-256` and `sha-512`, or `sha-256` if the client did not send
+    one. If the client only wants unsupported algorithms, or the
+    body is streaming or absent, no header is set. If the client
+    also sent `Want-Repr-Digest`, a `Repr-Digest` is set in the same
+    way, except for a `206 Partial Content` response, whose content is
+    not the full representation.
+
+    The digest is of the body at the time this is called, so it
+    must be called after anything that changes the body. The
+    `trillium-compression` handler compresses the body after the
+    handler that called this has run, and recomputes the digest
+    headers for the compressed body with its `checksum` feature, or
+    removes them without it.
+
+    # Examples
 
     ```
     use trillium_testing::prelude::*;
-    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
-        conn.halt()
-    });
-
-    assert!(conn.is_halted());
+    let handler = |conn: Conn| async move { conn.ok("hello").with_content_digest() };
+
+    assert_headers!(
+        get("/").on(&handler),
+        "content-digest" => "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:"
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header("want-content-digest", "sha-256=1, sha-512=3")
+            .on(&handler),
+        "content-digest" => "sha-512=:m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==:"
+    );
     ```
     */
+    #[cfg(feature = "checksum")]
     #[must_use]
-    pub fn halt(mut self) -> Self {
-        self.set_halted(true);
+    pub fn with_content_digest(mut self) -> Self {
+        let Some(body) = self.inner.response_body().and_then(Body::static_bytes) else {
+            return self;
+        };
+
+        let request_headers = self.inner.request_headers();
+        let content_digest =
+            crate::checksum::digest_header(body, request_headers.get_str("want-content-digest"));
+
+        let repr_digest = request_headers
+            .get_str("want-repr-digest")
+            .filter(|_| self.status() != Some(Status::PartialContent))
+            .and_then(|want| crate::checksum::digest_header(body, Some(want)));
+
+        if let Some(content_digest) = content_digest {
+            self.response_headers_mut()
+                .insert("content-digest", content_digest);
+        }
+
+        if let Some(repr_digest) = repr_digest {
+            self.response_headers_mut()
+                .insert("repr-digest", repr_digest);
+        }
+
         self
     }
 
     /**
-    sets the `halted` attribute of this conn. see [`Conn::halt`].
+    reads the request body and deserializes it as json. this requires
+    the `json` crate feature to be enabled.
+
+    # Errors
+
+    returns [`JsonError::Http`] if the body cannot be read or is not
+    utf8, and [`JsonError::Json`] if it cannot be deserialized as `T`
+    */
+    #[cfg(feature = "json")]
+    pub async fn request_body_json<T>(&mut self) -> Result<T, JsonError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let body = self.request_body_string().await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /**
+    reads the request body and deserializes it as an
+    `application/x-www-form-urlencoded` form, such as one submitted by
+    an html form. as with `Conn::query`, a repeated field can be
+    deserialized into a sequence. this requires the `json` crate
+    feature to be enabled.
 
     ```
     use trillium_testing::prelude::*;
-    let mut conn = get("/").on(&());
-    assert!(!conn.is_halted());
-    conn.set_halted(true);
-    assert!(conn.is_halted());
+    #[derive(serde::Deserialize)]
+    struct Signup {
+        email: String,
+        interests: Vec<String>,
+    }
+
+    let handler = |mut conn: Conn| async move {
+        let signup: Signup = conn.request_body_form().await.unwrap();
+        let body = format!("{}: {}", signup.email, signup.interests.join(", "));
+        conn.ok(body)
+    };
+
+    let conn = post("/")
+        .with_request_header("content-type", "application/x-www-form-urlencoded")
+        .with_request_body("email=who%40example.com&interests=rust&interests=http")
+        .on(&handler);
+    assert_ok!(conn, "who@example.com: rust, http");
     ```
+
+    # Errors
+
+    returns [`FormError::ContentType`] without reading the body if the
+    request content-type is not `application/x-www-form-urlencoded`,
+    [`FormError::Http`] if the body cannot be read or is not utf8, and
+    [`FormError::Form`] if it cannot be deserialized as `T`
     */
-    pub fn set_halted(&mut self, halted: bool) {
-        self.halted = halted;
-    }
+    #[cfg(feature = "json")]
+    pub async fn request_body_form<T>(&mut self) -> Result<T, FormError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let is_form = self
+            .request_headers()
+            .get_str(KnownHeaderName::ContentType)
+            .and_then(|content_type| content_type.split(';').next())
+            .is_some_and(|essence| {
+                essence
+                    .trim()
+                    .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+            });
+
+        if !is_form {
+            return Err(FormError::ContentType);
+        }
 
-    /// retrieves the halted state of this conn.  see [`Conn::halt`].
-    pub const fn is_halted(&self) -> bool {
-        self.halted
+        let body = self.request_body_string().await?;
+        Ok(crate::query::from_str(&body)?)
     }
 
-    /// predicate function to indicate whether the connection is
-    /// secure. note that this does not necessarily indicate that the
-    /// transport itself is secure, as it may indicate that
-    /// `trillium_http` is behind a trusted reverse proxy that has
-    /// terminated tls and provided appropriate headers to indicate
-    /// this.
-    pub fn is_secure(&self) -> bool {
-        self.inner.is_secure()
-    }
+    /**
+    Streams the request body to each of the provided async writers,
+    without buffering the whole body in memory. Each chunk is written
+    to every writer in order before the next chunk is read from the
+    transport, so reading is paced by the slowest writer. Returns the
+    number of bytes read from the request body.
 
-    /// returns an immutable reference to the inner
-    /// [`trillium_http::Conn`]. please open an issue if you need to do
-    /// this in application code.
-    ///
-    /// stability note: hopefully this can go away at some point, but
+    # Errors
+
+    This will return an error variant if:
+
+    * there is an io error when reading from the underlying transport
+    * more than `max_len` bytes are received, as
+      [`Error::ReceivedBodyTooLong`]. Writers may have already
+      received up to `max_len` bytes when this happens.
+    * any of the writers fails, as
+      [`Error::ReceivedBodyDestination`], which includes the index
+      of the writer that failed
+
+    # Examples
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = post("/").with_request_body("request body").on(&());
+
+    # trillium_testing::block_on(async {
+    let mut destinations = [Vec::new(), Vec::new()];
+    let bytes = conn.stream_request_body_to_all(&mut destinations, 1024).await.unwrap();
+    assert_eq!(bytes, 12);
+    assert_eq!(destinations[0], b"request body");
+    assert_eq!(destinations[1], b"request body");
+    # });
+
+    let mut conn = post("/").with_request_body("request body").on(&());
+    # trillium_testing::block_on(async {
+    let mut destinations = [Vec::new()];
+    assert!(conn.stream_request_body_to_all(&mut destinations, 5).await.is_err());
+    # });
+    ```
+    */
+    pub async fn stream_request_body_to_all<W>(
+        &mut self,
+        writers: &mut [W],
+        max_len: u64,
+    ) -> trillium_http::Result<u64>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut request_body = self.request_body().await;
+        let mut buf = vec![0; 4096];
+        let mut total = 0;
+
+        loop {
+            let bytes = request_body.read(&mut buf).await?;
+            if bytes == 0 {
+                break;
+            }
+
+            total += bytes as u64;
+            if total > max_len {
+                return Err(Error::ReceivedBodyTooLong(max_len));
+            }
+
+            for (index, writer) in writers.iter_mut().enumerate() {
+                writer
+                    .write_all(&buf[..bytes])
+                    .await
+                    .map_err(|source| Error::ReceivedBodyDestination { index, source })?;
+            }
+        }
+
+        for (index, writer) in writers.iter_mut().enumerate() {
+            writer
+                .flush()
+                .await
+                .map_err(|source| Error::ReceivedBodyDestination { index, source })?;
+        }
+
+        Ok(total)
+    }
+
+    /**
+    the declared length of the request body, before it is read. this
+    is `None` for a chunked request body, the length of which is not
+    known until it has been read, and `Some(0)` for a request without
+    a body. this can be used to decide whether to read a body into
+    memory at all.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = post("/").with_request_body("hello").on(&());
+    assert_eq!(conn.request_content_length().unwrap(), Some(5));
+
+    let conn = get("/").on(&());
+    assert_eq!(conn.request_content_length().unwrap(), Some(0));
+    ```
+
+    # Errors
+
+    see [`trillium_http::Conn::request_content_length`]
+    */
+    pub fn request_content_length(&self) -> trillium_http::Result<Option<u64>> {
+        self.inner.request_content_length()
+    }
+
+    /**
+    whether the request body has been read to its end, or there is
+    no request body to read. see
+    [`trillium_http::Conn::request_body_fully_read`]
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = post("/").with_request_body("hello").on(&());
+    assert!(!conn.request_body_fully_read());
+
+    let conn = post("/").with_request_body("hello").on(&|mut conn: Conn| async move {
+        let body = conn.request_body_string().await.unwrap();
+        conn.ok(body)
+    });
+    assert!(conn.request_body_fully_read());
+    ```
+    */
+    pub fn request_body_fully_read(&self) -> bool {
+        self.inner.request_body_fully_read()
+    }
+
+    /**
+    if there is a response body for this conn and it has a known
+    fixed length, it is returned from this function
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.with_body("hello")
+    });
+
+    assert_eq!(conn.response_len(), Some(5));
+    ```
+    */
+    pub fn response_len(&self) -> Option<u64> {
+        self.inner.response_body().and_then(Body::len)
+    }
+
+    /**
+    whether a response body has been set for this conn, including a
+    streaming body of unknown length, for which
+    [`Conn::response_len`] is `None`. unlike
+    [`Conn::take_response_body`], this leaves the body in place, so
+    a handler can decide whether to provide a default body without
+    clobbering one that is already set.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&());
+    assert!(!conn.has_response_body());
+
+    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
+        let reader = futures_lite::io::Cursor::new("hello");
+        conn.with_body_stream(reader, None)
+    });
+    assert_eq!(conn.response_len(), None);
+    assert!(conn.has_response_body());
+    ```
+    */
+    pub fn has_response_body(&self) -> bool {
+        self.inner.response_body().is_some()
+    }
+
+    /**
+    returns the request method for this conn.
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&());
+
+    assert_eq!(conn.method(), Method::Get);
+    ```
+
+    */
+    pub const fn method(&self) -> Method {
+        self.inner.method()
+    }
+
+    /**
+    predicate function to indicate whether the request method is
+    GET. there is a corresponding predicate for each of the standard
+    http methods.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&());
+    assert!(conn.is_get());
+    assert!(!conn.is_post());
+
+    let conn = post("/").on(&());
+    assert!(conn.is_post());
+    assert!(!conn.is_get());
+    ```
+    */
+    pub const fn is_get(&self) -> bool {
+        matches!(self.inner.method(), Method::Get)
+    }
+
+    /// predicate function to indicate whether the request method is HEAD
+    pub const fn is_head(&self) -> bool {
+        matches!(self.inner.method(), Method::Head)
+    }
+
+    /// predicate function to indicate whether the request method is POST
+    pub const fn is_post(&self) -> bool {
+        matches!(self.inner.method(), Method::Post)
+    }
+
+    /// predicate function to indicate whether the request method is PUT
+    pub const fn is_put(&self) -> bool {
+        matches!(self.inner.method(), Method::Put)
+    }
+
+    /// predicate function to indicate whether the request method is DELETE
+    pub const fn is_delete(&self) -> bool {
+        matches!(self.inner.method(), Method::Delete)
+    }
+
+    /// predicate function to indicate whether the request method is CONNECT
+    pub const fn is_connect(&self) -> bool {
+        matches!(self.inner.method(), Method::Connect)
+    }
+
+    /// predicate function to indicate whether the request method is OPTIONS
+    pub const fn is_options(&self) -> bool {
+        matches!(self.inner.method(), Method::Options)
+    }
+
+    /// predicate function to indicate whether the request method is TRACE
+    pub const fn is_trace(&self) -> bool {
+        matches!(self.inner.method(), Method::Trace)
+    }
+
+    /// predicate function to indicate whether the request method is PATCH
+    pub const fn is_patch(&self) -> bool {
+        matches!(self.inner.method(), Method::Patch)
+    }
+
+    /**
+    returns the [`AllowedMethods`] that have been registered for this
+    conn with [`Conn::add_allowed_methods`], if any
+    */
+    pub fn allowed_methods(&self) -> Option<&AllowedMethods> {
+        self.state()
+    }
+
+    /**
+    registers methods that are supported at the current request path,
+    merging them with any previously registered methods. this is
+    intended to be called by routers, so that handlers like
+    [`AutoOptions`](crate::AutoOptions) can respond without knowing about the router.
+
+    ```
+    use trillium::Method;
+    use trillium_testing::prelude::*;
+
+    let mut conn = get("/").on(&());
+    assert!(conn.allowed_methods().is_none());
+
+    conn.add_allowed_methods([Method::Get]);
+    conn.add_allowed_methods([Method::Post, Method::Get]);
+    assert_eq!(conn.allowed_methods().unwrap().to_string(), "GET, POST");
+    ```
+    */
+    pub fn add_allowed_methods(&mut self, methods: impl IntoIterator<Item = Method>) {
+        match self.state_mut::<AllowedMethods>() {
+            Some(allowed) => allowed.extend(methods),
+            None => {
+                self.set_state(methods.into_iter().collect::<AllowedMethods>());
+            }
+        }
+    }
+
+    /**
+    sets the `Allow` response header to the provided methods
+
+    ```
+    use trillium::Method;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: trillium::Conn| async move {
+        conn.with_allow_header([Method::Put, Method::Get]).ok("ok")
+    };
+
+    assert_ok!(get("/").on(&handler), "ok", "allow" => "GET, PUT");
+    ```
+    */
+    #[must_use]
+    pub fn with_allow_header(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        let allowed = methods.into_iter().collect::<AllowedMethods>();
+        self.response_headers_mut()
+            .insert(KnownHeaderName::Allow, allowed.to_string());
+        self
+    }
+
+    /**
+    responds with a 405 Method Not Allowed status and an `Allow`
+    header listing the provided methods, and halts the conn
+
+    ```
+    use trillium::Method;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: trillium::Conn| async move {
+        if conn.is_get() {
+            conn.ok("ok")
+        } else {
+            conn.with_method_not_allowed([Method::Get, Method::Head])
+        }
+    };
+
+    let mut conn = post("/").on(&handler);
+    assert_status!(&conn, 405);
+    assert_headers!(&mut conn, "allow" => "GET, HEAD");
+    ```
+    */
+    #[must_use]
+    pub fn with_method_not_allowed(self, allowed: impl IntoIterator<Item = Method>) -> Self {
+        self.with_allow_header(allowed)
+            .with_status(Status::MethodNotAllowed)
+            .halt()
+    }
+
+    /**
+    sets the `Accept-Patch` response header to the provided media
+    types, advertising the patch document formats that are accepted
+    for PATCH requests to this resource. this is usually set on
+    responses to OPTIONS requests, and on 415 responses with
+    [`Conn::with_unsupported_patch_format`].
+
+    ```
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: trillium::Conn| async move {
+        conn.with_accept_patch(&["application/json-patch+json", "application/merge-patch+json"])
+            .ok("ok")
+    };
+
+    assert_ok!(
+        get("/").on(&handler),
+        "ok",
+        "accept-patch" => "application/json-patch+json, application/merge-patch+json"
+    );
+    ```
+    */
+    #[must_use]
+    pub fn with_accept_patch(
+        mut self,
+        media_types: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Self {
+        let accept_patch = media_types
+            .into_iter()
+            .map(|media_type| media_type.as_ref().trim().to_string())
+            .filter(|media_type| !media_type.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.response_headers_mut()
+            .insert(KnownHeaderName::AcceptPatch, accept_patch);
+        self
+    }
+
+    /**
+    returns a named parameter of the request's `Content-Type` header,
+    such as the `charset` of a text type or the `boundary` of a
+    multipart body. parameter names are case-insensitive, and quoted
+    values are unquoted. see
+    [`header_param`](trillium_http::header_param) for details.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = post("/")
+        .with_request_header("content-type", r#"text/plain; Charset="utf-8""#)
+        .on(&());
+    assert_eq!(conn.request_content_type_param("charset").as_deref(), Some("utf-8"));
+    assert_eq!(conn.request_content_type_param("boundary"), None);
+    ```
+    */
+    pub fn request_content_type_param(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.request_headers()
+            .get_str(KnownHeaderName::ContentType)
+            .and_then(|content_type| trillium_http::header_param(content_type, name))
+    }
+
+    /**
+    responds with a 415 Unsupported Media Type status and an
+    `Accept-Patch` header listing the provided media types, and halts
+    the conn
+
+    ```
+    use trillium::KnownHeaderName;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: trillium::Conn| async move {
+        let formats = ["application/merge-patch+json"];
+        match conn.request_headers().get_str(KnownHeaderName::ContentType) {
+            Some(content_type) if formats.contains(&content_type) => conn.ok("patched"),
+            _ => conn.with_unsupported_patch_format(formats),
+        }
+    };
+
+    let mut conn = patch("/").with_request_header("content-type", "text/plain").on(&handler);
+    assert_status!(&conn, 415);
+    assert_headers!(&mut conn, "accept-patch" => "application/merge-patch+json");
+    ```
+    */
+    #[must_use]
+    pub fn with_unsupported_patch_format(
+        self,
+        media_types: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Self {
+        self.with_accept_patch(media_types)
+            .with_status(Status::UnsupportedMediaType)
+            .halt()
+    }
+
+    /**
+    parses the request's `Priority` header, if there is one. see
+    [`Priority`] for the defaults that apply to absent or invalid
+    parameters. a request without the header also has the default
+    priority, which is available with `unwrap_or_default`.
+
+    ```
+    use trillium::Priority;
+    use trillium_testing::prelude::*;
+
+    let conn = get("/").with_request_header("priority", "u=0").on(&());
+    assert_eq!(conn.request_priority(), Some(Priority::new(0, false)));
+
+    let conn = get("/").on(&());
+    assert_eq!(conn.request_priority(), None);
+    assert_eq!(conn.request_priority().unwrap_or_default().urgency(), 3);
+    ```
+    */
+    pub fn request_priority(&self) -> Option<Priority> {
+        self.request_headers()
+            .get_str("priority")
+            .and_then(|priority| priority.parse().ok())
+    }
+
+    /**
+    picks the best of the offered media types for the request's
+    `Accept` header, following [rfc 9110 section
+    12.5.1](https://www.rfc-editor.org/rfc/rfc9110#section-12.5.1).
+    each offered type takes the quality of the most specific media
+    range that matches it, such that `text/html` is preferred to
+    `text/*`, which is preferred to `*/*`. the offered type with the
+    highest quality above zero is returned, and the earliest offered
+    type wins a tie. a request without an `Accept` header accepts
+    anything, and so receives the first offered type.
+
+    this returns `None` if none of the offered types are acceptable,
+    in which case a `406 Not Acceptable` response is appropriate. a
+    response that depends on this should also include `Accept` in its
+    `Vary` header.
+
+    ```
+    use trillium_testing::prelude::*;
+
+    let offered = ["application/json", "text/html"];
+
+    let conn = get("/")
+        .with_request_header("accept", "text/html, application/json;q=0.9")
+        .on(&());
+    assert_eq!(conn.negotiate_content_type(&offered), Some("text/html"));
+
+    let conn = get("/")
+        .with_request_header("accept", "text/*;q=0.5, */*;q=0.1")
+        .on(&());
+    assert_eq!(conn.negotiate_content_type(&offered), Some("text/html"));
+
+    let conn = get("/").with_request_header("accept", "image/png").on(&());
+    assert_eq!(conn.negotiate_content_type(&offered), None);
+
+    let conn = get("/").on(&());
+    assert_eq!(conn.negotiate_content_type(&offered), Some("application/json"));
+    ```
+    */
+    pub fn negotiate_content_type<'a>(&self, offered: &[&'a str]) -> Option<&'a str> {
+        content_negotiation::negotiate(
+            self.request_headers().get_str(KnownHeaderName::Accept),
+            offered,
+        )
+    }
+
+    /**
+    picks the best of the offered [`Mime`](crate::mime::Mime)s for
+    the request's `Accept` header. see
+    [`Conn::negotiate_content_type`] for how the offered types are
+    ranked.
+
+    ```
+    use trillium::mime::{APPLICATION_JSON, TEXT_HTML};
+    use trillium_testing::prelude::*;
+
+    let offered = [APPLICATION_JSON, TEXT_HTML];
+
+    let conn = get("/")
+        .with_request_header("accept", "text/html, application/json;q=0.9")
+        .on(&());
+    assert_eq!(conn.negotiate_mime(&offered), Some(TEXT_HTML));
+
+    let conn = get("/").with_request_header("accept", "image/png").on(&());
+    assert_eq!(conn.negotiate_mime(&offered), None);
+    ```
+    */
+    #[cfg(feature = "mime")]
+    pub fn negotiate_mime(&self, offered: &[mime::Mime]) -> Option<mime::Mime> {
+        let offered_strs: Vec<String> = offered.iter().map(ToString::to_string).collect();
+        let offered_strs: Vec<&str> = offered_strs.iter().map(String::as_str).collect();
+        let negotiated = self.negotiate_content_type(&offered_strs)?;
+        offered
+            .iter()
+            .zip(&offered_strs)
+            .find(|(_, offered_str)| **offered_str == negotiated)
+            .map(|(mime, _)| mime.clone())
+    }
+
+    /**
+    whether the client has asked for reduced data usage with a
+    `Save-Data: on` request header, which browsers send when a data
+    saver mode is enabled. handlers can use this to serve a lighter
+    response, such as lower resolution images. the token is compared
+    case-insensitively.
+
+    because a response that branches on this is only valid for
+    requests with the same preference, this also adds `Save-Data` to
+    the `Vary` response header.
+
+    ```
+    use trillium_testing::prelude::*;
+
+    let mut conn = get("/").with_request_header("save-data", "On").on(&());
+    assert!(conn.save_data());
+    assert_headers!(&conn, "vary" => "Save-Data");
+
+    let mut conn = get("/").on(&());
+    conn.response_headers_mut().insert("vary", "Accept-Encoding");
+    assert!(!conn.save_data());
+    assert!(!conn.save_data());
+    assert_headers!(&conn, "vary" => "Accept-Encoding, Save-Data");
+    ```
+    */
+    pub fn save_data(&mut self) -> bool {
+        self.add_vary(KnownHeaderName::SaveData.as_ref());
+        self.request_headers()
+            .get_str(KnownHeaderName::SaveData)
+            .and_then(|save_data| save_data.split(';').next())
+            .is_some_and(|token| token.trim().eq_ignore_ascii_case("on"))
+    }
+
+    /**
+    adds a header name to the `Vary` response header, unless it is
+    already listed, compared case-insensitively, or the response
+    varies on `*`. any existing values are combined into one, so
+    several handlers can each add to the `Vary` header without
+    replacing each other's names.
+
+    ```
+    use trillium_testing::prelude::*;
+
+    let mut conn = get("/").on(&());
+    conn.add_vary("accept");
+    conn.add_vary("Accept");
+    assert_headers!(&conn, "vary" => "accept");
+    ```
+    */
+    pub fn add_vary(&mut self, header_name: &str) {
+        let headers = self.response_headers_mut();
+        let mut vary: Vec<String> = headers
+            .get_values(KnownHeaderName::Vary)
+            .map(|values| values.iter().map(ToString::to_string).collect())
+            .unwrap_or_default();
+
+        let listed = vary
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .any(|name| name == "*" || name.eq_ignore_ascii_case(header_name));
+
+        if !listed {
+            vary.push(header_name.to_string());
+            headers.insert(KnownHeaderName::Vary, vary.join(", "));
+        }
+    }
+
+    /**
+    chainable alternative to [`Conn::add_vary`]
+
+    ```
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: Conn| async move {
+        conn.vary("accept").vary("accept-encoding").vary("Accept")
+    };
+    assert_headers!(get("/").on(&handler), "vary" => "accept, accept-encoding");
+    ```
+    */
+    #[must_use]
+    pub fn vary(mut self, header_name: &str) -> Self {
+        self.add_vary(header_name);
+        self
+    }
+
+    /**
+    parses the request's `Range` header against a representation of
+    `total_len` bytes. see [`parse_range`](crate::parse_range) for
+    details. if there is no `Range` header, this is
+    [`RangeSet::Full`].
+
+    ```
+    use trillium::RangeSet;
+    use trillium_testing::prelude::*;
+
+    let conn = get("/").with_request_header("range", "bytes=-5").on(&());
+    assert_eq!(conn.request_range(20), RangeSet::Satisfiable(vec![15..20]));
+
+    let conn = get("/").on(&());
+    assert_eq!(conn.request_range(20), RangeSet::Full);
+    ```
+    */
+    pub fn request_range(&self, total_len: u64) -> RangeSet {
+        self.request_headers()
+            .get_str(KnownHeaderName::Range)
+            .map_or(RangeSet::Full, |range| crate::parse_range(range, total_len))
+    }
+
+    /// responds with a 416 Range Not Satisfiable status and a
+    /// `Content-Range` header of `bytes */{total_len}`, and halts the
+    /// conn
+    ///
+    /// ```
+    /// use trillium::RangeSet;
+    /// use trillium_testing::prelude::*;
+    ///
+    /// let handler = |conn: Conn| async move {
+    ///     let body = "0123456789";
+    ///     match conn.request_range(body.len() as u64) {
+    ///         RangeSet::Unsatisfiable => conn.with_range_not_satisfiable(body.len() as u64),
+    ///         _ => conn.ok(body),
+    ///     }
+    /// };
+    ///
+    /// let mut conn = get("/").with_request_header("range", "bytes=50-").on(&handler);
+    /// assert_status!(&conn, 416);
+    /// assert_headers!(&mut conn, "content-range" => "bytes */10");
+    /// ```
+    #[must_use]
+    pub fn with_range_not_satisfiable(self, total_len: u64) -> Self {
+        self.with_header(
+            KnownHeaderName::ContentRange,
+            format!("bytes */{total_len}"),
+        )
+        .with_status(Status::RequestedRangeNotSatisfiable)
+        .halt()
+    }
+
+    /**
+    responds to the request's `Range` header from a seekable source of
+    `total_len` bytes, such as a file, and halts the conn. a single
+    satisfiable range is sent with a 206 Partial Content status and a
+    `Content-Range` header, after seeking to its start. an
+    unsatisfiable range is responded to as with
+    [`Conn::with_range_not_satisfiable`]. otherwise, including when the
+    request has no `Range` header or asks for more than one range, the
+    full body is sent with a 200 Ok status.
+
+    ```
+    use trillium_testing::{futures_lite::io::Cursor, prelude::*};
+    let handler = |conn: Conn| async move {
+        conn.with_seekable_range_body(Cursor::new(b"0123456789"), 10)
+    };
+
+    let mut conn = get("/").with_request_header("range", "bytes=2-4").on(&handler);
+    assert_status!(&conn, 206);
+    assert_headers!(&mut conn, "content-range" => "bytes 2-4/10");
+    assert_body!(&mut conn, "234");
+
+    assert_ok!(get("/").on(&handler), "0123456789", "accept-ranges" => "bytes");
+    ```
+    */
+    #[must_use]
+    pub fn with_seekable_range_body<R>(self, reader: R, total_len: u64) -> Self
+    where
+        R: AsyncRead + AsyncSeek + Unpin + Send + Sync + 'static,
+    {
+        self.with_range_body(total_len, u64::MAX, |start, len| {
+            Body::new_streaming(SeekingReader::new(reader, start).take(len), Some(len))
+        })
+    }
+
+    /**
+    responds to the request's `Range` header from a streaming source of
+    `total_len` bytes that cannot seek, such as generated content, and
+    halts the conn. this behaves like
+    [`Conn::with_seekable_range_body`], except that the start of a
+    range is reached by reading and discarding the bytes before it.
+
+    because skipping costs as much as sending, a range starting more
+    than `max_skip` bytes in is ignored and the full body is sent with
+    a 200 Ok status. this keeps a small request for the end of a large
+    generated body from doing all of the work of sending it.
+
+    ```
+    use trillium_testing::{futures_lite::io::Cursor, prelude::*};
+    let handler = |conn: Conn| async move {
+        conn.with_streaming_range_body(Cursor::new(b"0123456789"), 10, 5)
+    };
+
+    let mut conn = get("/").with_request_header("range", "bytes=5-").on(&handler);
+    assert_status!(&conn, 206);
+    assert_body!(&mut conn, "56789");
+
+    let mut conn = get("/").with_request_header("range", "bytes=6-").on(&handler);
+    assert_status!(&conn, 200);
+    assert_body!(&mut conn, "0123456789");
+    ```
+    */
+    #[must_use]
+    pub fn with_streaming_range_body(
+        self,
+        reader: impl AsyncRead + Send + Sync + 'static,
+        total_len: u64,
+        max_skip: u64,
+    ) -> Self {
+        self.with_range_body(total_len, max_skip, |start, len| {
+            Body::new_streaming(SkippingReader::new(reader, start).take(len), Some(len))
+        })
+    }
+
+    /**
+    applies the request's `Range` header to the response body that has
+    already been set, if it has a known length and the response status
+    is 200 Ok or not yet set. a single satisfiable range is sent with a
+    206 Partial Content status and a `Content-Range` header, and only
+    that span of the body. an unsatisfiable range is responded to with
+    a 416 Range Not Satisfiable status and no body. otherwise, including
+    when the request has no `Range` header or asks for more than one
+    range, the full body is sent unchanged. unlike
+    [`Conn::with_seekable_range_body`], this does not halt the conn.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        conn.with_body("0123456789").with_range_support()
+    };
+
+    let mut conn = get("/").with_request_header("range", "bytes=-3").on(&handler);
+    assert_status!(&conn, 206);
+    assert_headers!(&mut conn, "content-range" => "bytes 7-9/10");
+    assert_body!(&mut conn, "789");
+
+    let conn = get("/").with_request_header("range", "bytes=20-").on(&handler);
+    assert_status!(&conn, 416);
+
+    let conn = get("/").with_request_header("range", "bytes=0-1,4-5");
+    assert_body!(conn.on(&handler), "0123456789");
+    ```
+    */
+    #[must_use]
+    pub fn with_range_support(mut self) -> Self {
+        if !matches!(self.status(), None | Some(Status::Ok)) {
+            return self;
+        }
+
+        let Some(body) = self.take_response_body() else {
+            return self;
+        };
+
+        let Some(total_len) = body.len() else {
+            return self.with_body(body);
+        };
+
+        self.response_headers_mut()
+            .insert(KnownHeaderName::AcceptRanges, "bytes");
+        match self.request_range(total_len) {
+            RangeSet::Unsatisfiable => self
+                .with_header(
+                    KnownHeaderName::ContentRange,
+                    format!("bytes */{total_len}"),
+                )
+                .with_status(Status::RequestedRangeNotSatisfiable),
+
+            RangeSet::Satisfiable(ranges) if ranges.len() == 1 => {
+                let range = &ranges[0];
+                let len = range.end - range.start;
+                let body = if body.is_static() {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let bytes = &body.static_bytes().unwrap_or_default()
+                        [range.start as usize..range.end as usize];
+                    Body::new_static(bytes.to_vec())
+                } else {
+                    Body::new_streaming(
+                        SkippingReader::new(body.into_reader(), range.start).take(len),
+                        Some(len),
+                    )
+                };
+
+                self.with_header(
+                    KnownHeaderName::ContentRange,
+                    crate::content_range(range, total_len),
+                )
+                .with_status(Status::PartialContent)
+                .with_body(body)
+            }
+
+            _ => self.with_body(body),
+        }
+    }
+
+    fn with_range_body(
+        self,
+        total_len: u64,
+        max_skip: u64,
+        body: impl FnOnce(u64, u64) -> Body,
+    ) -> Self {
+        let conn = self.with_header(KnownHeaderName::AcceptRanges, "bytes");
+        match conn.request_range(total_len) {
+            RangeSet::Unsatisfiable => conn.with_range_not_satisfiable(total_len),
+
+            RangeSet::Satisfiable(ranges) if ranges.len() == 1 && ranges[0].start <= max_skip => {
+                let range = &ranges[0];
+                conn.with_header(
+                    KnownHeaderName::ContentRange,
+                    crate::content_range(range, total_len),
+                )
+                .with_status(Status::PartialContent)
+                .with_body(body(range.start, range.end - range.start))
+                .halt()
+            }
+
+            _ => conn.ok(body(0, total_len)),
+        }
+    }
+
+    /**
+    advertises alternative services for this origin with an
+    `Alt-Svc` response header, replacing any previously set. Each
+    alternative is formatted as described on [`AltSvc`], in order of
+    preference. An empty iterator sends `Alt-Svc: clear`, which asks
+    the client to forget any alternatives it has cached for this
+    origin.
+
+    ```
+    use std::time::Duration;
+    use trillium::AltSvc;
+    use trillium_testing::prelude::*;
+
+    let handler = |conn: Conn| async move {
+        conn.with_alt_svc([
+            AltSvc::new("h3", ":443").with_max_age(Duration::from_secs(86400)),
+            AltSvc::new("h2", "alt.example.com:443"),
+        ])
+        .ok("ok")
+    };
+
+    assert_headers!(
+        get("/").on(&handler),
+        "alt-svc" => r#"h3=":443"; ma=86400, h2="alt.example.com:443""#
+    );
+
+    let clear = |conn: Conn| async move { conn.with_alt_svc([]).ok("ok") };
+    assert_headers!(get("/").on(&clear), "alt-svc" => "clear");
+    ```
+    */
+    #[must_use]
+    pub fn with_alt_svc(self, alternatives: impl IntoIterator<Item = AltSvc>) -> Self {
+        let value = alternatives
+            .into_iter()
+            .map(|alternative| alternative.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        if value.is_empty() {
+            self.with_header(KnownHeaderName::AltSvc, "clear")
+        } else {
+            self.with_header(KnownHeaderName::AltSvc, value)
+        }
+    }
+
+    /// returns the request headers
+    pub fn request_headers(&self) -> &Headers {
+        self.inner.request_headers()
+    }
+
+    /// returns the request headers
+    #[deprecated(since = "0.2.4", note = "use `Conn::request_headers`")]
+    pub fn headers(&self) -> &Headers {
+        self.request_headers()
+    }
+
+    /**
+    returns the first value of the named request header as a str, if
+    there is one and it is valid utf-8. for a slight performance
+    improvement, use a [`KnownHeaderName`](crate::KnownHeaderName) as
+    the argument instead of a str.
+
+    ```
+    use trillium::KnownHeaderName;
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        let accept = conn.request_header(KnownHeaderName::Accept).unwrap_or("anything");
+        let body = format!("accept: {accept}");
+        conn.ok(body)
+    };
+    assert_ok!(
+        get("/").with_request_header("accept", "text/html").on(&handler),
+        "accept: text/html"
+    );
+    assert_ok!(get("/").on(&handler), "accept: anything");
+    ```
+    */
+    pub fn request_header<'a>(&'a self, name: impl Into<HeaderName<'a>>) -> Option<&'a str> {
+        self.request_header_values(name)?
+            .first()
+            .and_then(HeaderValue::as_str)
+    }
+
+    /**
+    returns all of the values of the named request header, if it was
+    sent
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        let count = conn.request_header_values("x-forwarded-for").map_or(0, |values| values.len());
+        conn.ok(count.to_string())
+    };
+    let conn = get("/")
+        .with_request_header("x-forwarded-for", "10.0.0.1")
+        .with_request_header("x-forwarded-for", "10.0.0.2")
+        .on(&handler);
+    assert_ok!(conn, "2");
+    ```
+    */
+    pub fn request_header_values<'a>(
+        &'a self,
+        name: impl Into<HeaderName<'a>>,
+    ) -> Option<&'a HeaderValues> {
+        self.request_headers().get_values(name)
+    }
+
+    /**
+    returns an iterator over the names of the response headers that
+    have been set so far, borrowed from the conn
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        let conn = conn.with_header("x-custom", "value");
+        let names = conn
+            .response_header_names()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        assert!(names.iter().any(|name| name == "x-custom"));
+        conn.ok("ok")
+    };
+    assert_ok!(get("/").on(&handler), "ok");
+    ```
+    */
+    pub fn response_header_names(&self) -> impl Iterator<Item = HeaderName<'_>> {
+        self.inner.response_headers().names()
+    }
+
+    /**
+    returns the response headers that have been set so far. to
+    modify them, use [`Conn::response_headers_mut`]
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        let conn = conn.with_header("cache-control", "no-store");
+        let cached = !conn.response_headers().has_header("cache-control");
+        conn.with_header_if_unset("x-cached", cached.to_string())
+            .ok("ok")
+    };
+    assert_headers!(get("/").on(&handler), "x-cached" => "false");
+    ```
+    */
+    pub fn response_headers(&self) -> &Headers {
+        self.inner.response_headers()
+    }
+
+    /// returns the mutable response headers
+    pub fn response_headers_mut(&mut self) -> &mut Headers {
+        self.inner.response_headers_mut()
+    }
+
+    /// returns the mutable response headers
+    #[deprecated(since = "0.2.4", note = "use `Conn::response_headers_mut`")]
+    pub fn headers_mut(&mut self) -> &mut Headers {
+        self.response_headers_mut()
+    }
+
+    /**
+    serializes the provided value as json, sets it as the response
+    body, and sets the content-type to `application/json`. this
+    requires the `json` crate feature to be enabled. unlike
+    `ApiConnExt::with_json` in `trillium-api`, a serialization error
+    is returned rather than sent as a `500 Internal Server Error`.
+
+    # Errors
+
+    returns an error if the value cannot be serialized
+    */
+    #[cfg(feature = "json")]
+    pub fn with_json_body<T>(self, value: &T) -> serde_json::Result<Self>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        Ok(self
+            .with_body(serde_json::to_string(value)?)
+            .with_header(KnownHeaderName::ContentType, "application/json"))
+    }
+
+    /**
+    insert a header name and value/values into the response headers
+    and return the conn. for a slight performance improvement, use a
+    [`KnownHeaderName`](crate::KnownHeaderName) as the first argument instead of a
+    str.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.with_header("content-type", "application/html")
+    });
+    ```
+    */
+    #[must_use]
+    pub fn with_header(
+        mut self,
+        header_name: impl Into<HeaderName<'static>>,
+        header_value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.response_headers_mut()
+            .insert(header_name, header_value);
+        self
+    }
+
+    /**
+    insert a header name and value/values into the response headers
+    only if there is not already a value for that name, and return the
+    conn. this is useful for middleware that provides a default, such
+    as a `cache-control` policy, that a handler may have overridden.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.with_header("cache-control", "no-store")
+            .with_header_if_unset("cache-control", "max-age=60")
+            .with_header_if_unset("x-content-type-options", "nosniff")
+    });
+
+    assert_headers!(
+        conn,
+        "cache-control" => "no-store",
+        "x-content-type-options" => "nosniff"
+    );
+    ```
+    */
+    #[must_use]
+    pub fn with_header_if_unset(
+        mut self,
+        header_name: impl Into<HeaderName<'static>>,
+        header_value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.response_headers_mut()
+            .try_insert(header_name, header_value);
+        self
+    }
+
+    /**
+    sets the `content-type` response header from a typed
+    [`Mime`](crate::mime::Mime), replacing any content type that was
+    previously set, and return the conn.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.with_header("content-type", "text/plain")
+            .with_content_type(trillium::mime::TEXT_HTML_UTF_8)
+            .ok("<h1>hello</h1>")
+    });
+
+    assert_headers!(conn, "content-type" => "text/html; charset=utf-8");
+    ```
+    */
+    #[cfg(feature = "mime")]
+    #[must_use]
+    pub fn with_content_type(self, mime: impl Into<mime::Mime>) -> Self {
+        self.with_header(KnownHeaderName::ContentType, mime.into().to_string())
+    }
+
+    /**
+    sets the `content-type` response header by inspecting the first
+    bytes of the response body, recognizing png, jpeg, gif, pdf, html,
+    and plain text. this is opt-in and does nothing unless no
+    content type has been set, the body is held in memory with a
+    known length, and the response is not [`Conn::is_nosniff`]. only
+    the first 512 bytes of the body are inspected.
+
+    Since the body is only inspected when this is called, it must be
+    called after the body is set.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        conn.ok("<!DOCTYPE html><title>hi</title>")
+            .with_sniffed_content_type()
+    };
+    assert_headers!(get("/").on(&handler), "content-type" => "text/html; charset=utf-8");
+
+    let handler = |conn: Conn| async move {
+        conn.ok(&b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"[..])
+            .with_sniffed_content_type()
+    };
+    assert_headers!(get("/").on(&handler), "content-type" => "image/png");
+
+    let handler = |conn: Conn| async move {
+        conn.with_header("content-type", "text/css")
+            .ok("body { color: red }")
+            .with_sniffed_content_type()
+    };
+    assert_headers!(get("/").on(&handler), "content-type" => "text/css");
+    ```
+    */
+    #[must_use]
+    pub fn with_sniffed_content_type(mut self) -> Self {
+        if self.is_nosniff()
+            || self
+                .inner
+                .response_headers()
+                .has_header(KnownHeaderName::ContentType)
+        {
+            return self;
+        }
+
+        let content_type = self
+            .inner
+            .response_body()
+            .and_then(Body::static_bytes)
+            .and_then(crate::content_sniffing::sniff);
+
+        if let Some(content_type) = content_type {
+            self.response_headers_mut()
+                .insert(KnownHeaderName::ContentType, content_type);
+        }
+
+        self
+    }
+
+    /**
+    append a header name and value/values to the response headers,
+    keeping any values that were already set for that name, and return
+    the conn. this is useful for headers that may legitimately be
+    repeated, such as `link`, `vary`, or `set-cookie`.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.with_appended_header("link", "</style.css>; rel=preload")
+            .with_appended_header("link", "</script.js>; rel=preload")
+    });
+
+    let links = conn.inner().response_headers().get_values("link").unwrap();
+    assert_eq!(links.len(), 2);
+    ```
+    */
+    #[must_use]
+    pub fn with_appended_header(
+        mut self,
+        header_name: impl Into<HeaderName<'static>>,
+        header_value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.append_header(header_name, header_value);
+        self
+    }
+
+    /// append a header name and value/values to the response headers,
+    /// keeping any values that were already set for that name. see
+    /// [`Conn::with_appended_header`] for a chainable alternative.
+    pub fn append_header(
+        &mut self,
+        header_name: impl Into<HeaderName<'static>>,
+        header_value: impl Into<HeaderValues>,
+    ) {
+        self.response_headers_mut()
+            .append(header_name, header_value);
+    }
+
+    /**
+    appends a `Warning` response header with the three digit warning
+    code, the agent adding the warning, such as the host name or `-`
+    if unknown, and a warning text, which is quoted. multiple warnings
+    accumulate as separate header values. note that `Warning` is
+    obsolete as of [rfc 9111](https://www.rfc-editor.org/rfc/rfc9111#section-5.5),
+    but is still understood by many clients.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|mut conn: trillium::Conn| async move {
+        conn.add_warning(110, "-", "Response is Stale");
+        conn.add_warning(214, "proxy.example", r#"Transformation "Applied""#);
+        conn
+    });
+
+    let warnings = conn.inner().response_headers().get_values("warning").unwrap();
+    assert_eq!(warnings[0], r#"110 - "Response is Stale""#);
+    assert_eq!(warnings[1], r#"214 proxy.example "Transformation \"Applied\"""#);
+    ```
+    */
+    pub fn add_warning(&mut self, code: u16, agent: &str, text: &str) {
+        let mut warning = format!("{code:03} {agent} \"");
+        for c in text.chars() {
+            if matches!(c, '"' | '\\') {
+                warning.push('\\');
+            }
+            warning.push(c);
+        }
+        warning.push('"');
+        self.append_header(KnownHeaderName::Warning, warning);
+    }
+
+    /// chainable alternative to [`Conn::add_warning`]
+    #[must_use]
+    pub fn with_warning(mut self, code: u16, agent: &str, text: &str) -> Self {
+        self.add_warning(code, agent, text);
+        self
+    }
+
+    /**
+    whether the response tells the client not to sniff its content
+    type, with `X-Content-Type-Options: nosniff`. this is the case
+    when a handler such as `trillium-security-headers` has set it,
+    and any step that would otherwise guess a content type from the
+    response body must not do so, so that the server and the client
+    agree on the content type that was sent.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_header("x-content-type-options", "NoSniff").ok("ok")
+    });
+    assert!(conn.is_nosniff());
+    assert!(!get("/").on(&()).is_nosniff());
+    ```
+    */
+    pub fn is_nosniff(&self) -> bool {
+        self.inner
+            .response_headers()
+            .get_str(KnownHeaderName::XcontentTypeOptions)
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case("nosniff"))
+    }
+
+    /// returns the mutable response trailers, which are sent after a
+    /// chunked response body. see
+    /// [`trillium_http::Conn::response_trailers_mut`] for the rules
+    /// about which trailers are sent
+    pub fn response_trailers_mut(&mut self) -> &mut Headers {
+        self.inner.response_trailers_mut()
+    }
+
+    /**
+    whether the client has indicated that it accepts response
+    trailers, with `trailers` in the `TE` request header. see
+    [`trillium_http::Conn::accepts_trailers`]
+
+    ```
+    use trillium_testing::prelude::*;
+    assert!(get("/").with_request_header("te", "trailers").on(&()).accepts_trailers());
+    assert!(!get("/").on(&()).accepts_trailers());
+    ```
+    */
+    pub fn accepts_trailers(&self) -> bool {
+        self.inner.accepts_trailers()
+    }
+
+    /**
+    insert a trailer name and value/values into the response trailers
+    and return the conn. trailers are only sent with a chunked
+    response body to a client that [accepts
+    trailers](Conn::accepts_trailers), and fields such as
+    `content-length` that are not permitted as trailers are discarded.
+
+    ```
+    use trillium::Body;
+    use trillium_testing::{futures_lite::io::Cursor, prelude::*};
+    let handler = |conn: trillium::Conn| async move {
+        conn.with_body(Body::new_streaming(Cursor::new("hello"), None))
+            .with_response_trailer("server-timing", "total;dur=12")
+    };
+
+    let conn = get("/").on(&handler);
+    assert_headers!(&conn, "trailer" => None);
+
+    let conn = get("/").with_request_header("te", "trailers").on(&handler);
+    assert_headers!(&conn, "trailer" => "Server-Timing");
+    let trailers = conn.inner().response_trailers().unwrap();
+    assert_eq!(trailers.get_str("server-timing"), Some("total;dur=12"));
+    ```
+    */
+    #[must_use]
+    pub fn with_response_trailer(
+        mut self,
+        trailer_name: impl Into<HeaderName<'static>>,
+        trailer_value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.response_trailers_mut()
+            .insert(trailer_name, trailer_value);
+        self
+    }
+
+    /**
+    appends all of these trailers to the response trailers and
+    returns the conn, as with [`Conn::with_response_trailer`]. the
+    same rules apply: they are only sent with a chunked response body
+    to a client that accepts trailers, and are listed in the `Trailer`
+    response header when they are sent.
+
+    ```
+    use trillium::{Body, Headers};
+    use trillium_testing::{futures_lite::io::Cursor, prelude::*};
+    let handler = |conn: trillium::Conn| async move {
+        let trailers: Headers = [("grpc-status", "0"), ("grpc-message", "ok")]
+            .into_iter()
+            .collect();
+        conn.with_body(Body::new_streaming(Cursor::new("hello"), None))
+            .with_trailers(trailers)
+    };
+
+    let conn = get("/").with_request_header("te", "trailers").on(&handler);
+    let trailers = conn.inner().response_trailers().unwrap();
+    assert_eq!(trailers.get_str("grpc-status"), Some("0"));
+    assert_eq!(trailers.get_str("grpc-message"), Some("ok"));
+    assert!(conn.inner().response_headers().get_str("trailer").is_some());
+    ```
+    */
+    #[must_use]
+    pub fn with_trailers(mut self, trailers: Headers) -> Self {
+        self.response_trailers_mut().extend(trailers);
+        self
+    }
+
+    /**
+    redirects the client to the provided location with a 302 Found
+    status, and halts the conn. the location may be a `&'static str`
+    or a `String`. it is exactly identical to
+    `conn.with_header(KnownHeaderName::Location,
+    location).with_status(302).halt()`
+
+    clients may change the method of the redirected request from POST
+    to GET, so use [`Conn::temporary_redirect`] if the method and body
+    must be preserved.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move {
+        let location = format!("/users/{}", 10);
+        conn.redirect(location)
+    };
+    let mut conn = get("/").on(&handler);
+    assert_status!(&conn, 302);
+    assert_headers!(&mut conn, "location" => "/users/10");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn redirect(self, location: impl Into<HeaderValues>) -> Self {
+        self.redirect_with_status(location, Status::Found)
+    }
+
+    /**
+    redirects the client to the provided location with a 301 Moved
+    Permanently status, and halts the conn. see [`Conn::redirect`]
+
+    clients may change the method of the redirected request from POST
+    to GET, so use [`Conn::permanent_redirect`] if the method and body
+    must be preserved.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move { conn.redirect_permanent("/new-home") };
+    let mut conn = get("/").on(&handler);
+    assert_status!(&conn, 301);
+    assert_headers!(&mut conn, "location" => "/new-home");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn redirect_permanent(self, location: impl Into<HeaderValues>) -> Self {
+        self.redirect_with_status(location, Status::MovedPermanently)
+    }
+
+    /**
+    redirects the client to the provided location with a 303 See Other
+    status, and halts the conn.
+
+    clients always follow a 303 with a GET (or HEAD) request,
+    regardless of the original method. this is the usual response to
+    a form POST, directing the client to a page showing the result.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move { conn.redirect_see_other("/orders/1") };
+    let mut conn = post("/orders").on(&handler);
+    assert_status!(&conn, 303);
+    assert_headers!(&mut conn, "location" => "/orders/1");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn redirect_see_other(self, location: impl Into<HeaderValues>) -> Self {
+        self.redirect_with_status(location, Status::SeeOther)
+    }
+
+    /**
+    redirects the client to the provided location with a 307 Temporary
+    Redirect status, and halts the conn.
+
+    unlike [`Conn::redirect`], clients must repeat the request with
+    the same method and body, so this is appropriate for redirecting
+    non-GET api requests.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move { conn.temporary_redirect("/v2/uploads") };
+    let mut conn = put("/uploads").on(&handler);
+    assert_status!(&conn, 307);
+    assert_headers!(&mut conn, "location" => "/v2/uploads");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn temporary_redirect(self, location: impl Into<HeaderValues>) -> Self {
+        self.redirect_with_status(location, Status::TemporaryRedirect)
+    }
+
+    /**
+    redirects the client to the provided location with a 308 Permanent
+    Redirect status, and halts the conn.
+
+    unlike [`Conn::redirect_permanent`], clients must repeat the
+    request with the same method and body, and may remember the new
+    location for future requests.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |conn: Conn| async move { conn.permanent_redirect("/v2/uploads") };
+    let mut conn = post("/uploads").on(&handler);
+    assert_status!(&conn, 308);
+    assert_headers!(&mut conn, "location" => "/v2/uploads");
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn permanent_redirect(self, location: impl Into<HeaderValues>) -> Self {
+        self.redirect_with_status(location, Status::PermanentRedirect)
+    }
+
+    fn redirect_with_status(self, location: impl Into<HeaderValues>, status: Status) -> Self {
+        self.with_header(KnownHeaderName::Location, location)
+            .with_status(status)
+            .halt()
+    }
+
+    /**
+    returns the path for this request. note that this may not
+    represent the entire http request path if running nested
+    routers.
+    */
+    pub fn path(&self) -> &str {
+        self.path.last().map_or_else(|| self.inner.path(), |p| &**p)
+    }
+
+    /**
+    returns the [path](Conn::path) for this request, percent-decoded
+    exactly once, so that `/caf%C3%A9` and `/café` are the same path.
+    the querystring is not included.
+
+    a `%` that is not followed by two hex digits is left as it is,
+    and decoded bytes that are not valid utf8 are replaced with
+    U+FFFD, so this never fails.
+
+    encoded slashes (`%2F`) are not decoded, because a decoded slash
+    is indistinguishable from a path separator, which would let a
+    single encoded segment match several segments of a route, or
+    escape the directory that a handler intends to serve. for the
+    same reason, this must not be decoded a second time: `%252e`
+    decodes to `%2e`, not to `.`. a handler that maps the path onto
+    a filesystem must still reject `..` segments, which may have been
+    sent encoded as `%2e%2e`.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/caf%C3%A9/a%20b?q=%20").on(&());
+    assert_eq!(conn.decoded_path(), "/café/a b");
+
+    let conn = get("/a%2Fb/%2e%2e/100%").on(&());
+    assert_eq!(conn.decoded_path(), "/a%2Fb/../100%");
+
+    let conn = get("/%252e").on(&());
+    assert_eq!(conn.decoded_path(), "/%2e");
+    ```
+    */
+    pub fn decoded_path(&self) -> String {
+        decode_path(self.path()).into_owned()
+    }
+
+    /**
+    returns query part of the request path
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/a/b?c&d=e").on(&());
+    assert_eq!(conn.querystring(), "c&d=e");
+
+    let conn = get("/a/b").on(&());
+    assert_eq!(conn.querystring(), "");
+    ```
+    */
+    pub fn querystring(&self) -> &str {
+        self.inner.querystring()
+    }
+
+    /**
+    returns the percent-decoded value of the first occurrence of
+    `key` in the querystring, if any. a key without a value, as in
+    `?flag`, has an empty value. this does not require the `json`
+    crate feature, unlike `Conn::query`.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/?q=hello+world%21&flag&q=again").on(&());
+    assert_eq!(conn.query_param("q").as_deref(), Some("hello world!"));
+    assert_eq!(conn.query_param("flag").as_deref(), Some(""));
+    assert_eq!(conn.query_param("missing"), None);
+    ```
+    */
+    pub fn query_param(&self, key: &str) -> Option<Cow<'_, str>> {
+        self.query_pairs()
+            .find(|(name, _)| form_decode(name) == key)
+            .map(|(_, value)| form_decode(value))
+    }
+
+    /**
+    returns the percent-decoded values of every occurrence of `key`
+    in the querystring, in order. see [`Conn::query_param`]
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/?tag=a&other=b&tag=c%20d").on(&());
+    assert_eq!(conn.query_params("tag").collect::<Vec<_>>(), ["a", "c d"]);
+    assert_eq!(conn.query_params("missing").count(), 0);
+    ```
+    */
+    pub fn query_params<'a>(&'a self, key: &'a str) -> impl Iterator<Item = Cow<'a, str>> + 'a {
+        self.query_pairs()
+            .filter(move |(name, _)| form_decode(name) == key)
+            .map(|(_, value)| form_decode(value))
+    }
+
+    /// the undecoded key-value pairs of the querystring
+    fn query_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.querystring()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+    }
+
+    /**
+    deserializes the query part of the request path. repeated keys
+    can be deserialized into a `Vec`, and an empty querystring
+    deserializes into a struct whose fields are all optional. this
+    requires the `json` crate feature to be enabled.
+
+    ```
+    use serde::Deserialize;
+    use trillium_testing::prelude::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Query {
+        c: Option<String>,
+        d: Option<String>,
+        tag: Option<Vec<String>>,
+    }
+
+    let conn = get("/a/b?c&d=e").on(&());
+    assert_eq!(
+        conn.query::<Query>().unwrap(),
+        Query { c: Some("".into()), d: Some("e".into()), tag: None }
+    );
+
+    let conn = get("/a/b?tag=x&tag=y").on(&());
+    assert_eq!(conn.query::<Query>().unwrap().tag.unwrap(), ["x", "y"]);
+
+    let conn = get("/a/b").on(&());
+    assert_eq!(
+        conn.query::<Query>().unwrap(),
+        Query { c: None, d: None, tag: None }
+    );
+    ```
+
+    # Errors
+
+    returns an error if the querystring cannot be deserialized as `T`
+    */
+    #[cfg(feature = "json")]
+    pub fn query<T>(&self) -> Result<T, serde_urlencoded::de::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        crate::query::from_str(self.querystring())
+    }
+
+    /**
+    sets the `halted` attribute of this conn, preventing later
+    processing in a given tuple handler. returns
+    the conn for fluent chaining
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&|conn: trillium::Conn| async move {
+        conn.halt()
+    });
+
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn halt(mut self) -> Self {
+        self.set_halted(true);
+        self
+    }
+
+    /**
+    sets the `halted` attribute of this conn. see [`Conn::halt`].
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = get("/").on(&());
+    assert!(!conn.is_halted());
+    conn.set_halted(true);
+    assert!(conn.is_halted());
+    ```
+    */
+    pub fn set_halted(&mut self, halted: bool) {
+        self.halted = halted;
+    }
+
+    /// retrieves the halted state of this conn.  see [`Conn::halt`].
+    pub const fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// predicate function to indicate whether the connection is
+    /// secure. note that this does not necessarily indicate that the
+    /// transport itself is secure, as it may indicate that
+    /// `trillium_http` is behind a trusted reverse proxy that has
+    /// terminated tls and provided appropriate headers to indicate
+    /// this.
+    pub fn is_secure(&self) -> bool {
+        self.inner.is_secure()
+    }
+
+    /// returns an immutable reference to the inner
+    /// [`trillium_http::Conn`]. please open an issue if you need to do
+    /// this in application code.
+    ///
+    /// stability note: hopefully this can go away at some point, but
     /// for now is an escape hatch in case `trillium_http::Conn`
     /// presents interfaces that cannot be reached otherwise.
     pub const fn inner(&self) -> &trillium_http::Conn<BoxedTransport> {
@@ -489,6 +2620,93 @@ impl Conn {
         })
     }
 
+    /**
+    borrows the transport for this conn as the specified transport
+    type, returning None if it is a different type. unlike
+    [`Conn::into_inner`], this never panics, so transport-specific
+    behavior, such as reading tls peer certificates, can be skipped
+    on other transports.
+
+    ```
+    use trillium_testing::{prelude::*, TestTransport};
+    use trillium_http::Synthetic;
+    let conn = Conn::from(get("/").on(&()));
+    assert!(conn.transport_downcast_ref::<Synthetic>().is_some());
+    assert!(conn.transport_downcast_ref::<TestTransport>().is_none());
+    ```
+    */
+    pub fn transport_downcast_ref<T: Transport>(&self) -> Option<&T> {
+        self.inner.transport().downcast_ref()
+    }
+
+    /// mutably borrows the transport for this conn as the specified
+    /// transport type, returning None if it is a different type. see
+    /// [`Conn::transport_downcast_ref`]
+    pub fn transport_downcast_mut<T: Transport>(&mut self) -> Option<&mut T> {
+        self.inner.transport_mut().downcast_mut()
+    }
+
+    /**
+    consumes this conn, returning its response status, headers,
+    body, and halted state as [`ConnParts`]. unlike
+    [`Conn::into_inner`], this does not need to know the transport
+    type, which is dropped.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: Conn| async move { conn.with_status(404) });
+    let parts = Conn::from(conn).into_parts();
+    assert_eq!(parts.status, Some(Status::NotFound));
+    assert!(parts.response_body.is_none());
+    assert!(!parts.halted);
+    ```
+    */
+    pub fn into_parts(mut self) -> ConnParts {
+        ConnParts {
+            status: self.status(),
+            response_headers: std::mem::take(self.response_headers_mut()),
+            response_body: self.inner.take_response_body(),
+            halted: self.halted,
+        }
+    }
+
+    /**
+    applies [`ConnParts`] to this conn, replacing its response
+    headers, body, and halted state, and setting its status if the
+    parts have one. this is the inverse of [`Conn::into_parts`].
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: Conn| async move { conn.with_header("x-a", "b").ok("hello") });
+    let parts = Conn::from(conn).into_parts();
+
+    let conn = Conn::from(get("/").on(&())).with_parts(parts);
+    assert_eq!(conn.status(), Some(Status::Ok));
+    assert_eq!(conn.response_headers().get_str("x-a"), Some("b"));
+    assert!(conn.is_halted());
+    ```
+    */
+    #[must_use]
+    pub fn with_parts(mut self, parts: ConnParts) -> Self {
+        let ConnParts {
+            status,
+            response_headers,
+            response_body,
+            halted,
+        } = parts;
+
+        if let Some(status) = status {
+            self.set_status(status);
+        }
+        *self.response_headers_mut() = response_headers;
+        match response_body {
+            Some(body) => self.inner.set_response_body(body),
+            None => drop(self.inner.take_response_body()),
+        }
+        self.halted = halted;
+        self
+    }
+
     /// retrieves the remote ip address for this conn, if available.
     pub fn peer_ip(&self) -> Option<IpAddr> {
         self.inner().peer_ip()
@@ -499,6 +2717,101 @@ impl Conn {
         self.inner_mut().set_peer_ip(peer_ip);
     }
 
+    /// retrieves the remote socket address, including the port, for
+    /// this conn, if available.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.inner().peer_addr()
+    }
+
+    /// sets the remote socket address for this conn, which also sets
+    /// the [`Conn::peer_ip`]. see [`trillium_http::Conn::set_peer_addr`]
+    pub fn set_peer_addr(&mut self, peer_addr: Option<SocketAddr>) {
+        self.inner_mut().set_peer_addr(peer_addr);
+    }
+
+    /**
+    returns a clone of the request-scoped cancellation token for this
+    conn. it is stopped if sending the response fails, if a client
+    disconnect is detected by [`Conn::cancel_on_disconnect`], or if
+    any clone of it is explicitly stopped. see
+    [`trillium_http::Conn::cancellation_token`] for details.
+
+    ```
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&|conn: Conn| async move {
+        let token = conn.cancellation_token();
+        let body = if token.is_stopped() { "cancelled" } else { "ok" };
+        conn.ok(body)
+    });
+    assert_ok!(conn, "ok");
+    ```
+    */
+    pub fn cancellation_token(&self) -> Stopper {
+        self.inner.cancellation_token()
+    }
+
+    /**
+    awaits the provided future, returning None and stopping the
+    [cancellation token](Conn::cancellation_token) if the client
+    disconnects before it completes. disconnects can only be detected
+    after the request body has been read. see
+    [`trillium_http::Conn::cancel_on_disconnect`] for details.
+
+    ```
+    use trillium_testing::prelude::*;
+    let handler = |mut conn: Conn| async move {
+        match conn.cancel_on_disconnect(async { 6 * 7 }).await {
+            Some(answer) => conn.ok(answer.to_string()),
+            None => conn,
+        }
+    };
+    assert_ok!(get("/").on(&handler), "42");
+    ```
+    */
+    pub async fn cancel_on_disconnect<F: Future>(&mut self, future: F) -> Option<F::Output> {
+        self.inner.cancel_on_disconnect(future).await
+    }
+
+    /**
+    sends an informational (1xx) response, such as `103 Early Hints`,
+    ahead of the final response. see
+    [`trillium_http::Conn::send_informational`] for details.
+
+    # Errors
+
+    returns an error if the status is not an informational status
+    other than 101, or if the transport cannot be written to
+
+    ```
+    use trillium::{Headers, KnownHeaderName, Status};
+    use trillium_testing::prelude::*;
+    let handler = |mut conn: Conn| async move {
+        let mut hints = Headers::new();
+        hints.insert(KnownHeaderName::Link, "</app.js>; rel=preload; as=script");
+        if conn.send_informational(Status::EarlyHints, &hints).await.is_err() {
+            return conn.with_status(500);
+        }
+        conn.ok("ok")
+    };
+    let conn = get("/").on(&handler);
+    assert_eq!(conn.informational_responses(), &[Status::EarlyHints]);
+    assert_ok!(conn, "ok");
+    ```
+    */
+    pub async fn send_informational(
+        &mut self,
+        status: Status,
+        headers: &Headers,
+    ) -> trillium_http::Result<()> {
+        self.inner.send_informational(status, headers).await
+    }
+
+    /// the informational (1xx) responses that have been sent ahead of
+    /// the final response for this conn, in order
+    pub fn informational_responses(&self) -> &[Status] {
+        self.inner.informational_responses()
+    }
+
     /// for router implementations. pushes a route segment onto the path
     pub fn push_path(&mut self, path: String) {
         self.path.push(path);
@@ -508,6 +2821,36 @@ impl Conn {
     pub fn pop_path(&mut self) {
         self.path.pop();
     }
+
+    /**
+    returns the part of the request path that has already been
+    consumed by routers, without a trailing slash. this is the
+    difference between the full request path and the current
+    [`Conn::path`], and is empty unless this conn has been routed
+    into a nested router. a handler that is mounted within a router
+    can use this to build absolute urls without knowing where it was
+    mounted.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = Conn::from(get("/api/v1/users/1").on(&()));
+    assert_eq!(conn.route_prefix(), "");
+
+    conn.push_path(String::from("users/1"));
+    assert_eq!(conn.path(), "users/1");
+    assert_eq!(conn.route_prefix(), "/api/v1");
+    ```
+    */
+    pub fn route_prefix(&self) -> &str {
+        let prefix = self.path.last().map_or("", |path| {
+            self.inner
+                .path()
+                .strip_suffix(path.as_str())
+                .unwrap_or_default()
+        });
+
+        prefix.trim_end_matches('/')
+    }
 }
 
 impl AsMut<StateSet> for Conn {
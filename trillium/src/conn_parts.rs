@@ -0,0 +1,39 @@
+use crate::{Body, Headers, Status};
+
+/**
+# The response portion of a [`Conn`](crate::Conn)
+
+This is returned by [`Conn::into_parts`](crate::Conn::into_parts),
+which consumes the conn and leaves its transport behind, and can be
+applied to another conn with
+[`Conn::with_parts`](crate::Conn::with_parts).
+
+```
+use trillium::ConnParts;
+use trillium_testing::prelude::*;
+
+let conn = get("/").on(&|conn: Conn| async move {
+    conn.with_header("x-greeting", "hi").ok("hello")
+});
+
+let ConnParts { status, response_headers, response_body, halted } = Conn::from(conn).into_parts();
+assert_eq!(status, Some(Status::Ok));
+assert_eq!(response_headers.get_str("x-greeting"), Some("hi"));
+assert_eq!(response_body.unwrap().static_bytes(), Some(&b"hello"[..]));
+assert!(halted);
+```
+*/
+#[derive(Debug, Default)]
+pub struct ConnParts {
+    /// the response status, if one was set
+    pub status: Option<Status>,
+
+    /// the response headers
+    pub response_headers: Headers,
+
+    /// the response body, if one was set
+    pub response_body: Option<Body>,
+
+    /// whether the conn was halted
+    pub halted: bool,
+}
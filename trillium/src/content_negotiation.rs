@@ -0,0 +1,104 @@
+/// a single media range from an `Accept` header, such as
+/// `text/*;q=0.5`
+#[derive(Debug)]
+struct MediaRange<'a> {
+    r#type: &'a str,
+    subtype: &'a str,
+    params: Vec<(&'a str, &'a str)>,
+    quality: f32,
+}
+
+/// parses a media type or range into its type, its subtype, its
+/// parameters, and its quality, which defaults to 1
+fn parse_media_range(media_type: &str) -> Option<MediaRange<'_>> {
+    let mut parts = media_type.split(';');
+    let (r#type, subtype) = parts.next()?.trim().split_once('/')?;
+    let (r#type, subtype) = (r#type.trim(), subtype.trim());
+    if r#type.is_empty() || subtype.is_empty() {
+        return None;
+    }
+
+    let mut params = vec![];
+    let mut quality = 1.0;
+    for param in parts {
+        let Some((name, value)) = param.split_once('=') else {
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim().trim_matches('"'));
+        if name.eq_ignore_ascii_case("q") {
+            quality = value.parse::<f32>().map_or(0.0, |q| q.clamp(0.0, 1.0));
+        } else {
+            params.push((name, value));
+        }
+    }
+
+    Some(MediaRange {
+        r#type,
+        subtype,
+        params,
+        quality,
+    })
+}
+
+impl MediaRange<'_> {
+    /// how specifically this range matches the offered media type, if
+    /// it matches at all. a range with parameters is more specific
+    /// than the same range without them, which is more specific than
+    /// `type/*`, which is more specific than `*/*`
+    fn specificity(&self, offered: &MediaRange<'_>) -> Option<usize> {
+        let matches_params = self.params.iter().all(|(name, value)| {
+            offered
+                .params
+                .iter()
+                .any(|(n, v)| n.eq_ignore_ascii_case(name) && v.eq_ignore_ascii_case(value))
+        });
+
+        if self.r#type == "*" && self.subtype == "*" {
+            Some(0)
+        } else if !self.r#type.eq_ignore_ascii_case(offered.r#type) {
+            None
+        } else if self.subtype == "*" {
+            Some(1)
+        } else if !self.subtype.eq_ignore_ascii_case(offered.subtype) || !matches_params {
+            None
+        } else {
+            Some(2 + self.params.len())
+        }
+    }
+}
+
+/// the quality that the accept header assigns to this offered media
+/// type, which is that of the most specific range that matches it
+fn quality(ranges: &[MediaRange<'_>], offered: &str) -> f32 {
+    let Some(offered) = parse_media_range(offered) else {
+        return 0.0;
+    };
+
+    ranges
+        .iter()
+        .filter_map(|range| Some((range.specificity(&offered)?, range.quality)))
+        .max_by_key(|(specificity, _)| *specificity)
+        .map_or(0.0, |(_, quality)| quality)
+}
+
+/// picks the offered media type with the highest quality in this
+/// accept header, preferring the earliest offered in a tie
+pub fn negotiate<'a>(accept: Option<&str>, offered: &[&'a str]) -> Option<&'a str> {
+    let Some(accept) = accept else {
+        return offered.first().copied();
+    };
+
+    let ranges: Vec<_> = accept.split(',').filter_map(parse_media_range).collect();
+    offered
+        .iter()
+        .map(|offered| (*offered, quality(&ranges, offered)))
+        .filter(|(_, quality)| *quality > 0.0)
+        .fold(
+            None,
+            |best: Option<(&'a str, f32)>, (offered, quality)| match best {
+                Some((_, best_quality)) if best_quality >= quality => best,
+                _ => Some((offered, quality)),
+            },
+        )
+        .map(|(offered, _)| offered)
+}
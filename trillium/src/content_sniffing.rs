@@ -0,0 +1,72 @@
+/// the number of leading bytes of a body that are inspected, as in
+/// the resource header of the whatwg mime sniffing standard
+const SNIFF_LEN: usize = 512;
+
+/// binary signatures that identify a content type by the bytes that
+/// the content starts with
+const SIGNATURES: [(&[u8], &str); 6] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"\xef\xbb\xbf", "text/plain; charset=utf-8"),
+];
+
+/// tags that identify html when they start the content, after any
+/// leading whitespace. these are compared case-insensitively
+const HTML_TAGS: [&[u8]; 8] = [
+    b"<!doctype html",
+    b"<html",
+    b"<head",
+    b"<body",
+    b"<script",
+    b"<title",
+    b"<div",
+    b"<!--",
+];
+
+/// guesses the content type of a body from its leading bytes,
+/// distinguishing png, jpeg, gif, pdf, html, and plain text. returns
+/// None for content that is not recognized, such as arbitrary binary
+/// data
+pub fn sniff(body: &[u8]) -> Option<&'static str> {
+    let header = &body[..body.len().min(SNIFF_LEN)];
+
+    if let Some((_, content_type)) = SIGNATURES
+        .iter()
+        .find(|(signature, _)| header.starts_with(signature))
+    {
+        return Some(content_type);
+    }
+
+    if header.iter().any(|&byte| is_binary(byte)) {
+        return None;
+    }
+
+    let utf8 = match std::str::from_utf8(header) {
+        Ok(_) => true,
+        // a multibyte character may be cut off at the end of the header
+        Err(error) => error.error_len().is_none() && header.len() < body.len(),
+    };
+
+    let trimmed = header.trim_ascii_start();
+    let html = HTML_TAGS.iter().any(|tag| {
+        trimmed.len() > tag.len()
+            && trimmed[..tag.len()].eq_ignore_ascii_case(tag)
+            && (tag.ends_with(b"--") || matches!(trimmed[tag.len()], b' ' | b'>'))
+    });
+
+    match (html, utf8) {
+        (true, true) => Some("text/html; charset=utf-8"),
+        (true, false) => Some("text/html"),
+        (false, true) => Some("text/plain; charset=utf-8"),
+        (false, false) => Some("text/plain"),
+    }
+}
+
+/// whether this byte never occurs in text, as defined by the whatwg
+/// mime sniffing standard
+const fn is_binary(byte: u8) -> bool {
+    matches!(byte, 0x00..=0x08 | 0x0b | 0x0e..=0x1a | 0x1c..=0x1f)
+}
@@ -34,14 +34,35 @@ pub use handler::Handler;
 mod conn;
 pub use conn::Conn;
 
+mod body_iter;
+
+mod conn_parts;
+pub use conn_parts::ConnParts;
+
+#[cfg(feature = "json")]
+pub use conn::{FormError, JsonError};
+
+#[cfg(feature = "json")]
+mod query;
+
+#[cfg(feature = "checksum")]
+mod checksum;
+
+mod percent_decoding;
+
+mod content_sniffing;
+
 mod state;
 pub use state::{state, State};
 
 pub use async_trait::async_trait;
 
+#[cfg(feature = "mime")]
+pub use mime;
+
 pub use trillium_http::{
-    Body, HeaderName, HeaderValue, HeaderValues, Headers, KnownHeaderName, Method, StateSet,
-    Status, Version,
+    format_http_date, header_param, Body, HeaderName, HeaderValue, HeaderValues, Headers,
+    KnownHeaderName, Method, StateSet, Status, Version,
 };
 
 /**
@@ -62,3 +83,19 @@ pub use info::Info;
 
 mod init;
 pub use init::{init, Init};
+
+mod allowed_methods;
+pub use allowed_methods::{AllowedMethods, AutoOptions};
+
+mod range;
+pub use range::{content_range, parse_range, RangeSet};
+
+mod alt_svc;
+pub use alt_svc::AltSvc;
+
+pub mod structured_field;
+
+mod priority;
+pub use priority::Priority;
+
+mod content_negotiation;
@@ -0,0 +1,44 @@
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+
+/// percent-decodes a request path exactly once, leaving encoded
+/// slashes (`%2F`) encoded. a `%` that is not followed by two hex
+/// digits is left as it is, and decoded bytes that do not form valid
+/// utf8 are replaced with U+FFFD
+pub fn decode_path(input: &str) -> Cow<'_, str> {
+    if !input.contains('%') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut decoded = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(index) = find_encoded_slash(rest) {
+        decoded.push_str(&percent_decode_str(&rest[..index]).decode_utf8_lossy());
+        decoded.push_str(&rest[index..index + 3]);
+        rest = &rest[index + 3..];
+    }
+    decoded.push_str(&percent_decode_str(rest).decode_utf8_lossy());
+    Cow::Owned(decoded)
+}
+
+/// decodes a single key or value of an
+/// `application/x-www-form-urlencoded` string such as a querystring,
+/// in which a `+` is a space
+pub fn form_decode(input: &str) -> Cow<'_, str> {
+    if input.contains('+') {
+        Cow::Owned(
+            percent_decode_str(&input.replace('+', " "))
+                .decode_utf8_lossy()
+                .into_owned(),
+        )
+    } else {
+        percent_decode_str(input).decode_utf8_lossy()
+    }
+}
+
+fn find_encoded_slash(input: &str) -> Option<usize> {
+    input
+        .as_bytes()
+        .windows(3)
+        .position(|window| matches!(window, [b'%', b'2', b'f' | b'F']))
+}
@@ -0,0 +1,108 @@
+use crate::structured_field::{parse_dictionary, BareItem, Item};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/**
+# The extensible priority of a request
+
+This is the `Priority` request header described in [rfc
+9218](https://www.rfc-editor.org/rfc/rfc9218), which clients use to
+indicate how urgently they need a response relative to their other
+requests, and whether they can make use of it incrementally. Use
+[`Conn::request_priority`](crate::Conn::request_priority) to read it
+from a request.
+
+Absent or invalid parameters take their defaults, an urgency of 3
+and not incremental, and so a header that cannot be parsed at all is
+equivalent to [`Priority::default`].
+
+```
+use trillium::Priority;
+let priority: Priority = "u=1, i".parse().unwrap();
+assert_eq!(priority.urgency(), 1);
+assert!(priority.is_incremental());
+
+let priority: Priority = "i=?0".parse().unwrap();
+assert_eq!(priority.urgency(), 3);
+assert!(!priority.is_incremental());
+
+assert_eq!(Priority::new(0, true).to_string(), "u=0, i");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Priority {
+    urgency: u8,
+    incremental: bool,
+}
+
+const DEFAULT_URGENCY: u8 = 3;
+const MAX_URGENCY: u8 = 7;
+
+impl Priority {
+    /// constructs a new priority. urgency ranges from 0, the most
+    /// urgent, to 7, the least urgent, and values above 7 are
+    /// treated as 7
+    pub const fn new(urgency: u8, incremental: bool) -> Self {
+        Self {
+            urgency: if urgency > MAX_URGENCY {
+                MAX_URGENCY
+            } else {
+                urgency
+            },
+            incremental,
+        }
+    }
+
+    /// the urgency of this request, from 0, the most urgent, to 7,
+    /// the least urgent
+    pub const fn urgency(&self) -> u8 {
+        self.urgency
+    }
+
+    /// whether the client can make use of a partial response, and so
+    /// would benefit from it being interleaved with other responses
+    /// of the same urgency
+    pub const fn is_incremental(&self) -> bool {
+        self.incremental
+    }
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::new(DEFAULT_URGENCY, false)
+    }
+}
+
+impl FromStr for Priority {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut priority = Self::default();
+
+        for (key, member) in parse_dictionary(s).unwrap_or_default() {
+            match (&*key, member.as_item().map(Item::bare_item)) {
+                ("u", Some(&BareItem::Integer(urgency))) => {
+                    if let Ok(urgency @ 0..=MAX_URGENCY) = u8::try_from(urgency) {
+                        priority.urgency = urgency;
+                    }
+                }
+                ("i", Some(&BareItem::Boolean(incremental))) => priority.incremental = incremental,
+                _ => {}
+            }
+        }
+
+        Ok(priority)
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "u={}", self.urgency)?;
+        if self.incremental {
+            f.write_str(", i")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use futures_lite::{ready, AsyncRead, AsyncSeek};
+use std::{
+    io::{Result, SeekFrom},
+    ops::Range,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/**
+# The result of parsing a `Range` request header
+
+Produced by [`parse_range`] or [`Conn::request_range`](crate::Conn::request_range).
+Satisfiable ranges are half-open byte offsets into the
+representation, clamped to its length, sorted, and with overlapping
+or adjacent ranges coalesced.
+
+```
+use trillium::{parse_range, RangeSet};
+assert_eq!(parse_range("bytes=0-499", 1000), RangeSet::Satisfiable(vec![0..500]));
+assert_eq!(parse_range("bytes=500-", 1000), RangeSet::Satisfiable(vec![500..1000]));
+assert_eq!(parse_range("bytes=-100", 1000), RangeSet::Satisfiable(vec![900..1000]));
+assert_eq!(
+    parse_range("bytes=0-99, 50-149, 900-", 1000),
+    RangeSet::Satisfiable(vec![0..150, 900..1000])
+);
+assert_eq!(parse_range("bytes=1000-", 1000), RangeSet::Unsatisfiable);
+assert_eq!(parse_range("lines=1-2", 1000), RangeSet::Full);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RangeSet {
+    /// the header was absent, used a unit other than `bytes`, or was
+    /// syntactically invalid. as required by rfc 9110, the header
+    /// should be ignored and the full representation sent with a 200
+    Full,
+
+    /// at least one of the requested ranges overlaps the
+    /// representation. these should be sent with a 206
+    Satisfiable(Vec<Range<u64>>),
+
+    /// none of the requested ranges overlap the representation. this
+    /// should be responded to with a 416 and a `Content-Range` of
+    /// `bytes */{total_len}`, as with
+    /// [`Conn::with_range_not_satisfiable`](crate::Conn::with_range_not_satisfiable)
+    Unsatisfiable,
+}
+
+impl RangeSet {
+    /// returns the satisfiable ranges, if any
+    pub fn ranges(&self) -> Option<&[Range<u64>]> {
+        match self {
+            Self::Satisfiable(ranges) => Some(ranges),
+            _ => None,
+        }
+    }
+
+    /// determines whether this is [`RangeSet::Satisfiable`]
+    pub const fn is_satisfiable(&self) -> bool {
+        matches!(self, Self::Satisfiable(_))
+    }
+}
+
+/**
+formats a `Content-Range` header value for a satisfiable range of a
+representation of `total_len` bytes
+
+```
+assert_eq!(trillium::content_range(&(0..500), 1000), "bytes 0-499/1000");
+```
+*/
+pub fn content_range(range: &Range<u64>, total_len: u64) -> String {
+    format!(
+        "bytes {}-{}/{}",
+        range.start,
+        range.end.saturating_sub(1),
+        total_len
+    )
+}
+
+enum Spec {
+    /// `first-last` or `first-`
+    Int(u64, Option<u64>),
+    /// `-length`
+    Suffix(u64),
+}
+
+fn parse_spec(spec: &str) -> Option<Spec> {
+    let (first, last) = spec.split_once('-')?;
+    let parse = |n: &str| {
+        if !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()) {
+            n.parse::<u64>().ok()
+        } else {
+            None
+        }
+    };
+
+    match (first, last) {
+        ("", suffix) => parse(suffix).map(Spec::Suffix),
+        (first, "") => parse(first).map(|first| Spec::Int(first, None)),
+        (first, last) => {
+            let (first, last) = (parse(first)?, parse(last)?);
+            if last < first {
+                None
+            } else {
+                Some(Spec::Int(first, Some(last)))
+            }
+        }
+    }
+}
+
+/**
+parses a `Range` request header value against a representation of
+`total_len` bytes.
+
+* `bytes=0-499` is the first 500 bytes
+* `bytes=500-` is everything from offset 500 onward
+* `bytes=-500` is the final 500 bytes, or the entire representation
+  if it is shorter than that
+* multiple ranges may be separated by commas
+
+Ranges that extend past the end of the representation are clamped to
+it, and ranges that start past the end are dropped. If no ranges
+remain, the result is [`RangeSet::Unsatisfiable`]. A suffix of zero
+bytes (`bytes=-0`) is never satisfiable. Because an empty
+representation cannot be described by a `Content-Range`, a suffix
+range of an empty representation is [`RangeSet::Full`].
+
+If any part of the header is syntactically invalid, the whole header
+is ignored and the result is [`RangeSet::Full`].
+*/
+pub fn parse_range(header: &str, total_len: u64) -> RangeSet {
+    let specs = match header.trim().split_once('=') {
+        Some((unit, specs)) if unit.trim().eq_ignore_ascii_case("bytes") => specs,
+        _ => return RangeSet::Full,
+    };
+
+    let mut ranges = vec![];
+    let mut any_specs = false;
+    let mut empty_suffix = false;
+
+    for spec in specs
+        .split(',')
+        .map(str::trim)
+        .filter(|spec| !spec.is_empty())
+    {
+        any_specs = true;
+        match parse_spec(spec) {
+            None => return RangeSet::Full,
+
+            Some(Spec::Suffix(0)) => {}
+
+            Some(Spec::Suffix(_)) if total_len == 0 => empty_suffix = true,
+
+            Some(Spec::Suffix(len)) => ranges.push(total_len.saturating_sub(len)..total_len),
+
+            Some(Spec::Int(first, _)) if first >= total_len => {}
+
+            Some(Spec::Int(first, last)) => {
+                let end = last.map_or(total_len, |last| last.saturating_add(1).min(total_len));
+                ranges.push(first..end);
+            }
+        }
+    }
+
+    if !any_specs {
+        return RangeSet::Full;
+    }
+
+    if ranges.is_empty() {
+        return if empty_suffix {
+            RangeSet::Full
+        } else {
+            RangeSet::Unsatisfiable
+        };
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    let mut coalesced: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match coalesced.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => coalesced.push(range),
+        }
+    }
+
+    RangeSet::Satisfiable(coalesced)
+}
+
+/// a reader that seeks to an offset before its first read
+pub struct SeekingReader<R> {
+    reader: R,
+    start: Option<u64>,
+}
+
+impl<R> SeekingReader<R> {
+    pub const fn new(reader: R, start: u64) -> Self {
+        Self {
+            reader,
+            start: Some(start),
+        }
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncRead for SeekingReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        if let Some(start) = self.start {
+            ready!(Pin::new(&mut self.reader).poll_seek(cx, SeekFrom::Start(start)))?;
+            self.start = None;
+        }
+
+        Pin::new(&mut self.reader).poll_read(cx, buf)
+    }
+}
+
+/// a reader that reads and discards a number of bytes before its
+/// first read, for sources that cannot seek
+pub struct SkippingReader {
+    reader: Pin<Box<dyn AsyncRead + Send + Sync + 'static>>,
+    remaining: u64,
+}
+
+impl SkippingReader {
+    pub fn new(reader: impl AsyncRead + Send + Sync + 'static, skip: u64) -> Self {
+        Self {
+            reader: Box::pin(reader),
+            remaining: skip,
+        }
+    }
+}
+
+impl AsyncRead for SkippingReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        let Self { reader, remaining } = &mut *self;
+        while *remaining > 0 && !buf.is_empty() {
+            let len = usize::try_from(*remaining).map_or(buf.len(), |r| r.min(buf.len()));
+            let bytes = ready!(reader.as_mut().poll_read(cx, &mut buf[..len]))?;
+            if bytes == 0 {
+                return Poll::Ready(Ok(0));
+            }
+            *remaining -= bytes as u64;
+        }
+
+        reader.as_mut().poll_read(cx, buf)
+    }
+}
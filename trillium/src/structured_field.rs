@@ -0,0 +1,696 @@
+/*!
+# A parser and serializer for structured field values
+
+Several modern headers, such as `Priority`, `Accept-CH`, and
+`Cache-Status`, are defined as structured fields, as described in
+[rfc 8941](https://www.rfc-editor.org/rfc/rfc8941). A structured
+field is either a single [`Item`], a list of [`Member`]s, or a
+dictionary of keyed [`Member`]s. Each item and inner list may carry
+[`Parameters`].
+
+Parsing is strict: if any part of a value is invalid, the whole value
+is rejected and `None` is returned, as the rfc requires that such a
+field be ignored. Each of these types also implements `Display`,
+which serializes it in the canonical form, and
+[`serialize_list`] and [`serialize_dictionary`] serialize whole
+fields.
+
+```
+use trillium::structured_field::{parse_dictionary, parse_item, parse_list, BareItem, Item};
+
+let item = parse_item(r#""text/html"; q=0.5"#).unwrap();
+assert_eq!(item.bare_item().as_str(), Some("text/html"));
+assert_eq!(item.parameters().get("q"), Some(&BareItem::Decimal(0.5)));
+
+let list = parse_list("sec-ch-ua-platform, sec-ch-ua-arch").unwrap();
+assert_eq!(list.len(), 2);
+
+let dictionary = parse_dictionary("u=1, i").unwrap();
+assert_eq!(dictionary[0].0, "u");
+assert_eq!(dictionary[0].1.as_item().unwrap().bare_item().as_integer(), Some(1));
+assert_eq!(dictionary[1].1.as_item().unwrap().bare_item().as_bool(), Some(true));
+
+let item = Item::new(BareItem::Token("hit".into())).with_parameter("ttl", 42);
+assert_eq!(item.to_string(), "hit;ttl=42");
+```
+*/
+
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    ops::Deref,
+};
+
+/// the value of an [`Item`] or parameter, without any parameters
+#[derive(Debug, Clone, PartialEq)]
+pub enum BareItem {
+    /// an integer, such as `42` or `-7`
+    Integer(i64),
+
+    /// a decimal with up to three fractional digits, such as `0.5`
+    Decimal(f64),
+
+    /// a quoted string, such as `"hello"`, unescaped
+    String(String),
+
+    /// an unquoted token, such as `gzip` or `text/html`
+    Token(String),
+
+    /// a base64 byte sequence, such as `:aGVsbG8=:`, decoded
+    ByteSequence(Vec<u8>),
+
+    /// a boolean, `?1` or `?0`. a key with no value is also `true`
+    Boolean(bool),
+}
+
+impl BareItem {
+    /// returns the integer value, if this is an integer
+    pub const fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(integer) => Some(*integer),
+            _ => None,
+        }
+    }
+
+    /// returns the decimal value, if this is a decimal
+    pub const fn as_decimal(&self) -> Option<f64> {
+        match self {
+            Self::Decimal(decimal) => Some(*decimal),
+            _ => None,
+        }
+    }
+
+    /// returns the string content, if this is a string or a token
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) | Self::Token(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// returns the token, if this is a token
+    pub fn as_token(&self) -> Option<&str> {
+        match self {
+            Self::Token(token) => Some(token),
+            _ => None,
+        }
+    }
+
+    /// returns the decoded bytes, if this is a byte sequence
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::ByteSequence(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// returns the boolean value, if this is a boolean
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+}
+
+/// the ordered parameters of an [`Item`] or [`InnerList`]. when a
+/// key is repeated, the last value is kept in the position of the
+/// first
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Parameters(Vec<(String, BareItem)>);
+
+impl Parameters {
+    /// constructs an empty set of parameters
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// sets the parameter with this key, replacing any existing value
+    /// in its position
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<BareItem>) {
+        insert(&mut self.0, key.into(), value.into());
+    }
+
+    /// returns the value of the parameter with this key, if present
+    pub fn get(&self, key: &str) -> Option<&BareItem> {
+        self.0
+            .iter()
+            .find_map(|(k, value)| (k == key).then_some(value))
+    }
+}
+
+impl Deref for Parameters {
+    type Target = [(String, BareItem)];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// a [`BareItem`] with [`Parameters`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    bare_item: BareItem,
+    parameters: Parameters,
+}
+
+impl Item {
+    /// constructs an item with no parameters
+    pub const fn new(bare_item: BareItem) -> Self {
+        Self {
+            bare_item,
+            parameters: Parameters::new(),
+        }
+    }
+
+    /// chainable setter for a parameter of this item
+    #[must_use]
+    pub fn with_parameter(mut self, key: impl Into<String>, value: impl Into<BareItem>) -> Self {
+        self.parameters.insert(key, value);
+        self
+    }
+
+    /// the value of this item
+    pub const fn bare_item(&self) -> &BareItem {
+        &self.bare_item
+    }
+
+    /// the parameters of this item
+    pub const fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+}
+
+/// a parenthesized list of [`Item`]s with [`Parameters`], such as
+/// `("a" "b");q=1`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InnerList {
+    items: Vec<Item>,
+    parameters: Parameters,
+}
+
+impl InnerList {
+    /// constructs an inner list with no parameters
+    pub fn new(items: impl IntoIterator<Item = Item>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+            parameters: Parameters::new(),
+        }
+    }
+
+    /// chainable setter for a parameter of this inner list
+    #[must_use]
+    pub fn with_parameter(mut self, key: impl Into<String>, value: impl Into<BareItem>) -> Self {
+        self.parameters.insert(key, value);
+        self
+    }
+
+    /// the items in this inner list
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// the parameters of this inner list
+    pub const fn parameters(&self) -> &Parameters {
+        &self.parameters
+    }
+}
+
+/// a member of a list or dictionary
+#[derive(Debug, Clone, PartialEq)]
+pub enum Member {
+    /// a single item
+    Item(Item),
+
+    /// an inner list of items
+    InnerList(InnerList),
+}
+
+impl Member {
+    /// returns the item, if this member is an item
+    pub const fn as_item(&self) -> Option<&Item> {
+        match self {
+            Self::Item(item) => Some(item),
+            Self::InnerList(_) => None,
+        }
+    }
+
+    /// returns the inner list, if this member is an inner list
+    pub const fn as_inner_list(&self) -> Option<&InnerList> {
+        match self {
+            Self::InnerList(inner_list) => Some(inner_list),
+            Self::Item(_) => None,
+        }
+    }
+
+    /// the parameters of this member
+    pub const fn parameters(&self) -> &Parameters {
+        match self {
+            Self::Item(item) => &item.parameters,
+            Self::InnerList(inner_list) => &inner_list.parameters,
+        }
+    }
+}
+
+impl From<i64> for BareItem {
+    fn from(integer: i64) -> Self {
+        Self::Integer(integer)
+    }
+}
+
+impl From<f64> for BareItem {
+    fn from(decimal: f64) -> Self {
+        Self::Decimal(decimal)
+    }
+}
+
+impl From<bool> for BareItem {
+    fn from(boolean: bool) -> Self {
+        Self::Boolean(boolean)
+    }
+}
+
+impl From<Vec<u8>> for BareItem {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::ByteSequence(bytes)
+    }
+}
+
+impl From<BareItem> for Item {
+    fn from(bare_item: BareItem) -> Self {
+        Self::new(bare_item)
+    }
+}
+
+impl From<Item> for Member {
+    fn from(item: Item) -> Self {
+        Self::Item(item)
+    }
+}
+
+impl From<InnerList> for Member {
+    fn from(inner_list: InnerList) -> Self {
+        Self::InnerList(inner_list)
+    }
+}
+
+impl Display for BareItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Integer(integer) => write!(f, "{integer}"),
+            Self::Decimal(decimal) => {
+                let decimal = format!("{decimal:.3}");
+                f.write_str(decimal.trim_end_matches('0'))?;
+                if decimal.ends_with(".000") {
+                    f.write_char('0')?;
+                }
+                Ok(())
+            }
+            Self::String(string) => {
+                f.write_char('"')?;
+                for c in string.chars() {
+                    if matches!(c, '"' | '\\') {
+                        f.write_char('\\')?;
+                    }
+                    f.write_char(c)?;
+                }
+                f.write_char('"')
+            }
+            Self::Token(token) => f.write_str(token),
+            Self::ByteSequence(bytes) => write!(f, ":{}:", base64::encode(bytes)),
+            Self::Boolean(true) => f.write_str("?1"),
+            Self::Boolean(false) => f.write_str("?0"),
+        }
+    }
+}
+
+impl Display for Parameters {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.0 {
+            write!(f, ";{key}")?;
+            if value != &BareItem::Boolean(true) {
+                write!(f, "={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.bare_item, self.parameters)
+    }
+}
+
+impl Display for InnerList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('(')?;
+        for (i, item) in self.items.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            write!(f, "{item}")?;
+        }
+        write!(f, "){}", self.parameters)
+    }
+}
+
+impl Display for Member {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Item(item) => item.fmt(f),
+            Self::InnerList(inner_list) => inner_list.fmt(f),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        let mut parser = Self {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        parser.skip_sp();
+        parser
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &'a [u8] {
+        let start = self.position;
+        while matches!(self.peek(), Some(byte) if predicate(byte)) {
+            self.position += 1;
+        }
+        &self.input[start..self.position]
+    }
+
+    fn skip_sp(&mut self) {
+        self.take_while(|byte| byte == b' ');
+    }
+
+    fn skip_ows(&mut self) {
+        self.take_while(|byte| matches!(byte, b' ' | b'\t'));
+    }
+
+    fn finish<T>(mut self, value: T) -> Option<T> {
+        self.skip_sp();
+        self.peek().is_none().then_some(value)
+    }
+
+    // every byte this is called with is ascii, so nothing is lost
+    fn ascii(bytes: &[u8]) -> String {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    fn key(&mut self) -> Option<String> {
+        if !matches!(self.peek(), Some(b'a'..=b'z' | b'*')) {
+            return None;
+        }
+
+        Some(Self::ascii(self.take_while(
+            |byte| matches!(byte, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*'),
+        )))
+    }
+
+    fn bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'-' | b'0'..=b'9' => self.number(),
+            b'"' => self.string(),
+            b':' => self.byte_sequence(),
+            b'?' => {
+                self.position += 1;
+                let boolean = match self.peek()? {
+                    b'0' => false,
+                    b'1' => true,
+                    _ => return None,
+                };
+                self.position += 1;
+                Some(BareItem::Boolean(boolean))
+            }
+            byte if byte.is_ascii_alphabetic() || byte == b'*' => {
+                Some(BareItem::Token(Self::ascii(self.take_while(|byte| {
+                    is_tchar(byte) || matches!(byte, b':' | b'/')
+                }))))
+            }
+            _ => None,
+        }
+    }
+
+    fn number(&mut self) -> Option<BareItem> {
+        let start = self.position;
+        self.eat(b'-');
+        let integer = self.take_while(|byte| byte.is_ascii_digit());
+        if integer.is_empty() || integer.len() > 15 {
+            return None;
+        }
+
+        if self.eat(b'.') {
+            let fraction = self.take_while(|byte| byte.is_ascii_digit());
+            if fraction.is_empty() || fraction.len() > 3 || integer.len() > 12 {
+                return None;
+            }
+            return Self::ascii(&self.input[start..self.position])
+                .parse()
+                .ok()
+                .map(BareItem::Decimal);
+        }
+
+        Self::ascii(&self.input[start..self.position])
+            .parse()
+            .ok()
+            .map(BareItem::Integer)
+    }
+
+    fn string(&mut self) -> Option<BareItem> {
+        self.position += 1;
+        let mut string = String::new();
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.position += 1;
+                    return Some(BareItem::String(string));
+                }
+                b'\\' => {
+                    self.position += 1;
+                    match self.peek()? {
+                        byte @ (b'"' | b'\\') => string.push(char::from(byte)),
+                        _ => return None,
+                    }
+                }
+                byte @ 0x20..=0x7e => string.push(char::from(byte)),
+                _ => return None,
+            }
+            self.position += 1;
+        }
+    }
+
+    fn byte_sequence(&mut self) -> Option<BareItem> {
+        self.position += 1;
+        let encoded = self
+            .take_while(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'+' | b'/' | b'='));
+        if !self.eat(b':') {
+            return None;
+        }
+        decode_base64(encoded).map(BareItem::ByteSequence)
+    }
+
+    fn parameters(&mut self) -> Option<Parameters> {
+        let mut parameters: Vec<(String, BareItem)> = vec![];
+        while self.eat(b';') {
+            self.skip_sp();
+            let key = self.key()?;
+            let value = if self.eat(b'=') {
+                self.bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+            insert(&mut parameters, key, value);
+        }
+        Some(Parameters(parameters))
+    }
+
+    fn item(&mut self) -> Option<Item> {
+        let bare_item = self.bare_item()?;
+        let parameters = self.parameters()?;
+        Some(Item {
+            bare_item,
+            parameters,
+        })
+    }
+
+    fn inner_list(&mut self) -> Option<InnerList> {
+        self.position += 1;
+        let mut items = vec![];
+        loop {
+            self.skip_sp();
+            if self.eat(b')') {
+                let parameters = self.parameters()?;
+                return Some(InnerList { items, parameters });
+            }
+            items.push(self.item()?);
+            if !matches!(self.peek(), Some(b' ' | b')')) {
+                return None;
+            }
+        }
+    }
+
+    fn member(&mut self) -> Option<Member> {
+        if self.peek() == Some(b'(') {
+            self.inner_list().map(Member::InnerList)
+        } else {
+            self.item().map(Member::Item)
+        }
+    }
+
+    /// parses the separator between list or dictionary members,
+    /// returning false at the end of the input
+    fn separator(&mut self) -> Option<bool> {
+        self.skip_ows();
+        if self.peek().is_none() {
+            return Some(false);
+        }
+
+        if !self.eat(b',') {
+            return None;
+        }
+
+        self.skip_ows();
+        // a trailing comma is invalid
+        self.peek().map(|_| true)
+    }
+}
+
+fn insert<T>(members: &mut Vec<(String, T)>, key: String, value: T) {
+    match members.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, existing)) => *existing = value,
+        None => members.push((key, value)),
+    }
+}
+
+const fn is_tchar(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric()
+        || matches!(
+            byte,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+/// byte sequences are decoded leniently, as rfc 8941 recommends:
+/// padding is optional, and unused trailing bits are ignored
+const BASE64: base64::Config = base64::STANDARD_NO_PAD.decode_allow_trailing_bits(true);
+
+fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+    let data = encoded
+        .iter()
+        .position(|&byte| byte == b'=')
+        .map_or(encoded, |end| &encoded[..end]);
+    base64::decode_config(data, BASE64).ok()
+}
+
+/// parses a structured field item, such as `"text/html"; q=0.5`
+pub fn parse_item(input: &str) -> Option<Item> {
+    let mut parser = Parser::new(input);
+    let item = parser.item()?;
+    parser.finish(item)
+}
+
+/// parses a structured field list, such as `a, (b c);q=1, "d"`. an
+/// empty value is an empty list
+pub fn parse_list(input: &str) -> Option<Vec<Member>> {
+    let mut parser = Parser::new(input);
+    let mut members = vec![];
+    if parser.peek().is_none() {
+        return Some(members);
+    }
+
+    loop {
+        members.push(parser.member()?);
+        if !parser.separator()? {
+            return parser.finish(members);
+        }
+    }
+}
+
+/// parses a structured field dictionary, such as `u=1, i`, into its
+/// members in order.
+///
+/// a key without a value is the boolean `true`, and when a key is
+/// repeated, the last value is kept in the position of the first. an
+/// empty value is an empty dictionary
+pub fn parse_dictionary(input: &str) -> Option<Vec<(String, Member)>> {
+    let mut parser = Parser::new(input);
+    let mut members = vec![];
+    if parser.peek().is_none() {
+        return Some(members);
+    }
+
+    loop {
+        let key = parser.key()?;
+        let member = if parser.eat(b'=') {
+            parser.member()?
+        } else {
+            Member::Item(Item {
+                bare_item: BareItem::Boolean(true),
+                parameters: parser.parameters()?,
+            })
+        };
+        insert(&mut members, key, member);
+
+        if !parser.separator()? {
+            return parser.finish(members);
+        }
+    }
+}
+
+/// serializes a structured field list, such as `a, (b c);q=1, "d"`
+pub fn serialize_list(members: &[Member]) -> String {
+    members
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// serializes a structured field dictionary, such as `u=1, i`. a
+/// member that is the boolean `true` is serialized as its key and
+/// parameters alone
+pub fn serialize_dictionary(members: &[(String, Member)]) -> String {
+    members
+        .iter()
+        .map(|(key, member)| match member {
+            Member::Item(item) if item.bare_item == BareItem::Boolean(true) => {
+                format!("{key}{}", item.parameters)
+            }
+            member => format!("{key}={member}"),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use trillium::AltSvc;
+use trillium_testing::prelude::*;
+
+#[test]
+fn formatting() {
+    assert_eq!(AltSvc::new("h3", ":443").to_string(), r#"h3=":443""#);
+    assert_eq!(
+        AltSvc::new("h3-29", "alt.example.com:443")
+            .with_max_age(Duration::from_millis(3_600_500))
+            .with_persist()
+            .to_string(),
+        r#"h3-29="alt.example.com:443"; ma=3600; persist=1"#
+    );
+}
+
+#[test]
+fn escaping() {
+    assert_eq!(
+        AltSvc::new("w=x:y", ":80").to_string(),
+        r#"w%3Dx%3Ay=":80""#
+    );
+    assert_eq!(
+        AltSvc::new("h2", r#"a"b\c:80"#).to_string(),
+        r#"h2="a\"b\\c:80""#
+    );
+}
+
+#[test]
+fn replaces_previous_header() {
+    let handler = |conn: Conn| async move {
+        conn.with_alt_svc([AltSvc::new("h2", ":8443")])
+            .with_alt_svc([AltSvc::new("h3", ":443")])
+            .ok("ok")
+    };
+    assert_headers!(get("/").on(&handler), "alt-svc" => r#"h3=":443""#);
+}
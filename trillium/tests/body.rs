@@ -0,0 +1,100 @@
+use futures_lite::io::Cursor;
+use trillium::Conn;
+use trillium_testing::{block_on, prelude::*};
+
+#[test]
+fn body_stream() {
+    let handler = |conn: Conn| async move {
+        let len = conn.querystring().parse().ok();
+        conn.with_body_stream(Cursor::new("streamed body"), len)
+            .with_status(200)
+    };
+
+    let mut conn = get("/?13").on(&handler);
+    assert_eq!(conn.response_len(), Some(13));
+    assert_body!(&mut conn, "streamed body");
+
+    let mut conn = get("/").on(&handler);
+    assert_eq!(conn.response_len(), None);
+    assert_body!(&mut conn, "streamed body");
+}
+
+#[test]
+fn body_iter() {
+    let handler = |conn: Conn| async move {
+        let chunks = vec![b"a".to_vec(), vec![], vec![b'b'; 20_000], b"c".to_vec()];
+        conn.with_body_iter(chunks.into_iter()).with_status(200)
+    };
+
+    let mut conn = get("/").on(&handler);
+    assert_eq!(conn.response_len(), None);
+    let body = conn.take_response_body_string().unwrap();
+    assert_eq!(body, format!("a{}c", "b".repeat(20_000)));
+
+    let mut conn = get("/")
+        .on(&|conn: Conn| async move { conn.with_body_iter(std::iter::empty()).with_status(200) });
+    assert_body!(&mut conn, "");
+}
+
+#[test]
+fn buffered_request_body() {
+    block_on(async {
+        let mut conn = post("/").with_request_body("hello").on(&());
+        assert_eq!(conn.buffered_request_body(), None);
+        assert_eq!(
+            conn.buffer_request_body(5).await.unwrap(),
+            Some(&b"hello"[..])
+        );
+        assert_eq!(conn.request_body_bytes().await.unwrap(), b"hello");
+        assert_eq!(
+            conn.request_body_string_with_limit(5).await.unwrap(),
+            "hello"
+        );
+        assert!(conn.request_body_bytes_with_limit(4).await.is_err());
+        assert_eq!(conn.buffered_request_body(), Some(&b"hello"[..]));
+
+        // a chunked body is buffered up to the threshold
+        let mut conn = post("/")
+            .with_request_header("transfer-encoding", "chunked")
+            .with_request_body("5\r\nhello\r\n0\r\n\r\n")
+            .on(&());
+        assert_eq!(
+            conn.buffer_request_body(5).await.unwrap(),
+            Some(&b"hello"[..])
+        );
+        assert_eq!(conn.request_body_string().await.unwrap(), "hello");
+
+        let mut conn = post("/")
+            .with_request_header("transfer-encoding", "chunked")
+            .with_request_body("5\r\nhello\r\n0\r\n\r\n")
+            .on(&());
+        assert!(matches!(
+            conn.buffer_request_body(4).await,
+            Err(trillium_http::Error::ReceivedBodyTooLong(4))
+        ));
+        assert_eq!(conn.buffered_request_body(), None);
+    });
+}
+
+#[test]
+fn request_content_length() {
+    let conn = post("/").with_request_body("hello").on(&());
+    assert_eq!(conn.request_content_length().unwrap(), Some(5));
+
+    let conn = post("/")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_body("5\r\nhello\r\n0\r\n\r\n")
+        .on(&());
+    assert_eq!(conn.request_content_length().unwrap(), None);
+
+    let conn = get("/").on(&());
+    assert_eq!(conn.request_content_length().unwrap(), Some(0));
+
+    let conn = post("/")
+        .with_request_header("content-length", "5, 6")
+        .on(&());
+    assert!(matches!(
+        conn.request_content_length(),
+        Err(trillium_http::Error::MalformedHeader(_))
+    ));
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "checksum")]
+use trillium_http::Error;
+use trillium_testing::{block_on, prelude::*};
+
+const SHA256: &str = "uU0nuZNNPgilLlLX2n2r+sSE7+N6U4DukIj3rOLvzek=";
+
+#[test]
+fn trailer_checksum() {
+    let mut conn = post("/")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_body(format!(
+            "5\r\nhello\r\n6\r\n world\r\n0\r\nx-amz-checksum-sha256: {SHA256}\r\n\r\n"
+        ))
+        .on(&());
+
+    let body = block_on(conn.request_body_bytes_verified()).unwrap();
+    assert_eq!(body, b"hello world");
+}
+
+#[test]
+fn header_checksum() {
+    let mut conn = post("/")
+        .with_request_header("x-amz-checksum-sha256", SHA256)
+        .with_request_body("hello world")
+        .on(&());
+
+    let body = block_on(conn.request_body_bytes_verified()).unwrap();
+    assert_eq!(body, b"hello world");
+}
+
+#[test]
+fn mismatched_checksum() {
+    let mut conn = post("/")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_body("5\r\nhello\r\n0\r\nx-amz-checksum-crc32: AAAAAA==\r\n\r\n")
+        .on(&());
+
+    assert!(matches!(
+        block_on(conn.request_body_bytes_verified()),
+        Err(Error::ChecksumMismatch("x-amz-checksum-crc32"))
+    ));
+}
+
+#[test]
+fn trailers_without_checksum() {
+    let mut conn = post("/")
+        .with_request_header("transfer-encoding", "chunked")
+        .with_request_body("5\r\nhello\r\n0\r\nx-other: value\r\n\r\n")
+        .on(&());
+
+    assert_eq!(
+        block_on(conn.request_body_bytes_verified()).unwrap(),
+        b"hello"
+    );
+    let trailers = conn.request_trailers().unwrap();
+    assert_eq!(trailers.get_str("x-other"), Some("value"));
+}
+
+#[test]
+fn content_digest() {
+    const HELLO_SHA256: &str = "sha-256=:LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=:";
+    let handler = |conn: trillium::Conn| async move {
+        let status = conn.querystring().parse().unwrap_or(200);
+        conn.with_body("hello")
+            .with_status(status)
+            .with_content_digest()
+    };
+
+    assert_headers!(
+        get("/")
+            .with_request_header("want-repr-digest", "sha-512=0, sha-256=2")
+            .on(&handler),
+        "content-digest" => HELLO_SHA256,
+        "repr-digest" => HELLO_SHA256
+    );
+
+    assert_headers!(
+        get("/?206")
+            .with_request_header("want-repr-digest", "sha-256=2")
+            .on(&handler),
+        "content-digest" => HELLO_SHA256,
+        "repr-digest" => None
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header("want-content-digest", "sha-256=0, md5=10, unixsum=2")
+            .on(&handler),
+        "content-digest" => None
+    );
+
+    let streaming = |conn: trillium::Conn| async move {
+        conn.with_body_stream(futures_lite::io::Cursor::new("hello"), Some(5))
+            .with_status(200)
+            .with_content_digest()
+    };
+    assert_headers!(get("/").on(&streaming), "content-digest" => None);
+}
@@ -0,0 +1,36 @@
+use trillium::ConnParts;
+use trillium_testing::prelude::*;
+
+#[test]
+fn parts_round_trip() {
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_header("x-a", "b")
+            .with_status(201)
+            .with_body("created")
+    });
+    let parts = Conn::from(conn).into_parts();
+    assert_eq!(parts.status, Some(Status::Created));
+    assert!(!parts.halted);
+
+    let conn =
+        get("/").on(&|conn: Conn| async move { conn.with_header("x-stale", "1").ok("replaced") });
+    let conn = Conn::from(conn).with_parts(parts);
+    let ConnParts {
+        status,
+        response_headers,
+        response_body,
+        halted,
+    } = conn.into_parts();
+    assert_eq!(status, Some(Status::Created));
+    assert_eq!(response_headers.get_str("x-a"), Some("b"));
+    assert!(!response_headers.has_header("x-stale"));
+    assert_eq!(response_body.unwrap().static_bytes(), Some(&b"created"[..]));
+    assert!(!halted);
+
+    let conn = get("/").on(&|conn: Conn| async move { conn.ok("body") });
+    let conn = Conn::from(conn).with_parts(ConnParts::default());
+    let parts = conn.into_parts();
+    assert_eq!(parts.status, Some(Status::Ok));
+    assert!(parts.response_body.is_none());
+    assert!(!parts.halted);
+}
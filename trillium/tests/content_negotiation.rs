@@ -0,0 +1,69 @@
+use trillium_testing::prelude::*;
+
+fn negotiate(accept: &'static str, offered: &[&'static str]) -> Option<&'static str> {
+    get("/")
+        .with_request_header("accept", accept)
+        .on(&())
+        .negotiate_content_type(offered)
+}
+
+#[test]
+fn quality_ordering() {
+    let offered = ["application/json", "text/html", "text/plain"];
+    assert_eq!(
+        negotiate(
+            "text/plain;q=0.2, text/html;q=0.8, application/json;q=0.5",
+            &offered
+        ),
+        Some("text/html")
+    );
+    assert_eq!(
+        negotiate("text/html;q=0.5, application/json;q=0.5", &offered),
+        Some("application/json")
+    );
+    assert_eq!(
+        negotiate("TEXT/HTML; Q=1, application/json;q=0.999", &offered),
+        Some("text/html")
+    );
+    assert_eq!(
+        negotiate("text/html;q=0, text/plain;q=0", &offered[1..]),
+        None
+    );
+    assert_eq!(negotiate("text/html;q=nonsense", &["text/html"]), None);
+    assert_eq!(negotiate("", &offered), None);
+}
+
+#[test]
+fn wildcard_precedence() {
+    let offered = ["application/json", "text/html", "text/plain"];
+    assert_eq!(negotiate("*/*", &offered), Some("application/json"));
+    assert_eq!(
+        negotiate("*/*;q=0.1, text/*;q=0.5", &offered),
+        Some("text/html")
+    );
+
+    // the most specific range decides, even if it has a lower quality
+    assert_eq!(
+        negotiate("text/*, text/html;q=0.1, */*;q=0.5", &offered),
+        Some("text/plain")
+    );
+    assert_eq!(negotiate("text/*;q=0, */*", &offered[1..]), None);
+    assert_eq!(
+        negotiate("*/*, text/html;q=0", &offered[1..]),
+        Some("text/plain")
+    );
+}
+
+#[test]
+fn media_type_parameters() {
+    let offered = ["text/html;level=1", "text/html"];
+    assert_eq!(
+        negotiate("text/html;level=1;q=0.2, text/html", &offered),
+        Some("text/html")
+    );
+    assert_eq!(
+        negotiate("text/html;level=1, text/html;q=0.2", &offered),
+        Some("text/html;level=1")
+    );
+    assert_eq!(negotiate("text/html;level=2", &offered), None);
+}
@@ -0,0 +1,64 @@
+use trillium::{Body, Conn, KnownHeaderName};
+use trillium_testing::prelude::*;
+
+fn sniffed(body: &'static [u8]) -> Option<String> {
+    let conn =
+        get("/").on(&move |conn: Conn| async move { conn.ok(body).with_sniffed_content_type() });
+
+    conn.response_headers()
+        .get_str(KnownHeaderName::ContentType)
+        .map(String::from)
+}
+
+#[test]
+fn recognized_content() {
+    assert_eq!(
+        sniffed(b"\xff\xd8\xff\xe0\0\x10JFIF").as_deref(),
+        Some("image/jpeg")
+    );
+    assert_eq!(sniffed(b"GIF89a\x01\0\x01\0").as_deref(), Some("image/gif"));
+    assert_eq!(sniffed(b"%PDF-1.7\n").as_deref(), Some("application/pdf"));
+    assert_eq!(
+        sniffed(b"  \n<HTML lang=\"en\">").as_deref(),
+        Some("text/html; charset=utf-8")
+    );
+    assert_eq!(
+        sniffed("héllo".as_bytes()).as_deref(),
+        Some("text/plain; charset=utf-8")
+    );
+    assert_eq!(sniffed(b"caf\xe9").as_deref(), Some("text/plain"));
+    assert_eq!(
+        sniffed(b"<htmlish>").as_deref(),
+        Some("text/plain; charset=utf-8")
+    );
+}
+
+#[test]
+fn unrecognized_binary_content() {
+    assert_eq!(sniffed(b"\0\x01\x02\x03"), None);
+}
+
+#[test]
+fn multibyte_character_at_the_end_of_the_inspected_bytes() {
+    let body: &'static [u8] = Box::leak(format!("{}é", "a".repeat(511)).into_bytes().into());
+    assert_eq!(sniffed(body).as_deref(), Some("text/plain; charset=utf-8"));
+}
+
+#[test]
+fn nosniff_is_respected() {
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_header("x-content-type-options", "nosniff")
+            .ok("<html>")
+            .with_sniffed_content_type()
+    });
+    assert_eq!(conn.response_headers().get_str("content-type"), None);
+}
+
+#[test]
+fn streaming_bodies_are_not_sniffed() {
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_body(Body::new_streaming(&b"<html>"[..], Some(6)))
+            .with_sniffed_content_type()
+    });
+    assert_eq!(conn.response_headers().get_str("content-type"), None);
+}
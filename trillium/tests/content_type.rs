@@ -0,0 +1,19 @@
+#![cfg(feature = "mime")]
+
+use trillium::{mime, Conn, KnownHeaderName};
+use trillium_testing::prelude::*;
+
+#[test]
+fn with_content_type() {
+    let conn = get("/")
+        .on(&|conn: Conn| async move { conn.with_content_type(mime::APPLICATION_JSON).ok("{}") });
+    assert_headers!(conn, "content-type" => "application/json");
+
+    let conn = get("/").on(&|conn: Conn| async move {
+        let csv: mime::Mime = "text/csv; header=present".parse().unwrap();
+        conn.with_header(KnownHeaderName::ContentType, "text/plain")
+            .with_content_type(csv)
+            .ok("a,b")
+    });
+    assert_headers!(conn, "content-type" => "text/csv; header=present");
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "json")]
+
+use serde::Deserialize;
+use trillium::{Conn, FormError, Status};
+use trillium_testing::prelude::*;
+
+#[derive(Deserialize, Debug)]
+struct Comment {
+    author: String,
+    body: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+async fn comment(mut conn: Conn) -> Conn {
+    match conn.request_body_form::<Comment>().await {
+        Ok(comment) => {
+            let body = format!("{}: {} {:?}", comment.author, comment.body, comment.tags);
+            conn.ok(body)
+        }
+        Err(FormError::ContentType) => conn.halt_with_status(Status::UnsupportedMediaType),
+        Err(FormError::Form(e)) => conn.halt_with_body(Status::UnprocessableEntity, e.to_string()),
+        Err(_) => conn.halt_with_status(Status::BadRequest),
+    }
+}
+
+fn form(body: &'static str) -> trillium_testing::TestConn {
+    post("/")
+        .with_request_header(
+            "content-type",
+            "application/x-www-form-urlencoded; charset=utf-8",
+        )
+        .with_request_body(body)
+}
+
+#[test]
+fn deserializes_forms() {
+    assert_ok!(
+        form("author=jo&body=hello+there%21").on(&comment),
+        "jo: hello there! []"
+    );
+    assert_ok!(
+        form("tags=a&author=jo&tags=b&body=hi").on(&comment),
+        r#"jo: hi ["a", "b"]"#
+    );
+}
+
+#[test]
+fn rejects_invalid_forms() {
+    assert_status!(form("author=jo").on(&comment), 422);
+
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", "application/json")
+            .with_request_body(r#"{"author":"jo","body":"hi"}"#)
+            .on(&comment),
+        415
+    );
+    assert_status!(
+        post("/")
+            .with_request_body("author=jo&body=hi")
+            .on(&comment),
+        415
+    );
+}
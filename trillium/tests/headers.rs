@@ -0,0 +1,189 @@
+use trillium::KnownHeaderName;
+use trillium_testing::prelude::*;
+
+#[test]
+fn appended_headers_are_preserved() {
+    let handler = |conn: Conn| async move {
+        conn.with_appended_header("link", "</a.css>; rel=preload")
+            .with_appended_header(KnownHeaderName::Link, "</b.js>; rel=preload")
+            .ok("ok")
+    };
+
+    let conn = get("/").on(&handler);
+    let links = conn.inner().response_headers().get_values("link").unwrap();
+    let links: Vec<_> = links.iter().map(|link| link.to_string()).collect();
+    assert_eq!(links, ["</a.css>; rel=preload", "</b.js>; rel=preload"]);
+}
+
+#[test]
+fn append_after_insert() {
+    let handler = |mut conn: Conn| async move {
+        conn.append_header("vary", "accept-encoding");
+        conn.with_header("vary", "accept")
+            .with_appended_header("vary", "origin")
+            .ok("ok")
+    };
+
+    let conn = get("/").on(&handler);
+    let vary = conn.inner().response_headers().get_values("vary").unwrap();
+    let vary: Vec<_> = vary.iter().map(|value| value.to_string()).collect();
+    assert_eq!(vary, ["accept", "origin"]);
+}
+
+#[test]
+fn save_data() {
+    let handler = |mut conn: Conn| async move {
+        let body = if conn.save_data() { "light" } else { "full" };
+        conn.ok(body)
+    };
+
+    for value in ["on", "ON", " on ", "on; ext"] {
+        assert_ok!(
+            get("/").with_request_header("save-data", value).on(&handler),
+            "light",
+            "vary" => "Save-Data"
+        );
+    }
+
+    for value in ["off", "", "onward"] {
+        assert_ok!(
+            get("/").with_request_header("save-data", value).on(&handler),
+            "full",
+            "vary" => "Save-Data"
+        );
+    }
+
+    assert_ok!(get("/").on(&handler), "full", "vary" => "Save-Data");
+
+    let handler = |mut conn: Conn| async move {
+        conn.append_header("vary", "accept");
+        conn.append_header("vary", "origin, save-data");
+        conn.save_data();
+        conn.ok("ok")
+    };
+    let conn = get("/").on(&handler);
+    let vary = conn.inner().response_headers().get_values("vary").unwrap();
+    assert_eq!(vary.len(), 2);
+
+    let handler = |mut conn: Conn| async move {
+        conn.append_header("vary", "accept");
+        conn.append_header("vary", "origin");
+        conn.save_data();
+        conn.ok("ok")
+    };
+    assert_headers!(get("/").on(&handler), "vary" => "accept, origin, Save-Data");
+}
+
+#[test]
+fn vary() {
+    let handler = |conn: Conn| async move { conn.vary("accept").vary("accept-encoding").ok("ok") };
+    assert_headers!(get("/").on(&handler), "vary" => "accept, accept-encoding");
+
+    let handler = |conn: Conn| async move {
+        conn.with_header("vary", "Accept")
+            .vary("accept")
+            .vary("Origin")
+            .ok("ok")
+    };
+    assert_headers!(get("/").on(&handler), "vary" => "Accept, Origin");
+
+    let handler = |conn: Conn| async move { conn.with_header("vary", "*").vary("accept").ok("ok") };
+    assert_headers!(get("/").on(&handler), "vary" => "*");
+}
+
+#[test]
+fn warnings() {
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_warning(110, "cache.example", "Response is Stale")
+            .with_warning(7, "-", r"back\slash")
+            .ok("ok")
+    });
+
+    let warnings = conn
+        .inner()
+        .response_headers()
+        .get_values(KnownHeaderName::Warning)
+        .unwrap();
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0], r#"110 cache.example "Response is Stale""#);
+    assert_eq!(warnings[1], r#"007 - "back\\slash""#);
+}
+
+#[test]
+fn nosniff() {
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_header("x-content-type-options", " nosniff ")
+            .ok("ok")
+    });
+    assert!(conn.is_nosniff());
+
+    let conn = get("/").on(&|conn: Conn| async move {
+        conn.with_header("x-content-type-options", "sniff-away")
+            .ok("ok")
+    });
+    assert!(!conn.is_nosniff());
+}
+
+#[test]
+fn request_header() {
+    let handler = |conn: Conn| async move {
+        let first = conn
+            .request_header("x-value")
+            .unwrap_or_default()
+            .to_string();
+        let count = conn
+            .request_header_values(KnownHeaderName::Via)
+            .map_or(0, |values| values.len());
+        conn.ok(format!("{first} {count}"))
+    };
+
+    let conn = get("/")
+        .with_request_header("x-value", "one")
+        .with_request_header("x-value", "two")
+        .on(&handler);
+    assert_ok!(conn, "one 0");
+
+    let conn = get("/")
+        .with_request_header("via", "1.1 a")
+        .with_request_header("via", "1.1 b")
+        .with_request_header("via", "1.1 c")
+        .on(&handler);
+    assert_ok!(conn, " 3");
+}
+
+#[test]
+fn header_if_unset() {
+    let defaults = |conn: Conn| async move {
+        conn.with_header_if_unset(KnownHeaderName::CacheControl, "max-age=60")
+            .with_header_if_unset("x-frame-options", "deny")
+    };
+
+    let conn = get("/").on(&(
+        |conn: Conn| async move { conn.with_header("cache-control", "no-store") },
+        defaults,
+    ));
+    assert_headers!(conn, "cache-control" => "no-store", "x-frame-options" => "deny");
+
+    let conn = get("/").on(&defaults);
+    assert_headers!(conn, "cache-control" => "max-age=60");
+}
+
+#[test]
+fn request_and_response_headers() {
+    let handler = |mut conn: Conn| async move {
+        let accept = conn
+            .request_headers()
+            .get_str("accept")
+            .unwrap()
+            .to_string();
+        conn.response_headers_mut().insert("x-accept", accept);
+        assert!(conn.response_headers().has_header("x-accept"));
+        assert!(!conn.request_headers().has_header("x-accept"));
+        conn.ok("ok")
+    };
+
+    let conn = get("/")
+        .with_request_header("accept", "text/plain")
+        .on(&handler);
+    assert_headers!(conn, "x-accept" => "text/plain");
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "json")]
+
+use serde::{Deserialize, Serialize};
+use trillium::{Conn, JsonError, Status};
+use trillium_testing::prelude::*;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Todo {
+    title: String,
+    done: bool,
+}
+
+async fn toggle(mut conn: Conn) -> Conn {
+    match conn.request_body_json::<Todo>().await {
+        Ok(mut todo) => {
+            todo.done = !todo.done;
+            conn.with_json_body(&todo)
+                .unwrap()
+                .with_status(Status::Ok)
+                .halt()
+        }
+        Err(JsonError::Json(e)) => conn
+            .with_status(Status::UnprocessableEntity)
+            .with_body(e.to_string())
+            .halt(),
+        Err(JsonError::Http(_)) => conn.with_status(Status::BadRequest).halt(),
+        Err(_) => conn.with_status(Status::InternalServerError).halt(),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut conn = post("/")
+        .with_request_body(r#"{"title":"write tests","done":false}"#)
+        .on(&toggle);
+
+    assert_status!(&conn, 200);
+    assert_headers!(&mut conn, "content-type" => "application/json");
+    let body = conn.take_response_body_string().unwrap();
+    assert_eq!(
+        serde_json::from_str::<Todo>(&body).unwrap(),
+        Todo {
+            title: "write tests".into(),
+            done: true
+        }
+    );
+}
+
+#[test]
+fn invalid_json() {
+    assert_response!(
+        post("/").with_request_body(r#"{"title":"#).on(&toggle),
+        Status::UnprocessableEntity
+    );
+
+    assert_response!(
+        post("/").with_request_body(r#"{"done":true}"#).on(&toggle),
+        Status::UnprocessableEntity,
+        "missing field `title` at line 1 column 13"
+    );
+}
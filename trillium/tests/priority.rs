@@ -0,0 +1,60 @@
+use trillium::Priority;
+use trillium_testing::prelude::*;
+
+fn parse(header: &str) -> Priority {
+    header.parse().unwrap()
+}
+
+#[test]
+fn parameters() {
+    assert_eq!(parse("u=5"), Priority::new(5, false));
+    assert_eq!(parse("i"), Priority::new(3, true));
+    assert_eq!(parse("u=0, i"), Priority::new(0, true));
+    assert_eq!(parse("i=?1,u=7"), Priority::new(7, true));
+    assert_eq!(parse("u=1, i=?0"), Priority::new(1, false));
+}
+
+#[test]
+fn defaults() {
+    assert_eq!(Priority::default(), Priority::new(3, false));
+    assert_eq!(parse(""), Priority::default());
+    assert_eq!(parse("u=8"), Priority::default());
+    assert_eq!(parse("u=-1"), Priority::default());
+    assert_eq!(parse("u=1.5"), Priority::default());
+    assert_eq!(parse("u=\"1\", i=1"), Priority::default());
+    assert_eq!(parse("u=2, u=4"), Priority::new(4, false));
+}
+
+#[test]
+fn unknown_members_and_parameters_are_ignored() {
+    assert_eq!(
+        parse(r#"x-custom="a \"b\"", u=2;p=1, other=(a b);q, i;x=?1"#),
+        Priority::new(2, true)
+    );
+    assert_eq!(parse("u=2, y=:aGVsbG8=:"), Priority::new(2, false));
+}
+
+#[test]
+fn invalid_dictionaries_are_default() {
+    assert_eq!(parse("u=1,"), Priority::default());
+    assert_eq!(parse("U=1"), Priority::default());
+    assert_eq!(parse("u=1 i"), Priority::default());
+    assert_eq!(parse("u=1, i=?2"), Priority::default());
+}
+
+#[test]
+fn display() {
+    assert_eq!(Priority::new(3, false).to_string(), "u=3");
+    assert_eq!(Priority::new(9, true).to_string(), "u=7, i");
+    assert_eq!(
+        parse(&Priority::new(1, true).to_string()),
+        Priority::new(1, true)
+    );
+}
+
+#[test]
+fn conn() {
+    let conn = get("/").with_request_header("priority", "u=1, i").on(&());
+    assert_eq!(conn.request_priority(), Some(Priority::new(1, true)));
+    assert_eq!(get("/").on(&()).request_priority(), None);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "json")]
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use trillium_testing::prelude::*;
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Search {
+    q: String,
+    page: Option<u32>,
+    #[serde(default)]
+    ids: Vec<u64>,
+    order: Option<Order>,
+}
+
+#[test]
+fn typed_values() {
+    let conn = get("/?q=hello%20world&page=2&ids=1&ids=20&ids=300&order=desc").on(&());
+    assert_eq!(
+        conn.query::<Search>().unwrap(),
+        Search {
+            q: "hello world".into(),
+            page: Some(2),
+            ids: vec![1, 20, 300],
+            order: Some(Order::Desc)
+        }
+    );
+}
+
+#[test]
+fn single_value_into_vec() {
+    let conn = get("/?q=x&ids=7").on(&());
+    assert_eq!(conn.query::<Search>().unwrap().ids, vec![7]);
+}
+
+#[test]
+fn repeated_scalar_uses_last_value() {
+    let conn = get("/?q=first&q=second").on(&());
+    assert_eq!(conn.query::<Search>().unwrap().q, "second");
+
+    let conn = get("/?a=1&b=2&a=3").on(&());
+    let map = conn.query::<HashMap<String, String>>().unwrap();
+    assert_eq!(map["a"], "3");
+    assert_eq!(map["b"], "2");
+}
+
+#[test]
+fn errors() {
+    assert!(get("/").on(&()).query::<Search>().is_err());
+    assert!(get("/?q=x&page=two").on(&()).query::<Search>().is_err());
+    assert!(get("/?q=x&ids=1&ids=b").on(&()).query::<Search>().is_err());
+    assert!(get("/?q=x&order=sideways")
+        .on(&())
+        .query::<Search>()
+        .is_err());
+}
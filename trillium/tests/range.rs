@@ -0,0 +1,282 @@
+use trillium::{content_range, parse_range, RangeSet};
+
+fn satisfiable(ranges: &[(u64, u64)]) -> RangeSet {
+    RangeSet::Satisfiable(ranges.iter().map(|&(start, end)| start..end).collect())
+}
+
+#[test]
+fn closed_ranges() {
+    assert_eq!(parse_range("bytes=0-499", 1000), satisfiable(&[(0, 500)]));
+    assert_eq!(
+        parse_range("bytes=500-999", 1000),
+        satisfiable(&[(500, 1000)])
+    );
+    assert_eq!(parse_range("bytes=0-0", 1000), satisfiable(&[(0, 1)]));
+    assert_eq!(
+        parse_range("bytes=999-999", 1000),
+        satisfiable(&[(999, 1000)])
+    );
+}
+
+#[test]
+fn closed_ranges_are_clamped() {
+    assert_eq!(
+        parse_range("bytes=500-5000", 1000),
+        satisfiable(&[(500, 1000)])
+    );
+    assert_eq!(
+        parse_range("bytes=0-18446744073709551615", 10),
+        satisfiable(&[(0, 10)])
+    );
+}
+
+#[test]
+fn open_ranges() {
+    assert_eq!(parse_range("bytes=500-", 1000), satisfiable(&[(500, 1000)]));
+    assert_eq!(parse_range("bytes=0-", 1000), satisfiable(&[(0, 1000)]));
+    assert_eq!(parse_range("bytes=999-", 1000), satisfiable(&[(999, 1000)]));
+}
+
+#[test]
+fn suffix_ranges() {
+    assert_eq!(parse_range("bytes=-500", 1000), satisfiable(&[(500, 1000)]));
+    assert_eq!(parse_range("bytes=-1", 1000), satisfiable(&[(999, 1000)]));
+    assert_eq!(parse_range("bytes=-1000", 1000), satisfiable(&[(0, 1000)]));
+    assert_eq!(parse_range("bytes=-5000", 1000), satisfiable(&[(0, 1000)]));
+}
+
+#[test]
+fn unsatisfiable() {
+    assert_eq!(parse_range("bytes=1000-", 1000), RangeSet::Unsatisfiable);
+    assert_eq!(
+        parse_range("bytes=1000-2000", 1000),
+        RangeSet::Unsatisfiable
+    );
+    assert_eq!(parse_range("bytes=-0", 1000), RangeSet::Unsatisfiable);
+    assert_eq!(
+        parse_range("bytes=2000-, 1500-1600, -0", 1000),
+        RangeSet::Unsatisfiable
+    );
+}
+
+#[test]
+fn unsatisfiable_ranges_are_dropped_when_others_are_satisfiable() {
+    assert_eq!(
+        parse_range("bytes=2000-, 0-9", 1000),
+        satisfiable(&[(0, 10)])
+    );
+    assert_eq!(
+        parse_range("bytes=-0, -10", 1000),
+        satisfiable(&[(990, 1000)])
+    );
+}
+
+#[test]
+fn zero_length_representations() {
+    assert_eq!(parse_range("bytes=0-", 0), RangeSet::Unsatisfiable);
+    assert_eq!(parse_range("bytes=0-0", 0), RangeSet::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-0", 0), RangeSet::Unsatisfiable);
+    assert_eq!(parse_range("bytes=-10", 0), RangeSet::Full);
+}
+
+#[test]
+fn multiple_ranges() {
+    assert_eq!(
+        parse_range("bytes=0-9,20-29", 100),
+        satisfiable(&[(0, 10), (20, 30)])
+    );
+    assert_eq!(
+        parse_range("bytes=20-29, 0-9", 100),
+        satisfiable(&[(0, 10), (20, 30)])
+    );
+    assert_eq!(
+        parse_range("bytes=0-9, -10", 100),
+        satisfiable(&[(0, 10), (90, 100)])
+    );
+}
+
+#[test]
+fn coalescing() {
+    assert_eq!(
+        parse_range("bytes=0-99, 50-149", 1000),
+        satisfiable(&[(0, 150)])
+    );
+    assert_eq!(
+        parse_range("bytes=0-9, 10-19", 1000),
+        satisfiable(&[(0, 20)])
+    );
+    assert_eq!(parse_range("bytes=0-9, 2-3", 1000), satisfiable(&[(0, 10)]));
+    assert_eq!(
+        parse_range("bytes=900-, -50, 0-0", 1000),
+        satisfiable(&[(0, 1), (900, 1000)])
+    );
+    assert_eq!(
+        parse_range("bytes=0-0, 0-0, 0-0", 1000),
+        satisfiable(&[(0, 1)])
+    );
+}
+
+#[test]
+fn whitespace_and_case() {
+    assert_eq!(
+        parse_range("  Bytes = 0-9 ,, 20-29 , ", 100),
+        satisfiable(&[(0, 10), (20, 30)])
+    );
+    assert_eq!(parse_range("BYTES=0-9", 100), satisfiable(&[(0, 10)]));
+}
+
+#[test]
+fn invalid_headers_are_ignored() {
+    for header in [
+        "",
+        "bytes",
+        "bytes=",
+        "bytes=,",
+        "lines=0-9",
+        "bytes=9-0",
+        "bytes=-",
+        "bytes=a-b",
+        "bytes=0-9, x",
+        "bytes=+1-2",
+        "bytes=1--2",
+        "bytes=0 - 9",
+        "bytes=18446744073709551616-",
+        "bytes=0-9;10-19",
+    ] {
+        assert_eq!(parse_range(header, 100), RangeSet::Full, "{:?}", header);
+    }
+}
+
+#[test]
+fn content_range_formatting() {
+    assert_eq!(content_range(&(0..500), 1000), "bytes 0-499/1000");
+    assert_eq!(content_range(&(999..1000), 1000), "bytes 999-999/1000");
+}
+
+#[test]
+fn accessors() {
+    let ranges = parse_range("bytes=0-1", 10);
+    assert!(ranges.is_satisfiable());
+    assert_eq!(ranges.ranges().unwrap().first(), Some(&(0..2)));
+    assert!(!RangeSet::Full.is_satisfiable());
+    assert_eq!(RangeSet::Unsatisfiable.ranges(), None);
+}
+
+mod range_bodies {
+    use trillium_testing::{futures_lite::io::Cursor, prelude::*};
+
+    const BODY: &[u8] = b"0123456789";
+
+    async fn seekable(conn: Conn) -> Conn {
+        conn.with_seekable_range_body(Cursor::new(BODY), 10)
+    }
+
+    async fn streaming(conn: Conn) -> Conn {
+        conn.with_streaming_range_body(Cursor::new(BODY), 10, 4)
+    }
+
+    async fn static_range_support(conn: Conn) -> Conn {
+        conn.ok(BODY).with_range_support()
+    }
+
+    async fn streaming_range_support(conn: Conn) -> Conn {
+        conn.with_body_stream(Cursor::new(BODY), Some(10))
+            .with_status(200)
+            .with_range_support()
+    }
+
+    fn handlers() -> [Box<dyn trillium::Handler>; 4] {
+        [
+            Box::new(seekable),
+            Box::new(streaming),
+            Box::new(static_range_support),
+            Box::new(streaming_range_support),
+        ]
+    }
+
+    #[test]
+    fn single_ranges() {
+        for handler in handlers() {
+            let mut conn = get("/")
+                .with_request_header("range", "bytes=1-3")
+                .on(&handler);
+            assert_status!(&conn, 206);
+            assert_headers!(&mut conn, "content-range" => "bytes 1-3/10", "content-length" => "3");
+            assert_body!(&mut conn, "123");
+        }
+    }
+
+    #[test]
+    fn full_bodies() {
+        for handler in handlers() {
+            assert_ok!(get("/").on(&handler), "0123456789", "accept-ranges" => "bytes");
+
+            let mut conn = get("/")
+                .with_request_header("range", "bytes=0-1, 5-6")
+                .on(&handler);
+            assert_status!(&conn, 200);
+            assert_headers!(&mut conn, "content-range" => None);
+            assert_body!(&mut conn, "0123456789");
+        }
+    }
+
+    #[test]
+    fn unsatisfiable() {
+        for handler in handlers() {
+            let mut conn = get("/")
+                .with_request_header("range", "bytes=10-")
+                .on(&handler);
+            assert_status!(&conn, 416);
+            assert_headers!(&mut conn, "content-range" => "bytes */10");
+        }
+    }
+
+    #[test]
+    fn max_skip() {
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=4-5")
+            .on(&streaming);
+        assert_status!(&conn, 206);
+        assert_body!(&mut conn, "45");
+
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=5-6")
+            .on(&streaming);
+        assert_status!(&conn, 200);
+        assert_body!(&mut conn, "0123456789");
+
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=5-6")
+            .on(&seekable);
+        assert_status!(&conn, 206);
+        assert_body!(&mut conn, "56");
+
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=-2")
+            .on(&seekable);
+        assert_status!(&conn, 206);
+        assert_body!(&mut conn, "89");
+    }
+
+    #[test]
+    fn range_support_only_applies_to_known_length_ok_responses() {
+        let not_found =
+            |conn: Conn| async move { conn.with_status(404).with_body(BODY).with_range_support() };
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=1-3")
+            .on(&not_found);
+        assert_status!(&conn, 404);
+        assert_headers!(&mut conn, "accept-ranges" => None);
+        assert_body!(&mut conn, "0123456789");
+
+        let unknown_length = |conn: Conn| async move {
+            conn.with_body_stream(Cursor::new(BODY), None)
+                .with_range_support()
+        };
+        let mut conn = get("/")
+            .with_request_header("range", "bytes=1-3")
+            .on(&unknown_length);
+        assert_headers!(&mut conn, "content-range" => None);
+        assert_body!(&mut conn, "0123456789");
+    }
+}
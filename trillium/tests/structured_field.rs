@@ -0,0 +1,199 @@
+use trillium::structured_field::{
+    parse_dictionary, parse_item, parse_list, serialize_dictionary, serialize_list, BareItem,
+    InnerList, Item, Member,
+};
+
+fn bare_item(input: &str) -> Option<BareItem> {
+    parse_item(input).map(|item| item.bare_item().clone())
+}
+
+#[test]
+fn bare_items() {
+    assert_eq!(bare_item("42"), Some(BareItem::Integer(42)));
+    assert_eq!(bare_item("-7"), Some(BareItem::Integer(-7)));
+    assert_eq!(
+        bare_item("999999999999999"),
+        Some(BareItem::Integer(999_999_999_999_999))
+    );
+    assert_eq!(bare_item("1.5"), Some(BareItem::Decimal(1.5)));
+    assert_eq!(bare_item("-0.125"), Some(BareItem::Decimal(-0.125)));
+    assert_eq!(
+        bare_item(r#""a \"quoted\" \\ string""#),
+        Some(BareItem::String(r#"a "quoted" \ string"#.into()))
+    );
+    assert_eq!(
+        bare_item("text/html"),
+        Some(BareItem::Token("text/html".into()))
+    );
+    assert_eq!(
+        bare_item("*foo:bar"),
+        Some(BareItem::Token("*foo:bar".into()))
+    );
+    assert_eq!(
+        bare_item(":aGVsbG8=:"),
+        Some(BareItem::ByteSequence(b"hello".to_vec()))
+    );
+    assert_eq!(bare_item("::"), Some(BareItem::ByteSequence(vec![])));
+    assert_eq!(
+        bare_item(":aGVsbG8:"),
+        Some(BareItem::ByteSequence(b"hello".to_vec()))
+    );
+    assert_eq!(bare_item(":aGVsbG8*:"), None);
+    assert_eq!(bare_item("?1"), Some(BareItem::Boolean(true)));
+    assert_eq!(bare_item("?0"), Some(BareItem::Boolean(false)));
+    assert_eq!(bare_item("  42  "), Some(BareItem::Integer(42)));
+}
+
+#[test]
+fn invalid_items() {
+    for invalid in [
+        "",
+        "1000000000000000",
+        "1.2345",
+        "1.",
+        "1234567890123.5",
+        r#""unterminated"#,
+        r#""bad \escape""#,
+        ":not base64!:",
+        "?2",
+        "42 43",
+        "1;Key=2",
+        "(a b)",
+    ] {
+        assert_eq!(parse_item(invalid), None, "{invalid}");
+    }
+}
+
+#[test]
+fn parameters() {
+    let item = parse_item("abc;a=1;b=2; c=?0;d").unwrap();
+    let parameters = item.parameters();
+    assert_eq!(parameters.len(), 4);
+    assert_eq!(parameters.get("a"), Some(&BareItem::Integer(1)));
+    assert_eq!(parameters.get("c"), Some(&BareItem::Boolean(false)));
+    assert_eq!(parameters.get("d"), Some(&BareItem::Boolean(true)));
+    assert_eq!(parameters.get("e"), None);
+
+    let item = parse_item("abc;a=1;b=2;a=3").unwrap();
+    let keys = item
+        .parameters()
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["a", "b"]);
+    assert_eq!(item.parameters().get("a"), Some(&BareItem::Integer(3)));
+}
+
+#[test]
+fn lists() {
+    assert_eq!(parse_list(""), Some(vec![]));
+
+    let list = parse_list(r#"sugar, tea, "rum";q=1, (a b);x, ()"#).unwrap();
+    assert_eq!(list.len(), 5);
+    assert_eq!(
+        list[0].as_item().unwrap().bare_item().as_token(),
+        Some("sugar")
+    );
+    assert_eq!(list[2].as_item().unwrap().bare_item().as_str(), Some("rum"));
+    assert_eq!(list[2].parameters().get("q"), Some(&BareItem::Integer(1)));
+
+    let inner_list = list[3].as_inner_list().unwrap();
+    assert_eq!(inner_list.items().len(), 2);
+    assert_eq!(
+        inner_list.parameters().get("x"),
+        Some(&BareItem::Boolean(true))
+    );
+    assert!(list[4].as_inner_list().unwrap().items().is_empty());
+
+    assert_eq!(parse_list("a,b\t,\tc").map(|list| list.len()), Some(3));
+
+    for invalid in ["a,", "a,,b", "a b", "(a b", "(a,b)", ",a"] {
+        assert_eq!(parse_list(invalid), None, "{invalid}");
+    }
+}
+
+#[test]
+fn dictionaries() {
+    assert_eq!(parse_dictionary(""), Some(vec![]));
+
+    let dictionary = parse_dictionary(r#"a=1, b;x=?0, c=(1 2), d="x", a=3"#).unwrap();
+    let keys = dictionary
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["a", "b", "c", "d"]);
+    let value = |key: &str| {
+        dictionary
+            .iter()
+            .find_map(|(k, member)| (k == key).then_some(member))
+            .unwrap()
+    };
+    assert_eq!(
+        value("a").as_item().unwrap().bare_item(),
+        &BareItem::Integer(3)
+    );
+    assert_eq!(
+        value("b").as_item().unwrap().bare_item(),
+        &BareItem::Boolean(true)
+    );
+    assert_eq!(
+        value("b").parameters().get("x"),
+        Some(&BareItem::Boolean(false))
+    );
+    assert!(matches!(value("c"), Member::InnerList(list) if list.items().len() == 2));
+
+    for invalid in ["a=1,", "A=1", "a=1 b=2", "1=a", "a=", "a=1,,b=2"] {
+        assert_eq!(parse_dictionary(invalid), None, "{invalid}");
+    }
+}
+
+#[test]
+fn serialization() {
+    let cases = [
+        "42",
+        "-7",
+        "0.5",
+        "1.0",
+        "-0.125",
+        r#""say \"hi\" \\ bye""#,
+        "text/html",
+        ":aGVsbG8=:",
+        ":YQ==:",
+        "::",
+        "?0",
+        "?1",
+        "a;b;c=?0;d=1.5",
+    ];
+    for case in cases {
+        assert_eq!(parse_item(case).unwrap().to_string(), case);
+    }
+
+    assert_eq!(parse_item("1.50").unwrap().to_string(), "1.5");
+    assert_eq!(BareItem::Decimal(0.1234).to_string(), "0.123");
+
+    let list = parse_list(r#"a,  (b "c");q=1 , ?1;x"#).unwrap();
+    assert_eq!(serialize_list(&list), r#"a, (b "c");q=1, ?1;x"#);
+
+    let dictionary = parse_dictionary("u=1,   i, x=?0, y;a=2, z=(1 2)").unwrap();
+    assert_eq!(
+        serialize_dictionary(&dictionary),
+        "u=1, i, x=?0, y;a=2, z=(1 2)"
+    );
+}
+
+#[test]
+fn construction() {
+    let item = Item::new(BareItem::Token("cache".into()))
+        .with_parameter("hit", true)
+        .with_parameter("ttl", 10)
+        .with_parameter("ttl", 20);
+    assert_eq!(item.to_string(), "cache;hit;ttl=20");
+
+    let members: Vec<Member> = vec![
+        item.into(),
+        InnerList::new([Item::new(BareItem::ByteSequence(b"a".to_vec()))])
+            .with_parameter("q", 0.5)
+            .into(),
+    ];
+    assert_eq!(serialize_list(&members), "cache;hit;ttl=20, (:YQ==:);q=0.5");
+}
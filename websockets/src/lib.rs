@@ -94,6 +94,15 @@ pub use json::{json_websocket, JsonHandler, JsonWebSocketHandler};
 /**
 The trillium handler.
 See crate-level docs for example usage.
+
+On a request to upgrade to a websocket, this handler performs the
+handshake: a request without a base64 `Sec-WebSocket-Key` of sixteen
+bytes receives `400 Bad Request`, and a request for a
+`Sec-WebSocket-Version` other than 13 receives `426 Upgrade Required`.
+Otherwise the conn is halted with `101 Switching Protocols` and the
+`Upgrade`, `Connection`, and `Sec-WebSocket-Accept` response headers,
+and the transport is handed to the [`WebSocketHandler`] once the
+response has been sent.
 */
 #[derive(Debug)]
 pub struct WebSocket<H> {
@@ -191,6 +200,16 @@ where
 
         let websocket_peer_ip = WebsocketPeerIp(conn.peer_ip());
 
+        if !conn
+            .request_headers()
+            .eq_ignore_ascii_case(SecWebsocketVersion, "13")
+        {
+            return conn
+                .with_header(SecWebsocketVersion, "13")
+                .with_status(Status::UpgradeRequired)
+                .halt();
+        }
+
         let sec_websocket_accept = conn_unwrap!(
             websocket_accept_hash(&conn),
             conn.with_status(Status::BadRequest)
@@ -198,7 +217,7 @@ where
 
         let protocol = websocket_protocol(&conn, &self.protocols);
 
-        let headers = conn.headers_mut();
+        let headers = conn.response_headers_mut();
 
         headers.extend([
             (UpgradeHeader, "websocket"),
@@ -260,7 +279,7 @@ where
 }
 
 fn websocket_protocol(conn: &Conn, protocols: &[String]) -> Option<String> {
-    conn.headers()
+    conn.request_headers()
         .get_str(SecWebsocketProtocol)
         .and_then(|value| {
             value
@@ -272,7 +291,7 @@ fn websocket_protocol(conn: &Conn, protocols: &[String]) -> Option<String> {
 }
 
 fn connection_is_upgrade(conn: &Conn) -> bool {
-    conn.headers()
+    conn.request_headers()
         .get_str(Connection)
         .map(|connection| {
             connection
@@ -283,7 +302,7 @@ fn connection_is_upgrade(conn: &Conn) -> bool {
 }
 
 fn upgrade_to_websocket(conn: &Conn) -> bool {
-    conn.headers()
+    conn.request_headers()
         .eq_ignore_ascii_case(UpgradeHeader, "websocket")
 }
 
@@ -292,12 +311,32 @@ fn upgrade_requested(conn: &Conn) -> bool {
 }
 
 fn websocket_accept_hash(conn: &Conn) -> Option<String> {
-    let websocket_key = conn.headers().get_str(SecWebsocketKey)?;
+    let websocket_key = conn.request_headers().get_str(SecWebsocketKey)?.trim();
+    match base64::decode(websocket_key) {
+        Ok(nonce) if nonce.len() == 16 => Some(sec_websocket_accept(websocket_key)),
+        _ => None,
+    }
+}
 
+/**
+computes the `Sec-WebSocket-Accept` response header for a
+`Sec-WebSocket-Key` request header, as described in [rfc 6455 section
+4.2.2](https://www.rfc-editor.org/rfc/rfc6455#section-4.2.2). this is
+only needed to perform a websocket handshake without the [`WebSocket`]
+handler, which validates the key and sets this header.
+
+```
+assert_eq!(
+    trillium_websockets::sec_websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+    "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+);
+```
+*/
+pub fn sec_websocket_accept(sec_websocket_key: &str) -> String {
     let hash = Sha1::new()
-        .chain_update(websocket_key)
+        .chain_update(sec_websocket_key)
         .chain_update(WEBSOCKET_GUID)
         .finalize();
 
-    Some(base64::encode(&hash[..]))
+    base64::encode(&hash[..])
 }
@@ -0,0 +1,36 @@
+use trillium_testing::prelude::*;
+use trillium_websockets::{websocket, WebSocketConn};
+
+fn handshake(key: &'static str, version: &'static str) -> trillium_testing::TestConn {
+    get("/")
+        .with_request_header("connection", "keep-alive, Upgrade")
+        .with_request_header("upgrade", "websocket")
+        .with_request_header("sec-websocket-version", version)
+        .with_request_header("sec-websocket-key", key)
+        .on(&websocket(|_: WebSocketConn| async {}))
+}
+
+#[test]
+fn valid_handshake() {
+    let conn = handshake("dGhlIHNhbXBsZSBub25jZQ==", "13");
+    assert_status!(&conn, 101);
+    assert_headers!(
+        &conn,
+        "upgrade" => "websocket",
+        "connection" => "Upgrade",
+        "sec-websocket-accept" => "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+    );
+}
+
+#[test]
+fn invalid_key() {
+    assert_status!(handshake("not base64!", "13"), 400);
+    assert_status!(handshake("c2hvcnQ=", "13"), 400);
+}
+
+#[test]
+fn unsupported_version() {
+    let conn = handshake("dGhlIHNhbXBsZSBub25jZQ==", "8");
+    assert_status!(&conn, 426);
+    assert_headers!(&conn, "sec-websocket-version" => "13", "sec-websocket-accept" => None);
+}